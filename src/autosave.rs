@@ -0,0 +1,150 @@
+use crate::locale::*;
+use crate::universe::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+// The directory that autosaves are written to.
+const AUTOSAVE_DIRECTORY: &str = "autosaves";
+
+// Settings to autosave the universe with.
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    pub interval_minutes: f64,
+    pub interval_steps: usize,
+    pub slot_amount: usize,
+}
+
+// Default value for AutosaveSettings.
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 5.0,
+            interval_steps: 0,
+            slot_amount: 3,
+        }
+    }
+}
+
+// An autosaver that periodically saves the universe to a rotating set of files.
+pub struct Autosave {
+    pub autosave_settings: AutosaveSettings,
+    pub status: String,
+    next_slot: usize,
+    last_save: Instant,
+    last_save_steps: Option<usize>,
+}
+
+// Default value for Autosave.
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            autosave_settings: Default::default(),
+            status: tr("No autosave this session").to_string(),
+            next_slot: Autosave::first_slot(AutosaveSettings::default().slot_amount),
+            last_save: Instant::now(),
+            last_save_steps: None,
+        }
+    }
+}
+
+// Implementations for Autosave.
+impl Autosave {
+    // Return the path of the autosave file in the given slot.
+    fn slot_path(slot: usize) -> PathBuf {
+        Path::new(AUTOSAVE_DIRECTORY).join(format!("autosave_{}.json", slot))
+    }
+
+    // Return the slot after the most recently modified autosave file of the given amount of slots, so a new session doesn't overwrite the newest autosave of the last one.
+    fn first_slot(slot_amount: usize) -> usize {
+        let slot_amount = slot_amount.max(1);
+        (0..slot_amount)
+            .filter_map(|slot| {
+                let modified = fs::metadata(Self::slot_path(slot))
+                    .and_then(|metadata| metadata.modified())
+                    .ok()?;
+                Some((slot, modified))
+            })
+            .max_by_key(|(_, modified)| *modified)
+            .map_or(0, |(slot, _)| (slot + 1) % slot_amount)
+    }
+
+    // Update the autosaver, saving the universe if the time or step interval has passed.
+    pub fn update(&mut self, universe: &Universe) {
+        // Count the steps from the step count of the universe at the last save, starting over if there was none or the universe went back to fewer steps, such as when it was replaced.
+        let steps = universe.run_statistics.steps;
+        let last_save_steps = self
+            .last_save_steps
+            .filter(|&last_save_steps| last_save_steps <= steps)
+            .unwrap_or(steps);
+        self.last_save_steps = Some(last_save_steps);
+
+        // Don't save if autosaving is disabled or there's nothing to save.
+        if !self.autosave_settings.enabled || universe.bodies.is_empty() {
+            return;
+        }
+
+        // Check whether either interval has passed, ignoring intervals of 0.
+        let minutes_passed = self.autosave_settings.interval_minutes > 0.0
+            && self.last_save.elapsed().as_secs_f64()
+                >= self.autosave_settings.interval_minutes * 60.0;
+        let steps_passed = self.autosave_settings.interval_steps > 0
+            && steps - last_save_steps >= self.autosave_settings.interval_steps;
+
+        if minutes_passed || steps_passed {
+            self.save(universe);
+        }
+    }
+
+    // Save the universe to the next slot.
+    pub fn save(&mut self, universe: &Universe) {
+        // Save the universe and store the result as the status.
+        let path = Self::slot_path(self.next_slot);
+        self.status = match universe.save(&path) {
+            Ok(()) => tr_format("Autosaved to {}", &[&path.display()]),
+            Err(error) => tr_format("Autosave failed: {}", &[&error]),
+        };
+
+        // Move to the next slot and reset the intervals.
+        self.next_slot = (self.next_slot + 1) % self.autosave_settings.slot_amount.max(1);
+        self.last_save = Instant::now();
+        self.last_save_steps = Some(universe.run_statistics.steps);
+    }
+
+    // Return the path of the most recently modified autosave file if there is one.
+    fn last_autosave() -> Option<PathBuf> {
+        fs::read_dir(AUTOSAVE_DIRECTORY)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .max_by_key(|path| {
+                fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH)
+            })
+    }
+
+    // Restore the universe from the most recently modified autosave file, returning whether it was replaced.
+    pub fn restore(&mut self, universe: &mut Universe) -> bool {
+        // Find and load the last autosave, storing the result as the status.
+        let (status, restored) = match Self::last_autosave() {
+            Some(path) => match Universe::load(&path) {
+                Ok(loaded_universe) => {
+                    *universe = loaded_universe;
+                    (tr_format("Restored {}", &[&path.display()]), true)
+                }
+                Err(error) => (tr_format("Restore failed: {}", &[&error]), false),
+            },
+            None => (tr("No autosave found").to_string(), false),
+        };
+        self.status = status;
+        restored
+    }
+}
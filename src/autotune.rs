@@ -0,0 +1,195 @@
+use crate::inspector::*;
+use crate::locale::*;
+use crate::profiler::*;
+use crate::render::*;
+use crate::universe::*;
+use notan_egui::*;
+
+// The time in seconds between adjustments, so the frame time can settle after each one.
+const ADJUSTMENT_INTERVAL: f32 = 1.0;
+// The fraction of the frame budget that the frame time has to stay under before a relaxed setting is restored.
+const RESTORE_FRACTION: f64 = 0.5;
+// The shortest trail the selected body is relaxed to.
+const MINIMUM_TRAIL_LENGTH: usize = 50;
+// The longest interval in seconds the orbital energies are relaxed to.
+const MAXIMUM_ENERGY_INTERVAL: f32 = 4.0;
+// The least amount of massive bodies for the particle mesh solver to be switched to.
+const PARTICLE_MESH_MINIMUM: usize = 500;
+// The coarsest mesh resolution that is relaxed to.
+const MINIMUM_MESH_RESOLUTION: usize = 32;
+
+// A setting that was relaxed to save time, and the value it had before.
+#[derive(Clone, Copy)]
+pub enum Relaxation {
+    TrailLength(usize),
+    EnergyInterval(f32),
+    Solver(Solver),
+    MeshResolution(usize),
+}
+
+// Implementations for Relaxation.
+impl Relaxation {
+    // Return the name of the setting a relaxation changed.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Relaxation::TrailLength(_) => "Trail Length",
+            Relaxation::EnergyInterval(_) => "Energy Interval",
+            Relaxation::Solver(_) => "Solver",
+            Relaxation::MeshResolution(_) => "Mesh Resolution",
+        }
+    }
+}
+
+// A tuner that relaxes settings while the frame time is over the budget of the target frame rate, and restores them in reverse order once there's time to spare.
+pub struct AutoTuner {
+    pub enabled: bool,
+    pub target_fps: f64,
+    pub relaxations: Vec<Relaxation>,
+    pub timer: f32,
+}
+
+// Default value for AutoTuner.
+impl Default for AutoTuner {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_fps: 30.0,
+            relaxations: Vec::new(),
+            timer: 0.0,
+        }
+    }
+}
+
+// Implementations for AutoTuner.
+impl AutoTuner {
+    // Update the tuner with the smoothed frame time of the profiler, adjusting at most one setting per interval.
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        profiler: &Profiler,
+        universe: &mut Universe,
+        render_settings: &mut RenderSettings,
+        inspector: &mut Inspector,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.timer -= delta_time;
+        if self.timer > 0.0 {
+            return;
+        }
+        self.timer = ADJUSTMENT_INTERVAL;
+
+        // Relax the next setting that can be relaxed if the frame is over budget, or restore the last one if there's time to spare.
+        let budget = 1.0 / self.target_fps;
+        if profiler.total() > budget {
+            if let Some(relaxation) = Self::relax(universe, render_settings, inspector) {
+                self.relaxations.push(relaxation);
+            }
+        } else if profiler.total() < budget * RESTORE_FRACTION {
+            if let Some(relaxation) = self.relaxations.pop() {
+                Self::restore(relaxation, universe, render_settings, inspector);
+            }
+        }
+    }
+
+    // Relax the first setting that can still be relaxed, from the least to the most noticeable, and return what it was before.
+    fn relax(
+        universe: &mut Universe,
+        render_settings: &mut RenderSettings,
+        inspector: &mut Inspector,
+    ) -> Option<Relaxation> {
+        let universe_settings = &mut universe.universe_settings;
+        if inspector.trail_length > MINIMUM_TRAIL_LENGTH {
+            let relaxation = Relaxation::TrailLength(inspector.trail_length);
+            inspector.trail_length = (inspector.trail_length / 2).max(MINIMUM_TRAIL_LENGTH);
+            return Some(relaxation);
+        }
+        if render_settings.color_mode == ColorMode::OrbitalEnergy
+            && render_settings.energy_interval < MAXIMUM_ENERGY_INTERVAL
+        {
+            let relaxation = Relaxation::EnergyInterval(render_settings.energy_interval);
+            render_settings.energy_interval =
+                (render_settings.energy_interval * 2.0).clamp(0.25, MAXIMUM_ENERGY_INTERVAL);
+            return Some(relaxation);
+        }
+        let massive_bodies = universe
+            .bodies
+            .iter()
+            .filter(|body| body.is_massive())
+            .count();
+        if universe_settings.solver == Solver::Direct && massive_bodies >= PARTICLE_MESH_MINIMUM {
+            let relaxation = Relaxation::Solver(universe_settings.solver);
+            universe_settings.solver = Solver::ParticleMesh;
+            return Some(relaxation);
+        }
+        if universe_settings.solver == Solver::ParticleMesh
+            && universe_settings.mesh_resolution > MINIMUM_MESH_RESOLUTION
+        {
+            let relaxation = Relaxation::MeshResolution(universe_settings.mesh_resolution);
+            universe_settings.mesh_resolution =
+                (universe_settings.mesh_resolution / 2).max(MINIMUM_MESH_RESOLUTION);
+            return Some(relaxation);
+        }
+        None
+    }
+
+    // Restore a relaxed setting to what it was before.
+    fn restore(
+        relaxation: Relaxation,
+        universe: &mut Universe,
+        render_settings: &mut RenderSettings,
+        inspector: &mut Inspector,
+    ) {
+        match relaxation {
+            Relaxation::TrailLength(trail_length) => inspector.trail_length = trail_length,
+            Relaxation::EnergyInterval(energy_interval) => {
+                render_settings.energy_interval = energy_interval
+            }
+            Relaxation::Solver(solver) => universe.universe_settings.solver = solver,
+            Relaxation::MeshResolution(mesh_resolution) => {
+                universe.universe_settings.mesh_resolution = mesh_resolution
+            }
+        }
+    }
+
+    // Show the settings of the tuner and which settings it relaxed in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, profiler: &Profiler) {
+        // Create a checkbox to toggle the tuner and a drag value to modify the target frame rate.
+        let label_id = ui.label(tr("Enabled")).id;
+        ui.add(Checkbox::new(&mut self.enabled, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Target FPS")).id;
+        ui.add(
+            DragValue::new(&mut self.target_fps)
+                .speed(1.0)
+                .clamp_range(1.0..=240.0),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+
+        // Show the frame time against the budget.
+        ui.label(tr_format(
+            "Frame Time: {} ms of {} ms",
+            &[
+                &format!("{:.1}", profiler.total() * 1000.0),
+                &format!("{:.1}", 1000.0 / self.target_fps),
+            ],
+        ));
+        ui.end_row();
+
+        // Show which settings are relaxed, in the order they were relaxed.
+        if self.relaxations.is_empty() {
+            ui.label(tr("Nothing relaxed"));
+        } else {
+            for relaxation in self.relaxations.iter() {
+                ui.colored_label(
+                    Color32::YELLOW,
+                    tr_format("Relaxed {}", &[&tr(relaxation.name())]),
+                );
+            }
+        }
+        ui.end_row();
+    }
+}
@@ -0,0 +1,140 @@
+use notan::{draw::*, math::DVec2, prelude::*};
+
+// The largest amount of cells along each axis of the density field, beyond which the cells are made larger.
+const MAXIMUM_CELLS: usize = 512;
+
+// A body drawn as part of a blob, with the position, radius, and color it's drawn with.
+pub struct BlobBody {
+    pub position: DVec2,
+    pub radius: f64,
+    pub color: Color,
+}
+
+// Return the contribution to the density field of a body at the given squared distance, given the squared distance it reaches, which falls smoothly to zero at the edge of its reach.
+fn falloff(distance_squared: f64, reach_squared: f64) -> f64 {
+    let fraction = 1.0 - distance_squared / reach_squared;
+    if fraction > 0.0 {
+        fraction * fraction
+    } else {
+        0.0
+    }
+}
+
+// Draw bodies as metaballs that blend into smooth blobs where they're close, by adding up a density field on a grid with cells of the given size within the given bounds and filling where it's above a threshold with marching squares. Each body reaches the given multiple of its radius, and the threshold is where the density of a lone body falls at its radius, so lone bodies keep their size.
+pub fn draw_blobs(
+    draw: &mut Draw,
+    bodies: &[BlobBody],
+    bounds: (DVec2, DVec2),
+    cell_size: f64,
+    reach: f64,
+) {
+    if bodies.is_empty() {
+        return;
+    }
+    let reach = reach.max(1.01);
+    let threshold = falloff(1.0, reach * reach);
+
+    // Fit the grid around the reach of the bodies within the bounds, making the cells larger if there would be too many.
+    let (minimum, maximum) = bodies.iter().fold(
+        (DVec2::splat(f64::MAX), DVec2::splat(f64::MIN)),
+        |(minimum, maximum), body| {
+            let extent = DVec2::splat(body.radius * reach);
+            (
+                minimum.min(body.position - extent),
+                maximum.max(body.position + extent),
+            )
+        },
+    );
+    let minimum = minimum.max(bounds.0);
+    let maximum = maximum.min(bounds.1);
+    if minimum.x >= maximum.x || minimum.y >= maximum.y {
+        return;
+    }
+    let cell_size = cell_size
+        .max((maximum - minimum).max_element() / MAXIMUM_CELLS as f64)
+        .max(f64::MIN_POSITIVE);
+    let columns = ((maximum.x - minimum.x) / cell_size).ceil() as usize + 1;
+    let rows = ((maximum.y - minimum.y) / cell_size).ceil() as usize + 1;
+
+    // Add the density of each body to the grid points it reaches, along with its color weighted by its density so the colors of blended bodies mix.
+    let mut densities = vec![0.0; columns * rows];
+    let mut colors = vec![[0.0; 4]; columns * rows];
+    for body in bodies.iter() {
+        let body_reach = body.radius * reach;
+        let reach_squared = body_reach * body_reach;
+        let start = ((body.position - body_reach - minimum) / cell_size).floor();
+        let end = ((body.position + body_reach - minimum) / cell_size).ceil();
+        let (column_start, row_start) = (start.x.max(0.0) as usize, start.y.max(0.0) as usize);
+        let (column_end, row_end) = (
+            (end.x.max(0.0) as usize).min(columns - 1),
+            (end.y.max(0.0) as usize).min(rows - 1),
+        );
+        for row in row_start..=row_end {
+            for column in column_start..=column_end {
+                let point = minimum + DVec2::new(column as f64, row as f64) * cell_size;
+                let density = falloff(point.distance_squared(body.position), reach_squared);
+                if density > 0.0 {
+                    let index = row * columns + column;
+                    densities[index] += density;
+                    for (channel, value) in colors[index].iter_mut().zip(body.color.rgba()) {
+                        *channel += value as f64 * density;
+                    }
+                }
+            }
+        }
+    }
+
+    // Fill the part of each cell above the threshold, walking around its corners and adding each corner inside and each point where an edge crosses the threshold, which gives a convex polygon that can be drawn as a fan of triangles.
+    const CORNERS: [(usize, usize); 4] = [(0, 0), (1, 0), (1, 1), (0, 1)];
+    let mut polygon: Vec<(f32, f32)> = Vec::with_capacity(8);
+    for row in 0..rows - 1 {
+        for column in 0..columns - 1 {
+            let corner = |(x, y): (usize, usize)| (row + y) * columns + column + x;
+            let values = CORNERS.map(|offset| densities[corner(offset)]);
+            if values.iter().all(|value| *value < threshold) {
+                continue;
+            }
+
+            // Find the polygon of the part of the cell above the threshold.
+            polygon.clear();
+            let point = |(x, y): (f64, f64)| {
+                let point = minimum + DVec2::new(column as f64 + x, row as f64 + y) * cell_size;
+                (point.x as f32, point.y as f32)
+            };
+            for index in 0..4 {
+                let next = (index + 1) % 4;
+                let (offset, next_offset) = (CORNERS[index], CORNERS[next]);
+                if values[index] >= threshold {
+                    polygon.push(point((offset.0 as f64, offset.1 as f64)));
+                }
+                if (values[index] >= threshold) != (values[next] >= threshold) {
+                    let fraction = (threshold - values[index]) / (values[next] - values[index]);
+                    polygon.push(point((
+                        offset.0 as f64 + (next_offset.0 as f64 - offset.0 as f64) * fraction,
+                        offset.1 as f64 + (next_offset.1 as f64 - offset.1 as f64) * fraction,
+                    )));
+                }
+            }
+
+            // Color the cell with the average of the colors at its corners.
+            let (density, color) =
+                CORNERS
+                    .iter()
+                    .fold((0.0, [0.0; 4]), |(density, mut color), offset| {
+                        let index = corner(*offset);
+                        for (channel, value) in color.iter_mut().zip(colors[index]) {
+                            *channel += value;
+                        }
+                        (density + densities[index], color)
+                    });
+            let [red, green, blue, alpha] = color.map(|channel| (channel / density) as f32);
+            let color = Color::new(red, green, blue, alpha);
+
+            // Draw the polygon as a fan of triangles.
+            for index in 1..polygon.len().saturating_sub(1) {
+                draw.triangle(polygon[0], polygon[index], polygon[index + 1])
+                    .color(color);
+            }
+        }
+    }
+}
@@ -1,35 +1,136 @@
-use notan::{draw::*, math::DVec2};
+use notan::{draw::*, math::DVec2, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// A stable handle to a body that stays the same when the bodies are reordered, merged, or removed, unlike its index. Ids are never reused, and 0 means the body hasn't been given one yet.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct BodyId(pub u64);
+
+// Implementations for BodyId.
+impl BodyId {
+    // Return whether a body has been given an id.
+    pub fn is_assigned(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+// Show a body id as its number.
+impl fmt::Display for BodyId {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
 
 // A body that represents a massive object in space.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Body {
+    pub id: BodyId,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
     pub position: DVec2,
     pub velocity: DVec2,
     pub mass: f64,
+    pub frozen: bool,
+    pub test_particle: bool,
+    pub black_hole: bool,
+    pub accreted_mass: f64,
+    pub accreted_bodies: usize,
+    pub angle: f64,
+    pub spin: f64,
+    pub created_at: f64,
 }
 
 // The default value for Body.
 impl Default for Body {
     fn default() -> Self {
         Self {
+            id: BodyId::default(),
+            name: String::new(),
             position: Default::default(),
             velocity: Default::default(),
             mass: 1.0,
+            frozen: false,
+            test_particle: false,
+            black_hole: false,
+            accreted_mass: 0.0,
+            accreted_bodies: 0,
+            angle: 0.0,
+            spin: 0.0,
+            created_at: 0.0,
         }
     }
 }
 
 // Implementations for Body.
 impl Body {
+    // Return the radius of a body.
+    pub fn radius(&self) -> f64 {
+        self.mass.cbrt()
+    }
+
+    // Return whether the position, velocity, and mass of a body are all finite numbers.
+    pub fn is_finite(&self) -> bool {
+        self.position.is_finite() && self.velocity.is_finite() && self.mass.is_finite()
+    }
+
+    // Return the moment of inertia of a body about its center, treating it as a uniform sphere.
+    pub fn moment_of_inertia(&self) -> f64 {
+        0.4 * self.mass * self.radius().powi(2)
+    }
+
+    // Return the spin of the body two bodies merge into at the given position and velocity, keeping the sum of their spin angular momentum and the angular momentum of their orbit about the point of contact.
+    pub fn merged_spin(body1: &Body, body2: &Body, position: DVec2, velocity: DVec2) -> f64 {
+        let angular_momentum = [body1, body2]
+            .iter()
+            .map(|body| {
+                body.moment_of_inertia() * body.spin
+                    + body.mass * (body.position - position).perp_dot(body.velocity - velocity)
+            })
+            .sum::<f64>();
+        let merged = Body {
+            mass: body1.mass + body2.mass,
+            ..Default::default()
+        };
+        angular_momentum / merged.moment_of_inertia().max(f64::EPSILON)
+    }
+
+    // Return whether a body exerts gravity and collides with other bodies, which frozen bodies and test particles don't.
+    pub fn is_massive(&self) -> bool {
+        !self.frozen && !self.test_particle
+    }
+
     // Update a body.
     pub fn update(&mut self, delta_time: f64) {
-        // Integrate the body's velocity over time.
+        // Frozen bodies don't move.
+        if self.frozen {
+            return;
+        }
+
+        // Integrate the body's velocity and spin over time.
         self.position += self.velocity * delta_time;
+        self.angle = (self.angle + self.spin * delta_time).rem_euclid(std::f64::consts::TAU);
+    }
+
+    // Return the default color of a body, grayed out if it's frozen and tinted blue if it's a test particle.
+    pub fn color(&self) -> Color {
+        if self.frozen {
+            Color::GRAY
+        } else if self.test_particle {
+            Color::from_rgb(0.5, 0.75, 1.0)
+        } else {
+            Color::WHITE
+        }
     }
 
-    // Draw a body.
-    pub fn draw(&self, draw: &mut Draw) {
+    // Draw a body with the given color and radius, tessellating it with the given tolerance.
+    pub fn draw(&self, draw: &mut Draw, color: Color, radius: f64, tolerance: f32) {
         // Draw a circle to represent the body.
-        draw.circle(self.mass.cbrt() as f32)
-            .position(self.position.x as f32, self.position.y as f32);
+        draw.circle(radius as f32)
+            .position(self.position.x as f32, self.position.y as f32)
+            .tolerance(tolerance)
+            .color(color);
     }
 }
@@ -0,0 +1,153 @@
+use crate::camera::*;
+use crate::locale::*;
+use notan::{math::Vec2, prelude::*};
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+// The file that the camera bookmarks are stored in.
+const BOOKMARKS_PATH: &str = "config/camera_bookmarks.json";
+// The keys that recall the first nine bookmarks.
+const BOOKMARK_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+// A named camera position and zoom that can be returned to.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub translation: Vec2,
+    pub scale: f32,
+}
+
+// A list of camera bookmarks, loaded from and saved to the config.
+pub struct Bookmarks {
+    pub bookmarks: Vec<CameraBookmark>,
+    pub name: String,
+    pub status: String,
+}
+
+// Default value for Bookmarks.
+impl Default for Bookmarks {
+    fn default() -> Self {
+        // Load the bookmarks from the config, starting with none if there are none.
+        let (bookmarks, status) = match Self::load(Path::new(BOOKMARKS_PATH)) {
+            Ok(bookmarks) => (bookmarks, tr_format("Loaded {}", &[&BOOKMARKS_PATH])),
+            Err(_) => (vec![], String::new()),
+        };
+        Self {
+            bookmarks,
+            name: String::new(),
+            status,
+        }
+    }
+}
+
+// Implementations for Bookmarks.
+impl Bookmarks {
+    // Load bookmarks from a JSON file at the given path.
+    fn load(path: &Path) -> Result<Vec<CameraBookmark>, String> {
+        // Read the file and deserialize the bookmarks.
+        let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+
+    // Save the bookmarks to a JSON file at the given path.
+    fn write(&self, path: &Path) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the bookmarks and write them to the file.
+        let json =
+            serde_json::to_string_pretty(&self.bookmarks).map_err(|error| error.to_string())?;
+        fs::write(path, json).map_err(|error| error.to_string())
+    }
+
+    // Save the bookmarks to the config and store the result as the status.
+    fn save(&mut self) {
+        self.status = match self.write(Path::new(BOOKMARKS_PATH)) {
+            Ok(()) => tr_format("Saved {}", &[&BOOKMARKS_PATH]),
+            Err(error) => tr_format("Saving bookmarks failed: {}", &[&error]),
+        };
+    }
+
+    // Animate the camera target to a bookmark.
+    fn recall(camera: &mut Camera, bookmark: &CameraBookmark) {
+        camera.animate_to(
+            bookmark.translation,
+            bookmark.scale,
+            camera.camera_settings.animation_duration,
+        );
+    }
+
+    // Update the bookmarks, recalling the bookmark whose number key was pressed unless the UI is taking keyboard input.
+    pub fn update(&self, app: &App, camera: &mut Camera, keyboard_over_ui: bool) {
+        if keyboard_over_ui {
+            return;
+        }
+        for (bookmark, key) in self.bookmarks.iter().zip(BOOKMARK_KEYS) {
+            if app.keyboard.was_pressed(key) {
+                Self::recall(camera, bookmark);
+            }
+        }
+    }
+
+    // Show the bookmarks in the given UI, with buttons to save the current view and to recall or delete each bookmark.
+    pub fn show(&mut self, ui: &mut Ui, camera: &mut Camera) {
+        // Create a text field for the name of a new bookmark and a button to save the current view under it.
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.name).hint_text(tr("Name")));
+            if ui.button(tr("Save Current View")).clicked() {
+                let name = if self.name.trim().is_empty() {
+                    tr_format("Bookmark {}", &[&(self.bookmarks.len() + 1)])
+                } else {
+                    self.name.trim().to_string()
+                };
+                self.bookmarks.push(CameraBookmark {
+                    name,
+                    translation: camera.target_translation,
+                    scale: camera.target_scale,
+                });
+                self.name.clear();
+                self.save();
+            }
+        });
+        ui.end_row();
+
+        // List each bookmark with the key that recalls it, if it has one.
+        let mut removed = None;
+        for (index, bookmark) in self.bookmarks.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if index < BOOKMARK_KEYS.len() {
+                    ui.label(format!("{}.", index + 1));
+                }
+                ui.label(&bookmark.name);
+                if ui.button(tr("Go To")).clicked() {
+                    Self::recall(camera, bookmark);
+                }
+                if ui.button(tr("Delete")).clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.bookmarks.remove(index);
+            self.save();
+        }
+        ui.end_row();
+
+        // Show the status of the bookmarks.
+        ui.label(&self.status);
+        ui.end_row();
+    }
+}
@@ -0,0 +1,114 @@
+use crate::camera::*;
+use crate::locale::*;
+use crate::universe::*;
+use notan::{
+    draw::*,
+    math::{DVec2, Vec2},
+    prelude::*,
+};
+use notan_egui::*;
+
+// A brush that stirs the universe by imparting the velocity of the mouse to the bodies around it while dragging.
+pub struct Brush {
+    pub enabled: bool,
+    pub radius: f32,
+    pub strength: f64,
+    pub position: Option<DVec2>,
+}
+
+// Default value for Brush.
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 50.0,
+            strength: 1.0,
+            position: None,
+        }
+    }
+}
+
+// Implementations for Brush.
+impl Brush {
+    // Update the brush, stirring the bodies within its radius while the left mouse button is dragged outside of the UI.
+    pub fn update(
+        &mut self,
+        app: &mut App,
+        camera: &Camera,
+        universe: &mut Universe,
+        pointer_over_ui: bool,
+    ) {
+        // Only show the brush when it's enabled and the mouse is over the world.
+        self.position = None;
+        if !self.enabled || pointer_over_ui {
+            return;
+        }
+        let screen_position = Vec2::from(app.mouse.position());
+        let position = camera.screen_to_world(app, screen_position);
+        self.position = Some(position);
+        if !app.mouse.is_down(MouseButton::Left) {
+            return;
+        }
+
+        // Find how far the mouse was dragged in the world since the last frame.
+        let last_position = camera.screen_to_world(
+            app,
+            screen_position - DVec2::from(app.mouse.motion_delta).as_vec2(),
+        );
+        let drag = position - last_position;
+        if drag == DVec2::ZERO {
+            return;
+        }
+
+        // Add the drag to the velocity of each body within the radius, fading out linearly towards the edge.
+        let radius = (self.radius / camera.scale) as f64;
+        let strength = self.strength;
+        universe.edit(move |universe| {
+            for body in universe.bodies.iter_mut().filter(|body| !body.frozen) {
+                let distance = body.position.distance(position);
+                if distance < radius {
+                    body.velocity += drag * strength * (1.0 - distance / radius);
+                }
+            }
+        });
+    }
+
+    // Draw the outline of the brush around the mouse.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera) {
+        let Some(position) = self.position else {
+            return;
+        };
+        draw.circle(self.radius / camera.scale)
+            .position(position.x as f32, position.y as f32)
+            .tolerance(camera.circle_tolerance())
+            .stroke(1.0 / camera.scale)
+            .color(Color::from_rgba(0.5, 0.8, 1.0, 0.6));
+    }
+
+    // Show the settings of the brush in the given UI.
+    pub fn show(&mut self, ui: &mut Ui) {
+        // Create a checkbox to toggle the brush, which replaces selecting bodies with the left mouse button.
+        let label_id = ui.label(tr("Enabled")).id;
+        ui.add(Checkbox::new(&mut self.enabled, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create widgets to change the radius in pixels and the strength of the brush.
+        let label_id = ui.label(tr("Radius")).id;
+        ui.add(Slider::new(&mut self.radius, 5.0..=500.0))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Strength")).id;
+        ui.add(
+            DragValue::new(&mut self.strength)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        ui.label(tr(
+            "Drag with the left mouse button to stir the bodies under the brush.",
+        ));
+        ui.end_row();
+    }
+}
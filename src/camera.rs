@@ -1,13 +1,62 @@
+use crate::universe::*;
 use notan::{
     draw::*,
     math::{DVec2, Mat3, Vec2},
     prelude::*,
 };
+use serde::{Deserialize, Serialize};
+
+// The maximum distance in physical pixels between the edge of a tessellated circle and the true circle.
+const CIRCLE_TOLERANCE: f32 = 0.1;
+// The speed in pixels per second that the camera pans at with the keyboard.
+const KEYBOARD_PAN_SPEED: f32 = 600.0;
+// The rate that the camera zooms at with the keyboard, as the natural logarithm of the factor per second.
+const KEYBOARD_ZOOM_RATE: f32 = 1.5;
+// The fraction of the screen that the bodies fill when the camera fits all of them.
+const FIT_MARGIN: f32 = 0.9;
+
+// A reference frame that the world is viewed in, which doesn't change the physics.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReferenceFrame {
+    Inertial,
+    CoRotatingPair,
+    FixedRate,
+}
+
+// Implementations for ReferenceFrame.
+impl ReferenceFrame {
+    // All reference frames, in the order they are listed in the UI.
+    pub const ALL: [ReferenceFrame; 3] = [
+        ReferenceFrame::Inertial,
+        ReferenceFrame::CoRotatingPair,
+        ReferenceFrame::FixedRate,
+    ];
+
+    // Return the name of a reference frame.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReferenceFrame::Inertial => "Inertial",
+            ReferenceFrame::CoRotatingPair => "Co-Rotating With Pair",
+            ReferenceFrame::FixedRate => "Rotating at Fixed Rate",
+        }
+    }
+}
 
 // Settings for the camera.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CameraSettings {
     pub pan_sensitivity: f32,
     pub zoom_sensitivity: f32,
+    pub smoothing: bool,
+    pub damping: f32,
+    pub inertia: bool,
+    pub friction: f32,
+    pub touch_pan_sensitivity: f32,
+    pub pinch_sensitivity: f32,
+    pub reference_frame: ReferenceFrame,
+    pub angular_rate: f64,
+    pub animation_duration: f32,
 }
 
 // The default value for CameraSettings..
@@ -16,15 +65,60 @@ impl Default for CameraSettings {
         Self {
             pan_sensitivity: 1.0,
             zoom_sensitivity: 1.0,
+            smoothing: true,
+            damping: 15.0,
+            inertia: true,
+            friction: 5.0,
+            touch_pan_sensitivity: 1.0,
+            pinch_sensitivity: 1.0,
+            reference_frame: ReferenceFrame::Inertial,
+            angular_rate: 0.1,
+            animation_duration: 0.6,
         }
     }
 }
 
+// An animation of the camera target from one view to another, timed in seconds so it takes as long at any frame rate.
+#[derive(Clone, Copy)]
+pub struct CameraAnimation {
+    pub start_center: Vec2,
+    pub start_scale: f32,
+    pub end_center: Vec2,
+    pub end_scale: f32,
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+// Implementations for CameraAnimation.
+impl CameraAnimation {
+    // Return the translation and scale of the camera target at the elapsed time, easing in and out. The center is moved in a straight line and the scale changes by the same factor each moment, so zooming far in or out doesn't look sudden at either end.
+    fn current(&self) -> (Vec2, f32) {
+        let progress = (self.elapsed / self.duration).clamp(0.0, 1.0);
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        let center = self.start_center.lerp(self.end_center, eased);
+        let scale = self.start_scale * (self.end_scale / self.start_scale).powf(eased);
+        (-center * scale, scale)
+    }
+
+    // Return whether the animation has reached its end.
+    fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
 // A camera used to transform the screen.
 pub struct Camera {
     pub camera_settings: CameraSettings,
     pub translation: Vec2,
     pub scale: f32,
+    pub target_translation: Vec2,
+    pub target_scale: f32,
+    pub pan_velocity: Vec2,
+    pub animation: Option<CameraAnimation>,
+    pub previous_touches: Option<(Vec2, Vec2)>,
+    pub frame_center: DVec2,
+    pub frame_angle: f64,
+    pub dpi: f32,
 }
 
 // The default value for Camera.
@@ -34,30 +128,299 @@ impl Default for Camera {
             camera_settings: Default::default(),
             translation: Default::default(),
             scale: 1.0,
+            target_translation: Default::default(),
+            target_scale: 1.0,
+            pan_velocity: Default::default(),
+            animation: None,
+            previous_touches: None,
+            frame_center: DVec2::ZERO,
+            frame_angle: 0.0,
+            dpi: 1.0,
         }
     }
 }
 
 // Implementations for Camera.
 impl Camera {
+    // Reset the position and zoom of a camera, keeping its settings.
+    pub fn reset(&mut self) {
+        *self = Self {
+            camera_settings: std::mem::take(&mut self.camera_settings),
+            ..Default::default()
+        };
+    }
+
     // Create and return a matrix to transform a draw surface with.
     pub fn create_matrix(&self, draw: &Draw) -> Mat3 {
+        // Create and return the matrix using the size of the draw surface.
+        self.matrix(Vec2::from(draw.size()))
+    }
+
+    // Create and return a matrix to transform a surface of the given size with.
+    fn matrix(&self, size: Vec2) -> Mat3 {
         // Create and return the matrix.
-        Mat3::from_translation(Vec2::from(draw.size()) * 0.5 + self.translation)
+        Mat3::from_translation(size * 0.5 + self.translation)
             * Mat3::from_scale(Vec2::splat(self.scale))
+            * Mat3::from_angle(-self.frame_angle as f32)
+            * Mat3::from_translation(-self.frame_center.as_vec2())
+    }
+
+    // Update the center and angle of the reference frame of a camera from the universe, using the selected body for co-rotating pairs.
+    pub fn update_frame(&mut self, universe: &Universe, selected_body: Option<usize>) {
+        (self.frame_center, self.frame_angle) = match self.camera_settings.reference_frame {
+            ReferenceFrame::Inertial => (DVec2::ZERO, 0.0),
+            // Keep the selected body and its dominant body on the horizontal axis, centered on their barycenter.
+            ReferenceFrame::CoRotatingPair => {
+                match selected_body.and_then(|index| Some((index, universe.dominant_body(index)?)))
+                {
+                    Some((index, dominant_body)) => {
+                        let body = &universe.bodies[index];
+                        let other = &universe.bodies[dominant_body];
+                        let offset = body.position - other.position;
+                        (
+                            (body.position * body.mass + other.position * other.mass)
+                                / (body.mass + other.mass),
+                            offset.y.atan2(offset.x),
+                        )
+                    }
+                    None => (DVec2::ZERO, 0.0),
+                }
+            }
+            // Rotate about the center of mass at the angular rate over simulation time.
+            ReferenceFrame::FixedRate => (
+                universe.center_of_mass().0,
+                self.camera_settings.angular_rate * universe.simulation_time,
+            ),
+        };
+    }
+
+    // Return the tolerance in world units to tessellate circles with, so their edges stay smooth at the current zoom and pixel density.
+    pub fn circle_tolerance(&self) -> f32 {
+        CIRCLE_TOLERANCE / (self.scale * self.dpi)
+    }
+
+    // Convert a position in the world to a position in the reference frame of a camera.
+    pub fn world_to_frame(&self, position: DVec2) -> DVec2 {
+        DVec2::from_angle(-self.frame_angle).rotate(position - self.frame_center)
+    }
+
+    // Convert a position in the reference frame of a camera to a position in the world.
+    pub fn frame_to_world(&self, position: DVec2) -> DVec2 {
+        DVec2::from_angle(self.frame_angle).rotate(position) + self.frame_center
+    }
+
+    // Convert a position on the screen to a position in the world.
+    pub fn screen_to_world(&self, app: &mut App, position: Vec2) -> DVec2 {
+        // Get the size of the window.
+        let (width, height) = app.window().size();
+
+        // Transform the position using the inverse of the camera matrix.
+        self.matrix(Vec2::new(width as f32, height as f32))
+            .inverse()
+            .transform_point2(position)
+            .as_dvec2()
+    }
+
+    // Convert a position in the world to a position on the screen.
+    pub fn world_to_screen(&self, app: &mut App, position: DVec2) -> Vec2 {
+        // Get the size of the window.
+        let (width, height) = app.window().size();
+
+        // Transform the position using the camera matrix.
+        self.matrix(Vec2::new(width as f32, height as f32))
+            .transform_point2(position.as_vec2())
+    }
+
+    // Multiply the scale of the camera target by a factor while keeping the world position under the given screen position in place.
+    pub fn zoom_about(&mut self, app: &mut App, position: Vec2, factor: f32) {
+        // Find the center of the window.
+        let (width, height) = app.window().size();
+        let center = Vec2::new(width as f32, height as f32) * 0.5;
+
+        // Scale the offset between the screen position and the camera target translation.
+        self.animation = None;
+        let offset = position - center - self.target_translation;
+        self.target_translation = position - center - offset * factor;
+        self.target_scale *= factor;
+    }
+
+    // Animate the camera target to the given translation and scale over a duration in seconds, stopping any panning momentum. A duration of zero moves it at once.
+    pub fn animate_to(&mut self, translation: Vec2, scale: f32, duration: f32) {
+        self.pan_velocity = Vec2::ZERO;
+        if duration <= 0.0 || scale <= 0.0 || self.scale <= 0.0 {
+            self.animation = None;
+            self.target_translation = translation;
+            self.target_scale = scale;
+            return;
+        }
+        // Start from where the camera is rather than its target, so the animation doesn't jump if the camera was still catching up.
+        self.animation = Some(CameraAnimation {
+            start_center: -self.translation / self.scale,
+            start_scale: self.scale,
+            end_center: -translation / scale,
+            end_scale: scale,
+            duration,
+            elapsed: 0.0,
+        });
+    }
+
+    // Animate the camera target so the given world position is at the center of the screen, keeping the zoom.
+    pub fn center_on(&mut self, position: DVec2) {
+        self.animate_to(
+            -self.world_to_frame(position).as_vec2() * self.target_scale,
+            self.target_scale,
+            self.camera_settings.animation_duration,
+        );
+    }
+
+    // Animate the camera target so every body fits on the screen with a margin.
+    pub fn fit_all(&mut self, app: &mut App, universe: &Universe) {
+        if universe.bodies.is_empty() {
+            return;
+        }
+
+        // Find the bounds of the bodies in the reference frame, including their radii.
+        let (minimum, maximum) = universe.bodies.iter().fold(
+            (DVec2::splat(f64::MAX), DVec2::splat(f64::MIN)),
+            |(minimum, maximum), body| {
+                let position = self.world_to_frame(body.position);
+                let radius = DVec2::splat(body.radius());
+                (
+                    minimum.min(position - radius),
+                    maximum.max(position + radius),
+                )
+            },
+        );
+
+        // Zoom so the larger side of the bounds relative to the window fills it, and center on the bounds.
+        let (width, height) = app.window().size();
+        let size = (maximum - minimum).as_vec2().max(Vec2::splat(f32::EPSILON));
+        let scale = (width as f32 / size.x).min(height as f32 / size.y) * FIT_MARGIN;
+        let center = ((minimum + maximum) * 0.5).as_vec2();
+        self.animate_to(
+            -center * scale,
+            scale,
+            self.camera_settings.animation_duration,
+        );
+    }
+
+    // Pan the camera target in a direction on the screen with the keyboard, moving the view that way.
+    pub fn pan_with_keys(&mut self, direction: (f32, f32), delta_time: f32) {
+        self.target_translation -= Vec2::from(direction)
+            * KEYBOARD_PAN_SPEED
+            * self.camera_settings.pan_sensitivity
+            * delta_time;
+        self.pan_velocity = Vec2::ZERO;
+        self.animation = None;
+    }
+
+    // Zoom the camera target about the center of the screen with the keyboard, in with a positive direction and out with a negative one.
+    pub fn zoom_with_keys(&mut self, direction: f32, delta_time: f32) {
+        let factor =
+            (direction * KEYBOARD_ZOOM_RATE * self.camera_settings.zoom_sensitivity * delta_time)
+                .exp();
+        self.animation = None;
+        self.target_translation *= factor;
+        self.target_scale *= factor;
+    }
+
+    // Pan the camera target with the midpoint of two touches and zoom it with the distance between them.
+    fn update_touches(&mut self, app: &mut App) {
+        // Find the positions of the touches that are down, sorted by their ids so they stay in the same order.
+        let mut ids: Vec<u8> = app.touch.down.keys().copied().collect();
+        ids.sort_unstable();
+        let touches: Vec<Vec2> = ids
+            .iter()
+            .filter_map(|id| app.touch.position(*id))
+            .map(Vec2::from)
+            .collect();
+
+        // Only handle gestures with exactly two touches.
+        let [touch1, touch2] = touches[..] else {
+            self.previous_touches = None;
+            return;
+        };
+
+        // Compare the touches with the touches of the last frame.
+        if let Some((previous_touch1, previous_touch2)) = self.previous_touches {
+            // Pan the camera target by how much the midpoint moved.
+            let midpoint = (touch1 + touch2) * 0.5;
+            let previous_midpoint = (previous_touch1 + previous_touch2) * 0.5;
+            self.target_translation +=
+                (midpoint - previous_midpoint) * self.camera_settings.touch_pan_sensitivity;
+
+            // Zoom the camera target about the midpoint by how much the distance between the touches changed.
+            let previous_distance = previous_touch1.distance(previous_touch2);
+            if previous_distance > 0.0 {
+                let ratio = touch1.distance(touch2) / previous_distance;
+                let factor =
+                    (1.0 + (ratio - 1.0) * self.camera_settings.pinch_sensitivity).max(0.1);
+                self.zoom_about(app, midpoint, factor);
+            }
+        }
+
+        // Store the touches for the next frame.
+        self.previous_touches = Some((touch1, touch2));
     }
 
     // Update the camera.
     pub fn update(&mut self, app: &mut App) {
-        // If the left mouse button is down, pan the camera.
+        // Get the time since the last frame and the pixel density of the window.
+        let delta_time = app.timer.delta_f32();
+        self.dpi = app.window().dpi() as f32;
+
+        // Stop animating if the camera is moved by hand, which the rest of this update does to the camera target.
+        let manual = app.mouse.is_down(MouseButton::Right)
+            || app.mouse.is_scrolling()
+            || app.touch.down.len() >= 2;
+        if manual {
+            self.animation = None;
+        }
+
+        // If the right mouse button is down, pan the camera target and measure the pan velocity.
         if app.mouse.is_down(MouseButton::Right) {
-            self.translation += DVec2::from(app.mouse.motion_delta).as_vec2()
+            let pan = DVec2::from(app.mouse.motion_delta).as_vec2()
                 * self.camera_settings.pan_sensitivity;
+            self.target_translation += pan;
+            if delta_time > 0.0 {
+                self.pan_velocity = pan / delta_time;
+            }
+        } else if self.camera_settings.inertia {
+            // Keep panning the camera target with the remaining momentum, slowing it down with friction.
+            self.target_translation += self.pan_velocity * delta_time;
+            self.pan_velocity *= (-self.camera_settings.friction * delta_time).exp();
+        } else {
+            // Stop panning if inertia is disabled.
+            self.pan_velocity = Vec2::ZERO;
         }
 
-        // If the mouse is scrolling, zoom the camera.
+        // If the mouse is scrolling, zoom the camera target.
         if app.mouse.is_scrolling() {
-            self.scale *= app.mouse.wheel_delta.y * 0.5 + 1.0;
+            self.target_scale *=
+                (app.mouse.wheel_delta.y * 0.5 * self.camera_settings.zoom_sensitivity + 1.0)
+                    .max(0.1);
+        }
+
+        // Pan and zoom the camera target with two finger gestures.
+        self.update_touches(app);
+
+        // Advance the animation of the camera target and move the camera with it directly, since the animation is already smooth.
+        if let Some(animation) = &mut self.animation {
+            animation.elapsed += delta_time;
+            (self.target_translation, self.target_scale) = animation.current();
+            self.translation = self.target_translation;
+            self.scale = self.target_scale;
+            if animation.finished() {
+                self.animation = None;
+            }
+        } else if self.camera_settings.smoothing {
+            // Move the camera towards its target smoothly.
+            let blend = 1.0 - (-self.camera_settings.damping * delta_time).exp();
+            self.translation = self.translation.lerp(self.target_translation, blend);
+            self.scale += (self.target_scale - self.scale) * blend;
+        } else {
+            self.translation = self.target_translation;
+            self.scale = self.target_scale;
         }
     }
 }
@@ -0,0 +1,303 @@
+use crate::generation::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+// The directory that checkpoints are written to.
+const CHECKPOINT_DIRECTORY: &str = "checkpoints";
+// The file that the long-run settings are stored in.
+const LONG_RUN_PATH: &str = "config/long_run.json";
+
+// A checkpoint of a long run, with the universe, the generation settings whose seeds fully determine the random state of the run since the simulation itself draws no random numbers, and the real time spent running so far.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub universe: Universe,
+    pub generation_settings: GenerationSettings,
+    pub run_seconds: f64,
+}
+
+// Implementations for Checkpoint.
+impl Checkpoint {
+    // Save a checkpoint to a gzip compressed JSON file at the given path, writing to a temporary file first so a crash can't leave a partial checkpoint.
+    fn save(&self, path: &Path) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the checkpoint through the encoder into a temporary file.
+        let temporary_path = path.with_extension("tmp");
+        let file = File::create(&temporary_path).map_err(|error| error.to_string())?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut encoder, self).map_err(|error| error.to_string())?;
+        encoder.finish().map_err(|error| error.to_string())?;
+
+        // Replace the file with the temporary file.
+        fs::rename(&temporary_path, path).map_err(|error| error.to_string())
+    }
+
+    // Load a checkpoint from a gzip compressed JSON file at the given path.
+    fn load(path: &Path) -> Result<Self, String> {
+        // Read the file through the decoder and deserialize the checkpoint.
+        let file = File::open(path).map_err(|error| error.to_string())?;
+        serde_json::from_reader(GzDecoder::new(BufReader::new(file)))
+            .map_err(|error| error.to_string())
+    }
+}
+
+// Settings for the long-run mode, which are remembered in the config so a run resumes after a restart.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LongRunSettings {
+    pub enabled: bool,
+    pub interval_minutes: f64,
+    pub checkpoint_amount: usize,
+    pub resume_on_startup: bool,
+}
+
+// Default value for LongRunSettings.
+impl Default for LongRunSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 10.0,
+            checkpoint_amount: 5,
+            resume_on_startup: true,
+        }
+    }
+}
+
+// A long-run mode that periodically writes numbered, compressed checkpoints and keeps the latest few, so a run of many hours can resume from the latest one after the app is restarted.
+pub struct LongRun {
+    pub long_run_settings: LongRunSettings,
+    pub run_seconds: f64,
+    pub status: String,
+    started: bool,
+    last_checkpoint: Instant,
+}
+
+// Default value for LongRun.
+impl Default for LongRun {
+    fn default() -> Self {
+        // Load the settings from the config, starting with the defaults if there are none.
+        let long_run_settings = fs::read_to_string(LONG_RUN_PATH)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            long_run_settings,
+            run_seconds: 0.0,
+            status: tr("No checkpoint this session").to_string(),
+            started: false,
+            last_checkpoint: Instant::now(),
+        }
+    }
+}
+
+// Implementations for LongRun.
+impl LongRun {
+    // Return the numbered checkpoint files, sorted from oldest to newest.
+    fn checkpoints() -> Vec<(u64, PathBuf)> {
+        let mut checkpoints: Vec<(u64, PathBuf)> = fs::read_dir(CHECKPOINT_DIRECTORY)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?;
+                let number = name
+                    .strip_prefix("checkpoint_")?
+                    .strip_suffix(".json.gz")?
+                    .parse()
+                    .ok()?;
+                Some((number, path))
+            })
+            .collect();
+        checkpoints.sort_by_key(|(number, _)| *number);
+        checkpoints
+    }
+
+    // Save the settings to the config.
+    fn write_settings(&self) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = Path::new(LONG_RUN_PATH).parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the settings and write them to the file.
+        let json = serde_json::to_string_pretty(&self.long_run_settings)
+            .map_err(|error| error.to_string())?;
+        fs::write(LONG_RUN_PATH, json).map_err(|error| error.to_string())
+    }
+
+    // Update the long-run mode, resuming from the latest checkpoint on the first update if that's enabled, counting the real time the universe runs, and writing a checkpoint once the interval has passed.
+    pub fn update(
+        &mut self,
+        universe: &mut Universe,
+        generation_settings: &mut GenerationSettings,
+        delta_time: f64,
+    ) -> bool {
+        // Resume once at startup, returning whether the universe was replaced.
+        let mut resumed = false;
+        if !self.started {
+            self.started = true;
+            if self.long_run_settings.enabled && self.long_run_settings.resume_on_startup {
+                resumed = self.resume(universe, generation_settings);
+            }
+        }
+        if !self.long_run_settings.enabled {
+            return resumed;
+        }
+
+        // Count the time spent running, and write a checkpoint once the interval has passed.
+        if !universe.paused {
+            self.run_seconds += delta_time;
+        }
+        if !universe.bodies.is_empty()
+            && self.last_checkpoint.elapsed().as_secs_f64()
+                >= self.long_run_settings.interval_minutes * 60.0
+        {
+            self.checkpoint(universe, generation_settings);
+        }
+        resumed
+    }
+
+    // Write a checkpoint with the next number, deleting the oldest ones beyond the amount to keep.
+    pub fn checkpoint(&mut self, universe: &Universe, generation_settings: &GenerationSettings) {
+        self.last_checkpoint = Instant::now();
+        let checkpoints = Self::checkpoints();
+        let number = checkpoints.last().map_or(0, |(number, _)| number + 1);
+        let path = Path::new(CHECKPOINT_DIRECTORY).join(format!("checkpoint_{}.json.gz", number));
+        let checkpoint = Checkpoint {
+            universe: universe.clone(),
+            generation_settings: generation_settings.clone(),
+            run_seconds: self.run_seconds,
+        };
+        self.status = match checkpoint.save(&path) {
+            Ok(()) => tr_format("Wrote checkpoint {}", &[&path.display()]),
+            Err(error) => tr_format("Writing the checkpoint failed: {}", &[&error]),
+        };
+
+        // Delete the oldest checkpoints, counting the new one.
+        let amount = self.long_run_settings.checkpoint_amount.max(1);
+        let excess = (checkpoints.len() + 1).saturating_sub(amount);
+        for (_, path) in checkpoints.iter().take(excess) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    // Replace the universe and generation settings with the latest checkpoint, returning whether it was loaded.
+    pub fn resume(
+        &mut self,
+        universe: &mut Universe,
+        generation_settings: &mut GenerationSettings,
+    ) -> bool {
+        let Some((_, path)) = Self::checkpoints().pop() else {
+            self.status = tr("No checkpoint found").to_string();
+            return false;
+        };
+        match Checkpoint::load(&path) {
+            Ok(checkpoint) => {
+                *universe = checkpoint.universe;
+                *generation_settings = checkpoint.generation_settings;
+                self.run_seconds = checkpoint.run_seconds;
+                self.last_checkpoint = Instant::now();
+                self.status = tr_format("Resumed from {}", &[&path.display()]);
+                true
+            }
+            Err(error) => {
+                self.status = tr_format("Resuming failed: {}", &[&error]);
+                false
+            }
+        }
+    }
+
+    // Show the long-run mode in the given UI, returning whether the universe was replaced by a checkpoint.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        universe: &mut Universe,
+        generation_settings: &mut GenerationSettings,
+    ) -> bool {
+        // Create widgets to modify the settings, saving them to the config when they change.
+        let long_run_settings = &mut self.long_run_settings;
+        let label_id = ui.label(tr("Enable Long Run")).id;
+        let mut changed = ui
+            .add(Checkbox::new(&mut long_run_settings.enabled, ""))
+            .labelled_by(label_id)
+            .changed();
+        ui.end_row();
+        let label_id = ui.label(tr("Interval (Minutes)")).id;
+        changed |= ui
+            .add(
+                DragValue::new(&mut long_run_settings.interval_minutes)
+                    .speed(0.1)
+                    .clamp_range(0.1..=f64::MAX),
+            )
+            .labelled_by(label_id)
+            .changed();
+        ui.end_row();
+        let label_id = ui.label(tr("Checkpoints Kept")).id;
+        changed |= ui
+            .add(
+                DragValue::new(&mut long_run_settings.checkpoint_amount)
+                    .clamp_range(1..=usize::MAX),
+            )
+            .labelled_by(label_id)
+            .changed();
+        ui.end_row();
+        let label_id = ui.label(tr("Resume on Startup")).id;
+        changed |= ui
+            .add(Checkbox::new(&mut long_run_settings.resume_on_startup, ""))
+            .labelled_by(label_id)
+            .changed();
+        ui.end_row();
+        if changed {
+            if let Err(error) = self.write_settings() {
+                self.status = tr_format("Saving the settings failed: {}", &[&error]);
+            }
+        }
+
+        // Show the real and simulation time the run has accumulated.
+        let hours = (self.run_seconds / 3600.0).floor();
+        let minutes = (self.run_seconds / 60.0 % 60.0).floor();
+        let seconds = (self.run_seconds % 60.0).floor();
+        ui.label(tr_format(
+            "Running for {}:{}:{}, simulated {}",
+            &[
+                &hours,
+                &format!("{:02}", minutes),
+                &format!("{:02}", seconds),
+                &universe
+                    .universe_settings
+                    .units
+                    .format(Quantity::Time, universe.simulation_time),
+            ],
+        ));
+        ui.end_row();
+
+        // Create buttons to write a checkpoint now and to resume from the latest one.
+        let mut resumed = false;
+        ui.horizontal(|ui| {
+            if ui.button(tr("Write Checkpoint")).clicked() {
+                self.checkpoint(universe, generation_settings);
+            }
+            if ui.button(tr("Resume Latest Checkpoint")).clicked() {
+                resumed = self.resume(universe, generation_settings);
+            }
+        });
+        ui.end_row();
+        ui.label(&self.status);
+        ui.end_row();
+        resumed
+    }
+}
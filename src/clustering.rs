@@ -0,0 +1,344 @@
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{
+    math::DVec2,
+    random::{rand::Rng, utils::Random},
+};
+use notan_egui::*;
+
+// The maximum amount of points kept in the clustering history before it's thinned out.
+const HISTORY_LENGTH: usize = 1000;
+
+// A statistic of how clustered the positions of the bodies are.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ClusteringStatistic {
+    NearestNeighbor,
+    TwoPointCorrelation,
+}
+
+// Implementations for ClusteringStatistic.
+impl ClusteringStatistic {
+    // All clustering statistics, in the order they are listed in the UI.
+    pub const ALL: [ClusteringStatistic; 2] = [
+        ClusteringStatistic::NearestNeighbor,
+        ClusteringStatistic::TwoPointCorrelation,
+    ];
+
+    // Return the name of a clustering statistic.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClusteringStatistic::NearestNeighbor => "Nearest Neighbor Distances",
+            ClusteringStatistic::TwoPointCorrelation => "Two-Point Correlation",
+        }
+    }
+}
+
+// An analysis of how clustered the bodies are, as a histogram of nearest neighbor distances or the two-point correlation function, along with the Clark-Evans ratio of the mean nearest neighbor distance to that of randomly placed bodies, tracked over the run.
+pub struct ClusteringAnalysis {
+    pub statistic: ClusteringStatistic,
+    pub bin_count: usize,
+    pub sample_limit: usize,
+    pub live: bool,
+    pub interval: f32,
+    pub timer: f32,
+    pub analyze_requested: bool,
+    pub values: Vec<f64>,
+    pub maximum_separation: f64,
+    pub clark_evans_ratio: Option<f64>,
+    pub history: Vec<[f64; 2]>,
+}
+
+// Default value for ClusteringAnalysis.
+impl Default for ClusteringAnalysis {
+    fn default() -> Self {
+        Self {
+            statistic: ClusteringStatistic::NearestNeighbor,
+            bin_count: 30,
+            sample_limit: 1500,
+            live: false,
+            interval: 2.0,
+            timer: 0.0,
+            analyze_requested: false,
+            values: Vec::new(),
+            maximum_separation: 0.0,
+            clark_evans_ratio: None,
+            history: Vec::new(),
+        }
+    }
+}
+
+// Return the distance from each position to its nearest other position.
+fn nearest_neighbor_distances(positions: &[DVec2]) -> Vec<f64> {
+    positions
+        .iter()
+        .enumerate()
+        .map(|(i, position)| {
+            positions
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other)| position.distance_squared(*other))
+                .fold(f64::INFINITY, f64::min)
+                .sqrt()
+        })
+        .collect()
+}
+
+// Count the pairs of positions whose separations fall into each of the given amount of bins up to the maximum separation.
+fn pair_counts(positions: &[DVec2], bin_count: usize, maximum_separation: f64) -> Vec<f64> {
+    let mut counts = vec![0.0; bin_count];
+    for (i, position) in positions.iter().enumerate() {
+        for other in positions[i + 1..].iter() {
+            let separation = position.distance(*other);
+            if separation < maximum_separation {
+                counts[(separation / maximum_separation * bin_count as f64) as usize] += 1.0;
+            }
+        }
+    }
+    counts
+}
+
+// Implementations for ClusteringAnalysis.
+impl ClusteringAnalysis {
+    // Update the analysis using the time since the last frame, analyzing once per interval while it's live or when it's requested.
+    pub fn update(&mut self, universe: &Universe, delta_time: f32) {
+        self.timer -= delta_time;
+        if !(self.analyze_requested || (self.live && self.timer <= 0.0)) {
+            return;
+        }
+        self.analyze_requested = false;
+        self.timer = self.interval;
+        self.analyze(universe);
+    }
+
+    // Analyze the positions of the moving bodies, spreading the samples evenly over them if there are more than the limit.
+    fn analyze(&mut self, universe: &Universe) {
+        // Start the history over if the simulation time went backwards, since the bodies were replaced.
+        let time = universe.simulation_time;
+        if self.history.last().is_some_and(|point| time < point[0]) {
+            self.history.clear();
+        }
+
+        // Sample the positions, skipping the analysis if there aren't enough for pairs.
+        let moving = universe.bodies.iter().filter(|body| !body.frozen);
+        let stride = moving.clone().count().div_ceil(self.sample_limit.max(2));
+        let positions: Vec<DVec2> = moving
+            .step_by(stride.max(1))
+            .map(|body| body.position)
+            .collect();
+        if positions.len() < 2 || self.bin_count == 0 {
+            self.values.clear();
+            self.clark_evans_ratio = None;
+            return;
+        }
+
+        // Find the bounding box of the samples, which randomly placed bodies are compared against.
+        let (minimum, maximum) = positions.iter().fold(
+            (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)),
+            |(minimum, maximum), position| (minimum.min(*position), maximum.max(*position)),
+        );
+        let size = (maximum - minimum).max(DVec2::splat(f64::EPSILON));
+
+        // Find the Clark-Evans ratio, which is below 1 for clustered bodies and above 1 for evenly spread bodies, and record it in the history.
+        let distances = nearest_neighbor_distances(&positions);
+        let mean_distance = distances.iter().sum::<f64>() / distances.len() as f64;
+        let density = positions.len() as f64 / (size.x * size.y);
+        let clark_evans_ratio = mean_distance / (0.5 / density.sqrt());
+        self.clark_evans_ratio = Some(clark_evans_ratio);
+        if self.history.last().is_none_or(|point| time > point[0]) {
+            self.history.push([time, clark_evans_ratio]);
+            if self.history.len() > HISTORY_LENGTH {
+                self.history = self.history.iter().step_by(2).copied().collect();
+            }
+        }
+
+        match self.statistic {
+            // Count the nearest neighbor distances in bins up to the largest one, as fractions of the samples.
+            ClusteringStatistic::NearestNeighbor => {
+                self.maximum_separation = distances
+                    .iter()
+                    .fold(f64::EPSILON, |maximum, distance| maximum.max(*distance))
+                    * (1.0 + f64::EPSILON);
+                let mut values = vec![0.0; self.bin_count];
+                for distance in distances.iter() {
+                    let bin = (distance / self.maximum_separation * self.bin_count as f64) as usize;
+                    values[bin.min(self.bin_count - 1)] += 1.0 / distances.len() as f64;
+                }
+                self.values = values;
+            }
+            // Estimate the correlation function by comparing the pair counts of the samples to those of as many bodies placed randomly in the bounding box, up to a quarter of its diagonal.
+            ClusteringStatistic::TwoPointCorrelation => {
+                self.maximum_separation = size.length() * 0.25;
+                let mut rng = Random::new(1);
+                let random_positions: Vec<DVec2> = (0..positions.len())
+                    .map(|_| {
+                        minimum
+                            + size * DVec2::new(rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0))
+                    })
+                    .collect();
+                let data_pairs = pair_counts(&positions, self.bin_count, self.maximum_separation);
+                let random_pairs =
+                    pair_counts(&random_positions, self.bin_count, self.maximum_separation);
+                self.values = data_pairs
+                    .iter()
+                    .zip(random_pairs.iter())
+                    .map(|(data, random)| {
+                        if *random > 0.0 {
+                            data / random - 1.0
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    // Show the analysis in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe) {
+        // Create a combo box to select the statistic, analyzing again if it changes.
+        let label_id = ui.label(tr("Statistic")).id;
+        let previous_statistic = self.statistic;
+        ComboBox::from_id_source("Clustering Statistic")
+            .selected_text(tr(self.statistic.name()))
+            .show_ui(ui, |ui| {
+                for statistic in ClusteringStatistic::ALL {
+                    ui.selectable_value(&mut self.statistic, statistic, tr(statistic.name()));
+                }
+            })
+            .response
+            .labelled_by(label_id);
+        if self.statistic != previous_statistic {
+            self.analyze_requested = true;
+        }
+        ui.end_row();
+
+        // Create drag values to modify the amount of bins and the most bodies sampled, a checkbox and slider to analyze periodically, and a button to analyze now.
+        let label_id = ui.label(tr("Bins")).id;
+        ui.add(DragValue::new(&mut self.bin_count).clamp_range(1..=200))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Sample Limit")).id;
+        ui.add(DragValue::new(&mut self.sample_limit).clamp_range(2..=10000))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Live")).id;
+        ui.add(Checkbox::new(&mut self.live, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Update Interval")).id;
+        ui.add(Slider::new(&mut self.interval, 0.1..=10.0).suffix(" s"))
+            .labelled_by(label_id);
+        ui.end_row();
+        if ui.button(tr("Analyze Now")).clicked() {
+            self.analyze_requested = true;
+        }
+        ui.end_row();
+
+        // Show the Clark-Evans ratio of the last analysis.
+        let Some(clark_evans_ratio) = self.clark_evans_ratio else {
+            ui.label(tr("Not analyzed yet"));
+            ui.end_row();
+            return;
+        };
+        ui.label(tr_format(
+            "Clark-Evans Ratio: {} (below 1 is clustered)",
+            &[&format!("{:.3}", clark_evans_ratio)],
+        ));
+        ui.end_row();
+
+        // Allocate space for the statistic.
+        let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 140.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        let color = Color32::from_rgb(120, 200, 120);
+        let width = rect.width() / self.values.len().max(1) as f32;
+        match self.statistic {
+            // Draw the histogram as bars scaled to the fullest bin.
+            ClusteringStatistic::NearestNeighbor => {
+                let maximum = self.values.iter().fold(f64::EPSILON, |a, b| a.max(*b));
+                for (index, value) in self.values.iter().enumerate() {
+                    let height = (value / maximum) as f32 * rect.height();
+                    painter.rect_filled(
+                        Rect::from_min_max(
+                            pos2(rect.left() + index as f32 * width, rect.bottom() - height),
+                            pos2(
+                                rect.left() + (index + 1) as f32 * width - 1.0,
+                                rect.bottom(),
+                            ),
+                        ),
+                        0.0,
+                        color,
+                    );
+                }
+            }
+            // Draw the correlation function as a line scaled to fit, with a line at zero for no correlation.
+            ClusteringStatistic::TwoPointCorrelation => {
+                let (minimum, maximum) = self
+                    .values
+                    .iter()
+                    .fold((0.0f64, f64::EPSILON), |(minimum, maximum), value| {
+                        (minimum.min(*value), maximum.max(*value))
+                    });
+                let to_y = |value: f64| {
+                    rect.bottom() - ((value - minimum) / (maximum - minimum)) as f32 * rect.height()
+                };
+                painter.line_segment(
+                    [pos2(rect.left(), to_y(0.0)), pos2(rect.right(), to_y(0.0))],
+                    Stroke::new(1.0, ui.visuals().weak_text_color()),
+                );
+                let points: Vec<Pos2> = self
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        pos2(rect.left() + (index as f32 + 0.5) * width, to_y(*value))
+                    })
+                    .collect();
+                painter.add(Shape::line(points, Stroke::new(1.5, color)));
+                ui.label(tr_format(
+                    "Correlation: {} to {}",
+                    &[&format!("{:.2}", minimum), &format!("{:.2}", maximum)],
+                ));
+            }
+        }
+        let units = &universe.universe_settings.units;
+        ui.label(tr_format(
+            "Separation: 0 to {}",
+            &[&units.format(Quantity::Length, self.maximum_separation)],
+        ));
+        ui.end_row();
+
+        // Draw the history of the Clark-Evans ratio over the run, with a line at 1 for randomly placed bodies.
+        ui.label(tr("Clark-Evans Ratio Over Time"));
+        let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 80.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        if let (Some(first), Some(last)) = (self.history.first(), self.history.last()) {
+            let duration = (last[0] - first[0]).max(f64::EPSILON);
+            let maximum = self
+                .history
+                .iter()
+                .fold(1.0f64, |maximum, point| maximum.max(point[1]))
+                * 1.1;
+            let to_screen = |time: f64, ratio: f64| {
+                pos2(
+                    rect.left() + ((time - first[0]) / duration) as f32 * rect.width(),
+                    rect.bottom() - (ratio / maximum) as f32 * rect.height(),
+                )
+            };
+            painter.line_segment(
+                [to_screen(first[0], 1.0), to_screen(last[0], 1.0)],
+                Stroke::new(1.0, ui.visuals().weak_text_color()),
+            );
+            let points: Vec<Pos2> = self
+                .history
+                .iter()
+                .map(|point| to_screen(point[0], point[1]))
+                .collect();
+            painter.add(Shape::line(points, Stroke::new(1.0, Color32::LIGHT_BLUE)));
+        }
+        ui.end_row();
+    }
+}
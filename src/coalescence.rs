@@ -0,0 +1,164 @@
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan_egui::*;
+use std::collections::VecDeque;
+
+// The maximum amount of points kept in the growth curve before it's thinned out.
+const GROWTH_LENGTH: usize = 1000;
+// The amount of bodies shown in the mass rank table.
+const RANK_COUNT: usize = 10;
+// The amount of recent mergers the recent merger rate is measured over.
+const RATE_MERGERS: usize = 20;
+
+// Statistics about how bodies coalesce through mergers over a run.
+#[derive(Default)]
+pub struct CoalescenceStatistics {
+    pub growth: Vec<[f64; 2]>,
+    pub merger_times: VecDeque<f64>,
+}
+
+// Implementations for CoalescenceStatistics.
+impl CoalescenceStatistics {
+    // Update the statistics with the last step of the universe.
+    pub fn update(&mut self, universe: &Universe) {
+        // Start over if the simulation time went backwards, since the bodies were replaced.
+        let time = universe.simulation_time;
+        if self.growth.last().is_some_and(|point| time < point[0]) {
+            self.growth.clear();
+            self.merger_times.clear();
+        }
+
+        // Record when each merger of the last step happened.
+        for _ in universe.collision_events.iter() {
+            self.merger_times.push_back(time);
+        }
+        while self.merger_times.len() > RATE_MERGERS {
+            self.merger_times.pop_front();
+        }
+
+        // Record the mass of the largest body once per step, thinning out the curve by half when it's full.
+        if self.growth.last().is_some_and(|point| time == point[0]) {
+            return;
+        }
+        let largest_mass = universe
+            .bodies
+            .iter()
+            .filter(|body| body.is_massive())
+            .map(|body| body.mass)
+            .fold(0.0, f64::max);
+        self.growth.push([time, largest_mass]);
+        if self.growth.len() > GROWTH_LENGTH {
+            self.growth = self.growth.iter().step_by(2).copied().collect();
+        }
+    }
+
+    // Show the statistics in the given UI.
+    pub fn show(&self, ui: &mut Ui, universe: &Universe) {
+        let units = &universe.universe_settings.units;
+        let mergers = universe.run_statistics.mergers;
+        let time_unit = match units.symbol(Quantity::Time) {
+            "" => tr("time unit"),
+            symbol => symbol,
+        };
+
+        // Show the total mergers and the mean and recent merger rates per unit of simulated time.
+        ui.label(format!("{}: {}", tr("Total Mergers"), mergers));
+        if universe.simulation_time > 0.0 {
+            ui.label(tr_format(
+                "Mean Rate: {} per {}",
+                &[
+                    &format!(
+                        "{:.3}",
+                        mergers as f64
+                            / units.display_value(Quantity::Time, universe.simulation_time)
+                    ),
+                    &time_unit,
+                ],
+            ));
+        }
+        if let (Some(first), Some(last)) = (self.merger_times.front(), self.merger_times.back()) {
+            if last > first {
+                ui.label(tr_format(
+                    "Recent Rate: {} per {}",
+                    &[
+                        &format!(
+                            "{:.3}",
+                            (self.merger_times.len() - 1) as f64
+                                / units.display_value(Quantity::Time, last - first)
+                        ),
+                        &time_unit,
+                    ],
+                ));
+            }
+        }
+        ui.end_row();
+
+        // Allocate space for the growth curve of the largest body.
+        ui.label(tr("Largest Body Mass"));
+        let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 120.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        // Draw the growth curve scaled to fit, starting the mass axis at zero.
+        if let (Some(first), Some(last)) = (self.growth.first(), self.growth.last()) {
+            let duration = (last[0] - first[0]).max(f64::EPSILON);
+            let maximum_mass = self
+                .growth
+                .iter()
+                .map(|point| point[1])
+                .fold(f64::EPSILON, f64::max);
+            let points: Vec<Pos2> = self
+                .growth
+                .iter()
+                .map(|point| {
+                    pos2(
+                        rect.left() + ((point[0] - first[0]) / duration) as f32 * rect.width(),
+                        rect.bottom() - (point[1] / maximum_mass) as f32 * rect.height(),
+                    )
+                })
+                .collect();
+            painter.add(Shape::line(points, Stroke::new(1.0, Color32::LIGHT_BLUE)));
+            ui.label(tr_format(
+                "From {} to {}",
+                &[
+                    &units.format(Quantity::Mass, first[1]),
+                    &units.format(Quantity::Mass, last[1]),
+                ],
+            ));
+        }
+        ui.end_row();
+
+        // Find the most massive bodies, sorted from most to least massive, and their total mass.
+        let mut ranked: Vec<(usize, f64)> = universe
+            .bodies
+            .iter()
+            .enumerate()
+            .filter(|(_, body)| body.is_massive())
+            .map(|(index, body)| (index, body.mass))
+            .collect();
+        let total_mass = ranked
+            .iter()
+            .map(|(_, mass)| mass)
+            .sum::<f64>()
+            .max(f64::EPSILON);
+        ranked.sort_unstable_by(|(_, mass1), (_, mass2)| mass2.total_cmp(mass1));
+        ranked.truncate(RANK_COUNT);
+
+        // Show the most massive bodies in a table with their share of the total mass.
+        Grid::new("Mass Rank").striped(true).show(ui, |ui| {
+            ui.label(tr("Rank"));
+            ui.label(tr("Body"));
+            ui.label(tr("Mass"));
+            ui.label(tr("Share"));
+            ui.end_row();
+            for (rank, (index, mass)) in ranked.iter().enumerate() {
+                ui.label(format!("{}", rank + 1));
+                ui.label(format!("{}", index));
+                ui.label(units.format(Quantity::Mass, *mass));
+                ui.label(format!("{:.1}%", mass / total_mass * 100.0));
+                ui.end_row();
+            }
+        });
+    }
+}
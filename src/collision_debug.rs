@@ -0,0 +1,156 @@
+use crate::camera::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{draw::*, math::DVec2, prelude::*};
+use notan_egui::*;
+use std::collections::VecDeque;
+
+// The maximum amount of merges kept in the log.
+const MERGE_LOG_LENGTH: usize = 50;
+// The length in pixels of the contact normal drawn at each overlap.
+const NORMAL_LENGTH: f32 = 16.0;
+
+// A merge recorded in the log with when and where it happened.
+pub struct MergeRecord {
+    pub time: f64,
+    pub position: DVec2,
+    pub masses: [f64; 2],
+}
+
+// A pair of bodies whose collision radii overlap, with the contact point and the normal from the first body to the second.
+pub struct Overlap {
+    pub bodies: [usize; 2],
+    pub contact: DVec2,
+    pub normal: DVec2,
+}
+
+// A debug overlay that highlights overlapping bodies and logs recent merges.
+#[derive(Default)]
+pub struct CollisionDebugger {
+    pub show_overlaps: bool,
+    pub overlaps: Vec<Overlap>,
+    pub merge_log: VecDeque<MergeRecord>,
+}
+
+// Implementations for CollisionDebugger.
+impl CollisionDebugger {
+    // Update the collision debugger.
+    pub fn update(&mut self, universe: &Universe) {
+        // Log the merges of the last step, removing the oldest ones if there are too many.
+        for collision_event in universe.collision_events.iter() {
+            self.merge_log.push_front(MergeRecord {
+                time: universe.simulation_time,
+                position: collision_event.position,
+                masses: collision_event.masses,
+            });
+        }
+        self.merge_log.truncate(MERGE_LOG_LENGTH);
+
+        // Only look for overlaps if they're shown.
+        self.overlaps.clear();
+        if !self.show_overlaps {
+            return;
+        }
+
+        // Find each pair of massive bodies whose radii overlap, the same way collisions are checked.
+        let bodies = &universe.bodies;
+        for i in 0..bodies.len() {
+            if !bodies[i].is_massive() {
+                continue;
+            }
+            for j in (i + 1)..bodies.len() {
+                if !bodies[j].is_massive() {
+                    continue;
+                }
+                let offset = bodies[j].position - bodies[i].position;
+                if offset.length() <= bodies[i].radius() + bodies[j].radius() {
+                    // Find the normal between the bodies, picking an arbitrary one if they're at the same position.
+                    let normal = offset.try_normalize().unwrap_or(DVec2::X);
+                    self.overlaps.push(Overlap {
+                        bodies: [i, j],
+                        contact: bodies[i].position + normal * bodies[i].radius(),
+                        normal,
+                    });
+                }
+            }
+        }
+    }
+
+    // Draw an outline around each overlapping body and the contact normal between them.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera, universe: &Universe) {
+        for overlap in self.overlaps.iter() {
+            // Skip overlaps whose bodies were removed since they were found.
+            let (Some(body1), Some(body2)) = (
+                universe.bodies.get(overlap.bodies[0]),
+                universe.bodies.get(overlap.bodies[1]),
+            ) else {
+                continue;
+            };
+
+            // Draw an outline around both bodies.
+            for body in [body1, body2] {
+                draw.circle(body.radius() as f32 + 2.0 / camera.scale)
+                    .position(body.position.x as f32, body.position.y as f32)
+                    .tolerance(camera.circle_tolerance())
+                    .stroke(1.0 / camera.scale)
+                    .color(Color::ORANGE);
+            }
+
+            // Draw the contact normal from the contact point.
+            let end = overlap.contact + overlap.normal * (NORMAL_LENGTH / camera.scale) as f64;
+            draw.line(
+                (overlap.contact.x as f32, overlap.contact.y as f32),
+                (end.x as f32, end.y as f32),
+            )
+            .width(1.0 / camera.scale)
+            .color(Color::MAGENTA);
+        }
+    }
+
+    // Show the settings of the collision debugger and the merge log in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, camera: &mut Camera, universe: &Universe) {
+        // Create a checkbox to toggle the overlap overlay.
+        let label_id = ui.label(tr("Show Overlaps")).id;
+        ui.add(Checkbox::new(&mut self.show_overlaps, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        if self.show_overlaps {
+            ui.label(format!(
+                "{}: {}",
+                tr("Overlapping Pairs"),
+                self.overlaps.len()
+            ));
+            ui.end_row();
+        }
+
+        // Show the recent merges, each with a button to center the camera where it happened.
+        ui.label(format!("{}: {}", tr("Recent Merges"), self.merge_log.len()));
+        let units = &universe.universe_settings.units;
+        ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+            for merge_record in self.merge_log.iter() {
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Go To")).clicked() {
+                        camera.center_on(merge_record.position);
+                    }
+                    ui.label(tr_format(
+                        "{}: {} + {} at ({}, {})",
+                        &[
+                            &units.format(Quantity::Time, merge_record.time),
+                            &units.format(Quantity::Mass, merge_record.masses[0]),
+                            &units.format(Quantity::Mass, merge_record.masses[1]),
+                            &units.format(Quantity::Length, merge_record.position.x),
+                            &units.format(Quantity::Length, merge_record.position.y),
+                        ],
+                    ));
+                });
+            }
+        });
+
+        // Create a button to clear the merge log.
+        if ui.button(tr("Clear Log")).clicked() {
+            self.merge_log.clear();
+        }
+        ui.end_row();
+    }
+}
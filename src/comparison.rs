@@ -0,0 +1,166 @@
+use crate::body::*;
+use crate::camera::*;
+use crate::locale::*;
+use crate::profiler::*;
+use crate::universe::*;
+use notan::{draw::*, math::DVec2, prelude::*};
+use notan_egui::*;
+use std::collections::VecDeque;
+
+// The maximum amount of positions stored in each path.
+const PATH_LENGTH: usize = 2000;
+
+// A copy of the universe simulated without the post-Newtonian correction, whose paths are drawn over the real ones to show the precession it causes.
+pub struct NewtonianComparison {
+    pub enabled: bool,
+    pub tracked_bodies: usize,
+    pub shadow: Option<Universe>,
+    pub body_count: usize,
+    pub simulation_time: f64,
+    pub tracked: Vec<BodyId>,
+    pub paths: Vec<VecDeque<DVec2>>,
+    pub shadow_paths: Vec<VecDeque<DVec2>>,
+}
+
+// Default value for NewtonianComparison.
+impl Default for NewtonianComparison {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tracked_bodies: 4,
+            shadow: None,
+            body_count: 0,
+            simulation_time: 0.0,
+            tracked: Vec::new(),
+            paths: Vec::new(),
+            shadow_paths: Vec::new(),
+        }
+    }
+}
+
+// Implementations for NewtonianComparison.
+impl NewtonianComparison {
+    // Start over from a copy of the universe without the post-Newtonian correction.
+    fn reset(&mut self, universe: &Universe) {
+        let mut shadow = universe.clone();
+        shadow.universe_settings.enable_relativity = false;
+        shadow.universe_settings.reorder_interval = 0;
+        self.shadow = Some(shadow);
+        self.body_count = universe.bodies.len();
+        self.simulation_time = universe.simulation_time;
+        self.tracked = universe
+            .bodies
+            .iter()
+            .take(self.tracked_bodies)
+            .map(|body| body.id)
+            .collect();
+        self.paths = vec![VecDeque::new(); self.tracked.len()];
+        self.shadow_paths = vec![VecDeque::new(); self.tracked.len()];
+    }
+
+    // Update the comparison, stepping the copy by as much time as the universe advanced and recording the paths of both in the reference frame of the camera.
+    pub fn update(&mut self, camera: &Camera, universe: &Universe) {
+        // Only compare while enabled and the correction is on.
+        if !self.enabled || !universe.universe_settings.enable_relativity {
+            self.shadow = None;
+            return;
+        }
+
+        // Start over if there's no copy yet, the bodies or the amount of tracked bodies changed, or the simulation time went backwards.
+        if self.shadow.is_none()
+            || universe.bodies.len() != self.body_count
+            || self.tracked.len() != self.tracked_bodies.min(universe.bodies.len())
+            || universe.simulation_time < self.simulation_time
+        {
+            self.reset(universe);
+        }
+        let Some(shadow) = &mut self.shadow else {
+            return;
+        };
+
+        // Step the copy by the time the universe advanced, keeping its settings in sync apart from the correction.
+        let delta_time = universe.simulation_time - self.simulation_time;
+        if delta_time <= 0.0 {
+            return;
+        }
+        self.simulation_time = universe.simulation_time;
+        shadow.universe_settings = universe.universe_settings.clone();
+        shadow.universe_settings.enable_relativity = false;
+        shadow.universe_settings.reorder_interval = 0;
+
+        shadow.update(delta_time, &mut Profiler::default());
+
+        // Record the positions of the tracked bodies that still exist in both universes, found by id since the bodies of the universe may have been reordered.
+        for (slot, &id) in self.tracked.iter().enumerate() {
+            let (Some(index), Some(shadow_index)) = (universe.index_of(id), shadow.index_of(id))
+            else {
+                continue;
+            };
+            let (body, shadow_body) = (&universe.bodies[index], &shadow.bodies[shadow_index]);
+            for (path, body) in [
+                (&mut self.paths[slot], body),
+                (&mut self.shadow_paths[slot], shadow_body),
+            ] {
+                path.push_back(camera.world_to_frame(body.position));
+                if path.len() > PATH_LENGTH {
+                    path.pop_front();
+                }
+            }
+        }
+    }
+
+    // Draw the corrected paths in orange and the Newtonian paths in light blue.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera) {
+        if self.shadow.is_none() {
+            return;
+        }
+        for (paths, color) in [
+            (&self.paths, Color::from_rgb(1.0, 0.6, 0.2)),
+            (&self.shadow_paths, Color::from_rgb(0.5, 0.8, 1.0)),
+        ] {
+            for path in paths.iter() {
+                // Convert each segment from the reference frame so it's drawn as it was seen.
+                for (position1, position2) in path.iter().zip(path.iter().skip(1)) {
+                    let position1 = camera.frame_to_world(*position1);
+                    let position2 = camera.frame_to_world(*position2);
+                    draw.line(
+                        (position1.x as f32, position1.y as f32),
+                        (position2.x as f32, position2.y as f32),
+                    )
+                    .width(1.0 / camera.scale)
+                    .color(color);
+                }
+            }
+        }
+    }
+
+    // Show the settings of the comparison in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe) {
+        // Create a checkbox to toggle the comparison and a drag value to modify how many bodies have their paths drawn.
+        let label_id = ui.label(tr("Enabled")).id;
+        ui.add(Checkbox::new(&mut self.enabled, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Tracked Bodies")).id;
+        ui.add(DragValue::new(&mut self.tracked_bodies).clamp_range(1..=64))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Explain what the paths are, or that the correction needs to be on.
+        if universe.universe_settings.enable_relativity {
+            ui.colored_label(Color32::from_rgb(255, 153, 51), tr("1PN corrected paths"));
+            ui.colored_label(Color32::from_rgb(128, 204, 255), tr("Newtonian paths"));
+        } else {
+            ui.label(tr(
+                "Enable the 1PN correction in the universe settings to compare.",
+            ));
+        }
+        ui.end_row();
+
+        // Create a button to restart the comparison from the current state.
+        if ui.button(tr("Restart")).clicked() {
+            self.shadow = None;
+        }
+        ui.end_row();
+    }
+}
@@ -0,0 +1,79 @@
+use crate::body::*;
+use crate::thruster::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+// Optional data of one kind attached to bodies by their id, so features only some bodies use don't add a field to every body.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ComponentMap<T> {
+    components: BTreeMap<BodyId, T>,
+}
+
+// Default value for ComponentMap.
+impl<T> Default for ComponentMap<T> {
+    fn default() -> Self {
+        Self {
+            components: BTreeMap::new(),
+        }
+    }
+}
+
+// Implementations for ComponentMap.
+impl<T> ComponentMap<T> {
+    // Return the component of the body with the given id, if it has one.
+    pub fn get(&self, id: BodyId) -> Option<&T> {
+        self.components.get(&id)
+    }
+
+    // Return the component of the body with the given id mutably, if it has one.
+    pub fn get_mut(&mut self, id: BodyId) -> Option<&mut T> {
+        self.components.get_mut(&id)
+    }
+
+    // Attach a component to the body with the given id, replacing the one it had.
+    pub fn insert(&mut self, id: BodyId, component: T) {
+        self.components.insert(id, component);
+    }
+
+    // Detach the component of the body with the given id, returning it.
+    pub fn remove(&mut self, id: BodyId) -> Option<T> {
+        self.components.remove(&id)
+    }
+
+    // Return whether no body has this component.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    // Keep only the components of the bodies with the given ids.
+    fn retain(&mut self, ids: &HashSet<BodyId>) {
+        self.components.retain(|id, _| ids.contains(id));
+    }
+}
+
+// The optional components of the bodies of a universe. A body keeps its components when it's reordered or absorbs another body in a merger, and they're dropped once it's gone.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Components {
+    pub thrusters: ComponentMap<Thruster>,
+    pub tags: ComponentMap<Vec<String>>,
+}
+
+// Implementations for Components.
+impl Components {
+    // Return whether no body has any components.
+    pub fn is_empty(&self) -> bool {
+        self.thrusters.is_empty() && self.tags.is_empty()
+    }
+
+    // Drop the components of bodies that no longer exist.
+    pub fn prune(&mut self, bodies: &[Body]) {
+        if self.is_empty() {
+            return;
+        }
+        let ids: HashSet<BodyId> = bodies.iter().map(|body| body.id).collect();
+        self.thrusters.retain(&ids);
+        self.tags.retain(&ids);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{body::*, locale::*, units::*, universe::*};
+use notan::{
+    math::DVec2,
+    random::{rand::Rng, utils::Random},
+};
+use notan_egui::{
+    text::{CCursor, CCursorRange},
+    *,
+};
+use std::{collections::HashMap, path::Path};
+
+// The maximum amount of lines kept in the output of the console.
+const OUTPUT_LENGTH: usize = 200;
+// The commands the console accepts, which are completed with tab.
+const COMMANDS: [&str; 9] = [
+    "help", "spawn", "set", "save", "load", "clear", "pause", "resume", "step",
+];
+// The settings that the set command can modify, which are completed with tab.
+const SETTINGS: [&str; 4] = ["g", "collisions", "mass_transfer", "ejection_radius"];
+// The usage and description of each command shown by the help command.
+const HELP: [(&str, &str); 8] = [
+    ("help", "show this message"),
+    (
+        "spawn <amount> [mass=] [x=] [y=] [radius=] [speed=]",
+        "spawn bodies in a disc",
+    ),
+    (
+        "set <g|collisions|mass_transfer|ejection_radius> <value>",
+        "modify a setting",
+    ),
+    ("save <path>", "save the universe to a JSON file"),
+    ("load <path>", "load the universe from a JSON file"),
+    ("clear", "remove every body"),
+    ("pause, resume", "pause or resume the universe"),
+    ("step [amount]", "take single steps while paused"),
+];
+
+// A console that modifies the universe with text commands.
+#[derive(Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub output: Vec<String>,
+    pub history: Vec<String>,
+    history_index: Option<usize>,
+}
+
+// Parse a number from a string, naming it in the error.
+fn parse_number(name: &str, value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .map_err(|_| tr_format("{} must be a number, not {}", &[&name, &value]))
+}
+
+// Parse a boolean from a string, naming it in the error.
+fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "on" | "true" | "1" => Ok(true),
+        "off" | "false" | "0" => Ok(false),
+        _ => Err(tr_format("{} must be on or off, not {}", &[&name, &value])),
+    }
+}
+
+// Implementations for Console.
+impl Console {
+    // Add a line to the output of the console, removing the oldest line if there are too many.
+    fn print(&mut self, line: String) {
+        self.output.push(line);
+        if self.output.len() > OUTPUT_LENGTH {
+            self.output.remove(0);
+        }
+    }
+
+    // Execute a command on the universe and return a message describing the result.
+    pub fn execute(&mut self, line: &str, universe: &mut Universe) -> Result<String, String> {
+        // Split the command into its name, positional arguments, and key=value arguments.
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(String::new());
+        };
+        let (named, positional): (Vec<&str>, Vec<&str>) =
+            words.partition(|word| word.contains('='));
+        let named: HashMap<&str, &str> = named
+            .into_iter()
+            .filter_map(|word| word.split_once('='))
+            .collect();
+        let units = universe.universe_settings.units;
+
+        match command {
+            "help" => Ok(HELP
+                .iter()
+                .map(|(usage, description)| format!("{}: {}", usage, tr(description)))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            // Spawn bodies uniformly within a disc, with values in display units.
+            "spawn" => {
+                let amount = positional
+                    .first()
+                    .ok_or(tr("spawn needs an amount"))?
+                    .parse::<usize>()
+                    .map_err(|_| tr("The amount must be a whole number"))?;
+                let argument = |name: &str, default: f64| {
+                    named
+                        .get(name)
+                        .map_or(Ok(default), |value| parse_number(name, value))
+                };
+                let mass = argument("mass", 1.0)?;
+                let center = DVec2::new(argument("x", 0.0)?, argument("y", 0.0)?);
+                let radius = argument("radius", 100.0)?;
+                let speed = argument("speed", 0.0)?;
+                if mass <= 0.0 {
+                    return Err(tr("The mass must be positive").to_string());
+                }
+
+                let mut rng = Random::default();
+                let bodies: Vec<Body> = (0..amount)
+                    .map(|_| {
+                        let position_theta = rng.gen_range(0.0..(std::f64::consts::PI * 2.0));
+                        let velocity_theta = rng.gen_range(0.0..(std::f64::consts::PI * 2.0));
+                        let distance = radius * rng.gen_range(0.0..1.0f64).sqrt();
+                        Body {
+                            position: units.simulation_value(Quantity::Length, 1.0)
+                                * (center + DVec2::from_angle(position_theta) * distance),
+                            velocity: DVec2::from_angle(velocity_theta)
+                                * units.simulation_value(Quantity::Velocity, speed),
+                            mass: units.simulation_value(Quantity::Mass, mass),
+                            ..Default::default()
+                        }
+                    })
+                    .collect();
+                universe.edit(move |universe| universe.bodies.extend(bodies.iter().cloned()));
+                Ok(tr_format("Spawned {} bodies", &[&amount]))
+            }
+            // Modify a setting of the universe.
+            "set" => {
+                let [setting, value] = positional[..] else {
+                    return Err(tr("set needs a setting and a value").to_string());
+                };
+                let universe_settings = &mut universe.universe_settings;
+                match setting {
+                    "g" => universe_settings.gravitational_constant = parse_number(setting, value)?,
+                    "collisions" => {
+                        universe_settings.enable_collisions = parse_bool(setting, value)?
+                    }
+                    "mass_transfer" => {
+                        universe_settings.enable_mass_transfer = parse_bool(setting, value)?
+                    }
+                    "ejection_radius" => {
+                        universe_settings.ejection_radius = parse_number(setting, value)?
+                    }
+                    _ => return Err(tr_format("Unknown setting {}", &[&setting])),
+                }
+                Ok(tr_format("Set {} to {}", &[&setting, &value]))
+            }
+            "save" => {
+                let path = positional.first().ok_or(tr("save needs a path"))?;
+                universe.save(Path::new(path))?;
+                Ok(tr_format("Saved to {}", &[path]))
+            }
+            "load" => {
+                let path = positional.first().ok_or(tr("load needs a path"))?;
+                *universe = Universe::load(Path::new(path))?;
+                Ok(tr_format("Loaded {}", &[path]))
+            }
+            "clear" => {
+                universe.edit(|universe| universe.bodies.clear());
+                Ok(tr("Removed every body").to_string())
+            }
+            "pause" => {
+                universe.paused = true;
+                Ok(tr("Paused").to_string())
+            }
+            "resume" => {
+                universe.paused = false;
+                Ok(tr("Resumed").to_string())
+            }
+            "step" => {
+                let amount = positional
+                    .first()
+                    .map_or(Ok(1), |amount| amount.parse::<usize>())
+                    .map_err(|_| tr("The amount must be a whole number"))?;
+                universe.paused = true;
+                universe.pending_steps += amount;
+                Ok(tr_format("Stepping {} times", &[&amount]))
+            }
+            _ => Err(tr_format("Unknown command {}, try help", &[&command])),
+        }
+    }
+
+    // Complete the last word of the input with the first matching command or setting.
+    fn complete(&mut self) {
+        // Find the word being completed and the candidates for it.
+        let words: Vec<&str> = self.input.split(' ').collect();
+        let candidates: &[&str] = match words[..] {
+            [_] => &COMMANDS,
+            ["set", _] => &SETTINGS,
+            _ => return,
+        };
+        let word = words[words.len() - 1];
+
+        // Replace the word with the first candidate it's the start of.
+        if let Some(candidate) = candidates
+            .iter()
+            .find(|candidate| candidate.starts_with(word))
+        {
+            let start = self.input.len() - word.len();
+            self.input.replace_range(start.., candidate);
+            self.input.push(' ');
+        }
+    }
+
+    // Show the console as a window in the given context if it's open.
+    pub fn show(&mut self, context: &Context, universe: &mut Universe) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        // Create a window for the console, with an id that stays the same in every language.
+        Window::new(tr("Console"))
+            .id(Id::new("Console"))
+            .open(&mut open)
+            .default_width(480.0)
+            .show(context, |ui| {
+                // Show the output, scrolled to the newest line.
+                ScrollArea::vertical()
+                    .max_height(240.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in self.output.iter() {
+                            ui.monospace(line);
+                        }
+                    });
+
+                // Handle tab completion and history navigation before the text field sees the keys.
+                let id = Id::new("Console Input");
+                if ui.memory(|memory| memory.has_focus(id)) {
+                    let mut moved_cursor = false;
+                    if ui.input_mut(|input| input.consume_key(Modifiers::NONE, Key::Tab)) {
+                        self.complete();
+                        moved_cursor = true;
+                    }
+                    if ui.input_mut(|input| input.consume_key(Modifiers::NONE, Key::ArrowUp))
+                        && !self.history.is_empty()
+                    {
+                        let index = self
+                            .history_index
+                            .map_or(self.history.len() - 1, |index| index.saturating_sub(1));
+                        self.history_index = Some(index);
+                        self.input = self.history[index].clone();
+                        moved_cursor = true;
+                    }
+                    if ui.input_mut(|input| input.consume_key(Modifiers::NONE, Key::ArrowDown)) {
+                        self.history_index = self
+                            .history_index
+                            .map(|index| index + 1)
+                            .filter(|index| *index < self.history.len());
+                        self.input = self
+                            .history_index
+                            .map(|index| self.history[index].clone())
+                            .unwrap_or_default();
+                        moved_cursor = true;
+                    }
+
+                    // Move the cursor to the end of the replaced input.
+                    if moved_cursor {
+                        if let Some(mut state) = TextEdit::load_state(ui.ctx(), id) {
+                            state
+                                .cursor
+                                .set_char_range(Some(CCursorRange::one(CCursor::new(
+                                    self.input.chars().count(),
+                                ))));
+                            state.store(ui.ctx(), id);
+                        }
+                    }
+                }
+
+                // Create a text field for the command, executing it when enter is pressed.
+                let response = ui.add(
+                    TextEdit::singleline(&mut self.input)
+                        .id(id)
+                        .font(TextStyle::Monospace)
+                        .desired_width(f32::INFINITY)
+                        .hint_text(tr("Type help for a list of commands")),
+                );
+                if response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+                    let line = std::mem::take(&mut self.input);
+                    if !line.trim().is_empty() {
+                        self.print(format!("> {}", line));
+                        match self.execute(&line, universe) {
+                            Ok(message) => {
+                                for message_line in message.lines() {
+                                    self.print(message_line.to_string());
+                                }
+                            }
+                            Err(error) => self.print(tr_format("Error: {}", &[&error])),
+                        }
+                        self.history.push(line);
+                    }
+                    self.history_index = None;
+                    response.request_focus();
+                }
+            });
+        self.open = open;
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+#[cfg(feature = "dialogs")]
+use std::{fs, path::Path};
+
+// The file that the last directory a file dialog was used in is remembered in.
+#[cfg(feature = "dialogs")]
+const LAST_DIRECTORY_PATH: &str = "config/last_directory.txt";
+
+// Whether native file dialogs are available, which needs the dialogs feature.
+pub const DIALOGS_AVAILABLE: bool = cfg!(feature = "dialogs");
+
+// A named group of file extensions that a file dialog shows, which is only read when dialogs are available.
+#[cfg_attr(not(feature = "dialogs"), allow(dead_code))]
+pub struct FileFilter {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+}
+
+// The filter for saved universes, workspaces, and run summaries.
+pub const JSON_FILTER: FileFilter = FileFilter {
+    name: "JSON",
+    extensions: &["json"],
+};
+// The filter for files that bodies can be imported from.
+pub const IMPORT_FILTER: FileFilter = FileFilter {
+    name: "Initial Conditions",
+    extensions: &["csv", "txt", "dat", "nemo", "ascii", "tipsy", "std", "bin"],
+};
+
+// Return the directory a file dialog was last used in, or the working directory if there is none.
+#[cfg(feature = "dialogs")]
+fn last_directory() -> PathBuf {
+    fs::read_to_string(LAST_DIRECTORY_PATH)
+        .map(|directory| PathBuf::from(directory.trim()))
+        .ok()
+        .filter(|directory| directory.is_dir())
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+// Remember the directory of the file picked in a file dialog, ignoring failures since it's only a convenience.
+#[cfg(feature = "dialogs")]
+fn remember_directory(path: &Path) {
+    if let Some(directory) = path.parent() {
+        let _ = fs::create_dir_all("config");
+        let _ = fs::write(LAST_DIRECTORY_PATH, directory.to_string_lossy().as_bytes());
+    }
+}
+
+// Create a native file dialog with a title and filters, starting in the last directory.
+#[cfg(feature = "dialogs")]
+fn dialog(title: &str, filters: &[FileFilter]) -> rfd::FileDialog {
+    filters.iter().fold(
+        rfd::FileDialog::new()
+            .set_title(title)
+            .set_directory(last_directory()),
+        |dialog, filter| dialog.add_filter(filter.name, filter.extensions),
+    )
+}
+
+// Show a native dialog to pick a file to open, returning None if it was cancelled or dialogs aren't available.
+pub fn open_file(title: &str, filters: &[FileFilter]) -> Option<PathBuf> {
+    #[cfg(feature = "dialogs")]
+    {
+        let path = dialog(title, filters).pick_file()?;
+        remember_directory(&path);
+        Some(path)
+    }
+    #[cfg(not(feature = "dialogs"))]
+    {
+        let _ = (title, filters);
+        None
+    }
+}
+
+// Show a native dialog to pick where to save a file with a suggested name, returning None if it was cancelled or dialogs aren't available.
+pub fn save_file(title: &str, filters: &[FileFilter], file_name: &str) -> Option<PathBuf> {
+    #[cfg(feature = "dialogs")]
+    {
+        let path = dialog(title, filters)
+            .set_file_name(file_name)
+            .save_file()?;
+        remember_directory(&path);
+        Some(path)
+    }
+    #[cfg(not(feature = "dialogs"))]
+    {
+        let _ = (title, filters, file_name);
+        None
+    }
+}
@@ -0,0 +1,185 @@
+use crate::dialogs::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use notan_egui::*;
+use std::path::Path;
+
+// The maximum amount of bodies shown in the difference table.
+const DIFF_ROWS: usize = 50;
+
+// The difference between a body in two snapshots.
+pub struct BodyDiff {
+    pub id: usize,
+    pub displacement: DVec2,
+    pub velocity_change: DVec2,
+    pub mass_change: f64,
+}
+
+// The differences between two snapshots of a universe, with bodies matched by their ID, which is their index in the snapshot.
+pub struct SnapshotDiff {
+    pub bodies: Vec<BodyDiff>,
+    pub appeared: Vec<usize>,
+    pub disappeared: Vec<usize>,
+    pub time_change: f64,
+}
+
+// Implementations for SnapshotDiff.
+impl SnapshotDiff {
+    // Compare two universes, sorting the bodies from most to least displaced.
+    pub fn new(before: &Universe, after: &Universe) -> Self {
+        // Compare the bodies that exist in both universes.
+        let mut bodies: Vec<BodyDiff> = before
+            .bodies
+            .iter()
+            .zip(after.bodies.iter())
+            .enumerate()
+            .map(|(id, (body1, body2))| BodyDiff {
+                id,
+                displacement: body2.position - body1.position,
+                velocity_change: body2.velocity - body1.velocity,
+                mass_change: body2.mass - body1.mass,
+            })
+            .collect();
+        bodies.sort_by(|diff1, diff2| {
+            diff2
+                .displacement
+                .length()
+                .total_cmp(&diff1.displacement.length())
+        });
+
+        // Find the bodies that only exist in one of the universes.
+        let shared = before.bodies.len().min(after.bodies.len());
+        Self {
+            bodies,
+            appeared: (shared..after.bodies.len()).collect(),
+            disappeared: (shared..before.bodies.len()).collect(),
+            time_change: after.simulation_time - before.simulation_time,
+        }
+    }
+
+    // Return whether the snapshots are identical in the bodies they contain.
+    pub fn is_identical(&self) -> bool {
+        self.appeared.is_empty()
+            && self.disappeared.is_empty()
+            && self.bodies.iter().all(|diff| {
+                diff.displacement == DVec2::ZERO
+                    && diff.velocity_change == DVec2::ZERO
+                    && diff.mass_change == 0.0
+            })
+    }
+}
+
+// A tool that loads two saved universes and shows how their bodies differ.
+#[derive(Default)]
+pub struct DiffTool {
+    pub before_path: String,
+    pub after_path: String,
+    pub diff: Option<SnapshotDiff>,
+    pub status: String,
+}
+
+// Implementations for DiffTool.
+impl DiffTool {
+    // Load the two snapshots and compare them.
+    fn compare(&mut self) {
+        let result = Universe::load(Path::new(&self.before_path)).and_then(|before| {
+            Universe::load(Path::new(&self.after_path)).map(|after| (before, after))
+        });
+        match result {
+            Ok((before, after)) => {
+                self.diff = Some(SnapshotDiff::new(&before, &after));
+                self.status = String::new();
+            }
+            Err(error) => {
+                self.diff = None;
+                self.status = tr_format("Comparison failed: {}", &[&error]);
+            }
+        }
+    }
+
+    // Show the diff tool in the given UI, formatting values in the units of the universe.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe) {
+        // Create text fields to enter the paths of the snapshots, with buttons to pick them with file dialogs if they're available.
+        for (label, path, hint) in [
+            ("Before", &mut self.before_path, "autosaves/autosave_0.json"),
+            ("After", &mut self.after_path, "autosaves/autosave_1.json"),
+        ] {
+            let label_id = ui.label(tr(label)).id;
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(path).hint_text(hint))
+                    .labelled_by(label_id);
+                if DIALOGS_AVAILABLE && ui.button(tr("Browse...")).clicked() {
+                    if let Some(picked) = open_file(tr(label), &[JSON_FILTER]) {
+                        *path = picked.display().to_string();
+                    }
+                }
+            });
+            ui.end_row();
+        }
+
+        // Create a button to compare the snapshots.
+        if ui.button(tr("Compare")).clicked() {
+            self.compare();
+        }
+        ui.label(&self.status);
+        ui.end_row();
+
+        let Some(diff) = &self.diff else {
+            return;
+        };
+        let units = &universe.universe_settings.units;
+
+        // Show a summary of the differences.
+        if diff.is_identical() {
+            ui.colored_label(Color32::GREEN, tr("Snapshots are identical"));
+        }
+        ui.label(format!(
+            "{}: {}",
+            tr("Time Change"),
+            units.format(Quantity::Time, diff.time_change)
+        ));
+        ui.label(format!("{}: {}", tr("Matched Bodies"), diff.bodies.len()));
+        ui.label(format!(
+            "{}: {}",
+            tr("Appeared"),
+            format_ids(&diff.appeared)
+        ));
+        ui.label(format!(
+            "{}: {}",
+            tr("Disappeared"),
+            format_ids(&diff.disappeared)
+        ));
+        ui.end_row();
+
+        // Show the most displaced bodies in a table.
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("Snapshot Diff").striped(true).show(ui, |ui| {
+                ui.label(tr("Body"));
+                ui.label(tr("Displacement"));
+                ui.label(tr("Velocity Change"));
+                ui.label(tr("Mass Change"));
+                ui.end_row();
+                for body_diff in diff.bodies.iter().take(DIFF_ROWS) {
+                    ui.label(body_diff.id.to_string());
+                    ui.label(units.format(Quantity::Length, body_diff.displacement.length()));
+                    ui.label(units.format(Quantity::Velocity, body_diff.velocity_change.length()));
+                    ui.label(units.format(Quantity::Mass, body_diff.mass_change));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
+
+// Format a list of body IDs, or "None" if it's empty.
+fn format_ids(ids: &[usize]) -> String {
+    if ids.is_empty() {
+        return tr("None").to_string();
+    }
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
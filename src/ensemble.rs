@@ -0,0 +1,456 @@
+use crate::generation::*;
+use crate::locale::*;
+use crate::profiler::*;
+use crate::summary::*;
+use crate::units::*;
+use crate::universe::*;
+use notan_egui::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::SystemTime,
+};
+
+// The directory that ensemble results are exported to.
+const ENSEMBLE_DIRECTORY: &str = "exports";
+
+// Settings for an ensemble of universes that are generated with consecutive seeds but otherwise identical settings and run for the same amount of steps.
+#[derive(Clone)]
+pub struct EnsembleSettings {
+    pub generation_settings: GenerationSettings,
+    pub universe_settings: UniverseSettings,
+    pub first_seed: u64,
+    pub members: usize,
+    pub steps: usize,
+    pub delta_time: f64,
+}
+
+// Default value for EnsembleSettings.
+impl Default for EnsembleSettings {
+    fn default() -> Self {
+        Self {
+            generation_settings: Default::default(),
+            universe_settings: Default::default(),
+            first_seed: 1,
+            members: 8,
+            steps: 1000,
+            delta_time: STEP_DELTA_TIME,
+        }
+    }
+}
+
+// The final state of a single universe of an ensemble.
+#[derive(Clone, Copy)]
+pub struct EnsembleMember {
+    pub seed: u64,
+    pub body_amount: usize,
+    pub largest_mass: f64,
+    pub mergers: usize,
+    pub ejections: usize,
+    pub energy_drift_percentage: Option<f64>,
+}
+
+// A statistic aggregated over the members of an ensemble.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EnsembleMetric {
+    BodyAmount,
+    LargestMass,
+    Mergers,
+    Ejections,
+    EnergyDrift,
+}
+
+// Implementations for EnsembleMetric.
+impl EnsembleMetric {
+    // All metrics, in the order they are listed in the UI and the CSV.
+    pub const ALL: [EnsembleMetric; 5] = [
+        EnsembleMetric::BodyAmount,
+        EnsembleMetric::LargestMass,
+        EnsembleMetric::Mergers,
+        EnsembleMetric::Ejections,
+        EnsembleMetric::EnergyDrift,
+    ];
+
+    // Return the name of a metric.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnsembleMetric::BodyAmount => "Final Body Count",
+            EnsembleMetric::LargestMass => "Largest Mass",
+            EnsembleMetric::Mergers => "Mergers",
+            EnsembleMetric::Ejections => "Ejections",
+            EnsembleMetric::EnergyDrift => "Energy Drift",
+        }
+    }
+
+    // Return the column of a metric in the CSV.
+    fn column(&self) -> &'static str {
+        match self {
+            EnsembleMetric::BodyAmount => "body_amount",
+            EnsembleMetric::LargestMass => "largest_mass",
+            EnsembleMetric::Mergers => "mergers",
+            EnsembleMetric::Ejections => "ejections",
+            EnsembleMetric::EnergyDrift => "energy_drift_percentage",
+        }
+    }
+
+    // Return the value of a metric for a member, in simulation units, if it's available.
+    fn value(&self, member: &EnsembleMember) -> Option<f64> {
+        match self {
+            EnsembleMetric::BodyAmount => Some(member.body_amount as f64),
+            EnsembleMetric::LargestMass => Some(member.largest_mass),
+            EnsembleMetric::Mergers => Some(member.mergers as f64),
+            EnsembleMetric::Ejections => Some(member.ejections as f64),
+            EnsembleMetric::EnergyDrift => member.energy_drift_percentage,
+        }
+    }
+
+    // Format a value of a metric in the given units.
+    fn format(&self, value: f64, units: &Units) -> String {
+        match self {
+            EnsembleMetric::LargestMass => units.format(Quantity::Mass, value),
+            EnsembleMetric::EnergyDrift => format!("{:.4}%", value),
+            _ => format!("{:.2}", value),
+        }
+    }
+}
+
+// Return the mean and sample standard deviation of the given values, or None if there are none.
+fn mean_and_deviation(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let deviation = if values.len() > 1 {
+        (values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / (values.len() - 1) as f64)
+            .sqrt()
+    } else {
+        0.0
+    };
+    Some((mean, deviation))
+}
+
+// Implementations for EnsembleSettings.
+impl EnsembleSettings {
+    // Generate and run the member with the given seed, counting each step in the given progress, and return its final state, or None if cancelled.
+    fn run_member(
+        &self,
+        seed: u64,
+        progress: &AtomicUsize,
+        cancelled: &AtomicBool,
+    ) -> Option<EnsembleMember> {
+        // Generate the bodies with the seed, deriving every random stream from it so the members differ.
+        let generation_settings = GenerationSettings {
+            seed,
+            sub_seeds: Default::default(),
+            ..self.generation_settings.clone()
+        };
+        let mut universe = Universe {
+            universe_settings: self.universe_settings.clone(),
+            ..Default::default()
+        };
+        universe.replace_bodies(generation_settings.generate(
+            &self.universe_settings.units,
+            self.universe_settings.gravitational_constant,
+            &AtomicUsize::new(0),
+            cancelled,
+        )?);
+
+        // Run the steps.
+        let mut profiler = Profiler::default();
+        for _ in 0..self.steps {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            universe.update(self.delta_time, &mut profiler);
+            progress.fetch_add(1, Ordering::Relaxed);
+        }
+
+        // Measure the final state, using the run summary for the statistics it already has.
+        let summary = RunSummary::new(&universe);
+        Some(EnsembleMember {
+            seed,
+            body_amount: summary.body_amount,
+            largest_mass: universe
+                .bodies
+                .iter()
+                .map(|body| body.mass)
+                .fold(0.0, f64::max),
+            mergers: summary.mergers,
+            ejections: summary.ejections,
+            energy_drift_percentage: summary.energy_drift_percentage,
+        })
+    }
+
+    // Run every member of the ensemble, spread over a thread for each core, and return them in order of their seeds, or None if cancelled.
+    pub fn run(
+        &self,
+        progress: &AtomicUsize,
+        cancelled: &AtomicBool,
+    ) -> Option<Vec<EnsembleMember>> {
+        // Let each thread take the next member that hasn't been started until there are none left.
+        let next = AtomicUsize::new(0);
+        let thread_amount = thread::available_parallelism()
+            .map(|amount| amount.get())
+            .unwrap_or(1)
+            .min(self.members)
+            .max(1);
+        let mut members: Vec<EnsembleMember> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..thread_amount)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut members = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            if index >= self.members {
+                                return Some(members);
+                            }
+                            let seed = self.first_seed.max(1).wrapping_add(index as u64);
+                            members.push(self.run_member(seed, progress, cancelled)?);
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().ok().flatten())
+                .collect::<Option<Vec<_>>>()
+        })?
+        .into_iter()
+        .flatten()
+        .collect();
+        members.sort_by_key(|member| member.seed);
+        Some(members)
+    }
+}
+
+// An ensemble running on a background thread.
+pub struct EnsembleTask {
+    progress: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    total_steps: usize,
+    handle: Option<JoinHandle<Option<Vec<EnsembleMember>>>>,
+}
+
+// Implementations for EnsembleTask.
+impl EnsembleTask {
+    // Start running an ensemble with the given settings on a background thread.
+    pub fn start(ensemble_settings: &EnsembleSettings) -> Self {
+        let progress = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        // Move copies of the settings and counters into the thread.
+        let handle = {
+            let ensemble_settings = ensemble_settings.clone();
+            let progress = progress.clone();
+            let cancelled = cancelled.clone();
+            thread::spawn(move || ensemble_settings.run(&progress, &cancelled))
+        };
+
+        Self {
+            progress,
+            cancelled,
+            total_steps: ensemble_settings.members * ensemble_settings.steps,
+            handle: Some(handle),
+        }
+    }
+
+    // Return the fraction of steps that have been run over all members.
+    pub fn progress(&self) -> f32 {
+        if self.total_steps == 0 {
+            1.0
+        } else {
+            self.progress.load(Ordering::Relaxed) as f32 / self.total_steps as f32
+        }
+    }
+
+    // Cancel the ensemble.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // Return the members if the ensemble has finished, which are None if it was cancelled.
+    pub fn poll(&mut self) -> Option<Option<Vec<EnsembleMember>>> {
+        // Only join the thread once it has finished so the app doesn't block.
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        Some(self.handle.take()?.join().ok().flatten())
+    }
+}
+
+// A Monte Carlo ensemble mode that runs the current settings with many seeds in parallel and reports the mean and standard deviation of statistics of their final states.
+#[derive(Default)]
+pub struct Ensemble {
+    pub ensemble_settings: EnsembleSettings,
+    pub task: Option<EnsembleTask>,
+    pub members: Vec<EnsembleMember>,
+    pub status: String,
+}
+
+// Implementations for Ensemble.
+impl Ensemble {
+    // Store the members of the ensemble once it has finished.
+    pub fn update(&mut self) {
+        let Some(result) = self.task.as_mut().and_then(|task| task.poll()) else {
+            return;
+        };
+        self.task = None;
+        self.status = match result {
+            Some(members) => {
+                self.members = members;
+                tr_format("Finished {} runs", &[&self.members.len()])
+            }
+            None => tr("Ensemble cancelled").to_string(),
+        };
+    }
+
+    // Return the mean and standard deviation of a metric over the members, if any have a value for it.
+    fn statistic(&self, metric: EnsembleMetric) -> Option<(f64, f64)> {
+        let values: Vec<f64> = self
+            .members
+            .iter()
+            .filter_map(|member| metric.value(member))
+            .collect();
+        mean_and_deviation(&values)
+    }
+
+    // Export the members and the statistics to a new CSV file in the ensemble directory, named after the time since unix epoch, and return its path.
+    fn export(&self) -> Result<PathBuf, String> {
+        // Write a row for each member followed by rows for the means and standard deviations.
+        let mut csv = String::from("seed");
+        for metric in EnsembleMetric::ALL {
+            csv.push(',');
+            csv.push_str(metric.column());
+        }
+        csv.push('\n');
+        let format_value =
+            |value: Option<f64>| value.map_or(String::new(), |value| value.to_string());
+        for member in self.members.iter() {
+            csv.push_str(&member.seed.to_string());
+            for metric in EnsembleMetric::ALL {
+                csv.push(',');
+                csv.push_str(&format_value(metric.value(member)));
+            }
+            csv.push('\n');
+        }
+        for (row, deviation) in [("mean", false), ("standard_deviation", true)] {
+            csv.push_str(row);
+            for metric in EnsembleMetric::ALL {
+                csv.push(',');
+                csv.push_str(&format_value(self.statistic(metric).map(
+                    |(mean, standard_deviation)| {
+                        if deviation {
+                            standard_deviation
+                        } else {
+                            mean
+                        }
+                    },
+                )));
+            }
+            csv.push('\n');
+        }
+
+        // Write the rows to the file.
+        fs::create_dir_all(ENSEMBLE_DIRECTORY).map_err(|error| error.to_string())?;
+        let path = Path::new(ENSEMBLE_DIRECTORY).join(format!(
+            "ensemble_{}.csv",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+        ));
+        fs::write(&path, csv).map_err(|error| error.to_string())?;
+        Ok(path)
+    }
+
+    // Show the ensemble mode in the given UI, running the current universe and generation settings.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        universe: &Universe,
+        generation_settings: &GenerationSettings,
+    ) {
+        // Create widgets to modify the seeds, the amount of members, and how long each runs.
+        let ensemble_settings = &mut self.ensemble_settings;
+        let label_id = ui.label(tr("First Seed")).id;
+        ui.add(DragValue::new(&mut ensemble_settings.first_seed).clamp_range(1..=u64::MAX))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Runs")).id;
+        ui.add(DragValue::new(&mut ensemble_settings.members).clamp_range(1..=10000))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Steps per Run")).id;
+        ui.add(DragValue::new(&mut ensemble_settings.steps).clamp_range(1..=usize::MAX))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Step Size")).id;
+        ui.add(
+            DragValue::new(&mut ensemble_settings.delta_time)
+                .speed(0.0001)
+                .clamp_range(0.000001..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+
+        // Show the progress of a running ensemble with a button to cancel it, or a button to start one with the current settings.
+        ensemble_settings.generation_settings = generation_settings.clone();
+        ensemble_settings.universe_settings = universe.universe_settings.clone();
+        if let Some(task) = &self.task {
+            ui.add(ProgressBar::new(task.progress()).show_percentage());
+            if ui.button(tr("Cancel")).clicked() {
+                task.cancel();
+            }
+        } else if ui.button(tr("Run Ensemble")).clicked() {
+            self.task = Some(EnsembleTask::start(&self.ensemble_settings));
+            self.status = tr("Ensemble running").to_string();
+        }
+        ui.end_row();
+
+        // Show the mean and standard deviation of each metric over the finished members, with a button to export them.
+        if !self.members.is_empty() {
+            let units = universe.universe_settings.units;
+            Grid::new("Ensemble Statistics")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label(tr("Mean"));
+                    ui.label(tr("Standard Deviation"));
+                    ui.end_row();
+                    for metric in EnsembleMetric::ALL {
+                        ui.label(tr(metric.name()));
+                        match self.statistic(metric) {
+                            Some((mean, deviation)) => {
+                                ui.label(metric.format(mean, &units));
+                                ui.label(metric.format(deviation, &units));
+                            }
+                            None => {
+                                ui.label("-");
+                                ui.label("-");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            ui.end_row();
+            if ui.button(tr("Export CSV")).clicked() {
+                self.status = match self.export() {
+                    Ok(path) => tr_format("Wrote {}", &[&path.display()]),
+                    Err(error) => tr_format("Writing the results failed: {}", &[&error]),
+                };
+            }
+            ui.end_row();
+        }
+        ui.label(&self.status);
+        ui.end_row();
+    }
+}
@@ -0,0 +1,280 @@
+use crate::body::*;
+use crate::camera::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use notan_egui::*;
+use std::collections::{HashSet, VecDeque};
+
+// The maximum amount of events kept in the log.
+const EVENT_LIMIT: usize = 200;
+
+// A kind of notable event.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Merger,
+    Ejection,
+    NewHeaviest,
+}
+
+// Implementations for EventKind.
+impl EventKind {
+    // All event kinds, in the order they are listed in the UI.
+    pub const ALL: [EventKind; 3] = [
+        EventKind::Merger,
+        EventKind::Ejection,
+        EventKind::NewHeaviest,
+    ];
+
+    // Return the name of an event kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Merger => "Large Merger",
+            EventKind::Ejection => "Ejection",
+            EventKind::NewHeaviest => "New Most Massive Body",
+        }
+    }
+
+    // Return the color an event kind is listed in.
+    fn color(&self) -> Color32 {
+        match self {
+            EventKind::Merger => Color32::from_rgb(255, 153, 51),
+            EventKind::Ejection => Color32::from_rgb(90, 160, 230),
+            EventKind::NewHeaviest => Color32::from_rgb(230, 190, 80),
+        }
+    }
+}
+
+// A notable event with the simulation time and place it happened, and the body it's about if there is one so the camera can follow it there.
+#[derive(Clone, Copy)]
+pub struct NotableEvent {
+    pub kind: EventKind,
+    pub time: f64,
+    pub position: DVec2,
+    pub body: Option<BodyId>,
+    pub mass: f64,
+    pub speed: f64,
+}
+
+// A detector that watches the universe for notable events, such as large mergers, bodies flung out faster than a threshold, and a new most massive body, and logs them so a long run can be left alone and its highlights found later.
+pub struct EventDetector {
+    pub enabled: [bool; 3],
+    pub merger_mass: f64,
+    pub ejection_speed: f64,
+    pub pause_on_event: bool,
+    pub events: VecDeque<NotableEvent>,
+    pub ejected: HashSet<BodyId>,
+    pub heaviest: Option<BodyId>,
+    pub simulation_time: f64,
+}
+
+// Default value for EventDetector.
+impl Default for EventDetector {
+    fn default() -> Self {
+        Self {
+            enabled: [true; 3],
+            merger_mass: 100.0,
+            ejection_speed: 100.0,
+            pause_on_event: false,
+            events: VecDeque::new(),
+            ejected: HashSet::new(),
+            heaviest: None,
+            simulation_time: 0.0,
+        }
+    }
+}
+
+// Implementations for EventDetector.
+impl EventDetector {
+    // Return whether events of a kind are detected.
+    fn detects(&self, kind: EventKind) -> bool {
+        self.enabled[EventKind::ALL
+            .iter()
+            .position(|other| *other == kind)
+            .unwrap_or(0)]
+    }
+
+    // Log an event, dropping the oldest one if there are too many.
+    fn log(&mut self, event: NotableEvent) {
+        self.events.push_back(event);
+        while self.events.len() > EVENT_LIMIT {
+            self.events.pop_front();
+        }
+    }
+
+    // Update the detector with the last step of the universe, pausing it if an event was found and that's enabled.
+    pub fn update(&mut self, universe: &mut Universe) {
+        // Forget what was already reported if the simulation time went backwards, since the bodies were replaced.
+        let time = universe.simulation_time;
+        if time < self.simulation_time {
+            self.ejected.clear();
+            self.heaviest = None;
+        }
+        self.simulation_time = time;
+        let event_count = self.events.len();
+        let units = universe.universe_settings.units;
+
+        // Log the mergers of the last step whose combined mass is at least the threshold.
+        if self.detects(EventKind::Merger) {
+            let merger_mass = units.simulation_value(Quantity::Mass, self.merger_mass);
+            let mergers: Vec<NotableEvent> = universe
+                .collision_events
+                .iter()
+                .filter(|event| event.masses[0] + event.masses[1] >= merger_mass)
+                .map(|event| NotableEvent {
+                    kind: EventKind::Merger,
+                    time,
+                    position: event.position,
+                    body: None,
+                    mass: event.masses[0] + event.masses[1],
+                    speed: 0.0,
+                })
+                .collect();
+            for event in mergers {
+                self.log(event);
+            }
+        }
+
+        // Log each moving body once when it's faster than the threshold relative to the center of mass and fast enough to escape the total mass from where it is.
+        if self.detects(EventKind::Ejection) {
+            let ejection_speed = units.simulation_value(Quantity::Velocity, self.ejection_speed);
+            let (center_of_mass, center_velocity, total_mass) = universe.center_of_mass();
+            let gravitational_constant = universe.universe_settings.gravitational_constant;
+            let ejections: Vec<NotableEvent> = universe
+                .bodies
+                .iter()
+                .filter(|body| !body.frozen && !self.ejected.contains(&body.id))
+                .filter_map(|body| {
+                    let speed = body.velocity.distance(center_velocity);
+                    let distance = body.position.distance(center_of_mass).max(f64::EPSILON);
+                    let escape_speed_squared = 2.0 * gravitational_constant * total_mass / distance;
+                    (speed >= ejection_speed && speed * speed > escape_speed_squared).then_some(
+                        NotableEvent {
+                            kind: EventKind::Ejection,
+                            time,
+                            position: body.position,
+                            body: Some(body.id),
+                            mass: body.mass,
+                            speed,
+                        },
+                    )
+                })
+                .collect();
+            for event in ejections {
+                self.ejected.extend(event.body);
+                self.log(event);
+            }
+        }
+
+        // Log when a different body becomes the most massive, but not the first one found.
+        let heaviest = universe
+            .bodies
+            .iter()
+            .filter(|body| body.is_massive())
+            .max_by(|body1, body2| body1.mass.total_cmp(&body2.mass));
+        if let Some(body) = heaviest {
+            if self.heaviest.is_some_and(|id| id != body.id) && self.detects(EventKind::NewHeaviest)
+            {
+                self.log(NotableEvent {
+                    kind: EventKind::NewHeaviest,
+                    time,
+                    position: body.position,
+                    body: Some(body.id),
+                    mass: body.mass,
+                    speed: 0.0,
+                });
+            }
+            self.heaviest = Some(body.id);
+        }
+
+        // Pause so the event can be looked at.
+        if self.pause_on_event && self.events.len() > event_count {
+            universe.paused = true;
+        }
+    }
+
+    // Return a description of an event.
+    fn describe(event: &NotableEvent, units: &Units) -> String {
+        match event.kind {
+            EventKind::Merger => tr_format(
+                "Merger into {}",
+                &[&units.format(Quantity::Mass, event.mass)],
+            ),
+            EventKind::Ejection => tr_format(
+                "Body {} ejected at {}",
+                &[
+                    &event.body.unwrap_or_default(),
+                    &units.format(Quantity::Velocity, event.speed),
+                ],
+            ),
+            EventKind::NewHeaviest => tr_format(
+                "Body {} became the most massive at {}",
+                &[
+                    &event.body.unwrap_or_default(),
+                    &units.format(Quantity::Mass, event.mass),
+                ],
+            ),
+        }
+    }
+
+    // Show the detector in the given UI, with a button to move the camera to each logged event, or to where its body is now if it still exists.
+    pub fn show(&mut self, ui: &mut Ui, camera: &mut Camera, universe: &Universe) {
+        // Create checkboxes to choose which kinds of events are detected, and drag values to modify the thresholds.
+        let units = universe.universe_settings.units;
+        for (kind, enabled) in EventKind::ALL.iter().zip(self.enabled.iter_mut()) {
+            let label_id = ui.label(tr(kind.name())).id;
+            ui.add(Checkbox::new(enabled, "")).labelled_by(label_id);
+            ui.end_row();
+        }
+        let label_id = ui.label(tr("Merger Mass")).id;
+        ui.add(
+            DragValue::new(&mut self.merger_mass)
+                .speed(1.0)
+                .suffix(format!(" {}", units.symbol(Quantity::Mass)))
+                .clamp_range(0.0..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Ejection Speed")).id;
+        ui.add(
+            DragValue::new(&mut self.ejection_speed)
+                .speed(1.0)
+                .suffix(format!(" {}", units.symbol(Quantity::Velocity)))
+                .clamp_range(0.0..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Pause on Event")).id;
+        ui.add(Checkbox::new(&mut self.pause_on_event, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // List the events, newest first, with a button to jump to each and one to clear the log.
+        ui.label(tr_format("{} events", &[&self.events.len()]));
+        if ui.button(tr("Clear")).clicked() {
+            self.events.clear();
+        }
+        ui.end_row();
+        ScrollArea::vertical()
+            .id_source("Notable Events")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for event in self.events.iter().rev() {
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Jump")).clicked() {
+                            let position = event
+                                .body
+                                .and_then(|id| universe.index_of(id))
+                                .map(|index| universe.bodies[index].position)
+                                .unwrap_or(event.position);
+                            camera.center_on(position);
+                        }
+                        ui.label(units.format(Quantity::Time, event.time));
+                        ui.colored_label(event.kind.color(), Self::describe(event, &units));
+                    });
+                }
+            });
+        ui.end_row();
+    }
+}
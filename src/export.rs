@@ -0,0 +1,251 @@
+use crate::{body::*, dialogs::*, locale::*, units::*, universe::*};
+use notan_egui::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// The directory that snapshots are exported to.
+const EXPORT_DIRECTORY: &str = "exports";
+
+// A format that snapshots of the universe can be exported to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+// Implementations for ExportFormat.
+impl ExportFormat {
+    // All export formats, in the order they are listed in the UI.
+    pub const ALL: &'static [ExportFormat] = &[
+        ExportFormat::Csv,
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet,
+    ];
+
+    // Return the name of an export format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => "Parquet",
+        }
+    }
+
+    // Return the file extension of an export format.
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    // Return the file dialog filter of an export format.
+    fn file_filter(&self) -> FileFilter {
+        match self {
+            ExportFormat::Csv => FileFilter {
+                name: "CSV",
+                extensions: &["csv"],
+            },
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => FileFilter {
+                name: "Parquet",
+                extensions: &["parquet"],
+            },
+        }
+    }
+
+    // Write the id, position, velocity, and mass of each body in the universe to a file of an export format, with values in display units.
+    pub fn export(&self, path: &Path, universe: &Universe) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        match self {
+            ExportFormat::Csv => export_csv(path, universe),
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => export_parquet(path, universe),
+        }
+    }
+}
+
+// Return the columns of a snapshot of the universe, with values in display units.
+fn columns(universe: &Universe) -> [(&'static str, Vec<f64>); 5] {
+    let units = &universe.universe_settings.units;
+    let column = |quantity, value: fn(&Body) -> f64| {
+        universe
+            .bodies
+            .iter()
+            .map(|body| units.display_value(quantity, value(body)))
+            .collect()
+    };
+    [
+        ("x", column(Quantity::Length, |body| body.position.x)),
+        ("y", column(Quantity::Length, |body| body.position.y)),
+        ("vx", column(Quantity::Velocity, |body| body.velocity.x)),
+        ("vy", column(Quantity::Velocity, |body| body.velocity.y)),
+        ("mass", column(Quantity::Mass, |body| body.mass)),
+    ]
+}
+
+// Write a snapshot of the universe to a CSV file with a header row.
+fn export_csv(path: &Path, universe: &Universe) -> Result<(), String> {
+    let columns = columns(universe);
+
+    // Write the header followed by one row for each body.
+    let mut csv = String::from("id");
+    for (name, _) in columns.iter() {
+        csv.push(',');
+        csv.push_str(name);
+    }
+    csv.push('\n');
+    for index in 0..universe.bodies.len() {
+        csv.push_str(&index.to_string());
+        for (_, values) in columns.iter() {
+            csv.push(',');
+            csv.push_str(&values[index].to_string());
+        }
+        csv.push('\n');
+    }
+
+    fs::write(path, csv).map_err(|error| error.to_string())
+}
+
+// Write a snapshot of the universe to a Parquet file with a single row group.
+#[cfg(feature = "parquet")]
+fn export_parquet(path: &Path, universe: &Universe) -> Result<(), String> {
+    use parquet::{
+        data_type::{DoubleType, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+    use std::sync::Arc;
+
+    // Create a file writer with a schema containing the id and each column.
+    let columns = columns(universe);
+    let mut schema = String::from("message snapshot { REQUIRED INT64 id;");
+    for (name, _) in columns.iter() {
+        schema.push_str(&format!(" REQUIRED DOUBLE {};", name));
+    }
+    schema.push_str(" }");
+    let schema = Arc::new(parse_message_type(&schema).map_err(|error| error.to_string())?);
+    let file = fs::File::create(path).map_err(|error| error.to_string())?;
+    let mut writer = SerializedFileWriter::new(file, schema, Arc::new(WriterProperties::default()))
+        .map_err(|error| error.to_string())?;
+
+    // Write the id column followed by each other column.
+    let mut row_group = writer.next_row_group().map_err(|error| error.to_string())?;
+    let ids: Vec<i64> = (0..universe.bodies.len() as i64).collect();
+    if let Some(mut column) = row_group.next_column().map_err(|error| error.to_string())? {
+        column
+            .typed::<Int64Type>()
+            .write_batch(&ids, None, None)
+            .map_err(|error| error.to_string())?;
+        column.close().map_err(|error| error.to_string())?;
+    }
+    for (_, values) in columns.iter() {
+        if let Some(mut column) = row_group.next_column().map_err(|error| error.to_string())? {
+            column
+                .typed::<DoubleType>()
+                .write_batch(values, None, None)
+                .map_err(|error| error.to_string())?;
+            column.close().map_err(|error| error.to_string())?;
+        }
+    }
+    row_group.close().map_err(|error| error.to_string())?;
+    writer.close().map_err(|error| error.to_string())?;
+
+    Ok(())
+}
+
+// An exporter that writes snapshots of the universe to files.
+pub struct Exporter {
+    pub export_format: ExportFormat,
+    pub status: String,
+}
+
+// Default value for Exporter.
+impl Default for Exporter {
+    fn default() -> Self {
+        Self {
+            export_format: ExportFormat::Csv,
+            status: tr("Values are written in the current display units").to_string(),
+        }
+    }
+}
+
+// Implementations for Exporter.
+impl Exporter {
+    // Return the name of a new snapshot file, named after the time since unix epoch.
+    fn file_name(&self) -> String {
+        format!(
+            "snapshot_{}.{}",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+            self.export_format.extension()
+        )
+    }
+
+    // Export a snapshot of the universe to a new file in the export directory.
+    pub fn export(&mut self, universe: &Universe) {
+        let path: PathBuf = Path::new(EXPORT_DIRECTORY).join(self.file_name());
+        self.export_to(&path, universe);
+    }
+
+    // Export a snapshot of the universe to a file at the given path.
+    fn export_to(&mut self, path: &Path, universe: &Universe) {
+        // Export the snapshot and store the result as the status.
+        self.status = match self.export_format.export(path, universe) {
+            Ok(()) => tr_format(
+                "Exported {} bodies to {}",
+                &[&universe.bodies.len(), &path.display()],
+            ),
+            Err(error) => tr_format("Export failed: {}", &[&error]),
+        };
+    }
+
+    // Show the export settings in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe) {
+        // Create a combo box to select the export format.
+        let label_id = ui.label(tr("Format")).id;
+        ComboBox::from_id_source("Export Format")
+            .selected_text(tr(self.export_format.name()))
+            .show_ui(ui, |ui| {
+                for &export_format in ExportFormat::ALL {
+                    ui.selectable_value(
+                        &mut self.export_format,
+                        export_format,
+                        tr(export_format.name()),
+                    );
+                }
+            })
+            .response
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a button to export a snapshot to the export directory, and one to choose where to export it if file dialogs are available.
+        ui.horizontal(|ui| {
+            if ui.button(tr("Export Snapshot")).clicked() {
+                self.export(universe);
+            }
+            if DIALOGS_AVAILABLE && ui.button(tr("Export Snapshot As...")).clicked() {
+                if let Some(path) = save_file(
+                    tr("Export Snapshot"),
+                    &[self.export_format.file_filter()],
+                    &self.file_name(),
+                ) {
+                    self.export_to(&path, universe);
+                }
+            }
+        });
+        ui.label(&self.status);
+        ui.end_row();
+    }
+}
@@ -0,0 +1,50 @@
+use crate::locale::*;
+use crate::universe::*;
+use notan::prelude::*;
+use notan_egui::*;
+
+// The distance in points between the indicator and the top of the screen.
+const INDICATOR_MARGIN: f32 = 10.0;
+
+// A soft pause that pauses the universe while the window is unfocused and resumes it once the window is focused again, so the state doesn't evolve while the user is in another app.
+#[derive(Default)]
+pub struct FocusPause {
+    pub enabled: bool,
+    pub auto_paused: bool,
+}
+
+// Implementations for FocusPause.
+impl FocusPause {
+    // Pause the universe if the window lost focus while it was running, and resume it if the window regained focus or the soft pause was disabled, but only if it was paused by the soft pause.
+    pub fn update(&mut self, app: &mut App, universe: &mut Universe) {
+        // Forget the soft pause if the universe was resumed some other way.
+        if self.auto_paused && !universe.paused {
+            self.auto_paused = false;
+        }
+
+        let focused = app.window().is_focused();
+        if self.enabled && !focused && !universe.paused {
+            universe.paused = true;
+            self.auto_paused = true;
+        } else if self.auto_paused && (focused || !self.enabled) {
+            universe.paused = false;
+            self.auto_paused = false;
+        }
+    }
+
+    // Show an indicator at the top of the screen in the given context while the universe is paused by the soft pause.
+    pub fn show(&self, context: &Context) {
+        if !self.auto_paused {
+            return;
+        }
+
+        Area::new("Focus Pause Indicator")
+            .anchor(Align2::CENTER_TOP, vec2(0.0, INDICATOR_MARGIN))
+            .interactable(false)
+            .show(context, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(tr("Paused while the window is unfocused"));
+                });
+            });
+    }
+}
@@ -0,0 +1,205 @@
+use crate::generation::*;
+use crate::locale::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+// The file that the gallery is stored in.
+const GALLERY_PATH: &str = "config/gallery.json";
+// The width and height in pixels of each thumbnail.
+const THUMBNAIL_SIZE: usize = 64;
+// The size in points that thumbnails are shown at.
+const THUMBNAIL_POINTS: f32 = 64.0;
+
+// A run stored in the gallery, with the settings it was generated and simulated with and a grayscale thumbnail of how it looked.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GalleryEntry {
+    pub name: String,
+    pub generation_settings: GenerationSettings,
+    pub universe_settings: UniverseSettings,
+    pub body_amount: usize,
+    pub thumbnail: Vec<u8>,
+    #[serde(skip)]
+    pub texture: Option<TextureHandle>,
+}
+
+// Return a grayscale thumbnail of the bodies of a universe, fit to their bounds, with the brightness of each pixel rising with the logarithm of the mass in it so both dense cores and sparse halos show.
+fn render_thumbnail(universe: &Universe) -> Vec<u8> {
+    let mut pixels = vec![0.0; THUMBNAIL_SIZE * THUMBNAIL_SIZE];
+    if universe.bodies.is_empty() {
+        return vec![0; pixels.len()];
+    }
+
+    // Find a square around the bounds of the bodies, so the thumbnail isn't stretched.
+    let (minimum, maximum) = universe.bodies.iter().fold(
+        (DVec2::splat(f64::MAX), DVec2::splat(f64::MIN)),
+        |(minimum, maximum), body| (minimum.min(body.position), maximum.max(body.position)),
+    );
+    let center = (minimum + maximum) * 0.5;
+    let extent = (maximum - minimum).max_element().max(f64::EPSILON);
+
+    // Add the mass of each body to the pixel it falls in, with the vertical axis pointing down as on the screen.
+    for body in universe.bodies.iter() {
+        let cell = ((body.position - center) / extent + 0.5) * (THUMBNAIL_SIZE - 1) as f64;
+        let (x, y) = (cell.x.round() as usize, cell.y.round() as usize);
+        if x < THUMBNAIL_SIZE && y < THUMBNAIL_SIZE {
+            pixels[y * THUMBNAIL_SIZE + x] += body.mass.max(0.0);
+        }
+    }
+
+    // Scale the logarithm of the masses to the full range of brightness.
+    let maximum_mass = pixels.iter().copied().fold(0.0, f64::max);
+    let scale = (1.0 + maximum_mass).ln().max(f64::EPSILON);
+    pixels
+        .iter()
+        .map(|mass| ((1.0 + mass).ln() / scale * 255.0) as u8)
+        .collect()
+}
+
+// A gallery of interesting runs, loaded from and saved to the config, that can be restored with one click.
+pub struct Gallery {
+    pub entries: Vec<GalleryEntry>,
+    pub name: String,
+    pub status: String,
+}
+
+// Default value for Gallery.
+impl Default for Gallery {
+    fn default() -> Self {
+        // Load the gallery from the config, starting empty if there is none.
+        let (entries, status) = match Self::load(Path::new(GALLERY_PATH)) {
+            Ok(entries) => (entries, tr_format("Loaded {}", &[&GALLERY_PATH])),
+            Err(_) => (vec![], String::new()),
+        };
+        Self {
+            entries,
+            name: String::new(),
+            status,
+        }
+    }
+}
+
+// Implementations for Gallery.
+impl Gallery {
+    // Load gallery entries from a JSON file at the given path.
+    fn load(path: &Path) -> Result<Vec<GalleryEntry>, String> {
+        // Read the file and deserialize the entries.
+        let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+
+    // Save the gallery entries to a JSON file at the given path.
+    fn write(&self, path: &Path) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the entries and write them to the file.
+        let json = serde_json::to_string(&self.entries).map_err(|error| error.to_string())?;
+        fs::write(path, json).map_err(|error| error.to_string())
+    }
+
+    // Save the gallery to the config and store the result as the status.
+    fn save(&mut self) {
+        self.status = match self.write(Path::new(GALLERY_PATH)) {
+            Ok(()) => tr_format("Saved {}", &[&GALLERY_PATH]),
+            Err(error) => tr_format("Saving the gallery failed: {}", &[&error]),
+        };
+    }
+
+    // Show the gallery in the given UI, with a button to store the current run and buttons to restore or delete each entry. Return the settings of the entry to restore, if one was clicked.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        universe: &Universe,
+        generation_settings: &GenerationSettings,
+    ) -> Option<(GenerationSettings, UniverseSettings)> {
+        // Create a text field for the name of a new entry and a button to store the current run under it.
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.name).hint_text(tr("Name")));
+            if ui.button(tr("Add Current Run")).clicked() {
+                let name = if self.name.trim().is_empty() {
+                    tr_format("Seed {}", &[&generation_settings.seed])
+                } else {
+                    self.name.trim().to_string()
+                };
+                self.entries.push(GalleryEntry {
+                    name,
+                    generation_settings: generation_settings.clone(),
+                    universe_settings: universe.universe_settings.clone(),
+                    body_amount: generation_settings.body_amount(),
+                    thumbnail: render_thumbnail(universe),
+                    texture: None,
+                });
+                self.name.clear();
+                self.save();
+            }
+        });
+        ui.end_row();
+
+        // List each entry with its thumbnail, uploading the thumbnail the first time it's shown.
+        let mut restored = None;
+        let mut removed = None;
+        ScrollArea::vertical()
+            .id_source("Gallery")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for (index, entry) in self.entries.iter_mut().enumerate() {
+                    // Replace a thumbnail of the wrong size, such as from an edited file, with a black one.
+                    if entry.thumbnail.len() != THUMBNAIL_SIZE * THUMBNAIL_SIZE {
+                        entry.thumbnail = vec![0; THUMBNAIL_SIZE * THUMBNAIL_SIZE];
+                    }
+                    let texture = entry.texture.get_or_insert_with(|| {
+                        ui.ctx().load_texture(
+                            format!("Gallery Thumbnail {}", index),
+                            ColorImage::from_gray(
+                                [THUMBNAIL_SIZE, THUMBNAIL_SIZE],
+                                &entry.thumbnail,
+                            ),
+                            TextureOptions::LINEAR,
+                        )
+                    });
+                    ui.horizontal(|ui| {
+                        ui.image((texture.id(), vec2(THUMBNAIL_POINTS, THUMBNAIL_POINTS)));
+                        ui.vertical(|ui| {
+                            ui.label(&entry.name);
+                            ui.label(tr_format(
+                                "Seed {}, {} bodies",
+                                &[&entry.generation_settings.seed, &entry.body_amount],
+                            ));
+                            ui.horizontal(|ui| {
+                                if ui.button(tr("Restore")).clicked() {
+                                    restored = Some(index);
+                                }
+                                if ui.button(tr("Delete")).clicked() {
+                                    removed = Some(index);
+                                }
+                            });
+                        });
+                    });
+                }
+            });
+        ui.end_row();
+        let restored = restored.map(|index| {
+            let entry = &self.entries[index];
+            self.status = tr_format("Restored {}", &[&entry.name]);
+            (
+                entry.generation_settings.clone(),
+                entry.universe_settings.clone(),
+            )
+        });
+        if let Some(index) = removed {
+            self.entries.remove(index);
+            self.save();
+        }
+
+        // Show the status of the gallery.
+        ui.label(&self.status);
+        ui.end_row();
+
+        restored
+    }
+}
@@ -0,0 +1,203 @@
+use crate::camera::*;
+use crate::locale::*;
+use crate::universe::*;
+use gilrs::{Axis, Button, EventType, Gilrs};
+use notan::{math::Vec2, prelude::*};
+use notan_egui::*;
+
+// The buttons that can be bound to actions, in the order they are listed in the UI.
+const BINDABLE_BUTTONS: [Button; 16] = [
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::LeftTrigger,
+    Button::RightTrigger,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+    Button::Unknown,
+];
+
+// Bindings between gamepad buttons and actions.
+pub struct GamepadBindings {
+    pub pause: Button,
+    pub step: Button,
+    pub generate: Button,
+}
+
+// Default value for GamepadBindings.
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self {
+            pause: Button::Start,
+            step: Button::East,
+            generate: Button::North,
+        }
+    }
+}
+
+// Settings to control the simulation with a gamepad.
+pub struct GamepadSettings {
+    pub gamepad_bindings: GamepadBindings,
+    pub pan_speed: f32,
+    pub zoom_speed: f32,
+    pub dead_zone: f32,
+}
+
+// Default value for GamepadSettings.
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            gamepad_bindings: Default::default(),
+            pan_speed: 800.0,
+            zoom_speed: 2.0,
+            dead_zone: 0.15,
+        }
+    }
+}
+
+// A gamepad controller that pans and zooms the camera and triggers actions.
+pub struct Gamepad {
+    pub gamepad_settings: GamepadSettings,
+    gilrs: Option<Gilrs>,
+}
+
+// Default value for Gamepad.
+impl Default for Gamepad {
+    fn default() -> Self {
+        Self {
+            gamepad_settings: Default::default(),
+            gilrs: Gilrs::new().ok(),
+        }
+    }
+}
+
+// Return the name of a gamepad button.
+fn button_name(button: Button) -> String {
+    if button == Button::Unknown {
+        tr("Unbound").to_string()
+    } else {
+        format!("{:?}", button)
+    }
+}
+
+// Implementations for Gamepad.
+impl Gamepad {
+    // Update the gamepad controller and return whether generating new bodies was requested.
+    pub fn update(&mut self, app: &mut App, camera: &mut Camera, universe: &mut Universe) -> bool {
+        // Don't do anything if gamepads aren't supported.
+        let Some(gilrs) = &mut self.gilrs else {
+            return false;
+        };
+
+        // Trigger the action bound to each pressed button.
+        let gamepad_bindings = &self.gamepad_settings.gamepad_bindings;
+        let mut generate = false;
+        while let Some(event) = gilrs.next_event() {
+            if let EventType::ButtonPressed(button, _) = event.event {
+                if button == Button::Unknown {
+                    continue;
+                }
+                if button == gamepad_bindings.pause {
+                    universe.paused = !universe.paused;
+                }
+                if button == gamepad_bindings.step {
+                    universe.step();
+                }
+                if button == gamepad_bindings.generate {
+                    generate = true;
+                }
+            }
+        }
+
+        // Pan the camera with the left stick and zoom it with the triggers of each connected gamepad.
+        let delta_time = app.timer.delta_f32();
+        let dead_zone = self.gamepad_settings.dead_zone;
+        for (_, gamepad) in gilrs.gamepads() {
+            // Find the left stick position, ignoring it within the dead zone.
+            let stick = Vec2::new(
+                gamepad.value(Axis::LeftStickX),
+                -gamepad.value(Axis::LeftStickY),
+            );
+            if stick.length() > dead_zone {
+                camera.target_translation -= stick * self.gamepad_settings.pan_speed * delta_time;
+            }
+
+            // Find how far the triggers are pressed and zoom in with the right one and out with the left one.
+            let trigger = |button| {
+                gamepad
+                    .button_data(button)
+                    .map(|button_data| button_data.value())
+                    .unwrap_or_default()
+            };
+            let zoom = trigger(Button::RightTrigger2) - trigger(Button::LeftTrigger2);
+            if zoom.abs() > dead_zone {
+                camera.target_scale *= (zoom * self.gamepad_settings.zoom_speed * delta_time).exp();
+            }
+        }
+
+        generate
+    }
+
+    // Show the gamepad settings and bindings in the given UI.
+    pub fn show(&mut self, ui: &mut Ui) {
+        // Show whether gamepads are supported and how many are connected.
+        match &self.gilrs {
+            Some(gilrs) => ui.label(format!(
+                "{} {}",
+                gilrs.gamepads().count(),
+                tr("gamepads connected")
+            )),
+            None => ui.label(tr("Gamepads aren't supported on this system.")),
+        };
+        ui.end_row();
+
+        // Create sliders to modify the pan speed, zoom speed, and dead zone.
+        let gamepad_settings = &mut self.gamepad_settings;
+        let label_id = ui.label(tr("Pan Speed")).id;
+        ui.add(Slider::new(&mut gamepad_settings.pan_speed, 0.0..=2000.0))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Zoom Speed")).id;
+        ui.add(Slider::new(&mut gamepad_settings.zoom_speed, 0.0..=10.0))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Dead Zone")).id;
+        ui.add(Slider::new(&mut gamepad_settings.dead_zone, 0.0..=0.9))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a combo box to select the button bound to each action.
+        let gamepad_bindings = &mut gamepad_settings.gamepad_bindings;
+        for (label, binding) in [
+            ("Pause", &mut gamepad_bindings.pause),
+            ("Step", &mut gamepad_bindings.step),
+            ("Generate", &mut gamepad_bindings.generate),
+        ] {
+            let label_id = ui.label(tr(label)).id;
+            ComboBox::from_id_source(("Gamepad Binding", label))
+                .selected_text(button_name(*binding))
+                .show_ui(ui, |ui| {
+                    for button in BINDABLE_BUTTONS {
+                        ui.selectable_value(binding, button, button_name(button));
+                    }
+                })
+                .response
+                .labelled_by(label_id);
+            ui.end_row();
+        }
+
+        // Create a button to reset the gamepad settings.
+        if ui.button(tr("Reset")).clicked() {
+            self.gamepad_settings = GamepadSettings::default();
+        }
+        ui.end_row();
+    }
+}
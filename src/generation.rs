@@ -0,0 +1,574 @@
+use crate::{body::*, units::*};
+use notan::{
+    math::DVec2,
+    random::{
+        rand::{Rng, RngCore, SeedableRng},
+        utils::Random,
+    },
+};
+use rand_chacha::ChaCha8Rng;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::SystemTime,
+};
+
+// A distribution that the masses of generated bodies are drawn from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MassDistribution {
+    Uniform,
+    LogUniform,
+    PowerLaw,
+}
+
+// Implementations for MassDistribution.
+impl MassDistribution {
+    // All mass distributions, in the order they are listed in the UI.
+    pub const ALL: [MassDistribution; 3] = [
+        MassDistribution::Uniform,
+        MassDistribution::LogUniform,
+        MassDistribution::PowerLaw,
+    ];
+
+    // Return the name of a mass distribution.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MassDistribution::Uniform => "Uniform",
+            MassDistribution::LogUniform => "Log-Uniform",
+            MassDistribution::PowerLaw => "Power Law",
+        }
+    }
+
+    // Return a random mass within a range drawn from a mass distribution, using the given slope for power laws.
+    fn sample(&self, rng: &mut dyn RngCore, range: &Range<f64>, slope: f64) -> f64 {
+        // Keep the start of the range positive, since ranges from scenarios, sweeps, and configs skip the clamp of the UI and the logarithm of zero is infinite.
+        let start = range.start.max(f64::EPSILON);
+        // Use the start of the range if it's empty.
+        if (start..range.end).is_empty() {
+            return start;
+        }
+
+        match self {
+            MassDistribution::Uniform => rng.gen_range(start..range.end),
+            // Sample uniformly in the logarithm of the mass.
+            MassDistribution::LogUniform => rng.gen_range(start.ln()..range.end.ln()).exp(),
+            // Sample the power law dN/dm ∝ m^-slope by inverting its cumulative distribution, which is log-uniform for a slope of 1.
+            MassDistribution::PowerLaw => {
+                let exponent = 1.0 - slope;
+                if exponent.abs() < f64::EPSILON {
+                    MassDistribution::LogUniform.sample(rng, &(start..range.end), slope)
+                } else {
+                    // Scale the masses by the start of the range so that steep slopes don't overflow, falling back to the start if they still do.
+                    let ratio = (range.end / start).powf(exponent);
+                    let mass = start
+                        * (1.0 + rng.gen_range(0.0..1.0) * (ratio - 1.0)).powf(1.0 / exponent);
+                    mass.max(start).min(range.end)
+                }
+            }
+        }
+    }
+}
+
+// The maximum amount of attempts to draw a radius within the position range before clamping it.
+const RADIUS_ATTEMPTS: usize = 64;
+
+// A radial profile that the distances of generated bodies from the center of their cluster are drawn from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RadialProfile {
+    UniformRadius,
+    UniformArea,
+    Gaussian,
+    Exponential,
+}
+
+// Implementations for RadialProfile.
+impl RadialProfile {
+    // All radial profiles, in the order they are listed in the UI.
+    pub const ALL: [RadialProfile; 4] = [
+        RadialProfile::UniformRadius,
+        RadialProfile::UniformArea,
+        RadialProfile::Gaussian,
+        RadialProfile::Exponential,
+    ];
+
+    // Return the name of a radial profile.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RadialProfile::UniformRadius => "Uniform in Radius",
+            RadialProfile::UniformArea => "Uniform in Area",
+            RadialProfile::Gaussian => "Gaussian",
+            RadialProfile::Exponential => "Exponential Disc",
+        }
+    }
+
+    // Return whether a radial profile uses a scale length.
+    pub fn has_scale(&self) -> bool {
+        matches!(self, RadialProfile::Gaussian | RadialProfile::Exponential)
+    }
+
+    // Return a random radius within a range drawn from a radial profile, using the given scale length for Gaussian and exponential profiles.
+    fn sample(&self, rng: &mut dyn RngCore, range: &Range<f64>, scale: f64) -> f64 {
+        // Use the start of the range if it's empty.
+        if range.is_empty() {
+            return range.start;
+        }
+
+        match self {
+            RadialProfile::UniformRadius => rng.gen_range(range.clone()),
+            // Sample the radius so the surface density is constant.
+            RadialProfile::UniformArea => {
+                rng.gen_range(range.start.powi(2)..range.end.powi(2)).sqrt()
+            }
+            // Sample a Rayleigh distributed radius for a 2D Gaussian or a gamma distributed radius for an exponential disc until it's within the range.
+            RadialProfile::Gaussian | RadialProfile::Exponential => {
+                let mut radius = range.start;
+                for _ in 0..RADIUS_ATTEMPTS {
+                    radius = if *self == RadialProfile::Gaussian {
+                        scale * (-2.0 * (1.0 - rng.gen_range(0.0..1.0f64)).ln()).sqrt()
+                    } else {
+                        -scale
+                            * ((1.0 - rng.gen_range(0.0..1.0f64))
+                                * (1.0 - rng.gen_range(0.0..1.0f64)))
+                            .ln()
+                    };
+                    if range.contains(&radius) {
+                        break;
+                    }
+                }
+                radius.clamp(range.start, range.end)
+            }
+        }
+    }
+}
+
+// Settings to generate a cluster of bodies with. Values are given in display units.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterSettings {
+    pub body_amount: usize,
+    pub center: DVec2,
+    pub bulk_velocity: DVec2,
+    pub position_range: Range<f64>,
+    pub radial_profile: RadialProfile,
+    pub radial_scale: f64,
+    pub axis_ratio: f64,
+    pub rotation_angle: f64,
+    pub velocity_range: Range<f64>,
+    pub mass_range: Range<f64>,
+    pub mass_distribution: MassDistribution,
+    pub mass_slope: f64,
+    pub rotation_velocity: f64,
+    pub velocity_dispersion: f64,
+    pub test_particles: bool,
+    pub moon_fraction: f64,
+    pub moon_amount: usize,
+    pub moon_levels: usize,
+    pub moon_mass_ratio: f64,
+    pub moon_distance_range: Range<f64>,
+}
+
+// Default value for ClusterSettings.
+impl Default for ClusterSettings {
+    fn default() -> Self {
+        Self {
+            body_amount: 2500,
+            center: DVec2::ZERO,
+            bulk_velocity: DVec2::ZERO,
+            position_range: 0.0..250.0,
+            radial_profile: RadialProfile::UniformRadius,
+            radial_scale: 100.0,
+            axis_ratio: 1.0,
+            rotation_angle: 0.0,
+            velocity_range: 0.0..125.0,
+            mass_range: 1.0..10.0,
+            mass_distribution: MassDistribution::Uniform,
+            mass_slope: 2.35,
+            rotation_velocity: 0.0,
+            velocity_dispersion: 0.0,
+            test_particles: false,
+            moon_fraction: 0.0,
+            moon_amount: 2,
+            moon_levels: 1,
+            moon_mass_ratio: 0.01,
+            moon_distance_range: 3.0..10.0,
+        }
+    }
+}
+
+// A random number generator that bodies can be generated with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RngKind {
+    Notan,
+    Pcg64,
+    ChaCha8,
+}
+
+// Implementations for RngKind.
+impl RngKind {
+    // All random number generators, in the order they are listed in the UI.
+    pub const ALL: [RngKind; 3] = [RngKind::Notan, RngKind::Pcg64, RngKind::ChaCha8];
+
+    // Return the name of a random number generator.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RngKind::Notan => "notan Random",
+            RngKind::Pcg64 => "Pcg64",
+            RngKind::ChaCha8 => "ChaCha8 (Portable)",
+        }
+    }
+
+    // Create a random number generator of a kind from a seed.
+    fn create(&self, seed: u64) -> Box<dyn RngCore + Send> {
+        match self {
+            RngKind::Notan => Box::new((*Random::new(seed)).clone()),
+            RngKind::Pcg64 => Box::new(Pcg64::seed_from_u64(seed)),
+            RngKind::ChaCha8 => Box::new(ChaCha8Rng::seed_from_u64(seed)),
+        }
+    }
+}
+
+// Seeds for the separate random streams of positions, velocities, and masses, where 0 derives the seed from the main seed.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SubSeeds {
+    pub position: u64,
+    pub velocity: u64,
+    pub mass: u64,
+}
+
+// Settings to generate the universe with.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationSettings {
+    pub seed: u64,
+    pub rng_kind: RngKind,
+    pub sub_seeds: SubSeeds,
+    pub clusters: Vec<ClusterSettings>,
+}
+
+// Default value for GenerationSettings.
+impl Default for GenerationSettings {
+    fn default() -> Self {
+        Self {
+            seed: Default::default(),
+            rng_kind: RngKind::Notan,
+            sub_seeds: Default::default(),
+            clusters: vec![Default::default()],
+        }
+    }
+}
+
+// Random number generators for the separate streams of positions, velocities, masses, and moons, so changing how one is generated doesn't change the others.
+struct RngStreams {
+    position: Box<dyn RngCore + Send>,
+    velocity: Box<dyn RngCore + Send>,
+    mass: Box<dyn RngCore + Send>,
+    moon: Box<dyn RngCore + Send>,
+}
+
+// Return the seed of a random stream, derived from the main seed and the index of the stream unless a sub-seed is given.
+fn stream_seed(seed: u64, sub_seed: u64, stream: u64) -> u64 {
+    if sub_seed != 0 {
+        sub_seed
+    } else {
+        seed ^ (stream + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+}
+
+// Return a random value within a range, or the start of the range if it's empty.
+fn sample_range(rng: &mut dyn RngCore, range: &Range<f64>) -> f64 {
+    if range.is_empty() {
+        range.start
+    } else {
+        rng.gen_range(range.clone())
+    }
+}
+
+// Return a random value from the standard normal distribution using the Box-Muller transform.
+fn sample_normal(rng: &mut dyn RngCore) -> f64 {
+    let radius = (-2.0 * (1.0 - rng.gen_range(0.0..1.0f64)).ln()).sqrt();
+    let theta = rng.gen_range(0.0..(std::f64::consts::PI * 2.0));
+    radius * theta.cos()
+}
+
+// Implementations for GenerationSettings.
+impl GenerationSettings {
+    // Return the total amount of bodies that will be generated.
+    pub fn body_amount(&self) -> usize {
+        self.clusters
+            .iter()
+            .map(|cluster| cluster.body_amount)
+            .sum()
+    }
+
+    // Generate new bodies using the given units to convert from display units to simulation units and the given gravitational constant to put moons in circular orbits, counting each generated body in the given progress and returning None if cancelled.
+    pub fn generate(
+        &self,
+        units: &Units,
+        gravitational_constant: f64,
+        progress: &AtomicUsize,
+        cancelled: &AtomicBool,
+    ) -> Option<Vec<Body>> {
+        // Find the main seed, using time since unix epoch if the given seed is 0.
+        let seed = if self.seed == 0 {
+            // Use time since unix epoch.
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        } else {
+            // Use the given seed.
+            self.seed
+        };
+
+        // Create a random number generator of the chosen kind for each stream.
+        let mut rng = RngStreams {
+            position: self
+                .rng_kind
+                .create(stream_seed(seed, self.sub_seeds.position, 0)),
+            velocity: self
+                .rng_kind
+                .create(stream_seed(seed, self.sub_seeds.velocity, 1)),
+            mass: self
+                .rng_kind
+                .create(stream_seed(seed, self.sub_seeds.mass, 2)),
+            moon: self.rng_kind.create(stream_seed(seed, 0, 3)),
+        };
+
+        // Generate the bodies of each cluster in one pass.
+        let mut bodies = Vec::with_capacity(self.body_amount());
+        for cluster in self.clusters.iter() {
+            cluster.generate(
+                &mut rng,
+                units,
+                gravitational_constant,
+                &mut bodies,
+                progress,
+                cancelled,
+            )?;
+        }
+        Some(bodies)
+    }
+}
+
+// A generation running on a background thread so the app doesn't stall while generating many bodies.
+pub struct GenerationTask {
+    progress: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    body_amount: usize,
+    handle: Option<JoinHandle<Option<Vec<Body>>>>,
+}
+
+// Implementations for GenerationTask.
+impl GenerationTask {
+    // Start generating bodies on a background thread using the given settings, units, and gravitational constant.
+    pub fn start(
+        generation_settings: &GenerationSettings,
+        units: &Units,
+        gravitational_constant: f64,
+    ) -> Self {
+        let progress = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        // Move copies of the settings and counters into the thread.
+        let handle = {
+            let generation_settings = generation_settings.clone();
+            let units = *units;
+            let progress = progress.clone();
+            let cancelled = cancelled.clone();
+            thread::spawn(move || {
+                generation_settings.generate(&units, gravitational_constant, &progress, &cancelled)
+            })
+        };
+
+        Self {
+            progress,
+            cancelled,
+            body_amount: generation_settings.body_amount(),
+            handle: Some(handle),
+        }
+    }
+
+    // Return the fraction of bodies that have been generated.
+    pub fn progress(&self) -> f32 {
+        if self.body_amount == 0 {
+            1.0
+        } else {
+            self.progress.load(Ordering::Relaxed) as f32 / self.body_amount as f32
+        }
+    }
+
+    // Cancel the generation.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // Return the generated bodies if the generation has finished, which are None if it was cancelled.
+    pub fn poll(&mut self) -> Option<Option<Vec<Body>>> {
+        // Only join the thread once it has finished so the app doesn't block.
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        Some(self.handle.take()?.join().ok().flatten())
+    }
+}
+
+// Implementations for ClusterSettings.
+impl ClusterSettings {
+    // Return a histogram of radii drawn from the radial profile of a cluster, split evenly over the position range.
+    pub fn radial_histogram(&self, samples: usize, bins: usize) -> Vec<usize> {
+        // Use a fixed seed so the histogram doesn't flicker between frames.
+        let mut rng = Random::new(1);
+        let mut histogram = vec![0; bins];
+
+        // Count how many sampled radii fall into each bin.
+        let width = self.position_range.end - self.position_range.start;
+        for _ in 0..samples {
+            let radius =
+                self.radial_profile
+                    .sample(&mut *rng, &self.position_range, self.radial_scale);
+            let bin = if width > 0.0 {
+                ((radius - self.position_range.start) / width * bins as f64) as usize
+            } else {
+                0
+            };
+            histogram[bin.min(bins - 1)] += 1;
+        }
+
+        histogram
+    }
+
+    // Generate the bodies of a cluster and push them to the given bodies vector, followed by their moons, counting each body but not the moons in the given progress and returning None if cancelled.
+    fn generate(
+        &self,
+        rng: &mut RngStreams,
+        units: &Units,
+        gravitational_constant: f64,
+        bodies: &mut Vec<Body>,
+        progress: &AtomicUsize,
+        cancelled: &AtomicBool,
+    ) -> Option<()> {
+        // Convert the center and bulk velocity of the cluster to simulation units.
+        let center = self.center * units.simulation_value(Quantity::Length, 1.0);
+        let bulk_velocity = self.bulk_velocity * units.simulation_value(Quantity::Velocity, 1.0);
+        // Create a rotation from the rotation angle of the cluster.
+        let rotation = DVec2::from_angle(self.rotation_angle.to_radians());
+
+        // Generate the amount of bodies given.
+        let start = bodies.len();
+        for _ in 0..self.body_amount {
+            // Stop if the generation was cancelled.
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            progress.fetch_add(1, Ordering::Relaxed);
+
+            // Create a random angle for the position to be generated from.
+            let position_theta = rng.position.gen_range(0.0..(std::f64::consts::PI * 2.0));
+            // Create a random angle for the velocity to be generated from.
+            let velocity_theta = rng.velocity.gen_range(0.0..(std::f64::consts::PI * 2.0));
+            // Create the clockwise tangent of the ellipse at the position angle for the rotation velocity to be generated along.
+            let tangent = DVec2::new(
+                position_theta.sin(),
+                -position_theta.cos() * self.axis_ratio,
+            )
+            .normalize_or_zero();
+            // Create a random isotropic velocity using the velocity dispersion.
+            let dispersion = if self.velocity_dispersion > 0.0 {
+                DVec2::new(
+                    sample_normal(&mut *rng.velocity),
+                    sample_normal(&mut *rng.velocity),
+                ) * self.velocity_dispersion
+            } else {
+                DVec2::ZERO
+            };
+
+            // Push a new random body to the bodies vector.
+            bodies.push(Body {
+                // Generate a random position around the center using the position angle, radial profile, and position range, squashed into an ellipse and rotated.
+                position: center
+                    + rotation.rotate(
+                        DVec2::new(position_theta.cos(), position_theta.sin() * self.axis_ratio)
+                            * units.simulation_value(
+                                Quantity::Length,
+                                self.radial_profile.sample(
+                                    &mut *rng.position,
+                                    &self.position_range,
+                                    self.radial_scale,
+                                ),
+                            ),
+                    ),
+                // Generate a random velocity on top of the bulk velocity using the velocity angle and velocity range, adding the rotation velocity along the rotated tangent and the dispersion.
+                velocity: bulk_velocity
+                    + units.simulation_value(Quantity::Velocity, 1.0)
+                        * (DVec2::new(velocity_theta.cos(), velocity_theta.sin())
+                            * sample_range(&mut *rng.velocity, &self.velocity_range)
+                            + rotation.rotate(tangent) * self.rotation_velocity
+                            + dispersion),
+                // Generate a random mass using the mass distribution and mass range.
+                mass: units.simulation_value(
+                    Quantity::Mass,
+                    self.mass_distribution.sample(
+                        &mut *rng.mass,
+                        &self.mass_range,
+                        self.mass_slope,
+                    ),
+                ),
+                // Flag the body as a test particle if the cluster is made of them.
+                test_particle: self.test_particles,
+                ..Default::default()
+            });
+        }
+
+        // Give a fraction of the bodies moons, and a fraction of those moons moons of their own for each further level. Test particles exert no gravity, so nothing can orbit them.
+        if self.moon_fraction <= 0.0 || self.test_particles {
+            return Some(());
+        }
+        let mut parents = start..bodies.len();
+        for _ in 0..self.moon_levels {
+            let moons_start = bodies.len();
+            for parent in parents {
+                // Stop if the generation was cancelled.
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                if !rng.moon.gen_bool(self.moon_fraction.min(1.0)) {
+                    continue;
+                }
+
+                // Place each moon at a random angle and a distance in radii of its parent, moving at the circular velocity of the pair and recoiling the parent so the momentum of the system is unchanged.
+                for _ in 0..rng.moon.gen_range(1..=self.moon_amount.max(1)) {
+                    let (position, velocity, parent_mass) = {
+                        let parent = &bodies[parent];
+                        (parent.position, parent.velocity, parent.mass)
+                    };
+                    let distance = parent_mass.cbrt()
+                        * sample_range(&mut *rng.moon, &self.moon_distance_range);
+                    let direction =
+                        DVec2::from_angle(rng.moon.gen_range(0.0..(std::f64::consts::PI * 2.0)));
+                    let mass = parent_mass * self.moon_mass_ratio;
+                    if distance <= 0.0 || parent_mass + mass <= 0.0 {
+                        continue;
+                    }
+                    let relative_velocity = direction.perp()
+                        * (gravitational_constant * (parent_mass + mass) / distance).sqrt();
+                    bodies[parent].velocity -= relative_velocity * mass / (parent_mass + mass);
+                    bodies.push(Body {
+                        position: position + direction * distance,
+                        velocity: velocity + relative_velocity * parent_mass / (parent_mass + mass),
+                        mass,
+                        ..Default::default()
+                    });
+                }
+            }
+            parents = moons_start..bodies.len();
+        }
+
+        Some(())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::body::*;
+use crate::generation::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use proptest::prelude::*;
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicBool, AtomicUsize},
+};
+
+// The relative tolerance that generated values may exceed their range by due to rounding.
+const TOLERANCE: f64 = 1.0e-9;
+
+// Return a strategy for ranges between zero and a thousand, including empty, reversed, and single value ranges.
+fn range() -> impl Strategy<Value = Range<f64>> {
+    prop_oneof![
+        (0.0..1000.0f64, 0.0..1000.0f64).prop_map(|(start, end)| start..end),
+        (0.0..1000.0f64).prop_map(|value| value..value),
+    ]
+}
+
+// Return a strategy for ranges of mass, which must be positive for the logarithmic distributions.
+fn mass_range() -> impl Strategy<Value = Range<f64>> {
+    range().prop_map(|range| (range.start + 0.001)..(range.end + 0.001))
+}
+
+// Return a strategy for a cluster with random ranges and distributions, without rotation velocity or dispersion so velocities stay within the velocity range.
+fn cluster() -> impl Strategy<Value = ClusterSettings> {
+    (
+        0..50usize,
+        range(),
+        range(),
+        mass_range(),
+        prop::sample::select(RadialProfile::ALL.to_vec()),
+        prop::sample::select(MassDistribution::ALL.to_vec()),
+        1.0..500.0f64,
+        0.5..4.0f64,
+    )
+        .prop_map(
+            |(
+                body_amount,
+                position_range,
+                velocity_range,
+                mass_range,
+                radial_profile,
+                mass_distribution,
+                radial_scale,
+                mass_slope,
+            )| ClusterSettings {
+                body_amount,
+                position_range,
+                velocity_range,
+                mass_range,
+                radial_profile,
+                mass_distribution,
+                radial_scale,
+                mass_slope,
+                ..Default::default()
+            },
+        )
+}
+
+// Return whether a value is within a range, treating empty ranges as their start and allowing for rounding.
+fn within(value: f64, range: &Range<f64>) -> bool {
+    let tolerance = TOLERANCE * range.start.abs().max(range.end.abs()).max(1.0);
+    if range.is_empty() {
+        (value - range.start).abs() <= tolerance
+    } else {
+        value >= range.start - tolerance && value <= range.end + tolerance
+    }
+}
+
+// Generate bodies from the given settings with the default units.
+fn generate(generation_settings: &GenerationSettings) -> Vec<Body> {
+    generation_settings
+        .generate(
+            &Units::default(),
+            UniverseSettings::default().gravitational_constant,
+            &AtomicUsize::new(0),
+            &AtomicBool::new(false),
+        )
+        .expect("generation wasn't cancelled")
+}
+
+proptest! {
+    #[test]
+    fn generated_bodies_are_within_ranges(
+        seed in 1..u64::MAX,
+        rng_kind in prop::sample::select(RngKind::ALL.to_vec()),
+        cluster in cluster(),
+    ) {
+        let generation_settings = GenerationSettings {
+            seed,
+            rng_kind,
+            sub_seeds: SubSeeds::default(),
+            clusters: vec![cluster.clone()],
+        };
+        let bodies = generate(&generation_settings);
+        prop_assert_eq!(bodies.len(), cluster.body_amount);
+
+        // Check the distance from the center, speed, and mass of each body against its range.
+        for body in bodies.iter() {
+            let distance = body.position.distance(DVec2::ZERO);
+            prop_assert!(within(distance, &cluster.position_range), "distance {} outside {:?}", distance, cluster.position_range);
+            let speed = body.velocity.length();
+            prop_assert!(within(speed, &cluster.velocity_range), "speed {} outside {:?}", speed, cluster.velocity_range);
+            prop_assert!(within(body.mass, &cluster.mass_range), "mass {} outside {:?}", body.mass, cluster.mass_range);
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_identical_bodies(
+        seed in 1..u64::MAX,
+        rng_kind in prop::sample::select(RngKind::ALL.to_vec()),
+        clusters in prop::collection::vec(cluster(), 0..3),
+    ) {
+        let generation_settings = GenerationSettings {
+            seed,
+            rng_kind,
+            sub_seeds: SubSeeds::default(),
+            clusters,
+        };
+        prop_assert!(generate(&generation_settings) == generate(&generation_settings));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::locale::*;
+use crate::universe::*;
+use notan_egui::*;
+
+// The time in seconds that the real-time factor is averaged over.
+const MEASUREMENT_INTERVAL: f64 = 0.5;
+
+// A speed that the simulation can be locked to, as a factor of simulation time units per second of wall-clock time.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SpeedLock {
+    Unlocked,
+    Tenth,
+    RealTime,
+    Tenfold,
+    Hundredfold,
+}
+
+// Implementations for SpeedLock.
+impl SpeedLock {
+    // All speed locks, in the order they are listed in the UI.
+    pub const ALL: [SpeedLock; 5] = [
+        SpeedLock::Unlocked,
+        SpeedLock::Tenth,
+        SpeedLock::RealTime,
+        SpeedLock::Tenfold,
+        SpeedLock::Hundredfold,
+    ];
+
+    // Return the name of a speed lock.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpeedLock::Unlocked => "Unlocked",
+            SpeedLock::Tenth => "0.1x",
+            SpeedLock::RealTime => "1x",
+            SpeedLock::Tenfold => "10x",
+            SpeedLock::Hundredfold => "100x",
+        }
+    }
+
+    // Return the real-time factor of a speed lock, or None if the speed isn't locked.
+    pub fn factor(&self) -> Option<f64> {
+        match self {
+            SpeedLock::Unlocked => None,
+            SpeedLock::Tenth => Some(0.1),
+            SpeedLock::RealTime => Some(1.0),
+            SpeedLock::Tenfold => Some(10.0),
+            SpeedLock::Hundredfold => Some(100.0),
+        }
+    }
+}
+
+// How a frame advances the universe: the wall-clock time it took, and how many substeps of which length to take.
+#[derive(Clone, Copy)]
+pub struct Pacing {
+    pub wall_time: f64,
+    pub delta_time: f64,
+    pub substeps: usize,
+}
+
+// Implementations for Pacing.
+impl Pacing {
+    // Return the pacing that covers both this frame and the next, taking the substeps of both if they're as long, or one step as long as both otherwise. Substeps beyond the limit or the substeps of either frame are dropped, so a pipeline that can't keep up doesn't owe more every frame.
+    pub fn merge(self, next: Pacing, max_substeps: usize) -> Pacing {
+        let wall_time = self.wall_time + next.wall_time;
+        if self.substeps == 0 || next.substeps == 0 || self.delta_time == next.delta_time {
+            Pacing {
+                wall_time,
+                delta_time: if self.substeps == 0 {
+                    next.delta_time
+                } else {
+                    self.delta_time
+                },
+                substeps: (self.substeps + next.substeps)
+                    .min(max_substeps.max(self.substeps).max(next.substeps)),
+            }
+        } else {
+            Pacing {
+                wall_time,
+                delta_time: self.delta_time * self.substeps as f64
+                    + next.delta_time * next.substeps as f64,
+                substeps: 1,
+            }
+        }
+    }
+}
+
+// A governor that paces the universe, either with one step as long as the frame or with fixed substeps locked to a real-time factor, and measures the real-time factor that's actually reached. At high real-time factors it can sub-sample, batching the substeps of several frames into one so the state only changes every few frames and the work done for each change, like recording trails, is done less often.
+pub struct SpeedGovernor {
+    pub speed_lock: SpeedLock,
+    pub time_step: f64,
+    pub max_substeps: usize,
+    pub sub_sampling_factor: f64,
+    pub render_interval: usize,
+    pub accumulator: f64,
+    pub frame: usize,
+    pub substeps: usize,
+    pub falling_behind: bool,
+    pub real_time_factor: f64,
+    pub steps_per_frame: f64,
+    pub force_evaluation_rate: f64,
+    pub measured_wall_time: f64,
+    pub measured_simulation_time: f64,
+    pub measured_steps: usize,
+    pub measured_frames: usize,
+    pub measured_force_evaluations: usize,
+    pub last_simulation_time: f64,
+}
+
+// Default value for SpeedGovernor.
+impl Default for SpeedGovernor {
+    fn default() -> Self {
+        Self {
+            speed_lock: SpeedLock::Unlocked,
+            time_step: 1.0 / 60.0,
+            max_substeps: 64,
+            sub_sampling_factor: 10.0,
+            render_interval: 4,
+            accumulator: 0.0,
+            frame: 0,
+            substeps: 1,
+            falling_behind: false,
+            real_time_factor: 0.0,
+            steps_per_frame: 0.0,
+            force_evaluation_rate: 0.0,
+            measured_wall_time: 0.0,
+            measured_simulation_time: 0.0,
+            measured_steps: 0,
+            measured_frames: 0,
+            measured_force_evaluations: 0,
+            last_simulation_time: 0.0,
+        }
+    }
+}
+
+// Implementations for SpeedGovernor.
+impl SpeedGovernor {
+    // Return whether the speed is locked high enough that the substeps of several frames are batched into one.
+    pub fn sub_sampled(&self) -> bool {
+        self.render_interval > 1
+            && self
+                .speed_lock
+                .factor()
+                .is_some_and(|factor| factor >= self.sub_sampling_factor)
+    }
+
+    // Return whether the state of the universe was advanced this frame, which is every frame unless it's sub-sampled.
+    pub fn state_updated(&self) -> bool {
+        !self.sub_sampled() || self.substeps > 0
+    }
+
+    // Return how the universe should be advanced this frame, given the wall-clock time since the last frame.
+    pub fn pace(&mut self, wall_time: f64) -> Pacing {
+        // Take a single step as long as the frame if the speed isn't locked.
+        let Some(factor) = self.speed_lock.factor() else {
+            self.accumulator = 0.0;
+            self.substeps = 1;
+            self.falling_behind = false;
+            return Pacing {
+                wall_time,
+                delta_time: wall_time,
+                substeps: 1,
+            };
+        };
+
+        // Add the simulation time this frame is owed, and only take substeps every render interval of frames if it's sub-sampled, with a limit that covers all of them.
+        self.accumulator += wall_time * factor;
+        self.frame = self.frame.wrapping_add(1);
+        let max_substeps = if self.sub_sampled() {
+            if !self.frame.is_multiple_of(self.render_interval) {
+                self.substeps = 0;
+                return Pacing {
+                    wall_time,
+                    delta_time: self.time_step,
+                    substeps: 0,
+                };
+            }
+            self.max_substeps * self.render_interval
+        } else {
+            self.max_substeps
+        };
+
+        // Take as many fixed substeps as fit, carrying the remainder over to the next frame.
+        let substeps = (self.accumulator / self.time_step) as usize;
+        self.substeps = substeps.min(max_substeps);
+        self.accumulator -= self.substeps as f64 * self.time_step;
+
+        // Drop the time that doesn't fit into the substep limit so the simulation doesn't try to catch up forever.
+        self.falling_behind = substeps > max_substeps;
+        if self.falling_behind {
+            self.accumulator = 0.0;
+        }
+
+        Pacing {
+            wall_time,
+            delta_time: self.time_step,
+            substeps: self.substeps,
+        }
+    }
+
+    // Measure the real-time factor from how far the simulation time advanced over the wall-clock time since the last frame, the physics steps taken per rendered frame, and the force evaluations done per second of wall-clock time.
+    pub fn measure(&mut self, universe: &Universe, wall_time: f64) {
+        // Ignore jumps backwards, such as when new bodies were generated.
+        let simulated = universe.simulation_time - self.last_simulation_time;
+        self.last_simulation_time = universe.simulation_time;
+        if simulated >= 0.0 {
+            self.measured_simulation_time += simulated;
+        }
+        self.measured_wall_time += wall_time;
+        if !universe.paused {
+            self.measured_steps += self.substeps;
+        }
+        self.measured_frames += 1;
+        self.measured_force_evaluations += universe.frame_force_evaluations;
+
+        // Average over the interval so the factor is readable.
+        if self.measured_wall_time >= MEASUREMENT_INTERVAL {
+            self.real_time_factor = self.measured_simulation_time / self.measured_wall_time;
+            self.steps_per_frame = self.measured_steps as f64 / self.measured_frames as f64;
+            self.force_evaluation_rate =
+                self.measured_force_evaluations as f64 / self.measured_wall_time;
+            self.measured_simulation_time = 0.0;
+            self.measured_wall_time = 0.0;
+            self.measured_steps = 0;
+            self.measured_frames = 0;
+            self.measured_force_evaluations = 0;
+        }
+    }
+
+    // Show the settings and measurements of the governor in the given UI.
+    pub fn show(&mut self, ui: &mut Ui) {
+        // Show the measured real-time factor and how many physics steps are taken per rendered frame, and whether the frames are sub-sampled.
+        ui.label(tr_format(
+            "Real-Time Factor: {}x",
+            &[&format!("{:.2}", self.real_time_factor)],
+        ));
+        ui.label(tr_format(
+            "Physics Steps per Rendered Frame: {}",
+            &[&format!("{:.1}", self.steps_per_frame)],
+        ));
+        if self.sub_sampled() {
+            ui.label(tr_format(
+                "Sub-sampled, the state changes every {} frames",
+                &[&self.render_interval],
+            ));
+        }
+        if self.falling_behind {
+            ui.colored_label(
+                Color32::from_rgb(255, 153, 51),
+                tr("Falling behind, the substep limit was reached"),
+            );
+        }
+        ui.end_row();
+
+        // Create a combo box to lock the speed, and drag values to modify the fixed time step and the most substeps per frame.
+        let label_id = ui.label(tr("Speed Lock")).id;
+        ComboBox::from_id_source("Speed Lock")
+            .selected_text(tr(self.speed_lock.name()))
+            .show_ui(ui, |ui| {
+                for speed_lock in SpeedLock::ALL {
+                    ui.selectable_value(&mut self.speed_lock, speed_lock, tr(speed_lock.name()));
+                }
+            })
+            .response
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Fixed Time Step")).id;
+        ui.add(
+            DragValue::new(&mut self.time_step)
+                .speed(0.001)
+                .clamp_range(0.0001..=1.0),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Max Substeps")).id;
+        ui.add(DragValue::new(&mut self.max_substeps).clamp_range(1..=1000))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create drag values to modify the real-time factor that sub-sampling starts at and how many frames the substeps are batched over, where 1 turns it off.
+        let label_id = ui.label(tr("Sub-Sample From")).id;
+        ui.add(
+            DragValue::new(&mut self.sub_sampling_factor)
+                .speed(0.1)
+                .clamp_range(0.1..=1000.0)
+                .suffix("x"),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Render Interval")).id;
+        ui.add(DragValue::new(&mut self.render_interval).clamp_range(1..=60))
+            .labelled_by(label_id);
+        ui.end_row();
+    }
+}
@@ -0,0 +1,467 @@
+// The shader macros of notan check for a wgpu feature that this crate doesn't have.
+#![allow(unexpected_cfgs)]
+
+use crate::body::*;
+use crate::universe::*;
+use notan::{
+    math::{DVec2, Mat3, Mat4, Vec2, Vec4},
+    prelude::*,
+};
+use std::collections::{HashMap, HashSet};
+
+// The floats in each trail vertex, which are its position, color, and the position in the ring of samples it was recorded at.
+const TRAIL_VERTEX_FLOATS: usize = 7;
+// The floats in each point instance, which are its center, radius, and color.
+const POINT_INSTANCE_FLOATS: usize = 7;
+// The amount of trail slots the trail buffer starts with and grows by at least.
+const MINIMUM_SLOTS: usize = 64;
+// The smallest radius in pixels points are drawn with, so bodies smaller than a pixel stay visible.
+const MINIMUM_POINT_PIXELS: f32 = 0.75;
+
+// The vertex shader of the trails, which fades each segment by how many samples ago it was recorded, found from the position in the ring of samples it was recorded at.
+//language=glsl
+const TRAIL_VERTEX: ShaderSource = notan::vertex_shader! {
+    r#"
+    #version 450
+    layout(location = 0) in vec2 a_position;
+    layout(location = 1) in vec4 a_color;
+    layout(location = 2) in float a_sample;
+
+    layout(location = 0) out vec4 v_color;
+    layout(set = 0, binding = 0) uniform Locals {
+        mat4 u_transform;
+        vec4 u_parameters;
+    };
+
+    void main() {
+        int trail_length = int(u_parameters.y);
+        int age = (int(u_parameters.x) - int(a_sample) + trail_length) % trail_length;
+        v_color = vec4(a_color.rgb, a_color.a * (1.0 - float(age) / float(trail_length)));
+        gl_Position = u_transform * vec4(a_position, 0.0, 1.0);
+    }
+    "#
+};
+
+// The fragment shader of the trails.
+//language=glsl
+const TRAIL_FRAGMENT: ShaderSource = notan::fragment_shader! {
+    r#"
+    #version 450
+    precision mediump float;
+
+    layout(location = 0) in vec4 v_color;
+    layout(location = 0) out vec4 color;
+
+    void main() {
+        if (v_color.a <= 0.0) {
+            discard;
+        }
+        color = v_color;
+    }
+    "#
+};
+
+// The vertex shader of the points, which stretches a quad around each instance, at least a minimum amount of pixels in radius and with a pixel of margin to smooth the edge in.
+//language=glsl
+const POINT_VERTEX: ShaderSource = notan::vertex_shader! {
+    r#"
+    #version 450
+    layout(location = 0) in vec2 a_corner;
+    layout(location = 1) in vec2 a_center;
+    layout(location = 2) in float a_radius;
+    layout(location = 3) in vec4 a_color;
+
+    layout(location = 0) out vec4 v_color;
+    layout(location = 1) out vec2 v_corner;
+    layout(location = 2) out float v_pixels;
+    layout(set = 0, binding = 0) uniform Locals {
+        mat4 u_transform;
+        vec4 u_parameters;
+    };
+
+    void main() {
+        float radius = max(a_radius, u_parameters.w / u_parameters.z);
+        v_pixels = radius * u_parameters.z;
+        v_color = a_color;
+        v_corner = a_corner * (v_pixels + 1.0) / v_pixels;
+        gl_Position = u_transform * vec4(a_center + a_corner * (radius + 1.0 / u_parameters.z), 0.0, 1.0);
+    }
+    "#
+};
+
+// The fragment shader of the points, which cuts a circle out of each quad with a smooth edge.
+//language=glsl
+const POINT_FRAGMENT: ShaderSource = notan::fragment_shader! {
+    r#"
+    #version 450
+    precision mediump float;
+
+    layout(location = 0) in vec4 v_color;
+    layout(location = 1) in vec2 v_corner;
+    layout(location = 2) in float v_pixels;
+    layout(location = 0) out vec4 color;
+
+    void main() {
+        float coverage = clamp((1.0 - length(v_corner)) * v_pixels + 0.5, 0.0, 1.0);
+        if (coverage <= 0.0) {
+            discard;
+        }
+        color = vec4(v_color.rgb, v_color.a * coverage);
+    }
+    "#
+};
+
+// Return the layout of a trail vertex.
+fn trail_vertex_info() -> VertexInfo {
+    VertexInfo::new()
+        .attr(0, VertexFormat::Float32x2)
+        .attr(1, VertexFormat::Float32x4)
+        .attr(2, VertexFormat::Float32)
+}
+
+// Return the layout of a corner of the point quad.
+fn corner_vertex_info() -> VertexInfo {
+    VertexInfo::new().attr(0, VertexFormat::Float32x2)
+}
+
+// Return the layout of a point instance.
+fn point_instance_info() -> VertexInfo {
+    VertexInfo::new()
+        .attr(1, VertexFormat::Float32x2)
+        .attr(2, VertexFormat::Float32)
+        .attr(3, VertexFormat::Float32x4)
+        .step_mode(VertexStepMode::Instance)
+}
+
+// The pipelines and buffers of the GPU renderer, which live as long as it does, with a trail buffer for each position in the ring of samples.
+struct GpuResources {
+    trail_pipeline: Pipeline,
+    point_pipeline: Pipeline,
+    trail_buffers: Vec<Buffer>,
+    corner_buffer: Buffer,
+    instance_buffer: Buffer,
+    uniform_buffer: Buffer,
+}
+
+// Implementations for GpuResources.
+impl GpuResources {
+    // Create the pipelines and buffers with the given graphics.
+    fn new(graphics: &mut Graphics) -> Result<Self, String> {
+        let trail_pipeline = graphics
+            .create_pipeline()
+            .from(&TRAIL_VERTEX, &TRAIL_FRAGMENT)
+            .with_vertex_info(&trail_vertex_info())
+            .with_color_blend(BlendMode::NORMAL)
+            .build()?;
+        let point_pipeline = graphics
+            .create_pipeline()
+            .from(&POINT_VERTEX, &POINT_FRAGMENT)
+            .with_vertex_info(&corner_vertex_info())
+            .with_vertex_info(&point_instance_info())
+            .with_color_blend(BlendMode::NORMAL)
+            .build()?;
+        #[rustfmt::skip]
+        let corners: [f32; 12] = [
+            -1.0, -1.0,
+            1.0, -1.0,
+            1.0, 1.0,
+            -1.0, -1.0,
+            1.0, 1.0,
+            -1.0, 1.0,
+        ];
+        let corner_buffer = graphics
+            .create_vertex_buffer()
+            .with_info(&corner_vertex_info())
+            .with_data(&corners)
+            .build()?;
+        let instance_buffer = graphics
+            .create_vertex_buffer()
+            .with_info(&point_instance_info())
+            .build()?;
+        let uniform_buffer = graphics
+            .create_uniform_buffer(0, "Locals")
+            .with_data(&[0.0; 20])
+            .build()?;
+        Ok(Self {
+            trail_pipeline,
+            point_pipeline,
+            trail_buffers: Vec::new(),
+            corner_buffer,
+            instance_buffer,
+            uniform_buffer,
+        })
+    }
+}
+
+// A renderer that draws the trails of all bodies and the bodies themselves as points with custom pipelines, instead of a draw call for each segment and circle. Each body gets a slot of segments that is used as a ring, and the segments recorded at each position in the ring are kept in their own vertex buffer, so recording a sample only writes one segment per body and only the buffer of the newest position is uploaded.
+#[derive(Default)]
+pub struct GpuRenderer {
+    resources: Option<GpuResources>,
+    pub error: Option<String>,
+    trail_slots: HashMap<BodyId, usize>,
+    free_slots: Vec<usize>,
+    fading_slots: Vec<(usize, usize)>,
+    slot_amount: usize,
+    last_positions: Vec<Vec2>,
+    trail_samples: Vec<Vec<f32>>,
+    dirty_samples: Vec<bool>,
+    trail_length: usize,
+    trail_head: usize,
+    last_time: Option<f64>,
+    point_instances: Vec<f32>,
+}
+
+// Implementations for GpuRenderer.
+impl GpuRenderer {
+    // Create the pipelines and buffers if they don't exist yet, remembering the error and not trying again if that fails.
+    pub fn prepare(&mut self, graphics: &mut Graphics) {
+        if self.resources.is_some() || self.error.is_some() {
+            return;
+        }
+        match GpuResources::new(graphics) {
+            Ok(resources) => self.resources = Some(resources),
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    // Return whether the pipelines and buffers exist.
+    pub fn is_ready(&self) -> bool {
+        self.resources.is_some()
+    }
+
+    // Remove all trails.
+    pub fn clear_trails(&mut self) {
+        if self.slot_amount == 0 {
+            return;
+        }
+        self.trail_slots.clear();
+        self.free_slots.clear();
+        self.fading_slots.clear();
+        self.slot_amount = 0;
+        self.last_positions.clear();
+        self.trail_samples.clear();
+        self.dirty_samples.clear();
+        self.trail_head = 0;
+        self.last_time = None;
+    }
+
+    // Return a free trail slot, growing the segments of every position in the ring and uploading them all again if there is none.
+    fn allocate_slot(&mut self) -> usize {
+        if let Some(slot) = self.free_slots.pop() {
+            return slot;
+        }
+        let slot_amount = self.slot_amount;
+        self.slot_amount = (slot_amount * 2).max(MINIMUM_SLOTS);
+        self.last_positions.resize(self.slot_amount, Vec2::ZERO);
+        for samples in self.trail_samples.iter_mut() {
+            samples.resize(self.slot_amount * 2 * TRAIL_VERTEX_FLOATS, 0.0);
+        }
+        self.dirty_samples.fill(true);
+        self.free_slots
+            .extend((slot_amount + 1..self.slot_amount).rev());
+        slot_amount
+    }
+
+    // Write the segment of a slot at the head of the ring, where a transparent color hides it.
+    fn write_segment(&mut self, slot: usize, start: Vec2, end: Vec2, color: Color) {
+        let head = self.trail_head;
+        let samples = &mut self.trail_samples[head];
+        let offset = slot * 2 * TRAIL_VERTEX_FLOATS;
+        for (vertex, position) in [start, end].iter().enumerate() {
+            let vertex_offset = offset + vertex * TRAIL_VERTEX_FLOATS;
+            samples[vertex_offset..vertex_offset + TRAIL_VERTEX_FLOATS].copy_from_slice(&[
+                position.x,
+                position.y,
+                color.r,
+                color.g,
+                color.b,
+                color.a,
+                head as f32,
+            ]);
+        }
+    }
+
+    // Record a sample of the trails of all bodies with the given length and the colors of the bodies if the universe stepped since the last one, starting over if the length changed or the simulation time went backwards.
+    pub fn record_trails(
+        &mut self,
+        universe: &Universe,
+        trail_length: usize,
+        body_colors: impl FnOnce() -> Vec<Color>,
+    ) {
+        let trail_length = trail_length.max(2);
+        if trail_length != self.trail_length
+            || self
+                .last_time
+                .is_some_and(|time| universe.simulation_time < time)
+        {
+            self.clear_trails();
+            self.trail_length = trail_length;
+        }
+        if self.last_time == Some(universe.simulation_time) {
+            return;
+        }
+        self.last_time = Some(universe.simulation_time);
+        if self.trail_samples.len() != self.trail_length {
+            self.trail_samples = vec![Vec::new(); self.trail_length];
+            self.dirty_samples = vec![true; self.trail_length];
+        }
+        self.trail_head = (self.trail_head + 1) % self.trail_length;
+        self.dirty_samples[self.trail_head] = true;
+
+        // Write a segment from the last position of each body to where it is now, hiding the first segment of new bodies.
+        let body_colors = body_colors();
+        let mut seen = HashSet::with_capacity(universe.bodies.len());
+        for (body, &body_color) in universe.bodies.iter().zip(body_colors.iter()) {
+            let position = body.position.as_vec2();
+            let (slot, color) = match self.trail_slots.get(&body.id) {
+                Some(&slot) => (slot, body_color),
+                None => {
+                    let slot = self.allocate_slot();
+                    self.trail_slots.insert(body.id, slot);
+                    (slot, Color::TRANSPARENT)
+                }
+            };
+            let start = if color.a > 0.0 {
+                self.last_positions[slot]
+            } else {
+                position
+            };
+            self.write_segment(slot, start, position, color);
+            self.last_positions[slot] = position;
+            seen.insert(body.id);
+        }
+
+        // Hide the newest segment of the slots of bodies that are gone, freeing each slot once its trail has faded out and all of its segments are hidden.
+        let gone: Vec<(BodyId, usize)> = self
+            .trail_slots
+            .iter()
+            .filter(|(id, _)| !seen.contains(id))
+            .map(|(id, slot)| (*id, *slot))
+            .collect();
+        for (id, slot) in gone {
+            self.trail_slots.remove(&id);
+            self.fading_slots.push((slot, self.trail_length));
+        }
+        let mut fading_slots = std::mem::take(&mut self.fading_slots);
+        fading_slots.retain_mut(|(slot, remaining)| {
+            self.write_segment(*slot, Vec2::ZERO, Vec2::ZERO, Color::TRANSPARENT);
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.free_slots.push(*slot);
+            }
+            *remaining > 0
+        });
+        self.fading_slots = fading_slots;
+    }
+
+    // Remove all points queued for this frame.
+    pub fn clear_points(&mut self) {
+        self.point_instances.clear();
+    }
+
+    // Queue a point for this frame, drawn in the order it was queued.
+    pub fn push_point(&mut self, position: DVec2, radius: f64, color: Color) {
+        self.point_instances.extend_from_slice(&[
+            position.x as f32,
+            position.y as f32,
+            radius as f32,
+            color.r,
+            color.g,
+            color.b,
+            color.a,
+        ]);
+    }
+
+    // Create a renderer that clears the screen and draws the trails and queued points with the given camera matrix and scale on a surface of the given size, or return none if the pipelines don't exist.
+    pub fn create_renderer(
+        &mut self,
+        graphics: &mut Graphics,
+        camera_matrix: Mat3,
+        camera_scale: f32,
+        size: (f32, f32),
+    ) -> Option<Renderer> {
+        let resources = self.resources.as_mut()?;
+
+        // Create a trail buffer for each position in the ring that doesn't have one yet, remembering the error and leaving out the positions without one if that fails.
+        while resources.trail_buffers.len() < self.trail_samples.len() {
+            match graphics
+                .create_vertex_buffer()
+                .with_info(&trail_vertex_info())
+                .build()
+            {
+                Ok(buffer) => resources.trail_buffers.push(buffer),
+                Err(error) => {
+                    self.error = Some(error);
+                    break;
+                }
+            }
+        }
+
+        // Upload the segments of the positions in the ring that were written since the last frame, which is only the newest one unless the slots grew, and the points of this frame.
+        for ((samples, dirty), buffer) in self
+            .trail_samples
+            .iter()
+            .zip(self.dirty_samples.iter_mut())
+            .zip(resources.trail_buffers.iter())
+        {
+            if *dirty && !samples.is_empty() {
+                graphics.set_buffer_data(buffer, samples.as_slice());
+            }
+            *dirty = false;
+        }
+        if !self.point_instances.is_empty() {
+            graphics.set_buffer_data(&resources.instance_buffer, self.point_instances.as_slice());
+        }
+
+        // Combine the projection of the surface with the camera matrix, and pass the ring head and length and the minimum point size along with it.
+        let projection = Mat4::orthographic_rh_gl(0.0, size.0, size.1, 0.0, -1.0, 1.0);
+        let transform = projection
+            * Mat4::from_cols(
+                camera_matrix.x_axis.extend(0.0),
+                camera_matrix.y_axis.extend(0.0),
+                Vec4::Z,
+                Vec4::new(camera_matrix.z_axis.x, camera_matrix.z_axis.y, 0.0, 1.0),
+            );
+        let mut uniforms = [0.0; 20];
+        uniforms[..16].copy_from_slice(&transform.to_cols_array());
+        uniforms[16..].copy_from_slice(&[
+            self.trail_head as f32,
+            self.trail_length.max(1) as f32,
+            camera_scale,
+            MINIMUM_POINT_PIXELS,
+        ]);
+        graphics.set_buffer_data(&resources.uniform_buffer, &uniforms);
+
+        // Clear the screen, then draw the trails under the points.
+        let mut renderer = graphics.create_renderer();
+        renderer.begin(Some(ClearOptions::color(Color::BLACK)));
+        if self.slot_amount > 0 && !self.trail_samples.is_empty() {
+            renderer.set_pipeline(&resources.trail_pipeline);
+            renderer.set_primitive(DrawPrimitive::Lines);
+            for buffer in resources
+                .trail_buffers
+                .iter()
+                .take(self.trail_samples.len())
+            {
+                renderer.bind_buffers(&[&resources.uniform_buffer, buffer]);
+                renderer.draw(0, (self.slot_amount * 2) as i32);
+            }
+        }
+        if !self.point_instances.is_empty() {
+            renderer.set_pipeline(&resources.point_pipeline);
+            renderer.set_primitive(DrawPrimitive::Triangles);
+            renderer.bind_buffers(&[
+                &resources.uniform_buffer,
+                &resources.corner_buffer,
+                &resources.instance_buffer,
+            ]);
+            renderer.draw_instanced(
+                0,
+                6,
+                (self.point_instances.len() / POINT_INSTANCE_FLOATS) as i32,
+            );
+        }
+        renderer.end();
+        Some(renderer)
+    }
+}
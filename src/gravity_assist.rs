@@ -0,0 +1,212 @@
+use crate::camera::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{draw::*, math::DVec2, prelude::*};
+use notan_egui::*;
+
+// The amount of line segments the conic is drawn with.
+const CONIC_SEGMENTS: usize = 256;
+// The multiple of the larger of the current and periapsis distances that the arms of an open conic are drawn out to.
+const ARM_LENGTH: f64 = 4.0;
+// The distance in points between the periapsis and its annotation.
+const ANNOTATION_MARGIN: f32 = 8.0;
+
+// The instantaneous two-body conic of a body around the body with the strongest pull on it, in the frame of that body.
+#[derive(Clone, Copy)]
+pub struct Conic {
+    pub center: DVec2,
+    pub periapsis_direction: DVec2,
+    pub eccentricity: f64,
+    pub semi_latus_rectum: f64,
+    pub distance: f64,
+}
+
+// Implementations for Conic.
+impl Conic {
+    // Return the conic of a body with the given position and velocity relative to a primary at the given center, with the given gravitational parameter of both.
+    fn new(
+        center: DVec2,
+        offset: DVec2,
+        velocity: DVec2,
+        gravitational_parameter: f64,
+    ) -> Option<Self> {
+        let distance = offset.length();
+        if distance <= 0.0 || gravitational_parameter <= 0.0 {
+            return None;
+        }
+
+        // Find the eccentricity vector, which points at periapsis, and the semi-latus rectum from the specific angular momentum.
+        let angular_momentum = offset.perp_dot(velocity);
+        let eccentricity_vector =
+            ((velocity.length_squared() - gravitational_parameter / distance) * offset
+                - offset.dot(velocity) * velocity)
+                / gravitational_parameter;
+        let eccentricity = eccentricity_vector.length();
+        let periapsis_direction = if eccentricity > f64::EPSILON {
+            eccentricity_vector / eccentricity
+        } else {
+            offset / distance
+        };
+        Some(Self {
+            center,
+            periapsis_direction,
+            eccentricity,
+            semi_latus_rectum: angular_momentum * angular_momentum / gravitational_parameter,
+            distance,
+        })
+    }
+
+    // Return the distance of the closest approach.
+    pub fn periapsis_distance(&self) -> f64 {
+        self.semi_latus_rectum / (1.0 + self.eccentricity)
+    }
+
+    // Return the angle in radians the velocity is turned by between approach and departure, if the conic is open.
+    pub fn deflection(&self) -> Option<f64> {
+        (self.eccentricity > 1.0).then(|| 2.0 * (1.0 / self.eccentricity).asin())
+    }
+
+    // Return the position on the conic at the given angle from periapsis.
+    fn point(&self, angle: f64) -> DVec2 {
+        let radius = self.semi_latus_rectum / (1.0 + self.eccentricity * angle.cos());
+        self.center
+            + (self.periapsis_direction * angle.cos()
+                + self.periapsis_direction.perp() * angle.sin())
+                * radius
+    }
+}
+
+// A visualizer for gravity assists that draws the instantaneous two-body conic of the selected body in the frame of the body with the strongest pull on it, annotated with the periapsis distance and the deflection angle.
+#[derive(Default)]
+pub struct GravityAssist {
+    pub enabled: bool,
+    pub conic: Option<Conic>,
+}
+
+// Implementations for GravityAssist.
+impl GravityAssist {
+    // Update the conic of the selected body around the body with the strongest pull on it.
+    pub fn update(&mut self, universe: &Universe, selected: Option<usize>) {
+        self.conic = None;
+        if !self.enabled {
+            return;
+        }
+        let Some(index) = selected else {
+            return;
+        };
+        let body = &universe.bodies[index];
+        let Some(primary) = universe.dominant_body_at(body.position, Some(index)) else {
+            return;
+        };
+        let primary = &universe.bodies[primary];
+        self.conic = Conic::new(
+            primary.position,
+            body.position - primary.position,
+            body.velocity - primary.velocity,
+            universe.universe_settings.gravitational_constant * (primary.mass + body.mass),
+        );
+    }
+
+    // Draw the conic and a line from the primary to periapsis.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera) {
+        let Some(conic) = self.conic else {
+            return;
+        };
+        let color = Color::from_rgba(0.4, 0.8, 1.0, 0.7);
+
+        // Sweep the whole ellipse, or the arms of an open conic short of its asymptotes out to a multiple of the current distance.
+        let (start, end) = if conic.eccentricity < 1.0 {
+            (-std::f64::consts::PI, std::f64::consts::PI)
+        } else {
+            let limit = ARM_LENGTH * conic.distance.max(conic.periapsis_distance());
+            let asymptote = (-1.0 / conic.eccentricity).acos();
+            let arm = ((conic.semi_latus_rectum / limit - 1.0) / conic.eccentricity)
+                .clamp(-1.0, 1.0)
+                .acos()
+                .min(asymptote * 0.999);
+            (-arm, arm)
+        };
+        let points: Vec<DVec2> = (0..=CONIC_SEGMENTS)
+            .map(|segment| {
+                conic.point(start + (end - start) * segment as f64 / CONIC_SEGMENTS as f64)
+            })
+            .collect();
+        for (position1, position2) in points.iter().zip(points.iter().skip(1)) {
+            draw.line(
+                (position1.x as f32, position1.y as f32),
+                (position2.x as f32, position2.y as f32),
+            )
+            .width(1.0 / camera.scale)
+            .color(color);
+        }
+
+        // Mark periapsis with a line from the primary and a small circle.
+        let periapsis = conic.point(0.0);
+        draw.line(
+            (conic.center.x as f32, conic.center.y as f32),
+            (periapsis.x as f32, periapsis.y as f32),
+        )
+        .width(1.0 / camera.scale)
+        .color(color.with_alpha(0.4));
+        draw.circle(3.0 / camera.scale)
+            .position(periapsis.x as f32, periapsis.y as f32)
+            .tolerance(camera.circle_tolerance())
+            .color(color);
+    }
+
+    // Annotate periapsis in the given context with its distance and the deflection angle, or the eccentricity if the conic is closed.
+    pub fn show_annotation(
+        &self,
+        context: &Context,
+        app: &mut App,
+        camera: &Camera,
+        universe: &Universe,
+    ) {
+        let Some(conic) = self.conic else {
+            return;
+        };
+        let units = &universe.universe_settings.units;
+        let mut text = tr_format(
+            "Periapsis: {}",
+            &[&units.format(Quantity::Length, conic.periapsis_distance())],
+        );
+        text.push('\n');
+        text.push_str(&match conic.deflection() {
+            Some(deflection) => tr_format(
+                "Deflection: {}°",
+                &[&format!("{:.1}", deflection.to_degrees())],
+            ),
+            None => tr_format(
+                "Bound, Eccentricity: {}",
+                &[&format!("{:.3}", conic.eccentricity)],
+            ),
+        });
+
+        // Draw the annotation behind the UI, next to periapsis.
+        let position = camera.world_to_screen(app, conic.point(0.0));
+        context.layer_painter(LayerId::background()).text(
+            pos2(
+                position.x + ANNOTATION_MARGIN,
+                position.y + ANNOTATION_MARGIN,
+            ),
+            Align2::LEFT_TOP,
+            text,
+            FontId::proportional(12.0),
+            Color32::from_rgb(102, 204, 255),
+        );
+    }
+
+    // Show the settings of the visualizer in the given UI.
+    pub fn show(&mut self, ui: &mut Ui) {
+        // Create a checkbox to toggle the visualizer.
+        let label_id = ui.label(tr("Show Conic of Selected Body")).id;
+        ui.add(Checkbox::new(&mut self.enabled, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        ui.label(tr(
+            "Draws the two-body orbit of the selected body around the body with the strongest pull on it.",
+        ));
+        ui.end_row();
+    }
+}
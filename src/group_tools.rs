@@ -0,0 +1,297 @@
+use crate::body::*;
+use crate::camera::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{
+    draw::*,
+    math::{DVec2, Vec2},
+    prelude::*,
+};
+use notan_egui::*;
+use std::collections::HashSet;
+
+// The seconds between recomputing the preview while nothing it depends on changes, since the energy takes time proportional to the square of the amount of bodies.
+const PREVIEW_INTERVAL: f32 = 0.5;
+
+// The total mass and energy of the universe before and after each group transform.
+#[derive(Clone, Copy)]
+pub struct GroupPreview {
+    pub total_mass: f64,
+    pub total_energy: f64,
+    pub scaled_mass: f64,
+    pub scaled_energy: f64,
+    pub damped_energy: f64,
+}
+
+// Tools that select a group of bodies with a box and scale their masses or damp their velocities together, such as to cool down a cluster.
+#[derive(Clone)]
+pub struct GroupTools {
+    pub selected: Vec<BodyId>,
+    pub mass_factor: f64,
+    pub velocity_factor: f64,
+    pub relative_to_group: bool,
+    pub box_start: Option<Vec2>,
+    pub box_corners: Option<[DVec2; 4]>,
+    pub preview: Option<GroupPreview>,
+    pub preview_timer: f32,
+}
+
+// Default value for GroupTools.
+impl Default for GroupTools {
+    fn default() -> Self {
+        Self {
+            selected: Vec::new(),
+            mass_factor: 1.0,
+            velocity_factor: 0.5,
+            relative_to_group: true,
+            box_start: None,
+            box_corners: None,
+            preview: None,
+            preview_timer: 0.0,
+        }
+    }
+}
+
+// Implementations for GroupTools.
+impl GroupTools {
+    // Return whether the mouse is used to select a box of bodies, which happens while control is held or a box is being dragged.
+    pub fn is_selecting(&self, app: &App) -> bool {
+        app.keyboard.ctrl() || self.box_start.is_some()
+    }
+
+    // Return the indices of the selected bodies that still exist.
+    fn selected_indices(&self, universe: &Universe) -> Vec<usize> {
+        let selected: HashSet<BodyId> = self.selected.iter().copied().collect();
+        (0..universe.bodies.len())
+            .filter(|&index| selected.contains(&universe.bodies[index].id))
+            .collect()
+    }
+
+    // Multiply the masses of the bodies at the given indices by the mass factor.
+    fn scale_masses(&self, universe: &mut Universe, indices: &[usize]) {
+        for &index in indices {
+            universe.bodies[index].mass *= self.mass_factor;
+        }
+    }
+
+    // Multiply the velocities of the bodies at the given indices by the velocity factor, relative to the velocity of their center of mass if it's enabled so the group keeps drifting as a whole.
+    fn damp_velocities(&self, universe: &mut Universe, indices: &[usize]) {
+        let (momentum, mass) = indices
+            .iter()
+            .map(|&index| &universe.bodies[index])
+            .fold((DVec2::ZERO, 0.0), |(momentum, mass), body| {
+                (momentum + body.velocity * body.mass, mass + body.mass)
+            });
+        let group_velocity = if self.relative_to_group && mass > 0.0 {
+            momentum / mass
+        } else {
+            DVec2::ZERO
+        };
+        for &index in indices {
+            let body = &mut universe.bodies[index];
+            if !body.frozen {
+                body.velocity =
+                    group_velocity + (body.velocity - group_velocity) * self.velocity_factor;
+            }
+        }
+    }
+
+    // Recompute the total mass and energy of the universe now and after each transform.
+    fn compute_preview(&mut self, universe: &Universe) {
+        let indices = self.selected_indices(universe);
+        let mut scaled = universe.clone();
+        self.scale_masses(&mut scaled, &indices);
+        let mut damped = universe.clone();
+        self.damp_velocities(&mut damped, &indices);
+        self.preview = Some(GroupPreview {
+            total_mass: universe.total_mass(),
+            total_energy: universe.total_energy(),
+            scaled_mass: scaled.total_mass(),
+            scaled_energy: scaled.total_energy(),
+            damped_energy: damped.total_energy(),
+        });
+    }
+
+    // Update the group tools, selecting the bodies inside the box dragged with the left mouse button while control is held, adding them to the selection if shift is held too.
+    pub fn update(
+        &mut self,
+        app: &mut App,
+        camera: &Camera,
+        universe: &Universe,
+        pointer_over_ui: bool,
+    ) {
+        // Start the box where the left mouse button is pressed.
+        let mouse_position = Vec2::from(app.mouse.position());
+        if app.keyboard.ctrl() && app.mouse.left_was_pressed() && !pointer_over_ui {
+            self.box_start = Some(mouse_position);
+        }
+        self.box_corners = None;
+        let Some(start) = self.box_start else {
+            return;
+        };
+
+        // Find the corners of the box on the screen, and in the world so it can be drawn.
+        let (minimum, maximum) = (start.min(mouse_position), start.max(mouse_position));
+        self.box_corners = Some(
+            [
+                minimum,
+                Vec2::new(maximum.x, minimum.y),
+                maximum,
+                Vec2::new(minimum.x, maximum.y),
+            ]
+            .map(|corner| camera.screen_to_world(app, corner)),
+        );
+        if app.mouse.left_is_down() {
+            return;
+        }
+
+        // Select the bodies inside the box once the left mouse button is released.
+        self.box_start = None;
+        self.box_corners = None;
+        if !app.keyboard.shift() {
+            self.selected.clear();
+        }
+        let selected: HashSet<BodyId> = self.selected.iter().copied().collect();
+        for body in universe.bodies.iter() {
+            let position = camera.world_to_screen(app, body.position);
+            if position.cmpge(minimum).all()
+                && position.cmple(maximum).all()
+                && !selected.contains(&body.id)
+            {
+                self.selected.push(body.id);
+            }
+        }
+        self.preview = None;
+    }
+
+    // Draw the box being dragged and an outline around each selected body.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera, universe: &Universe) {
+        let color = Color::from_rgba(1.0, 0.8, 0.2, 0.8);
+        if let Some(corners) = self.box_corners {
+            for (corner1, corner2) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+                draw.line(
+                    (corner1.x as f32, corner1.y as f32),
+                    (corner2.x as f32, corner2.y as f32),
+                )
+                .width(1.0 / camera.scale)
+                .color(color);
+            }
+        }
+        for index in self.selected_indices(universe) {
+            let body = &universe.bodies[index];
+            draw.circle(body.radius() as f32 + 3.0 / camera.scale)
+                .position(body.position.x as f32, body.position.y as f32)
+                .tolerance(camera.circle_tolerance())
+                .stroke(1.0 / camera.scale)
+                .color(color);
+        }
+    }
+
+    // Show the group tools in the given UI, with a preview of the total mass and energy after each transform.
+    pub fn show(&mut self, ui: &mut Ui, universe: &mut Universe, delta_time: f32) {
+        // Show how to select bodies and how many are selected, with buttons to select all or none.
+        ui.label(tr(
+            "Hold Ctrl and drag a box with the left mouse button to select bodies, holding Shift as well to add to the selection.",
+        ));
+        ui.end_row();
+        let indices = self.selected_indices(universe);
+        ui.label(tr_format("{} bodies selected", &[&indices.len()]));
+        ui.horizontal(|ui| {
+            if ui.button(tr("Select All")).clicked() {
+                self.selected = universe.bodies.iter().map(|body| body.id).collect();
+                self.preview = None;
+            }
+            if ui.button(tr("Clear Selection")).clicked() {
+                self.selected.clear();
+                self.preview = None;
+            }
+        });
+        ui.end_row();
+
+        // Create widgets to modify the factors, recomputing the preview when they change.
+        let label_id = ui.label(tr("Mass Factor")).id;
+        let mass_changed = ui
+            .add(
+                DragValue::new(&mut self.mass_factor)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::MAX),
+            )
+            .labelled_by(label_id)
+            .changed();
+        ui.end_row();
+        let label_id = ui.label(tr("Velocity Factor")).id;
+        let velocity_changed = ui
+            .add(Slider::new(&mut self.velocity_factor, 0.0..=1.0))
+            .labelled_by(label_id)
+            .changed();
+        ui.end_row();
+        let label_id = ui.label(tr("Relative to Group Velocity")).id;
+        let relative_changed = ui
+            .add(Checkbox::new(&mut self.relative_to_group, ""))
+            .labelled_by(label_id)
+            .changed();
+        ui.end_row();
+        if indices.is_empty() {
+            self.preview = None;
+            return;
+        }
+
+        // Recompute the preview when something it depends on changed, or once per interval as the universe moves on.
+        self.preview_timer -= delta_time;
+        if mass_changed
+            || velocity_changed
+            || relative_changed
+            || self.preview.is_none()
+            || self.preview_timer <= 0.0
+        {
+            self.preview_timer = PREVIEW_INTERVAL;
+            self.compute_preview(universe);
+        }
+
+        // Show the totals before and after each transform, with a button to apply it.
+        let Some(preview) = self.preview else {
+            return;
+        };
+        let units = universe.universe_settings.units;
+        ui.label(tr_format(
+            "Total Mass: {} → {}",
+            &[
+                &units.format(Quantity::Mass, preview.total_mass),
+                &units.format(Quantity::Mass, preview.scaled_mass),
+            ],
+        ));
+        ui.label(tr_format(
+            "Total Energy: {} → {}",
+            &[
+                &format!("{:.4e}", preview.total_energy),
+                &format!("{:.4e}", preview.scaled_energy),
+            ],
+        ));
+        if ui.button(tr("Scale Masses")).clicked() {
+            let group_tools = self.clone();
+            universe.edit(move |universe| {
+                let indices = group_tools.selected_indices(universe);
+                group_tools.scale_masses(universe, &indices);
+            });
+            self.preview = None;
+        }
+        ui.end_row();
+        ui.label(tr_format(
+            "Total Energy: {} → {}",
+            &[
+                &format!("{:.4e}", preview.total_energy),
+                &format!("{:.4e}", preview.damped_energy),
+            ],
+        ));
+        if ui.button(tr("Damp Velocities")).clicked() {
+            let group_tools = self.clone();
+            universe.edit(move |universe| {
+                let indices = group_tools.selected_indices(universe);
+                group_tools.damp_velocities(universe, &indices);
+            });
+            self.preview = None;
+        }
+        ui.end_row();
+    }
+}
@@ -0,0 +1,224 @@
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{draw::*, prelude::*};
+use notan_egui::*;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// The directory that heat map images are exported to.
+const HEAT_MAP_DIRECTORY: &str = "exports";
+// The heat below which a cell is forgotten, so decayed cells don't pile up.
+const MINIMUM_HEAT: f64 = 0.001;
+// The largest width and height in pixels of an exported image, beyond which several cells share a pixel.
+const MAXIMUM_IMAGE_SIZE: i64 = 4096;
+
+// Return the color of a cell from transparent through red and yellow to white as its heat rises from zero to the given maximum.
+fn heat_color(heat: f64, maximum_heat: f64) -> [f32; 4] {
+    let fraction = (heat / maximum_heat.max(f64::EPSILON)).clamp(0.0, 1.0) as f32;
+    [
+        (fraction * 3.0).min(1.0),
+        (fraction * 3.0 - 1.0).clamp(0.0, 1.0),
+        (fraction * 3.0 - 2.0).clamp(0.0, 1.0),
+        fraction.sqrt(),
+    ]
+}
+
+// A heat map that counts mergers in a grid of cells over the world, fading older ones with a half-life, and overlays where collisions concentrate.
+pub struct CollisionHeatMap {
+    pub show_overlay: bool,
+    pub cell_size: f64,
+    pub half_life: f64,
+    pub opacity: f32,
+    pub cells: HashMap<(i64, i64), f64>,
+    pub simulation_time: f64,
+    pub status: String,
+}
+
+// Default value for CollisionHeatMap.
+impl Default for CollisionHeatMap {
+    fn default() -> Self {
+        Self {
+            show_overlay: false,
+            cell_size: 10.0,
+            half_life: 0.0,
+            opacity: 0.6,
+            cells: HashMap::new(),
+            simulation_time: 0.0,
+            status: String::new(),
+        }
+    }
+}
+
+// Implementations for CollisionHeatMap.
+impl CollisionHeatMap {
+    // Return the heat of the hottest cell.
+    fn maximum_heat(&self) -> f64 {
+        self.cells.values().copied().fold(0.0, f64::max)
+    }
+
+    // Update the heat map with the mergers of the last step, fading the cells by the simulation time that passed if there's a half-life.
+    pub fn update(&mut self, universe: &Universe) {
+        // Fade the cells by the time since the last update, forgetting the ones that faded away.
+        let elapsed = universe.simulation_time - self.simulation_time;
+        self.simulation_time = universe.simulation_time;
+        if self.half_life > 0.0 && elapsed > 0.0 {
+            let factor = 0.5_f64.powf(elapsed / self.half_life);
+            self.cells.retain(|_, heat| {
+                *heat *= factor;
+                *heat >= MINIMUM_HEAT
+            });
+        }
+
+        // Count each merger in the cell it happened in.
+        for collision_event in universe.collision_events.iter() {
+            let cell = (collision_event.position / self.cell_size).floor();
+            *self
+                .cells
+                .entry((cell.x as i64, cell.y as i64))
+                .or_default() += 1.0;
+        }
+    }
+
+    // Draw each cell of the heat map over the world if the overlay is shown.
+    pub fn draw(&self, draw: &mut Draw) {
+        if !self.show_overlay {
+            return;
+        }
+        let maximum_heat = self.maximum_heat();
+        let size = self.cell_size as f32;
+        for (&(x, y), &heat) in self.cells.iter() {
+            let [red, green, blue, alpha] = heat_color(heat, maximum_heat);
+            draw.rect((x as f32 * size, y as f32 * size), (size, size))
+                .color(Color::new(red, green, blue, alpha * self.opacity));
+        }
+    }
+
+    // Export the heat map to a new PNG file in the heat map directory, named after the time since unix epoch, with a pixel for each cell within the bounds of the hot cells, and return its path.
+    fn export(&self) -> Result<PathBuf, String> {
+        // Find the bounds of the cells, and how many cells share a pixel so the image isn't too large.
+        if self.cells.is_empty() {
+            return Err(tr("There are no collisions to export").to_string());
+        }
+        let (minimum, maximum) = self.cells.keys().fold(
+            ((i64::MAX, i64::MAX), (i64::MIN, i64::MIN)),
+            |(minimum, maximum), &(x, y)| {
+                (
+                    (minimum.0.min(x), minimum.1.min(y)),
+                    (maximum.0.max(x), maximum.1.max(y)),
+                )
+            },
+        );
+        let extent = (maximum.0 - minimum.0).max(maximum.1 - minimum.1) + 1;
+        let cells_per_pixel = ((extent + MAXIMUM_IMAGE_SIZE - 1) / MAXIMUM_IMAGE_SIZE).max(1);
+        let width = (maximum.0 - minimum.0) / cells_per_pixel + 1;
+        let height = (maximum.1 - minimum.1) / cells_per_pixel + 1;
+
+        // Add up the heat of the cells in each pixel and color the pixels on a black background.
+        let mut heats = vec![0.0; (width * height) as usize];
+        for (&(x, y), &heat) in self.cells.iter() {
+            let column = (x - minimum.0) / cells_per_pixel;
+            let row = (y - minimum.1) / cells_per_pixel;
+            heats[(row * width + column) as usize] += heat;
+        }
+        let maximum_heat = heats.iter().copied().fold(0.0, f64::max);
+        let pixels: Vec<u8> = heats
+            .iter()
+            .flat_map(|&heat| {
+                let [red, green, blue, alpha] = heat_color(heat, maximum_heat);
+                [red, green, blue].map(|channel| (channel * alpha * 255.0) as u8)
+            })
+            .collect();
+
+        // Encode the pixels to the file.
+        fs::create_dir_all(HEAT_MAP_DIRECTORY).map_err(|error| error.to_string())?;
+        let path = Path::new(HEAT_MAP_DIRECTORY).join(format!(
+            "collision_heat_map_{}.png",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+        ));
+        let file = File::create(&path).map_err(|error| error.to_string())?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|error| error.to_string())?;
+        Ok(path)
+    }
+
+    // Show the settings of the heat map in the given UI, with buttons to clear and export it.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe) {
+        // Create widgets to toggle the overlay and modify how it looks.
+        let units = &universe.universe_settings.units;
+        let label_id = ui.label(tr("Show Heat Map")).id;
+        ui.add(Checkbox::new(&mut self.show_overlay, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Opacity")).id;
+        ui.add(Slider::new(&mut self.opacity, 0.0..=1.0))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a drag value to modify the size of the cells, which clears the heat map since the old cells don't fit the new ones.
+        let label_id = ui.label(tr("Cell Size")).id;
+        let mut cell_size = units.display_value(Quantity::Length, self.cell_size);
+        if ui
+            .add(
+                DragValue::new(&mut cell_size)
+                    .speed(0.1)
+                    .suffix(format!(" {}", units.symbol(Quantity::Length)))
+                    .clamp_range(f64::EPSILON..=f64::MAX),
+            )
+            .labelled_by(label_id)
+            .changed()
+        {
+            self.cell_size = units.simulation_value(Quantity::Length, cell_size);
+            self.cells.clear();
+        }
+        ui.end_row();
+
+        // Create a drag value to modify the half-life of the heat, where zero keeps it forever.
+        let label_id = ui.label(tr("Half-Life")).id;
+        let mut half_life = units.display_value(Quantity::Time, self.half_life);
+        if ui
+            .add(
+                DragValue::new(&mut half_life)
+                    .speed(0.1)
+                    .suffix(format!(" {}", units.symbol(Quantity::Time)))
+                    .clamp_range(0.0..=f64::MAX),
+            )
+            .on_hover_text(tr("Zero keeps collisions forever"))
+            .labelled_by(label_id)
+            .changed()
+        {
+            self.half_life = units.simulation_value(Quantity::Time, half_life);
+        }
+        ui.end_row();
+
+        // Show how many cells are hot, with buttons to clear and export the heat map.
+        ui.label(tr_format("{} cells", &[&self.cells.len()]));
+        ui.horizontal(|ui| {
+            if ui.button(tr("Clear")).clicked() {
+                self.cells.clear();
+            }
+            if ui.button(tr("Export Image")).clicked() {
+                self.status = match self.export() {
+                    Ok(path) => tr_format("Exported to {}", &[&path.display()]),
+                    Err(error) => tr_format("Export failed: {}", &[&error]),
+                };
+            }
+        });
+        ui.end_row();
+        ui.label(&self.status);
+        ui.end_row();
+    }
+}
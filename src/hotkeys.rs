@@ -0,0 +1,312 @@
+use crate::locale::*;
+use notan::prelude::*;
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+// The file that the key bindings are stored in.
+const KEY_BINDINGS_PATH: &str = "config/key_bindings.json";
+
+// An action that can be triggered with a hotkey.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Pause,
+    Step,
+    ResetCamera,
+    FitAll,
+    Generate,
+    Screenshot,
+    ToggleUI,
+    ToggleConsole,
+    FocusUI,
+    SelectNext,
+    SelectPrevious,
+    Deselect,
+    CenterSelected,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+}
+
+// Implementations for Action.
+impl Action {
+    // All actions, in the order they are listed in the UI.
+    pub const ALL: [Action; 19] = [
+        Action::Pause,
+        Action::Step,
+        Action::ResetCamera,
+        Action::FitAll,
+        Action::Generate,
+        Action::Screenshot,
+        Action::ToggleUI,
+        Action::ToggleConsole,
+        Action::FocusUI,
+        Action::SelectNext,
+        Action::SelectPrevious,
+        Action::Deselect,
+        Action::CenterSelected,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::PanUp,
+        Action::PanDown,
+        Action::ZoomIn,
+        Action::ZoomOut,
+    ];
+
+    // Return the name of an action.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Pause => "Pause",
+            Action::Step => "Step",
+            Action::ResetCamera => "Reset Camera",
+            Action::FitAll => "Fit All Bodies",
+            Action::Generate => "Generate",
+            Action::Screenshot => "Screenshot",
+            Action::ToggleUI => "Toggle UI",
+            Action::ToggleConsole => "Toggle Console",
+            Action::FocusUI => "Focus UI",
+            Action::SelectNext => "Select Next Body",
+            Action::SelectPrevious => "Select Previous Body",
+            Action::Deselect => "Deselect Body",
+            Action::CenterSelected => "Center on Selected Body",
+            Action::PanLeft => "Pan Left",
+            Action::PanRight => "Pan Right",
+            Action::PanUp => "Pan Up",
+            Action::PanDown => "Pan Down",
+            Action::ZoomIn => "Zoom In",
+            Action::ZoomOut => "Zoom Out",
+        }
+    }
+
+    // Return whether an action is triggered on every frame its key is held, rather than once when it's pressed.
+    pub fn is_held(&self) -> bool {
+        matches!(
+            self,
+            Action::PanLeft
+                | Action::PanRight
+                | Action::PanUp
+                | Action::PanDown
+                | Action::ZoomIn
+                | Action::ZoomOut
+        )
+    }
+}
+
+// Bindings between keys and actions.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub pause: KeyCode,
+    pub step: KeyCode,
+    pub reset_camera: KeyCode,
+    pub fit_all: KeyCode,
+    pub generate: KeyCode,
+    pub screenshot: KeyCode,
+    pub toggle_ui: KeyCode,
+    pub toggle_console: KeyCode,
+    pub focus_ui: KeyCode,
+    pub select_next: KeyCode,
+    pub select_previous: KeyCode,
+    pub deselect: KeyCode,
+    pub center_selected: KeyCode,
+    pub pan_left: KeyCode,
+    pub pan_right: KeyCode,
+    pub pan_up: KeyCode,
+    pub pan_down: KeyCode,
+    pub zoom_in: KeyCode,
+    pub zoom_out: KeyCode,
+}
+
+// Default value for KeyBindings.
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            pause: KeyCode::Space,
+            step: KeyCode::Period,
+            reset_camera: KeyCode::Home,
+            fit_all: KeyCode::F,
+            generate: KeyCode::G,
+            screenshot: KeyCode::F12,
+            toggle_ui: KeyCode::F1,
+            toggle_console: KeyCode::Grave,
+            focus_ui: KeyCode::F6,
+            select_next: KeyCode::RBracket,
+            select_previous: KeyCode::LBracket,
+            deselect: KeyCode::Escape,
+            center_selected: KeyCode::C,
+            pan_left: KeyCode::Left,
+            pan_right: KeyCode::Right,
+            pan_up: KeyCode::Up,
+            pan_down: KeyCode::Down,
+            zoom_in: KeyCode::Equals,
+            zoom_out: KeyCode::Minus,
+        }
+    }
+}
+
+// Implementations for KeyBindings.
+impl KeyBindings {
+    // Return a mutable reference to the key bound to an action.
+    pub fn key(&mut self, action: Action) -> &mut KeyCode {
+        match action {
+            Action::Pause => &mut self.pause,
+            Action::Step => &mut self.step,
+            Action::ResetCamera => &mut self.reset_camera,
+            Action::FitAll => &mut self.fit_all,
+            Action::Generate => &mut self.generate,
+            Action::Screenshot => &mut self.screenshot,
+            Action::ToggleUI => &mut self.toggle_ui,
+            Action::ToggleConsole => &mut self.toggle_console,
+            Action::FocusUI => &mut self.focus_ui,
+            Action::SelectNext => &mut self.select_next,
+            Action::SelectPrevious => &mut self.select_previous,
+            Action::Deselect => &mut self.deselect,
+            Action::CenterSelected => &mut self.center_selected,
+            Action::PanLeft => &mut self.pan_left,
+            Action::PanRight => &mut self.pan_right,
+            Action::PanUp => &mut self.pan_up,
+            Action::PanDown => &mut self.pan_down,
+            Action::ZoomIn => &mut self.zoom_in,
+            Action::ZoomOut => &mut self.zoom_out,
+        }
+    }
+
+    // Save key bindings to a JSON file at the given path.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the key bindings and write them to the file.
+        let json = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        fs::write(path, json).map_err(|error| error.to_string())
+    }
+
+    // Load key bindings from a JSON file at the given path.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        // Read the file and deserialize the key bindings.
+        let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+}
+
+// Return the name of a key.
+pub fn key_name(key: KeyCode) -> String {
+    if key == KeyCode::Unknown {
+        tr("Unbound").to_string()
+    } else {
+        format!("{:?}", key)
+    }
+}
+
+// A set of hotkeys that trigger actions, with bindings loaded from and saved to the config.
+pub struct Hotkeys {
+    pub key_bindings: KeyBindings,
+    pub status: String,
+    rebinding: Option<Action>,
+}
+
+// Default value for Hotkeys.
+impl Default for Hotkeys {
+    fn default() -> Self {
+        // Load the key bindings from the config, falling back to the defaults if there are none.
+        let (key_bindings, status) = match KeyBindings::load(Path::new(KEY_BINDINGS_PATH)) {
+            Ok(key_bindings) => (key_bindings, tr_format("Loaded {}", &[&KEY_BINDINGS_PATH])),
+            Err(_) => (
+                KeyBindings::default(),
+                tr("Using default key bindings").to_string(),
+            ),
+        };
+
+        Self {
+            key_bindings,
+            status,
+            rebinding: None,
+        }
+    }
+}
+
+// Implementations for Hotkeys.
+impl Hotkeys {
+    // Save the key bindings to the config and store the result as the status.
+    fn save(&mut self) {
+        self.status = match self.key_bindings.save(Path::new(KEY_BINDINGS_PATH)) {
+            Ok(()) => tr_format("Saved {}", &[&KEY_BINDINGS_PATH]),
+            Err(error) => tr_format("Saving key bindings failed: {}", &[&error]),
+        };
+    }
+
+    // Update the hotkeys and return the actions whose keys were pressed, ignoring keys while the UI is taking keyboard input.
+    pub fn update(&mut self, app: &App, keyboard_over_ui: bool) -> Vec<Action> {
+        // If an action is being rebound, bind it to the first pressed key, or cancel if escape is pressed.
+        if let Some(action) = self.rebinding {
+            if let Some(&key) = app.keyboard.pressed.iter().next() {
+                if key != KeyCode::Escape {
+                    *self.key_bindings.key(action) = key;
+                    self.save();
+                }
+                self.rebinding = None;
+            }
+            return vec![];
+        }
+
+        // Don't trigger actions while typing in or navigating the UI.
+        if keyboard_over_ui {
+            return vec![];
+        }
+
+        // Return every action whose key was pressed, or is held for actions that repeat.
+        Action::ALL
+            .into_iter()
+            .filter(|&action| {
+                let key = *self.key_bindings.key(action);
+                key != KeyCode::Unknown
+                    && if action.is_held() {
+                        app.keyboard.is_down(key)
+                    } else {
+                        app.keyboard.was_pressed(key)
+                    }
+            })
+            .collect()
+    }
+
+    // Show the key bindings in the given UI, with buttons to rebind each action.
+    pub fn show(&mut self, ui: &mut Ui) {
+        for action in Action::ALL {
+            // Create a button showing the key bound to the action that waits for a new key when clicked.
+            ui.horizontal(|ui| {
+                let label_id = ui.label(tr(action.name())).id;
+                let text = if self.rebinding == Some(action) {
+                    tr("Press a key...").to_string()
+                } else {
+                    key_name(*self.key_bindings.key(action))
+                };
+                if ui.button(text).labelled_by(label_id).clicked() {
+                    self.rebinding = Some(action);
+                }
+                // Create a button to unbind the action.
+                if ui.button(tr("Unbind")).clicked() {
+                    *self.key_bindings.key(action) = KeyCode::Unknown;
+                    self.save();
+                }
+            });
+        }
+        ui.end_row();
+
+        // Show the status of the key bindings.
+        ui.label(&self.status);
+        ui.end_row();
+
+        // Create a button to reset the key bindings.
+        if ui.button(tr("Reset")).clicked() {
+            self.key_bindings = KeyBindings::default();
+            self.rebinding = None;
+            self.save();
+        }
+        ui.end_row();
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{body::*, dialogs::*, locale::*, units::*, universe::*};
+use notan::math::DVec2;
+use notan_egui::*;
+use std::{fs, path::Path};
+
+// The size in bytes of a tipsy header with and without its trailing padding.
+const TIPSY_HEADER_SIZES: [usize; 2] = [32, 28];
+// The amount of 32 bit floats stored for each gas, dark matter, and star particle in a tipsy file.
+const TIPSY_PARTICLE_FLOATS: [usize; 3] = [12, 9, 11];
+
+// A format that N-body initial conditions can be imported from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    NemoAscii,
+    Tipsy,
+}
+
+// Implementations for ImportFormat.
+impl ImportFormat {
+    // All import formats, in the order they are listed in the UI.
+    pub const ALL: [ImportFormat; 3] = [
+        ImportFormat::Csv,
+        ImportFormat::NemoAscii,
+        ImportFormat::Tipsy,
+    ];
+
+    // Return the name of an import format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "CSV (x y vx vy m)",
+            ImportFormat::NemoAscii => "NEMO ASCII (m x y z vx vy vz)",
+            ImportFormat::Tipsy => "Tipsy",
+        }
+    }
+
+    // Return the import format that a file is most likely in based on its extension.
+    pub fn from_path(path: &Path) -> Option<ImportFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "txt" | "dat" | "nemo" | "ascii" => Some(ImportFormat::NemoAscii),
+            "tipsy" | "std" | "bin" => Some(ImportFormat::Tipsy),
+            _ => None,
+        }
+    }
+
+    // Read the bodies in a file of an import format, with values in the given units.
+    pub fn import(&self, path: &Path, units: &Units) -> Result<Vec<Body>, String> {
+        // Read the position, velocity, and mass of each body in display units.
+        let bodies = match self {
+            ImportFormat::Csv => {
+                let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+                parse_rows(&text, 5)?
+                    .into_iter()
+                    .map(|row| {
+                        (
+                            DVec2::new(row[0], row[1]),
+                            DVec2::new(row[2], row[3]),
+                            row[4],
+                        )
+                    })
+                    .collect()
+            }
+            ImportFormat::NemoAscii => {
+                let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+                parse_rows(&text, 7)?
+                    .into_iter()
+                    .map(|row| {
+                        (
+                            DVec2::new(row[1], row[2]),
+                            DVec2::new(row[4], row[5]),
+                            row[0],
+                        )
+                    })
+                    .collect()
+            }
+            ImportFormat::Tipsy => {
+                parse_tipsy(&fs::read(path).map_err(|error| error.to_string())?)?
+            }
+        };
+
+        // Convert the bodies to simulation units, rejecting bodies without a positive mass.
+        bodies
+            .into_iter()
+            .enumerate()
+            .map(|(index, (position, velocity, mass))| {
+                if mass > 0.0 {
+                    Ok(Body {
+                        position: position * units.simulation_value(Quantity::Length, 1.0),
+                        velocity: velocity * units.simulation_value(Quantity::Velocity, 1.0),
+                        mass: units.simulation_value(Quantity::Mass, mass),
+                        ..Default::default()
+                    })
+                } else {
+                    Err(tr_format("Body {} has a mass of {}", &[&index, &mass]))
+                }
+            })
+            .collect()
+    }
+}
+
+// Parse at least the given amount of comma or whitespace separated numbers from each line of text, skipping blank lines, comments, and a header line.
+fn parse_rows(text: &str, columns: usize) -> Result<Vec<Vec<f64>>, String> {
+    let mut rows = vec![];
+    for (line_index, line) in text.lines().enumerate() {
+        // Skip blank lines and comments.
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+
+        // Parse the numbers in the line, skipping it if it's a header before any rows.
+        let row = line
+            .split(|character: char| character == ',' || character.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>();
+        match row {
+            Ok(row) if row.len() >= columns => rows.push(row),
+            Ok(row) => {
+                return Err(tr_format(
+                    "Line {} has {} columns but {} are needed",
+                    &[&(line_index + 1), &row.len(), &columns],
+                ))
+            }
+            Err(_) if rows.is_empty() => continue,
+            Err(error) => return Err(tr_format("Line {}: {}", &[&(line_index + 1), &error])),
+        }
+    }
+    Ok(rows)
+}
+
+// Parse the position, velocity, and mass of each particle in a tipsy file, detecting its byte order and dropping the z axis.
+fn parse_tipsy(bytes: &[u8]) -> Result<Vec<(DVec2, DVec2, f64)>, String> {
+    // Read a 32 bit value at an offset in the given byte order.
+    let read = |offset: usize, big_endian: bool| -> Result<[u8; 4], String> {
+        let mut value: [u8; 4] = bytes
+            .get(offset..offset + 4)
+            .ok_or("Unexpected end of file")?
+            .try_into()
+            .map_err(|_| "Unexpected end of file")?;
+        if big_endian != cfg!(target_endian = "big") {
+            value.reverse();
+        }
+        Ok(value)
+    };
+
+    // Use the byte order in which the dimension count is sensible.
+    let big_endian = match (
+        i32::from_ne_bytes(read(12, false)?),
+        i32::from_ne_bytes(read(12, true)?),
+    ) {
+        (2 | 3, _) => false,
+        (_, 2 | 3) => true,
+        _ => return Err(tr("Not a tipsy file").to_string()),
+    };
+
+    // Read the amount of gas, dark matter, and star particles.
+    let mut counts = [0; 3];
+    for (count, offset) in counts.iter_mut().zip([16, 20, 24]) {
+        *count = i32::from_ne_bytes(read(offset, big_endian)?).max(0) as usize;
+    }
+
+    // Find the header size from the file size, since some writers leave out the padding.
+    let particle_size: usize = counts
+        .iter()
+        .zip(TIPSY_PARTICLE_FLOATS)
+        .map(|(count, floats)| count * floats * 4)
+        .sum();
+    let mut offset = TIPSY_HEADER_SIZES
+        .into_iter()
+        .find(|header_size| header_size + particle_size == bytes.len())
+        .ok_or("The file size doesn't match the particle counts in the header")?;
+
+    // Read the mass, position, and velocity at the start of each particle.
+    let mut bodies = vec![];
+    for (count, floats) in counts.into_iter().zip(TIPSY_PARTICLE_FLOATS) {
+        for _ in 0..count {
+            let value = |index: usize| {
+                read(offset + index * 4, big_endian).map(|value| f32::from_ne_bytes(value) as f64)
+            };
+            bodies.push((
+                DVec2::new(value(1)?, value(2)?),
+                DVec2::new(value(4)?, value(5)?),
+                value(0)?,
+            ));
+            offset += floats * 4;
+        }
+    }
+    Ok(bodies)
+}
+
+// An importer that loads N-body initial conditions from a file into the universe.
+pub struct Importer {
+    pub path: String,
+    pub import_format: ImportFormat,
+    pub status: String,
+}
+
+// Default value for Importer.
+impl Default for Importer {
+    fn default() -> Self {
+        Self {
+            path: Default::default(),
+            import_format: ImportFormat::Csv,
+            status: tr("Values are read in the current display units").to_string(),
+        }
+    }
+}
+
+// Implementations for Importer.
+impl Importer {
+    // Import the bodies in the file at the path, replacing the bodies of the given universe, and return whether it succeeded.
+    pub fn import(&mut self, universe: &mut Universe) -> bool {
+        // Import the bodies and store the result as the status.
+        match self
+            .import_format
+            .import(Path::new(&self.path), &universe.universe_settings.units)
+        {
+            Ok(bodies) => {
+                self.status = tr_format("Imported {} bodies from {}", &[&bodies.len(), &self.path]);
+                universe.replace_bodies(bodies);
+                true
+            }
+            Err(error) => {
+                self.status = tr_format("Import failed: {}", &[&error]);
+                false
+            }
+        }
+    }
+
+    // Show the import settings in the given UI and return whether bodies were imported.
+    pub fn show(&mut self, ui: &mut Ui, universe: &mut Universe) -> bool {
+        // Create a text field to enter the path, and a button to pick it with a file dialog if they're available, guessing the format from its extension when it changes.
+        let label_id = ui.label(tr("Path")).id;
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed = ui
+                .text_edit_singleline(&mut self.path)
+                .labelled_by(label_id)
+                .changed();
+            if DIALOGS_AVAILABLE && ui.button(tr("Browse...")).clicked() {
+                if let Some(path) = open_file(tr("Import Bodies"), &[IMPORT_FILTER]) {
+                    self.path = path.display().to_string();
+                    changed = true;
+                }
+            }
+        });
+        if changed {
+            if let Some(import_format) = ImportFormat::from_path(Path::new(&self.path)) {
+                self.import_format = import_format;
+            }
+        }
+        ui.end_row();
+
+        // Create a combo box to select the import format.
+        let label_id = ui.label(tr("Format")).id;
+        ComboBox::from_id_source("Import Format")
+            .selected_text(tr(self.import_format.name()))
+            .show_ui(ui, |ui| {
+                for import_format in ImportFormat::ALL {
+                    ui.selectable_value(
+                        &mut self.import_format,
+                        import_format,
+                        tr(import_format.name()),
+                    );
+                }
+            })
+            .response
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a button to import the file.
+        let imported = ui.button(tr("Import")).clicked() && self.import(universe);
+        ui.label(&self.status);
+        ui.end_row();
+
+        imported
+    }
+}
@@ -0,0 +1,792 @@
+use crate::body::*;
+use crate::camera::*;
+use crate::locale::*;
+use crate::spatial::*;
+use crate::thruster::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{
+    draw::*,
+    math::{DVec2, Vec2},
+    prelude::*,
+};
+use notan_egui::*;
+use std::{collections::VecDeque, time::Instant};
+
+// The default maximum amount of positions stored in the trail of the selected body.
+const TRAIL_LENGTH: usize = 500;
+// The radius in pixels around the mouse that bodies can be selected within.
+const PICK_RADIUS: f32 = 8.0;
+// The maximum time in seconds between the clicks of a double click.
+const DOUBLE_CLICK_TIME: f32 = 0.3;
+// The maximum distance in pixels between the clicks of a double click.
+const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+// The minimum amount of bodies within the pick radius of the mouse for them to be shown as an aggregate.
+const AGGREGATE_MINIMUM: usize = 3;
+
+// A way to color the segments of the trail of the selected body.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrailColor {
+    Binding,
+    Speed,
+}
+
+// Implementations for TrailColor.
+impl TrailColor {
+    // All trail colors, in the order they are listed in the UI.
+    pub const ALL: [TrailColor; 2] = [TrailColor::Binding, TrailColor::Speed];
+
+    // Return the name of a trail color.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrailColor::Binding => "Bound or Escaping",
+            TrailColor::Speed => "Speed",
+        }
+    }
+}
+
+// A position in the trail of the selected body and the state of the body when it was recorded.
+pub struct TrailPoint {
+    pub position: DVec2,
+    pub bound: bool,
+    pub speed: f64,
+    pub radius: f64,
+}
+
+// Aggregate statistics of the bodies within the pick radius of the mouse.
+pub struct Aggregate {
+    pub count: usize,
+    pub total_mass: f64,
+    pub mean_velocity: DVec2,
+}
+
+// An inspector that selects a body and shows information about it.
+pub struct Inspector {
+    pub selected: Option<BodyId>,
+    pub trail: VecDeque<TrailPoint>,
+    pub trail_color: TrailColor,
+    pub trail_width_by_mass: bool,
+    pub trail_length: usize,
+    pub last_click: Option<(Instant, Vec2)>,
+    pub split_count: usize,
+    pub split_speed: f64,
+    pub show_aggregates: bool,
+    pub aggregate: Option<Aggregate>,
+    pub impulse_angle: f64,
+    pub impulse_magnitude: f64,
+    pub impulse_sensitivity: f64,
+    pub impulse_target: Option<DVec2>,
+    pub tag_text: String,
+    pub editing_tags: bool,
+}
+
+// Default value for Inspector.
+impl Default for Inspector {
+    fn default() -> Self {
+        Self {
+            selected: None,
+            trail: VecDeque::new(),
+            trail_color: TrailColor::Speed,
+            trail_width_by_mass: false,
+            trail_length: TRAIL_LENGTH,
+            last_click: None,
+            split_count: 8,
+            split_speed: 1.0,
+            show_aggregates: true,
+            aggregate: None,
+            impulse_angle: 0.0,
+            impulse_magnitude: 1.0,
+            impulse_sensitivity: 0.1,
+            impulse_target: None,
+            tag_text: String::new(),
+            editing_tags: false,
+        }
+    }
+}
+
+// Implementations for Inspector.
+impl Inspector {
+    // Select the body with the given id and clear the trail.
+    pub fn select(&mut self, selected: Option<BodyId>) {
+        self.selected = selected;
+        self.trail.clear();
+    }
+
+    // Select the body after the selected one, or the one before it, wrapping around so every body can be reached with the keyboard.
+    pub fn select_adjacent(&mut self, universe: &Universe, forward: bool) {
+        let amount = universe.bodies.len();
+        if amount == 0 {
+            self.select(None);
+            return;
+        }
+        let index = match (self.selected_body(universe), forward) {
+            (Some(index), true) => (index + 1) % amount,
+            (Some(index), false) => (index + amount - 1) % amount,
+            (None, true) => 0,
+            (None, false) => amount - 1,
+        };
+        self.select(Some(universe.bodies[index].id));
+    }
+
+    // Return the index of the selected body if it still exists in the universe, wherever it moved to.
+    pub fn selected_body(&self, universe: &Universe) -> Option<usize> {
+        self.selected.and_then(|id| universe.index_of(id))
+    }
+
+    // Return whether the body at the given index is bound to its dominant body.
+    fn is_bound(universe: &Universe, index: usize) -> Option<bool> {
+        // Compare the relative speed between the bodies with the escape velocity.
+        let dominant_body = universe.dominant_body(index)?;
+        let relative_speed = universe.bodies[index]
+            .velocity
+            .distance(universe.bodies[dominant_body].velocity);
+        Some(relative_speed < universe.escape_velocity(index, dominant_body))
+    }
+
+    // Update the inspector.
+    pub fn update(
+        &mut self,
+        app: &mut App,
+        camera: &mut Camera,
+        universe: &mut Universe,
+        pointer_over_ui: bool,
+        record_trail: bool,
+    ) {
+        // Drag an impulse gizmo out of the selected body while shift is held, and apply the impulse it points out when the left mouse button is released.
+        if let Some(index) = self.selected_body(universe) {
+            let mouse_position = camera.screen_to_world(app, Vec2::from(app.mouse.position()));
+            if app.mouse.left_was_pressed() && app.keyboard.shift() && !pointer_over_ui {
+                self.impulse_target = Some(mouse_position);
+            } else if let Some(target) = self.impulse_target {
+                if app.mouse.left_is_down() {
+                    self.impulse_target = Some(mouse_position);
+                } else {
+                    let impulse_sensitivity = self.impulse_sensitivity;
+                    universe.edit_body(universe.bodies[index].id, move |body| {
+                        body.velocity += (target - body.position) * impulse_sensitivity;
+                    });
+                    self.impulse_target = None;
+                }
+            }
+        } else {
+            self.impulse_target = None;
+        }
+
+        // If the left mouse button is pressed outside of the UI without dragging an impulse, select the closest body within the pick radius.
+        if app.mouse.left_was_pressed() && !pointer_over_ui && self.impulse_target.is_none() {
+            // Find the mouse position on the screen and in the world.
+            let screen_position = Vec2::from(app.mouse.position());
+            let mouse_position = camera.screen_to_world(app, screen_position);
+            // Find the closest body that is within its radius or the pick radius of the mouse.
+            let pick_radius = (PICK_RADIUS / camera.scale) as f64;
+            let selected_body = universe
+                .bodies
+                .iter()
+                .enumerate()
+                .map(|(index, body)| (index, body.position.distance(mouse_position), body))
+                .filter(|(_, distance, body)| *distance <= body.radius().max(pick_radius))
+                .min_by(|(_, distance1, _), (_, distance2, _)| distance1.total_cmp(distance2))
+                .map(|(index, _, _)| index);
+            self.select(selected_body.map(|index| universe.bodies[index].id));
+
+            // If this click is close in time and space to the last one, center the camera on the selected body or the clicked position.
+            let double_click = self.last_click.is_some_and(|(time, position)| {
+                time.elapsed().as_secs_f32() <= DOUBLE_CLICK_TIME
+                    && position.distance(screen_position) <= DOUBLE_CLICK_DISTANCE
+            });
+            if double_click {
+                camera.center_on(
+                    selected_body
+                        .map(|index| universe.bodies[index].position)
+                        .unwrap_or(mouse_position),
+                );
+                self.last_click = None;
+            } else {
+                self.last_click = Some((Instant::now(), screen_position));
+            }
+        }
+
+        // Aggregate the bodies under the mouse if there are enough that picking one is meaningless.
+        self.aggregate = None;
+        if self.show_aggregates && !pointer_over_ui && !universe.bodies.is_empty() {
+            let mouse_position = camera.screen_to_world(app, Vec2::from(app.mouse.position()));
+            let pick_radius = (PICK_RADIUS / camera.scale) as f64;
+            self.aggregate = Self::aggregate(universe, mouse_position, pick_radius)
+                .filter(|aggregate| aggregate.count >= AGGREGATE_MINIMUM);
+        }
+
+        // Record the position of the selected body in the reference frame of the camera, whether it's bound, its speed, and its radius, unless the state didn't change this frame.
+        if let Some(index) = self.selected_body(universe).filter(|_| record_trail) {
+            let body = &universe.bodies[index];
+            self.trail.push_back(TrailPoint {
+                position: camera.world_to_frame(body.position),
+                bound: Self::is_bound(universe, index).unwrap_or(true),
+                speed: body.velocity.length(),
+                radius: body.radius(),
+            });
+            // Remove the oldest positions if the trail is too long.
+            while self.trail.len() > self.trail_length {
+                self.trail.pop_front();
+            }
+        }
+    }
+
+    // Return the aggregate statistics of the bodies within a radius of a position, found with a spatial grid as wide as the radius.
+    fn aggregate(universe: &Universe, position: DVec2, radius: f64) -> Option<Aggregate> {
+        let spatial_grid = SpatialGrid::new(
+            radius,
+            universe
+                .bodies
+                .iter()
+                .enumerate()
+                .map(|(index, body)| (index, body.position)),
+        );
+
+        // Sum the count, mass, and momentum of the bodies within the radius.
+        let (count, total_mass, momentum) = spatial_grid
+            .query(position, radius)
+            .map(|index| &universe.bodies[index])
+            .filter(|body| body.position.distance(position) <= radius)
+            .fold((0, 0.0, DVec2::ZERO), |(count, mass, momentum), body| {
+                (
+                    count + 1,
+                    mass + body.mass,
+                    momentum + body.velocity * body.mass,
+                )
+            });
+
+        (count > 0 && total_mass > 0.0).then(|| Aggregate {
+            count,
+            total_mass,
+            mean_velocity: momentum / total_mass,
+        })
+    }
+
+    // Show the aggregate statistics of the bodies under the mouse in a tooltip next to it.
+    pub fn show_aggregate(&self, context: &Context, universe: &Universe) {
+        let Some(aggregate) = &self.aggregate else {
+            return;
+        };
+        let units = &universe.universe_settings.units;
+        show_tooltip_at_pointer(context, Id::new("Aggregate"), |ui| {
+            ui.label(format!("{} {}", aggregate.count, tr("bodies")));
+            ui.label(format!(
+                "{}: {}",
+                tr("Total Mass"),
+                units.format(Quantity::Mass, aggregate.total_mass)
+            ));
+            ui.label(tr_format(
+                "Mean Velocity: ({}, {})",
+                &[
+                    &units.format(Quantity::Velocity, aggregate.mean_velocity.x),
+                    &units.format(Quantity::Velocity, aggregate.mean_velocity.y),
+                ],
+            ));
+        });
+    }
+
+    // Draw the trail and an outline around the selected body.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera, universe: &Universe) {
+        // Only draw if a body is selected.
+        let Some(index) = self.selected_body(universe) else {
+            return;
+        };
+
+        // Find the range of speeds in the trail to scale the speed colors to.
+        let (min_speed, max_speed) = self
+            .trail
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), point| {
+                (min.min(point.speed), max.max(point.speed))
+            });
+        let speed_range = (max_speed - min_speed).max(f64::EPSILON);
+
+        // Draw each segment of the trail colored by whether it was bound or by its speed from blue when slowest to red when fastest, converting it from the reference frame so it's drawn as it was seen.
+        for (point1, point2) in self.trail.iter().zip(self.trail.iter().skip(1)) {
+            let position1 = camera.frame_to_world(point1.position);
+            let position2 = camera.frame_to_world(point2.position);
+            let color = match self.trail_color {
+                TrailColor::Binding if point2.bound => Color::GREEN,
+                TrailColor::Binding => Color::RED,
+                TrailColor::Speed => {
+                    let fraction = ((point2.speed - min_speed) / speed_range) as f32;
+                    Color::from_rgb(fraction, 0.3, 1.0 - fraction)
+                }
+            };
+            // Widen the segment to the radius the body had if enabled, keeping it at least a pixel wide.
+            let width = if self.trail_width_by_mass {
+                (point2.radius as f32).max(1.0 / camera.scale)
+            } else {
+                1.0 / camera.scale
+            };
+            draw.line(
+                (position1.x as f32, position1.y as f32),
+                (position2.x as f32, position2.y as f32),
+            )
+            .width(width)
+            .color(color);
+        }
+
+        // Draw an arrow from the selected body to the target of the impulse being dragged.
+        let body = &universe.bodies[index];
+        if let Some(target) = self.impulse_target {
+            let direction = (target - body.position).try_normalize().unwrap_or(DVec2::X);
+            let head_length = 8.0 / camera.scale as f64;
+            for (start, end) in [
+                (body.position, target),
+                (
+                    target,
+                    target - direction.rotate(DVec2::from_angle(0.5)) * head_length,
+                ),
+                (
+                    target,
+                    target - direction.rotate(DVec2::from_angle(-0.5)) * head_length,
+                ),
+            ] {
+                draw.line(
+                    (start.x as f32, start.y as f32),
+                    (end.x as f32, end.y as f32),
+                )
+                .width(1.5 / camera.scale)
+                .color(Color::ORANGE);
+            }
+        }
+
+        // Draw an outline around the selected body.
+        draw.circle(body.radius() as f32 + 4.0 / camera.scale)
+            .position(body.position.x as f32, body.position.y as f32)
+            .tolerance(camera.circle_tolerance())
+            .stroke(1.0 / camera.scale)
+            .color(Color::YELLOW);
+    }
+
+    // Show information about the selected body in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, universe: &mut Universe) {
+        // Create a checkbox to toggle aggregating the bodies under the mouse.
+        let label_id = ui.label(tr("Show Hover Aggregates")).id;
+        ui.add(Checkbox::new(&mut self.show_aggregates, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a combo box to select how the trail is colored and a checkbox to widen it with the mass of the body.
+        let label_id = ui.label(tr("Trail Color")).id;
+        ComboBox::from_id_source("Trail Color")
+            .selected_text(tr(self.trail_color.name()))
+            .show_ui(ui, |ui| {
+                for trail_color in TrailColor::ALL {
+                    ui.selectable_value(&mut self.trail_color, trail_color, tr(trail_color.name()));
+                }
+            })
+            .response
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Widen Trail With Mass")).id;
+        ui.add(Checkbox::new(&mut self.trail_width_by_mass, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Show a message if no body is selected.
+        let Some(index) = self.selected_body(universe) else {
+            ui.label(tr(
+                "Left click a body to select it, or double click to center the camera on it.",
+            ));
+            return;
+        };
+
+        // Show the state of the selected body.
+        let units = &universe.universe_settings.units;
+        let body = &universe.bodies[index];
+        if body.name.is_empty() {
+            ui.label(tr_format("Body {} (ID {})", &[&index, &body.id]));
+        } else {
+            ui.label(tr_format(
+                "Body {} (ID {}, {})",
+                &[&index, &body.id, &body.name],
+            ));
+        }
+        ui.label(tr_format(
+            "Position: ({}, {})",
+            &[
+                &units.format(Quantity::Length, body.position.x),
+                &units.format(Quantity::Length, body.position.y),
+            ],
+        ));
+        ui.label(tr_format(
+            "Velocity: ({}, {})",
+            &[
+                &units.format(Quantity::Velocity, body.velocity.x),
+                &units.format(Quantity::Velocity, body.velocity.y),
+            ],
+        ));
+        ui.label(format!(
+            "{}: {}",
+            tr("Mass"),
+            units.format(Quantity::Mass, body.mass)
+        ));
+        let time_unit = match units.symbol(Quantity::Time) {
+            "" => tr("time unit"),
+            symbol => symbol,
+        };
+        ui.label(tr_format(
+            "Angle: {}°, Spin: {}° per {}",
+            &[
+                &format!("{:.1}", body.angle.to_degrees()),
+                &format!(
+                    "{:.1}",
+                    units.simulation_value(Quantity::Time, body.spin.to_degrees())
+                ),
+                &time_unit,
+            ],
+        ));
+        if body.created_at > 0.0 {
+            ui.label(tr_format(
+                "Created At: {}",
+                &[&units.format(Quantity::Time, body.created_at)],
+            ));
+        } else {
+            ui.label(tr("Primordial"));
+        }
+        ui.end_row();
+
+        // Show how much a black hole has accreted and the mean rate since the start of the run.
+        if body.black_hole {
+            ui.label(tr_format(
+                "Accreted Mass: {} from {} bodies",
+                &[
+                    &units.format(Quantity::Mass, body.accreted_mass),
+                    &body.accreted_bodies,
+                ],
+            ));
+            if universe.simulation_time > 0.0 {
+                let time_unit = match units.symbol(Quantity::Time) {
+                    "" => tr("time unit"),
+                    symbol => symbol,
+                };
+                ui.label(tr_format(
+                    "Mean Accretion Rate: {} per {}",
+                    &[
+                        &units.format(
+                            Quantity::Mass,
+                            body.accreted_mass
+                                / units.display_value(Quantity::Time, universe.simulation_time),
+                        ),
+                        &time_unit,
+                    ],
+                ));
+            }
+            ui.end_row();
+        }
+
+        // Show the state of the selected body relative to its dominant body.
+        if let Some(dominant_body) = universe.dominant_body(index) {
+            let other = &universe.bodies[dominant_body];
+            let distance = body.position.distance(other.position);
+            let relative_speed = body.velocity.distance(other.velocity);
+            let escape_velocity = universe.escape_velocity(index, dominant_body);
+            ui.label(format!("{}: {}", tr("Dominant Body"), dominant_body));
+            ui.label(format!(
+                "{}: {}",
+                tr("Distance"),
+                units.format(Quantity::Length, distance)
+            ));
+            ui.label(format!(
+                "{}: {}",
+                tr("Relative Speed"),
+                units.format(Quantity::Velocity, relative_speed)
+            ));
+            ui.label(format!(
+                "{}: {}",
+                tr("Escape Velocity"),
+                units.format(Quantity::Velocity, escape_velocity)
+            ));
+
+            // Show whether the selected body is bound or escaping.
+            if relative_speed < escape_velocity {
+                ui.colored_label(Color32::GREEN, tr("Bound"));
+            } else {
+                ui.colored_label(Color32::RED, tr("Escaping"));
+            }
+            ui.end_row();
+        }
+
+        // Create a checkbox to turn the body into a black hole.
+        let id = universe.bodies[index].id;
+        let label_id = ui.label(tr("Black Hole")).id;
+        let mut black_hole = universe.bodies[index].black_hole;
+        if ui
+            .add(Checkbox::new(&mut black_hole, ""))
+            .labelled_by(label_id)
+            .changed()
+        {
+            universe.edit_body(id, move |body| body.black_hole = black_hole);
+        }
+        ui.end_row();
+
+        // Create a drag value to modify how fast the body spins, in degrees per display time unit.
+        let units = universe.universe_settings.units;
+        let label_id = ui.label(tr("Spin")).id;
+        let mut spin =
+            units.simulation_value(Quantity::Time, universe.bodies[index].spin.to_degrees());
+        if ui
+            .add(DragValue::new(&mut spin).speed(1.0).suffix(format!(
+                "° / {}",
+                match units.symbol(Quantity::Time) {
+                    "" => tr("time unit"),
+                    symbol => symbol,
+                }
+            )))
+            .labelled_by(label_id)
+            .changed()
+        {
+            let spin = units.display_value(Quantity::Time, spin).to_radians();
+            universe.edit_body(id, move |body| body.spin = spin);
+        }
+        ui.end_row();
+
+        // Create widgets to apply an instantaneous change in velocity to the body in a direction given as an angle, with buttons to point it along or against the velocity relative to the dominant body.
+        let label_id = ui.label(tr("Impulse Direction")).id;
+        ui.add(
+            DragValue::new(&mut self.impulse_angle)
+                .speed(1.0)
+                .clamp_range(-180.0..=180.0)
+                .suffix("°"),
+        )
+        .labelled_by(label_id);
+        ui.horizontal(|ui| {
+            let relative_velocity = universe.bodies[index].velocity
+                - universe
+                    .dominant_body(index)
+                    .map(|dominant_body| universe.bodies[dominant_body].velocity)
+                    .unwrap_or(DVec2::ZERO);
+            if ui.button(tr("Prograde")).clicked() {
+                self.impulse_angle = relative_velocity.y.atan2(relative_velocity.x).to_degrees();
+            }
+            if ui.button(tr("Retrograde")).clicked() {
+                self.impulse_angle = (-relative_velocity.y)
+                    .atan2(-relative_velocity.x)
+                    .to_degrees();
+            }
+        });
+        ui.end_row();
+        let label_id = ui.label(tr("Impulse Magnitude")).id;
+        ui.add(
+            DragValue::new(&mut self.impulse_magnitude)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        if ui.button(tr("Apply Impulse")).clicked() {
+            let delta_v =
+                DVec2::from_angle(self.impulse_angle.to_radians()) * self.impulse_magnitude;
+            universe.edit_body(id, move |body| body.velocity += delta_v);
+        }
+        ui.end_row();
+
+        // Create a drag value to modify how much velocity the impulse gizmo applies per unit of distance it's dragged.
+        let label_id = ui.label(tr("Gizmo Sensitivity")).id;
+        ui.add(
+            DragValue::new(&mut self.impulse_sensitivity)
+                .speed(0.001)
+                .clamp_range(0.0..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        ui.label(tr(
+            "Hold shift and drag from the selected body to apply an impulse with the mouse.",
+        ));
+        ui.end_row();
+
+        // Create a text field to tag the body with comma separated tags, only showing the stored tags while it isn't being edited so separators can be typed.
+        if !self.editing_tags {
+            self.tag_text = universe
+                .components
+                .tags
+                .get(id)
+                .map(|tags| tags.join(", "))
+                .unwrap_or_default();
+        }
+        let label_id = ui.label(tr("Tags")).id;
+        let response = ui
+            .add(TextEdit::singleline(&mut self.tag_text).hint_text(tr("Comma separated")))
+            .labelled_by(label_id);
+        self.editing_tags = response.has_focus();
+        if response.changed() {
+            let tags: Vec<String> = self
+                .tag_text
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            universe.edit(move |universe| {
+                if tags.is_empty() {
+                    universe.components.tags.remove(id);
+                } else {
+                    universe.components.tags.insert(id, tags.clone());
+                }
+            });
+        }
+        ui.end_row();
+
+        // Create a checkbox to give the body a thruster, starting with half of its mass as fuel.
+        let simulation_time = universe.simulation_time;
+        let mass = universe.bodies[index].mass;
+        let mut has_thruster = universe.components.thrusters.get(id).is_some();
+        let label_id = ui.label(tr("Thruster")).id;
+        if ui
+            .add(Checkbox::new(&mut has_thruster, ""))
+            .labelled_by(label_id)
+            .changed()
+        {
+            universe.edit(move |universe| {
+                if has_thruster {
+                    universe.components.thrusters.insert(
+                        id,
+                        Thruster {
+                            fuel: mass * 0.5,
+                            ..Default::default()
+                        },
+                    );
+                } else {
+                    universe.components.thrusters.remove(id);
+                }
+            });
+        }
+        ui.end_row();
+
+        // Create widgets to modify a copy of the thruster of the body, applying it as an edit if it changed.
+        if let Some(original) = universe.components.thrusters.get(id).cloned() {
+            let mut thruster = original.clone();
+            let label_id = ui.label(tr("Thrust Direction")).id;
+            ComboBox::from_id_source("Thrust Direction")
+                .selected_text(tr(thruster.direction.name()))
+                .show_ui(ui, |ui| {
+                    for direction in ThrustDirection::ALL {
+                        ui.selectable_value(
+                            &mut thruster.direction,
+                            direction,
+                            tr(direction.name()),
+                        );
+                    }
+                })
+                .response
+                .labelled_by(label_id);
+            if thruster.direction == ThrustDirection::Fixed {
+                ui.add(
+                    DragValue::new(&mut thruster.angle)
+                        .speed(1.0)
+                        .clamp_range(-180.0..=180.0)
+                        .suffix("°"),
+                );
+            }
+            ui.end_row();
+            let label_id = ui.label(tr("Thrust Force")).id;
+            ui.add(
+                DragValue::new(&mut thruster.force)
+                    .speed(0.1)
+                    .clamp_range(0.0..=f64::MAX),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = ui.label(tr("Exhaust Velocity")).id;
+            ui.add(
+                DragValue::new(&mut thruster.exhaust_velocity)
+                    .speed(1.0)
+                    .clamp_range(0.0..=f64::MAX),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            let label_id = ui.label(tr("Fuel")).id;
+            ui.add(
+                DragValue::new(&mut thruster.fuel)
+                    .speed(0.01)
+                    .clamp_range(0.0..=mass),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+
+            // Show the scheduled burns with buttons to remove them or add one starting now, firing whenever there's fuel if there are none.
+            ui.label(tr("Burns"));
+            if thruster.burns.is_empty() {
+                ui.label(tr("None, so the thruster fires until its fuel runs out."));
+            }
+            ui.end_row();
+            let mut removed_burn = None;
+            for (burn_index, burn) in thruster.burns.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let label_id = ui.label(tr("Start")).id;
+                    ui.add(DragValue::new(&mut burn.start).speed(0.1))
+                        .labelled_by(label_id);
+                    let label_id = ui.label(tr("Duration")).id;
+                    ui.add(
+                        DragValue::new(&mut burn.duration)
+                            .speed(0.1)
+                            .clamp_range(0.0..=f64::MAX),
+                    )
+                    .labelled_by(label_id);
+                    if ui.button(tr("Remove")).clicked() {
+                        removed_burn = Some(burn_index);
+                    }
+                });
+                ui.end_row();
+            }
+            if let Some(burn_index) = removed_burn {
+                thruster.burns.remove(burn_index);
+            }
+            if ui.button(tr("Add Burn")).clicked() {
+                thruster.burns.push(Burn {
+                    start: simulation_time,
+                    duration: 1.0,
+                });
+            }
+            ui.end_row();
+
+            // Apply the copy if it changed, keeping the fuel burnt since it was made unless the fuel itself was changed.
+            if thruster != original {
+                universe.edit(move |universe| {
+                    if let Some(current) = universe.components.thrusters.get_mut(id) {
+                        let fuel = if thruster.fuel != original.fuel {
+                            thruster.fuel
+                        } else {
+                            current.fuel
+                        };
+                        *current = Thruster {
+                            fuel,
+                            ..thruster.clone()
+                        };
+                    }
+                });
+            }
+        }
+
+        // Create a button to deselect the body.
+        if ui.button(tr("Deselect")).clicked() {
+            self.select(None);
+        }
+        ui.end_row();
+
+        // Create widgets to split the body into a ring of smaller bodies moving outward, deselecting it since it no longer exists.
+        let label_id = ui.label(tr("Split Into")).id;
+        ui.add(DragValue::new(&mut self.split_count).clamp_range(2..=1000))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Dispersal Speed")).id;
+        ui.add(
+            DragValue::new(&mut self.split_speed)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        if ui.button(tr("Split")).clicked() {
+            let (split_count, split_speed) = (self.split_count, self.split_speed);
+            universe.edit(move |universe| {
+                if let Some(index) = universe.index_of(id) {
+                    universe.split_body(index, split_count, split_speed);
+                }
+            });
+            self.select(None);
+        }
+        ui.end_row();
+    }
+}
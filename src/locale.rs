@@ -0,0 +1,129 @@
+use notan_egui::*;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
+
+// The file that the chosen language is stored in.
+const LANGUAGE_PATH: &str = "config/language.txt";
+
+// A language that the UI can be shown in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+// Implementations for Language.
+impl Language {
+    // All languages, in the order they are listed in the UI.
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    // Return the name of a language in that language, so it can be found without reading the current one.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    // Return the bundle of a language, which maps each English string to its translation, one "English => Translation" pair per line. English has no bundle since the strings are already in English.
+    fn bundle(&self) -> &'static str {
+        match self {
+            Language::English => "",
+            Language::German => include_str!("../locales/de.txt"),
+        }
+    }
+}
+
+// The index of the current language in Language::ALL.
+static LANGUAGE: AtomicUsize = AtomicUsize::new(0);
+// The parsed bundles of all languages, in the order of Language::ALL.
+static BUNDLES: OnceLock<Vec<HashMap<&'static str, &'static str>>> = OnceLock::new();
+
+// Parse a bundle into a map, skipping blank lines and lines starting with #.
+fn parse_bundle(bundle: &'static str) -> HashMap<&'static str, &'static str> {
+    bundle
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(" => "))
+        .map(|(english, translation)| (english.trim(), translation.trim()))
+        .collect()
+}
+
+// Return the current language.
+pub fn language() -> Language {
+    Language::ALL[LANGUAGE.load(Ordering::Relaxed)]
+}
+
+// Set the current language and remember it in the config, ignoring failures since it's only a convenience.
+pub fn set_language(language: Language) {
+    let index = Language::ALL
+        .iter()
+        .position(|other| *other == language)
+        .unwrap_or_default();
+    LANGUAGE.store(index, Ordering::Relaxed);
+    let _ = fs::create_dir_all("config");
+    let _ = fs::write(LANGUAGE_PATH, language.name());
+}
+
+// Load the language remembered in the config, staying in English if there is none.
+pub fn load_language() {
+    if let Some(index) = fs::read_to_string(LANGUAGE_PATH).ok().and_then(|name| {
+        Language::ALL
+            .iter()
+            .position(|language| language.name() == name.trim())
+    }) {
+        LANGUAGE.store(index, Ordering::Relaxed);
+    }
+}
+
+// Translate an English UI string to the current language, falling back to English if it has no translation.
+pub fn tr(text: &str) -> &str {
+    let bundles = BUNDLES.get_or_init(|| {
+        Language::ALL
+            .iter()
+            .map(|language| parse_bundle(language.bundle()))
+            .collect()
+    });
+    bundles[LANGUAGE.load(Ordering::Relaxed)]
+        .get(text)
+        .copied()
+        .unwrap_or(text)
+}
+
+// Translate an English UI template to the current language and fill each {} in it with the next argument, so the words around the values are translated as a whole.
+pub fn tr_format(template: &str, arguments: &[&dyn Display]) -> String {
+    let mut parts = tr(template).split("{}");
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for (part, argument) in parts.zip(
+        arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .chain(std::iter::repeat(String::new())),
+    ) {
+        text.push_str(&argument);
+        text.push_str(part);
+    }
+    text
+}
+
+// Show a combo box to choose the language in the given UI.
+pub fn show_language(ui: &mut Ui) {
+    let mut current = language();
+    ComboBox::from_id_source("language")
+        .selected_text(current.name())
+        .show_ui(ui, |ui| {
+            for language in Language::ALL {
+                ui.selectable_value(&mut current, language, language.name());
+            }
+        });
+    if current != language() {
+        set_language(current);
+    }
+}
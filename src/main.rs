@@ -6,46 +6,117 @@
 // - Everyone who maintains Rust!
 //
 
+mod autosave;
+mod autotune;
+mod blobs;
 mod body;
+mod bookmarks;
+mod brush;
 mod camera;
+mod checkpoint;
+mod clustering;
+mod coalescence;
+mod collision_debug;
+mod comparison;
+mod components;
+mod console;
+mod dialogs;
+mod diff;
+mod ensemble;
+mod events;
+mod export;
+mod focus_pause;
+mod gallery;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod generation;
+#[cfg(test)]
+mod generation_tests;
+mod governor;
+mod gpu_render;
+mod gravity_assist;
+mod group_tools;
+mod heat_map;
+mod hotkeys;
+mod import;
+mod inspector;
+mod locale;
+mod mesh;
+mod minimap;
+mod pairs;
+mod periodic;
+mod phase_space;
+#[cfg(test)]
+mod physics_tests;
+mod pipeline;
+mod planner;
+mod profiler;
+mod render;
+mod rewind;
+mod rotation_curve;
+mod scenario;
+mod spatial;
+mod spawner;
+mod status_bar;
+mod summary;
+mod sweep;
+mod theme;
+mod thruster;
+mod trajectory;
+mod tutorial;
 mod ui;
+mod units;
 mod universe;
+mod workspace;
 
 use camera::*;
+use locale::*;
 use notan::{draw::*, prelude::*};
 use notan_egui::*;
+use pipeline::*;
+use profiler::*;
+use render::*;
+use std::time::Instant;
 use ui::*;
 use universe::*;
 
 // A struct to store the state of the app.
-#[derive(AppState)]
+#[derive(AppState, Default)]
 struct State {
     camera: Camera,
     universe: Universe,
     ui: UI,
-}
-
-// Default value for State.
-impl Default for State {
-    fn default() -> Self {
-        Self {
-            camera: Default::default(),
-            universe: Default::default(),
-            ui: Default::default(),
-        }
-    }
+    profiler: Profiler,
+    scene_renderer: SceneRenderer,
+    pipeline: PhysicsPipeline,
 }
 
 // The main notan function.
 #[notan_main]
 fn main() -> Result<(), String> {
+    // Load the language remembered in the config before the UI is shown.
+    load_language();
+
+    // Run a parameter sweep without a window if one was requested, reading the given settings file or the saved one.
+    let arguments: Vec<String> = std::env::args().collect();
+    if let Some(index) = arguments.iter().position(|argument| argument == "--sweep") {
+        let path = arguments
+            .get(index + 1)
+            .map_or(sweep::SWEEP_PATH, |path| path.as_str());
+        let results = sweep::run_headless_sweep(std::path::Path::new(path))?;
+        println!("{}", tr_format("Wrote {}", &[&results.display()]));
+        return Ok(());
+    }
+
     // Create a new notan app and add all the necessary configs and functions.
     notan::init_with(State::default)
         .add_config(
             WindowConfig::default()
                 .set_title("N-Body Simulation")
                 .set_fullscreen(true)
-                .set_vsync(true),
+                .set_vsync(true)
+                .set_high_dpi(true)
+                .set_multisampling(4),
         )
         .add_config(DrawConfig)
         .add_config(EguiConfig)
@@ -58,36 +129,132 @@ fn main() -> Result<(), String> {
 fn update(app: &mut App, state: &mut State) {
     // Update the camera using the app.
     state.camera.update(app);
-    // Update the universe using the time since the last frame.
-    state.universe.update(app.timer.delta().as_secs_f64());
+    // Pause or resume the universe if the window lost or regained focus, before it's advanced.
+    state.ui.focus_pause.update(app, &mut state.universe);
+    // Advance the universe by the substeps the governor paces for the time since the last frame, or swap in the step running on the worker thread once it's ready and start the next one if the pipeline is enabled.
+    let delta_time = app.timer.delta().as_secs_f64();
+    let pacing = state.ui.governor.pace(delta_time);
+    let pipelined_physics = state.scene_renderer.render_settings.pipelined_physics;
+    state.pipeline.advance(
+        &mut state.universe,
+        pacing,
+        state.ui.governor.max_substeps,
+        &mut state.profiler,
+        pipelined_physics,
+    );
+    // Measure the real-time factor the universe advanced at.
+    state.ui.governor.measure(&state.universe, delta_time);
+    // Update the reference frame of the camera using the universe and the selected body.
+    let selected_body = state.ui.inspector.selected_body(&state.universe);
+    state.camera.update_frame(&state.universe, selected_body);
+    // Update the scene renderer using the universe and the time since the last frame.
+    state
+        .scene_renderer
+        .update(&state.universe, app.timer.delta_f32());
+    // Update the UI using the app, camera, and universe.
+    state.ui.update(app, &mut state.camera, &mut state.universe);
+    // Relax or restore settings to keep the frame time within the budget of the target frame rate.
+    state.ui.auto_tuner.update(
+        app.timer.delta_f32(),
+        &state.profiler,
+        &mut state.universe,
+        &mut state.scene_renderer.render_settings,
+        &mut state.ui.inspector,
+    );
+
+    // Only redraw when there are events while the universe is paused, the window is unfocused, and nothing is generating, if power saving is enabled.
+    let idle = state.scene_renderer.render_settings.power_saving
+        && state.universe.paused
+        && !app.window().is_focused()
+        && state.ui.generation_task.is_none();
+    if app.window().lazy_loop() != idle {
+        app.window().set_lazy_loop(idle);
+    }
 }
 
 // Draw the app.
 fn draw(app: &mut App, graphics: &mut Graphics, plugins: &mut Plugins, state: &mut State) {
-    // Create a new draw surface and clear it to black.
+    // Start timing the drawing.
+    let drawing_start = Instant::now();
+
+    // Create the pipelines of the GPU renderer if they're needed and load the sprites, then create a new draw surface and clear it to black unless the GPU renderer already clears the screen under it.
+    state.scene_renderer.prepare_gpu(graphics);
+    state.scene_renderer.prepare_sprites(graphics);
     let mut draw = graphics.create_draw();
-    draw.clear(Color::BLACK);
+    if !state.scene_renderer.uses_gpu() {
+        draw.clear(Color::BLACK);
+    }
 
     // Get the camera matrix and push it to the draw transform.
     let camera_matrix = state.camera.create_matrix(&draw);
     draw.transform().push(camera_matrix);
 
-    // Draw the universe.
-    state.universe.draw(&mut draw);
+    // Draw the bodies of the universe.
+    state
+        .scene_renderer
+        .draw_bodies(&mut draw, &state.camera, &state.universe);
+    // Draw the effects of the scene renderer.
+    state.scene_renderer.draw(&mut draw, &state.camera);
+    // Draw the markers of the origin, center of mass, and principal axes.
+    state
+        .scene_renderer
+        .draw_markers(&mut draw, &state.camera, &state.universe);
+    // Draw the parts of the UI that are in the world.
+    state
+        .ui
+        .draw_world(&mut draw, &state.camera, &state.universe);
 
     // Pop the draw transform.
     draw.transform().pop();
 
-    // Render the draw surface.
+    // Render the trails and points of the GPU renderer, then the draw surface over them, and record how long drawing took.
+    let gpu_renderer = state.scene_renderer.create_gpu_renderer(
+        graphics,
+        &state.camera,
+        camera_matrix,
+        draw.size(),
+    );
+    if let Some(gpu_renderer) = &gpu_renderer {
+        graphics.render(gpu_renderer);
+    }
     graphics.render(&draw);
+    // Save the draw surface as a screenshot if one was requested, without the UI.
+    if state.ui.screenshot_requested {
+        state.ui.screenshot_requested = false;
+        state.ui.hotkeys.status = match save_screenshot(graphics, gpu_renderer.as_ref(), &draw) {
+            Ok(path) => tr_format("Saved screenshot to {}", &[&path.display()]),
+            Err(error) => tr_format("Screenshot failed: {}", &[&error]),
+        };
+    }
+    state
+        .profiler
+        .record(Phase::Drawing, drawing_start.elapsed());
 
     // Create a new output to draw the UI.
+    let ui_start = Instant::now();
     let ui_output = plugins.egui(|context| {
-        state
-            .ui
-            .draw(context, app, &mut state.camera, &mut state.universe);
+        // Draw the labels of the scene renderer behind the UI.
+        state.scene_renderer.draw_labels(
+            context,
+            app,
+            &state.camera,
+            &state.universe,
+            state.ui.hidden,
+        );
+        state.ui.draw(
+            context,
+            app,
+            &mut state.camera,
+            &mut state.universe,
+            &state.profiler,
+            &mut state.scene_renderer,
+        );
     });
 
-    // Render the UI.
+    // Render the UI and record how long it took.
     graphics.render(&ui_output);
+    state.profiler.record(Phase::UI, ui_start.elapsed());
+
+    // Finish the frame in the profiler.
+    state.profiler.finish_frame();
 }
@@ -0,0 +1,246 @@
+use notan::math::DVec2;
+use std::f64::consts::PI;
+
+// The scale length that splits forces between the mesh and direct summation, in mesh cells.
+const SPLIT_CELLS: f64 = 2.0;
+// The distance beyond which short range forces are ignored, in split scale lengths.
+const SHORT_RANGE_CUTOFF: f64 = 4.5;
+// The fraction of the extent of the bodies left empty around them on the mesh.
+const MESH_MARGIN: f64 = 0.05;
+
+// A complex number used by the fast fourier transform.
+#[derive(Clone, Copy, Default)]
+struct Complex {
+    real: f64,
+    imaginary: f64,
+}
+
+// Implementations for Complex.
+impl Complex {
+    // Return the complex number with a magnitude of one at the given angle.
+    fn from_angle(angle: f64) -> Self {
+        Self {
+            real: angle.cos(),
+            imaginary: angle.sin(),
+        }
+    }
+
+    // Return the sum of two complex numbers.
+    fn add(self, other: Self) -> Self {
+        Self {
+            real: self.real + other.real,
+            imaginary: self.imaginary + other.imaginary,
+        }
+    }
+
+    // Return the difference of two complex numbers.
+    fn sub(self, other: Self) -> Self {
+        Self {
+            real: self.real - other.real,
+            imaginary: self.imaginary - other.imaginary,
+        }
+    }
+
+    // Return the product of two complex numbers.
+    fn mul(self, other: Self) -> Self {
+        Self {
+            real: self.real * other.real - self.imaginary * other.imaginary,
+            imaginary: self.real * other.imaginary + self.imaginary * other.real,
+        }
+    }
+}
+
+// Transform data whose length is a power of two in place with a radix-2 fast fourier transform, or its unnormalized inverse.
+fn fft(data: &mut [Complex], inverse: bool) {
+    let length = data.len();
+
+    // Reorder the data by bit reversed index.
+    let mut j = 0;
+    for i in 1..length {
+        let mut bit = length >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    // Combine transforms of doubling size.
+    let mut size = 2;
+    while size <= length {
+        let step = Complex::from_angle(if inverse { 2.0 } else { -2.0 } * PI / size as f64);
+        for start in (0..length).step_by(size) {
+            let mut twiddle = Complex::from_angle(0.0);
+            for k in 0..(size / 2) {
+                let even = data[start + k];
+                let odd = data[start + k + size / 2].mul(twiddle);
+                data[start + k] = even.add(odd);
+                data[start + k + size / 2] = even.sub(odd);
+                twiddle = twiddle.mul(step);
+            }
+        }
+        size <<= 1;
+    }
+}
+
+// Transform a square row-major grid in place with a 2D fast fourier transform, or its unnormalized inverse.
+fn fft_2d(data: &mut [Complex], size: usize, inverse: bool) {
+    // Transform each row.
+    for row in data.chunks_mut(size) {
+        fft(row, inverse);
+    }
+
+    // Transform each column.
+    let mut column = vec![Complex::default(); size];
+    for x in 0..size {
+        for y in 0..size {
+            column[y] = data[y * size + x];
+        }
+        fft(&mut column, inverse);
+        for y in 0..size {
+            data[y * size + x] = column[y];
+        }
+    }
+}
+
+// Return the complementary error function of a non-negative value, with an error below 1.5e-7.
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let polynomial = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    polynomial * (-x * x).exp()
+}
+
+// Return the long range part of the inverse distance that the mesh handles, which is finite at zero.
+fn long_range_inverse_distance(distance: f64, split_scale: f64) -> f64 {
+    if distance > 0.0 {
+        (1.0 - erfc(distance / (2.0 * split_scale))) / distance
+    } else {
+        1.0 / (split_scale * PI.sqrt())
+    }
+}
+
+// A mesh that computes the long range gravitational acceleration of a set of sources with fast fourier transforms.
+pub struct ParticleMesh {
+    resolution: usize,
+    origin: DVec2,
+    cell_size: f64,
+    accelerations: Vec<DVec2>,
+}
+
+// Implementations for ParticleMesh.
+impl ParticleMesh {
+    // Build a mesh with the given resolution, which must be a power of two, covering the given sources of position and mass.
+    pub fn new(sources: &[(DVec2, f64)], resolution: usize, gravitational_constant: f64) -> Self {
+        // Round the resolution up to a power of two in case it was loaded from a file.
+        let resolution = resolution.next_power_of_two().max(4);
+
+        // Fit a square mesh around the sources with a margin.
+        let (minimum, maximum) = sources.iter().fold(
+            (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)),
+            |(minimum, maximum), (position, _)| (minimum.min(*position), maximum.max(*position)),
+        );
+        let extent = (maximum - minimum).max_element().max(1.0) * (1.0 + 2.0 * MESH_MARGIN);
+        let cell_size = extent / (resolution - 1) as f64;
+        let origin = (minimum + maximum) * 0.5 - DVec2::splat(extent * 0.5);
+        let mut particle_mesh = Self {
+            resolution,
+            origin,
+            cell_size,
+            accelerations: vec![DVec2::ZERO; resolution * resolution],
+        };
+
+        // Assign the mass of each source to the nearest nodes of a grid padded to twice the size, so the sources don't feel periodic images.
+        let padded = resolution * 2;
+        let mut masses = vec![Complex::default(); padded * padded];
+        for &(position, mass) in sources.iter() {
+            for (node, weight) in particle_mesh.cloud_in_cell(position) {
+                masses[node.1 * padded + node.0].real += mass * weight;
+            }
+        }
+
+        // Sample the long range potential of a unit mass on the padded grid, measuring distances across the wraparound.
+        let split_scale = particle_mesh.split_scale();
+        let mut kernel = vec![Complex::default(); padded * padded];
+        for y in 0..padded {
+            for x in 0..padded {
+                let dx = x.min(padded - x) as f64;
+                let dy = y.min(padded - y) as f64;
+                let distance = cell_size * dx.hypot(dy);
+                kernel[y * padded + x].real =
+                    -gravitational_constant * long_range_inverse_distance(distance, split_scale);
+            }
+        }
+
+        // Convolve the masses with the kernel to find the potential.
+        fft_2d(&mut masses, padded, false);
+        fft_2d(&mut kernel, padded, false);
+        for (mass, kernel) in masses.iter_mut().zip(kernel.iter()) {
+            *mass = mass.mul(*kernel);
+        }
+        fft_2d(&mut masses, padded, true);
+        let normalization = (padded * padded) as f64;
+        let potential = |x: usize, y: usize| masses[y * padded + x].real / normalization;
+
+        // Differentiate the potential to find the acceleration at each node, using one sided differences at the edges.
+        for y in 0..resolution {
+            for x in 0..resolution {
+                let (left, right) = (x.saturating_sub(1), (x + 1).min(resolution - 1));
+                let (top, bottom) = (y.saturating_sub(1), (y + 1).min(resolution - 1));
+                particle_mesh.accelerations[y * resolution + x] = -DVec2::new(
+                    (potential(right, y) - potential(left, y))
+                        / ((right - left) as f64 * cell_size),
+                    (potential(x, bottom) - potential(x, top))
+                        / ((bottom - top) as f64 * cell_size),
+                );
+            }
+        }
+
+        particle_mesh
+    }
+
+    // Return the four nodes around a position with the weight of each, clamped to the mesh.
+    fn cloud_in_cell(&self, position: DVec2) -> [((usize, usize), f64); 4] {
+        let last = (self.resolution - 1) as f64;
+        let cell =
+            ((position - self.origin) / self.cell_size).clamp(DVec2::ZERO, DVec2::splat(last));
+        let base = cell.floor().min(DVec2::splat(last - 1.0));
+        let fraction = cell - base;
+        let (x, y) = (base.x as usize, base.y as usize);
+        [
+            ((x, y), (1.0 - fraction.x) * (1.0 - fraction.y)),
+            ((x + 1, y), fraction.x * (1.0 - fraction.y)),
+            ((x, y + 1), (1.0 - fraction.x) * fraction.y),
+            ((x + 1, y + 1), fraction.x * fraction.y),
+        ]
+    }
+
+    // Return the long range acceleration at a position, interpolated from the nearest nodes.
+    pub fn acceleration(&self, position: DVec2) -> DVec2 {
+        self.cloud_in_cell(position)
+            .iter()
+            .map(|&((x, y), weight)| self.accelerations[y * self.resolution + x] * weight)
+            .sum()
+    }
+
+    // Return the scale length that splits forces between the mesh and direct summation.
+    pub fn split_scale(&self) -> f64 {
+        SPLIT_CELLS * self.cell_size
+    }
+
+    // Return the distance beyond which the short range force is negligible.
+    pub fn cutoff(&self) -> f64 {
+        SHORT_RANGE_CUTOFF * self.split_scale()
+    }
+
+    // Return the fraction of the inverse square force between two bodies at a distance that isn't handled by the mesh.
+    pub fn short_range_factor(&self, distance: f64) -> f64 {
+        let x = distance / (2.0 * self.split_scale());
+        erfc(x) + 2.0 * x / PI.sqrt() * (-x * x).exp()
+    }
+}
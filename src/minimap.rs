@@ -0,0 +1,98 @@
+use crate::camera::*;
+use crate::universe::*;
+use notan::{
+    math::{DVec2, Vec2},
+    prelude::*,
+};
+use notan_egui::*;
+
+// The size in points of the minimap.
+const MINIMAP_SIZE: f32 = 180.0;
+// The distance in points between the minimap and the corner of the screen.
+const MINIMAP_MARGIN: f32 = 10.0;
+
+// A minimap in the corner of the screen that shows the extent of the bodies and the viewport.
+pub struct Minimap {
+    pub enabled: bool,
+}
+
+// Default value for Minimap.
+impl Default for Minimap {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+// Implementations for Minimap.
+impl Minimap {
+    // Show the minimap in the given context, centering the camera on the position that's clicked or dragged to.
+    pub fn show(&self, context: &Context, app: &mut App, camera: &mut Camera, universe: &Universe) {
+        if !self.enabled || universe.bodies.is_empty() {
+            return;
+        }
+
+        // Find the corners of the viewport in the reference frame of the camera.
+        let (width, height) = app.window().size();
+        let viewport = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(width as f32, 0.0),
+            Vec2::new(width as f32, height as f32),
+            Vec2::new(0.0, height as f32),
+        ]
+        .map(|corner| camera.world_to_frame(camera.screen_to_world(app, corner)));
+
+        // Find the bounds of the bodies and the viewport in the reference frame of the camera.
+        let (minimum, maximum) = universe
+            .bodies
+            .iter()
+            .map(|body| camera.world_to_frame(body.position))
+            .chain(viewport)
+            .fold(
+                (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)),
+                |(minimum, maximum), position| (minimum.min(position), maximum.max(position)),
+            );
+        let center = (minimum + maximum) * 0.5;
+        let extent = (maximum - minimum).max_element().max(f64::EPSILON);
+
+        Area::new("Minimap")
+            .anchor(Align2::RIGHT_BOTTOM, vec2(-MINIMAP_MARGIN, -MINIMAP_MARGIN))
+            .show(context, |ui| {
+                // Allocate the minimap and draw its background.
+                let (response, painter) =
+                    ui.allocate_painter(vec2(MINIMAP_SIZE, MINIMAP_SIZE), Sense::click_and_drag());
+                let rect = response.rect;
+                painter.rect_filled(rect, 4.0, Color32::from_black_alpha(192));
+                painter.rect_stroke(rect, 4.0, Stroke::new(1.0, Color32::GRAY));
+
+                // Convert between the reference frame and the minimap, keeping a margin inside its edges.
+                let scale = (MINIMAP_SIZE * 0.9) as f64 / extent;
+                let to_minimap = |position: DVec2| {
+                    let offset = (position - center) * scale;
+                    rect.center() + vec2(offset.x as f32, offset.y as f32)
+                };
+
+                // Draw each body as a dot.
+                for body in universe.bodies.iter() {
+                    painter.circle_filled(
+                        to_minimap(camera.world_to_frame(body.position)),
+                        1.0,
+                        Color32::WHITE,
+                    );
+                }
+
+                // Draw the outline of the viewport.
+                let points: Vec<Pos2> = viewport.iter().map(|corner| to_minimap(*corner)).collect();
+                painter.add(Shape::closed_line(
+                    points,
+                    Stroke::new(1.0, Color32::YELLOW),
+                ));
+
+                // Center the camera on the position that's clicked or dragged to.
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    let offset = (pointer - rect.center()) / scale as f32;
+                    let position = center + DVec2::new(offset.x as f64, offset.y as f64);
+                    camera.center_on(camera.frame_to_world(position));
+                }
+            });
+    }
+}
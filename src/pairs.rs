@@ -0,0 +1,173 @@
+use crate::universe::*;
+use notan::math::DVec2;
+
+// The positions and masses of bodies in separate arrays, so the pairs of a body can be measured in flat loops without going through the bodies.
+#[derive(Default)]
+pub struct BodyArrays {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    mass: Vec<f64>,
+}
+
+// Implementations for BodyArrays.
+impl BodyArrays {
+    // Copy the positions and masses of every body in the universe into the arrays.
+    pub fn new(universe: &Universe) -> Self {
+        Self {
+            x: universe.bodies.iter().map(|body| body.position.x).collect(),
+            y: universe.bodies.iter().map(|body| body.position.y).collect(),
+            mass: universe.bodies.iter().map(|body| body.mass).collect(),
+        }
+    }
+
+    // Copy the positions and masses of the bodies at the given indices into the arrays in that order, so the bodies after each one are contiguous slices of them.
+    pub fn gather(universe: &Universe, indices: &[usize]) -> Self {
+        Self {
+            x: indices
+                .iter()
+                .map(|&index| universe.bodies[index].position.x)
+                .collect(),
+            y: indices
+                .iter()
+                .map(|&index| universe.bodies[index].position.y)
+                .collect(),
+            mass: indices
+                .iter()
+                .map(|&index| universe.bodies[index].mass)
+                .collect(),
+        }
+    }
+
+    // Return the position of the body at the given index.
+    pub fn position(&self, index: usize) -> DVec2 {
+        DVec2::new(self.x[index], self.y[index])
+    }
+}
+
+// A buffer of the pairs between one body and its partners, with the offsets, square distances, and strengths of gravity of the pairs in separate arrays, so the narrow phase is a series of flat loops over them that the compiler can vectorize. The partners are either every body after the body in the arrays, read as contiguous slices, or a list of indices found in a broad phase when only some of them are paired.
+#[derive(Default)]
+pub struct PairBuffer {
+    pub partners: Vec<usize>,
+    offset_x: Vec<f64>,
+    offset_y: Vec<f64>,
+    distance_squared: Vec<f64>,
+    strength: Vec<f64>,
+}
+
+// Implementations for PairBuffer.
+impl PairBuffer {
+    // Find the offset and square distance from the body at the given index to each body after it in the arrays.
+    pub fn measure_following(&mut self, body_arrays: &BodyArrays, index: usize) {
+        let (origin_x, origin_y) = (body_arrays.x[index], body_arrays.y[index]);
+        self.offset_x.clear();
+        self.offset_x
+            .extend(body_arrays.x[(index + 1)..].iter().map(|x| x - origin_x));
+        self.offset_y.clear();
+        self.offset_y
+            .extend(body_arrays.y[(index + 1)..].iter().map(|y| y - origin_y));
+        self.find_distances();
+    }
+
+    // Find the offset and square distance from the body at the given index to each partner in the list.
+    pub fn measure(&mut self, body_arrays: &BodyArrays, index: usize) {
+        let (origin_x, origin_y) = (body_arrays.x[index], body_arrays.y[index]);
+        self.offset_x.clear();
+        self.offset_x.extend(
+            self.partners
+                .iter()
+                .map(|&partner| body_arrays.x[partner] - origin_x),
+        );
+        self.offset_y.clear();
+        self.offset_y.extend(
+            self.partners
+                .iter()
+                .map(|&partner| body_arrays.y[partner] - origin_y),
+        );
+        self.find_distances();
+    }
+
+    // Find the square distance of each pair from its offset.
+    fn find_distances(&mut self) {
+        self.distance_squared.clear();
+        self.distance_squared.extend(
+            self.offset_x
+                .iter()
+                .zip(self.offset_y.iter())
+                .map(|(offset_x, offset_y)| offset_x * offset_x + offset_y * offset_y),
+        );
+    }
+
+    // Find the strength of gravity of each pair as the acceleration per unit of mass and offset, leaving out pairs in the same position without branching on them.
+    pub fn weigh(&mut self, gravitational_constant: f64) {
+        self.strength.clear();
+        self.strength
+            .extend(self.distance_squared.iter().map(|&distance_squared| {
+                if distance_squared > 0.0 {
+                    gravitational_constant / (distance_squared * distance_squared.sqrt())
+                } else {
+                    0.0
+                }
+            }));
+    }
+
+    // Scale the strength of each pair by the taper of the interaction cutoff, returning how many pairs still interact.
+    pub fn taper(&mut self, universe_settings: &UniverseSettings) -> usize {
+        let mut interacting_pairs = 0;
+        for (strength, &distance_squared) in
+            self.strength.iter_mut().zip(self.distance_squared.iter())
+        {
+            *strength *= universe_settings.cutoff_factor(distance_squared);
+            if *strength > 0.0 {
+                interacting_pairs += 1;
+            }
+        }
+        interacting_pairs
+    }
+
+    // Add the acceleration of gravity of each pair to the body at the given index and each body after it in the arrays, with the accelerations in separate arrays in the same order.
+    pub fn apply_following(
+        &self,
+        body_arrays: &BodyArrays,
+        index: usize,
+        acceleration_x: &mut [f64],
+        acceleration_y: &mut [f64],
+    ) {
+        let mass = body_arrays.mass[index];
+        let (mut sum_x, mut sum_y) = (0.0, 0.0);
+        for (((((partner_x, partner_y), &partner_mass), &offset_x), &offset_y), &strength) in
+            acceleration_x[(index + 1)..]
+                .iter_mut()
+                .zip(acceleration_y[(index + 1)..].iter_mut())
+                .zip(body_arrays.mass[(index + 1)..].iter())
+                .zip(self.offset_x.iter())
+                .zip(self.offset_y.iter())
+                .zip(self.strength.iter())
+        {
+            let (force_x, force_y) = (offset_x * strength, offset_y * strength);
+            sum_x += force_x * partner_mass;
+            sum_y += force_y * partner_mass;
+            *partner_x -= force_x * mass;
+            *partner_y -= force_y * mass;
+        }
+        acceleration_x[index] += sum_x;
+        acceleration_y[index] += sum_y;
+    }
+
+    // Add the acceleration of gravity of each pair to the body at the given index and its partner in the list.
+    pub fn apply(&self, body_arrays: &BodyArrays, index: usize, accelerations: &mut [DVec2]) {
+        let mass = body_arrays.mass[index];
+        let mut sum = DVec2::ZERO;
+        for (((&partner, &offset_x), &offset_y), &strength) in self
+            .partners
+            .iter()
+            .zip(self.offset_x.iter())
+            .zip(self.offset_y.iter())
+            .zip(self.strength.iter())
+        {
+            let force = DVec2::new(offset_x, offset_y) * strength;
+            sum += force * body_arrays.mass[partner];
+            accelerations[partner] -= force * mass;
+        }
+        accelerations[index] += sum;
+    }
+}
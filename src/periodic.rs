@@ -0,0 +1,570 @@
+use crate::body::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use notan_egui::*;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+// The amount of fourth order Runge-Kutta steps one period is integrated with.
+const STEPS_PER_PERIOD: usize = 20000;
+// The distance between bodies below which the steps are shrunk.
+const CLOSE_DISTANCE: f64 = 0.5;
+// The smallest fraction of the usual step that steps are shrunk to.
+const MINIMUM_STEP_FRACTION: f64 = 0.001;
+// The most corrections the refiner makes before giving up.
+const MAXIMUM_ITERATIONS: usize = 20;
+// The change of each initial condition used to find the derivatives of the closure error.
+const DERIVATIVE_STEP: f64 = 1.0e-7;
+// The damping added to the corrections, relative to the largest derivative, so the symmetries of the problem don't make them singular.
+const DAMPING: f64 = 1.0e-14;
+
+// The positions and velocities of three bodies, in the order x1, y1, x2, y2, x3, y3 followed by the velocities in the same order.
+type State = [f64; 12];
+
+// A classic periodic solution of the three-body problem with equal masses and a gravitational constant of one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PeriodicOrbit {
+    FigureEight,
+    ButterflyI,
+    MothI,
+    BrouckeA1,
+    BrouckeA2,
+    BrouckeR1,
+}
+
+// Implementations for PeriodicOrbit.
+impl PeriodicOrbit {
+    // All periodic orbits, in the order they are listed in the UI.
+    pub const ALL: [PeriodicOrbit; 6] = [
+        PeriodicOrbit::FigureEight,
+        PeriodicOrbit::ButterflyI,
+        PeriodicOrbit::MothI,
+        PeriodicOrbit::BrouckeA1,
+        PeriodicOrbit::BrouckeA2,
+        PeriodicOrbit::BrouckeR1,
+    ];
+
+    // Return the name of a periodic orbit.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PeriodicOrbit::FigureEight => "Figure-Eight",
+            PeriodicOrbit::ButterflyI => "Butterfly I",
+            PeriodicOrbit::MothI => "Moth I",
+            PeriodicOrbit::BrouckeA1 => "Broucke A1",
+            PeriodicOrbit::BrouckeA2 => "Broucke A2",
+            PeriodicOrbit::BrouckeR1 => "Broucke R1",
+        }
+    }
+
+    // Return the published initial conditions and period of a periodic orbit.
+    fn initial_conditions(&self) -> (State, f64) {
+        // Orbits starting from a collinear configuration with the bodies at rest in the middle and ends, moving with the given velocity at the ends.
+        let free_fall = |vx: f64, vy: f64| {
+            [
+                -1.0,
+                0.0,
+                1.0,
+                0.0,
+                0.0,
+                0.0,
+                vx,
+                vy,
+                vx,
+                vy,
+                -2.0 * vx,
+                -2.0 * vy,
+            ]
+        };
+        // Orbits starting from a collinear configuration along the x axis with velocities along the y axis.
+        let broucke = |x: [f64; 3], vy: [f64; 3]| {
+            [
+                x[0], 0.0, x[1], 0.0, x[2], 0.0, 0.0, vy[0], 0.0, vy[1], 0.0, vy[2],
+            ]
+        };
+        match self {
+            PeriodicOrbit::FigureEight => (
+                [
+                    -0.97000436,
+                    0.24308753,
+                    0.97000436,
+                    -0.24308753,
+                    0.0,
+                    0.0,
+                    0.466203685,
+                    0.43236573,
+                    0.466203685,
+                    0.43236573,
+                    -0.93240737,
+                    -0.86473146,
+                ],
+                6.32591398,
+            ),
+            PeriodicOrbit::ButterflyI => (free_fall(0.306893, 0.125507), 6.235641),
+            PeriodicOrbit::MothI => (free_fall(0.464445, 0.396060), 14.893911),
+            PeriodicOrbit::BrouckeA1 => (
+                broucke(
+                    [-0.9892620043, 2.2096177241, -1.2203557197],
+                    [1.9169244185, 0.1910268738, -2.1079512924],
+                ),
+                6.283213,
+            ),
+            PeriodicOrbit::BrouckeA2 => (
+                broucke(
+                    [0.3361300950, 0.7699893804, -1.1061194753],
+                    [1.5324315370, -0.6287350978, -0.9036964391],
+                ),
+                7.702408,
+            ),
+            PeriodicOrbit::BrouckeR1 => (
+                broucke(
+                    [0.8083106230, -0.4954148566, -0.3128957664],
+                    [0.9901979166, -2.7171431768, 1.7269452602],
+                ),
+                5.226525,
+            ),
+        }
+    }
+}
+
+// Return the rate of change of a state of three bodies with unit masses and a gravitational constant of one.
+fn derivative(state: &State) -> State {
+    let mut rate = [0.0; 12];
+    rate[..6].copy_from_slice(&state[6..]);
+    for i in 0..3 {
+        for j in (i + 1)..3 {
+            let offset = DVec2::new(
+                state[2 * j] - state[2 * i],
+                state[2 * j + 1] - state[2 * i + 1],
+            );
+            let acceleration = offset / offset.length_squared().max(f64::EPSILON).powf(1.5);
+            rate[6 + 2 * i] += acceleration.x;
+            rate[7 + 2 * i] += acceleration.y;
+            rate[6 + 2 * j] -= acceleration.x;
+            rate[7 + 2 * j] -= acceleration.y;
+        }
+    }
+    rate
+}
+
+// Return the smallest distance between two of the bodies of a state.
+fn minimum_distance(state: &State) -> f64 {
+    [(0, 1), (0, 2), (1, 2)]
+        .iter()
+        .map(|&(i, j)| {
+            DVec2::new(state[2 * i], state[2 * i + 1])
+                .distance(DVec2::new(state[2 * j], state[2 * j + 1]))
+        })
+        .fold(f64::MAX, f64::min)
+}
+
+// Return the state after integrating the given state for the given time with fourth order Runge-Kutta steps, shrinking the steps while bodies are closer than the close distance so near collisions stay accurate.
+fn integrate(state: &State, time: f64) -> State {
+    let base_step = time / STEPS_PER_PERIOD as f64;
+    let add = |state: &State, rate: &State, factor: f64| -> State {
+        std::array::from_fn(|index| state[index] + rate[index] * factor)
+    };
+    let mut state = *state;
+    let mut elapsed = 0.0;
+    while elapsed < time {
+        let step = (base_step
+            * (minimum_distance(&state) / CLOSE_DISTANCE)
+                .min(1.0)
+                .powf(1.5))
+        .max(base_step * MINIMUM_STEP_FRACTION)
+        .min(time - elapsed);
+        let k1 = derivative(&state);
+        let k2 = derivative(&add(&state, &k1, step * 0.5));
+        let k3 = derivative(&add(&state, &k2, step * 0.5));
+        let k4 = derivative(&add(&state, &k3, step));
+        state = std::array::from_fn(|index| {
+            state[index] + step / 6.0 * (k1[index] + 2.0 * (k2[index] + k3[index]) + k4[index])
+        });
+        elapsed += step;
+    }
+    state
+}
+
+// Return how far a state is from where it started after the given period.
+fn closure_error(state: &State, period: f64) -> State {
+    let end = integrate(state, period);
+    std::array::from_fn(|index| end[index] - state[index])
+}
+
+// Return the length of a vector.
+fn norm(vector: &[f64]) -> f64 {
+    vector.iter().map(|value| value * value).sum::<f64>().sqrt()
+}
+
+// Solve the linear system with the given matrix and right hand side by Gaussian elimination with partial pivoting.
+fn solve(mut matrix: [[f64; 12]; 12], mut right: [f64; 12]) -> [f64; 12] {
+    for column in 0..12 {
+        // Swap the row with the largest value in the column to the top.
+        let pivot = (column..12)
+            .max_by(|&row1, &row2| {
+                matrix[row1][column]
+                    .abs()
+                    .total_cmp(&matrix[row2][column].abs())
+            })
+            .unwrap_or(column);
+        matrix.swap(column, pivot);
+        right.swap(column, pivot);
+        if matrix[column][column] == 0.0 {
+            continue;
+        }
+
+        // Eliminate the column from the rows below.
+        let pivot_row = matrix[column];
+        for row in (column + 1)..12 {
+            let factor = matrix[row][column] / pivot_row[column];
+            for (value, pivot_value) in matrix[row].iter_mut().zip(pivot_row).skip(column) {
+                *value -= factor * pivot_value;
+            }
+            right[row] -= factor * right[column];
+        }
+    }
+
+    // Substitute back from the bottom row.
+    let mut solution = [0.0; 12];
+    for row in (0..12).rev() {
+        let sum: f64 = ((row + 1)..12)
+            .map(|column| matrix[row][column] * solution[column])
+            .sum();
+        solution[row] = if matrix[row][row] == 0.0 {
+            0.0
+        } else {
+            (right[row] - sum) / matrix[row][row]
+        };
+    }
+    solution
+}
+
+// The initial conditions and period an orbit was refined to, with the remaining closure error and the amount of corrections made.
+struct Refinement {
+    state: State,
+    period: f64,
+    error: f64,
+    iterations: usize,
+}
+
+// Refine the given initial conditions and period with Gauss-Newton steps of the smallest size that close the orbit, halving steps that make it worse, until the closure error is within the tolerance, counting the corrections made in the progress. Return None if it was cancelled.
+fn refine(
+    mut state: State,
+    mut period: f64,
+    tolerance: f64,
+    progress: &AtomicUsize,
+    cancelled: &AtomicBool,
+) -> Option<Refinement> {
+    let mut error = closure_error(&state, period);
+    let mut iterations = 0;
+    while norm(&error) > tolerance && iterations < MAXIMUM_ITERATIONS {
+        iterations += 1;
+
+        // Find the derivatives of the closure error with respect to each initial condition and the period by finite differences, checking for cancellation between integrations.
+        let mut jacobian = [[0.0; 13]; 12];
+        for unknown in 0..13 {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let (mut shifted_state, mut shifted_period) = (state, period);
+            if unknown < 12 {
+                shifted_state[unknown] += DERIVATIVE_STEP;
+            } else {
+                shifted_period += DERIVATIVE_STEP;
+            }
+            let shifted = closure_error(&shifted_state, shifted_period);
+            for row in 0..12 {
+                jacobian[row][unknown] = (shifted[row] - error[row]) / DERIVATIVE_STEP;
+            }
+        }
+
+        // Find the smallest correction that cancels the error, solving with the damped product of the derivatives with themselves.
+        let mut product = [[0.0; 12]; 12];
+        for row in 0..12 {
+            for column in 0..12 {
+                product[row][column] = (0..13)
+                    .map(|unknown| jacobian[row][unknown] * jacobian[column][unknown])
+                    .sum();
+            }
+        }
+        let damping = DAMPING
+            * (0..12)
+                .map(|index| product[index][index])
+                .fold(f64::EPSILON, f64::max);
+        for (index, row) in product.iter_mut().enumerate() {
+            row[index] += damping;
+        }
+        let multipliers = solve(product, error);
+        let correction: [f64; 13] = std::array::from_fn(|unknown| {
+            -(0..12)
+                .map(|row| jacobian[row][unknown] * multipliers[row])
+                .sum::<f64>()
+        });
+
+        // Take the correction, halving it while it makes the error worse, and stop if even a small part of it doesn't help.
+        let mut fraction = 1.0;
+        let improved = loop {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let corrected_state: State =
+                std::array::from_fn(|index| state[index] + correction[index] * fraction);
+            let corrected_period = period + correction[12] * fraction;
+            let new_error = closure_error(&corrected_state, corrected_period);
+            if norm(&new_error) < norm(&error) {
+                state = corrected_state;
+                period = corrected_period;
+                error = new_error;
+                break true;
+            }
+            fraction *= 0.5;
+            if fraction < 1.0e-3 {
+                break false;
+            }
+        };
+        progress.fetch_add(1, Ordering::Relaxed);
+        if !improved {
+            break;
+        }
+    }
+    Some(Refinement {
+        state,
+        period,
+        error: norm(&error),
+        iterations,
+    })
+}
+
+// A refinement of the initial conditions of an orbit running on a background thread.
+pub struct RefineTask {
+    progress: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Option<Refinement>>>,
+}
+
+// Implementations for RefineTask.
+impl RefineTask {
+    // Start refining the given initial conditions and period to the given tolerance on a background thread.
+    fn start(state: State, period: f64, tolerance: f64) -> Self {
+        let progress = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        // Move the counters into the thread.
+        let handle = {
+            let progress = progress.clone();
+            let cancelled = cancelled.clone();
+            thread::spawn(move || refine(state, period, tolerance, &progress, &cancelled))
+        };
+
+        Self {
+            progress,
+            cancelled,
+            handle: Some(handle),
+        }
+    }
+
+    // Return the fraction of the most corrections the refiner makes that have been made.
+    pub fn progress(&self) -> f32 {
+        self.progress.load(Ordering::Relaxed) as f32 / MAXIMUM_ITERATIONS as f32
+    }
+
+    // Cancel the refinement.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // Return the refinement if it has finished, which is None if it was cancelled.
+    fn poll(&mut self) -> Option<Option<Refinement>> {
+        // Only join the thread once it has finished so the app doesn't block.
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        Some(self.handle.take()?.join().ok().flatten())
+    }
+}
+
+// A tool that loads classic periodic three-body orbits and refines their initial conditions by differential correction until the orbit closes within a tolerance.
+pub struct PeriodicOrbitFinder {
+    pub orbit: PeriodicOrbit,
+    pub state: State,
+    pub period: f64,
+    pub tolerance: f64,
+    pub error: Option<f64>,
+    pub orbit_size: f64,
+    pub body_mass: f64,
+    pub task: Option<RefineTask>,
+    pub status: String,
+}
+
+// Default value for PeriodicOrbitFinder.
+impl Default for PeriodicOrbitFinder {
+    fn default() -> Self {
+        let orbit = PeriodicOrbit::FigureEight;
+        let (state, period) = orbit.initial_conditions();
+        Self {
+            orbit,
+            state,
+            period,
+            tolerance: 1.0e-9,
+            error: None,
+            orbit_size: 100.0,
+            body_mass: 10.0,
+            task: None,
+            status: String::new(),
+        }
+    }
+}
+
+// Implementations for PeriodicOrbitFinder.
+impl PeriodicOrbitFinder {
+    // Update the orbit finder, taking the refined initial conditions and period once the refinement has finished.
+    pub fn update(&mut self) {
+        let Some(result) = self.task.as_mut().and_then(|task| task.poll()) else {
+            return;
+        };
+        self.task = None;
+        self.status = match result {
+            Some(refinement) => {
+                self.state = refinement.state;
+                self.period = refinement.period;
+                self.error = Some(refinement.error);
+                if refinement.error <= self.tolerance {
+                    tr_format(
+                        "Closed within the tolerance after {} corrections",
+                        &[&refinement.iterations],
+                    )
+                } else {
+                    tr_format("Not closed after {} corrections", &[&refinement.iterations])
+                }
+            }
+            None => tr("Refinement cancelled").to_string(),
+        };
+    }
+
+    // Return the bodies of the orbit at the given size and mass, in simulation units, for a universe with the given gravitational constant, along with the period in simulation time.
+    fn bodies(&self, gravitational_constant: f64) -> (Vec<Body>, f64) {
+        // Scale velocities and time so the orbit keeps its shape with the given size, mass, and gravitational constant.
+        let velocity_scale = (gravitational_constant * self.body_mass / self.orbit_size)
+            .max(0.0)
+            .sqrt();
+        let bodies = (0..3)
+            .map(|index| Body {
+                position: DVec2::new(self.state[2 * index], self.state[2 * index + 1])
+                    * self.orbit_size,
+                velocity: DVec2::new(self.state[6 + 2 * index], self.state[7 + 2 * index])
+                    * velocity_scale,
+                mass: self.body_mass,
+                ..Default::default()
+            })
+            .collect();
+        (
+            bodies,
+            self.period * self.orbit_size / velocity_scale.max(f64::EPSILON),
+        )
+    }
+
+    // Show the orbit finder in the given UI, returning whether the bodies of the universe were replaced.
+    pub fn show(&mut self, ui: &mut Ui, universe: &mut Universe) -> bool {
+        // Create a combo box to select an orbit, loading its published initial conditions.
+        let label_id = ui.label(tr("Orbit")).id;
+        let mut orbit = self.orbit;
+        ComboBox::from_id_source("Periodic Orbit")
+            .selected_text(tr(orbit.name()))
+            .show_ui(ui, |ui| {
+                for periodic_orbit in PeriodicOrbit::ALL {
+                    ui.selectable_value(&mut orbit, periodic_orbit, tr(periodic_orbit.name()));
+                }
+            })
+            .response
+            .labelled_by(label_id);
+        ui.end_row();
+        if orbit != self.orbit && self.task.is_none() {
+            self.orbit = orbit;
+            (self.state, self.period) = orbit.initial_conditions();
+            self.error = None;
+            self.status.clear();
+        }
+
+        // Create drag values to modify the size of the orbit and the mass of the bodies in display units.
+        let units = universe.universe_settings.units;
+        let label_id = ui.label(tr("Orbit Size")).id;
+        let mut orbit_size = units.display_value(Quantity::Length, self.orbit_size);
+        if ui
+            .add(
+                DragValue::new(&mut orbit_size)
+                    .speed(1.0)
+                    .suffix(format!(" {}", units.symbol(Quantity::Length)))
+                    .clamp_range(f64::EPSILON..=f64::MAX),
+            )
+            .labelled_by(label_id)
+            .changed()
+        {
+            self.orbit_size = units.simulation_value(Quantity::Length, orbit_size);
+        }
+        ui.end_row();
+        let label_id = ui.label(tr("Body Mass")).id;
+        let mut body_mass = units.display_value(Quantity::Mass, self.body_mass);
+        if ui
+            .add(
+                DragValue::new(&mut body_mass)
+                    .speed(0.1)
+                    .suffix(format!(" {}", units.symbol(Quantity::Mass)))
+                    .clamp_range(f64::EPSILON..=f64::MAX),
+            )
+            .labelled_by(label_id)
+            .changed()
+        {
+            self.body_mass = units.simulation_value(Quantity::Mass, body_mass);
+        }
+        ui.end_row();
+
+        // Create a drag value to modify the tolerance.
+        let label_id = ui.label(tr("Tolerance")).id;
+        ui.add(
+            DragValue::new(&mut self.tolerance)
+                .speed(1.0e-10)
+                .clamp_range(1.0e-14..=1.0)
+                .custom_formatter(|value, _| format!("{:.1e}", value)),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        // Create a button to start or cancel the refinement, showing its progress while it runs.
+        if let Some(task) = &self.task {
+            ui.add(ProgressBar::new(task.progress()).show_percentage());
+            if ui.button(tr("Cancel")).clicked() {
+                task.cancel();
+            }
+        } else if ui.button(tr("Refine")).clicked() {
+            self.status = tr("Refining").to_string();
+            self.task = Some(RefineTask::start(self.state, self.period, self.tolerance));
+        }
+        ui.end_row();
+        if let Some(error) = self.error {
+            ui.label(tr_format("Closure Error: {}", &[&format!("{:.3e}", error)]));
+            ui.end_row();
+        }
+
+        // Create a button to replace the bodies of the universe with the orbit, showing the period it will have.
+        let (bodies, period) = self.bodies(universe.universe_settings.gravitational_constant);
+        ui.label(tr_format(
+            "Period: {}",
+            &[&units.format(Quantity::Time, period)],
+        ));
+        ui.end_row();
+        let loaded = ui.button(tr("Load into Universe")).clicked();
+        if loaded {
+            universe.replace_bodies(bodies);
+            self.status = tr_format("Loaded {}", &[&tr(self.orbit.name())]);
+        }
+        ui.end_row();
+        ui.label(&self.status);
+        ui.end_row();
+        loaded
+    }
+}
@@ -0,0 +1,234 @@
+use crate::body::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use notan_egui::*;
+
+// The maximum amount of points stored for each tracked body.
+const POINT_LIMIT: usize = 5000;
+// The colors that tracked bodies are plotted in, cycled through in order.
+const TRACK_COLORS: [Color32; 4] = [
+    Color32::from_rgb(90, 160, 230),
+    Color32::from_rgb(230, 190, 80),
+    Color32::from_rgb(120, 200, 120),
+    Color32::from_rgb(230, 90, 90),
+];
+
+// When points are recorded in a phase space plot.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SamplingMode {
+    EveryStep,
+    PoincareSection,
+}
+
+// Implementations for SamplingMode.
+impl SamplingMode {
+    // All sampling modes, in the order they are listed in the UI.
+    pub const ALL: [SamplingMode; 2] = [SamplingMode::EveryStep, SamplingMode::PoincareSection];
+
+    // Return the name of a sampling mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SamplingMode::EveryStep => "Every Step",
+            SamplingMode::PoincareSection => "Poincaré Section (y = 0, ascending)",
+        }
+    }
+}
+
+// A body tracked in a phase space plot with its recorded radius and radial velocity pairs.
+pub struct TrackedBody {
+    pub id: BodyId,
+    pub points: Vec<[f64; 2]>,
+    previous_offset: Option<DVec2>,
+}
+
+// A plot of tracked bodies in phase space, as radius against radial velocity relative to the center of mass, accumulated over time.
+pub struct PhaseSpacePlot {
+    pub sampling_mode: SamplingMode,
+    pub tracked_bodies: Vec<TrackedBody>,
+    last_step: usize,
+}
+
+// Default value for PhaseSpacePlot.
+impl Default for PhaseSpacePlot {
+    fn default() -> Self {
+        Self {
+            sampling_mode: SamplingMode::EveryStep,
+            tracked_bodies: vec![],
+            last_step: 0,
+        }
+    }
+}
+
+// Implementations for PhaseSpacePlot.
+impl PhaseSpacePlot {
+    // Track the body with the given id if it isn't already tracked.
+    pub fn track(&mut self, id: BodyId) {
+        if !self.tracked_bodies.iter().any(|tracked| tracked.id == id) {
+            self.tracked_bodies.push(TrackedBody {
+                id,
+                points: vec![],
+                previous_offset: None,
+            });
+        }
+    }
+
+    // Record a point for each tracked body once per step of the universe.
+    pub fn update(&mut self, universe: &Universe) {
+        // Only record when the universe has taken a new step.
+        if universe.run_statistics.steps == self.last_step {
+            return;
+        }
+        self.last_step = universe.run_statistics.steps;
+
+        // Stop tracking bodies that no longer exist.
+        self.tracked_bodies
+            .retain(|tracked| universe.index_of(tracked.id).is_some());
+
+        // Find the position and velocity of each tracked body relative to the center of mass.
+        let (center_of_mass, center_of_mass_velocity, _) = universe.center_of_mass();
+        for tracked in self.tracked_bodies.iter_mut() {
+            let Some(index) = universe.index_of(tracked.id) else {
+                continue;
+            };
+            let body = &universe.bodies[index];
+            let offset = body.position - center_of_mass;
+            let velocity = body.velocity - center_of_mass_velocity;
+
+            // Record every step, or only when the body crosses the horizontal axis upwards for a Poincaré section.
+            let record = match self.sampling_mode {
+                SamplingMode::EveryStep => true,
+                SamplingMode::PoincareSection => tracked
+                    .previous_offset
+                    .is_some_and(|previous_offset| previous_offset.y < 0.0 && offset.y >= 0.0),
+            };
+            tracked.previous_offset = Some(offset);
+            if !record {
+                continue;
+            }
+
+            // Record the radius and radial velocity, removing the oldest point if there are too many.
+            let radius = offset.length();
+            let radial_velocity = if radius > 0.0 {
+                velocity.dot(offset) / radius
+            } else {
+                0.0
+            };
+            tracked.points.push([radius, radial_velocity]);
+            if tracked.points.len() > POINT_LIMIT {
+                tracked.points.remove(0);
+            }
+        }
+    }
+
+    // Show the phase space plot in the given UI, with a button to track the selected body.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe, selected_body: Option<usize>) {
+        // Create a combo box to select the sampling mode, clearing the points if it changes.
+        let label_id = ui.label(tr("Sampling")).id;
+        let previous_sampling_mode = self.sampling_mode;
+        ComboBox::from_id_source("Phase Space Sampling")
+            .selected_text(tr(self.sampling_mode.name()))
+            .show_ui(ui, |ui| {
+                for sampling_mode in SamplingMode::ALL {
+                    ui.selectable_value(
+                        &mut self.sampling_mode,
+                        sampling_mode,
+                        tr(sampling_mode.name()),
+                    );
+                }
+            })
+            .response
+            .labelled_by(label_id);
+        if self.sampling_mode != previous_sampling_mode {
+            for tracked in self.tracked_bodies.iter_mut() {
+                tracked.points.clear();
+            }
+        }
+        ui.end_row();
+
+        // Create buttons to track the selected body and to stop tracking all bodies.
+        if ui
+            .add_enabled(
+                selected_body.is_some(),
+                Button::new(tr("Track Selected Body")),
+            )
+            .clicked()
+        {
+            if let Some(index) = selected_body {
+                self.track(universe.bodies[index].id);
+            }
+        }
+        if ui.button(tr("Clear")).clicked() {
+            self.tracked_bodies.clear();
+        }
+        ui.end_row();
+
+        // Show which bodies are tracked in the color they are plotted in.
+        for (tracked, color) in self.tracked_bodies.iter().zip(TRACK_COLORS.iter().cycle()) {
+            ui.colored_label(
+                *color,
+                tr_format("Body {} ({} points)", &[&tracked.id, &tracked.points.len()]),
+            );
+        }
+        ui.end_row();
+
+        // Allocate space for the plot.
+        let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 160.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        // Find the bounds of all points, keeping the zero radial velocity line visible.
+        let points = self
+            .tracked_bodies
+            .iter()
+            .flat_map(|tracked| tracked.points.iter());
+        let (mut minimum, mut maximum) = (DVec2::new(0.0, 0.0), DVec2::new(0.0, 0.0));
+        for point in points {
+            minimum = minimum.min(DVec2::from(*point));
+            maximum = maximum.max(DVec2::from(*point));
+        }
+        let size = (maximum - minimum).max(DVec2::splat(f64::EPSILON));
+
+        // Draw the zero radial velocity line.
+        let to_screen = |point: DVec2| {
+            let normalized = (point - minimum) / size;
+            pos2(
+                rect.left() + normalized.x as f32 * rect.width(),
+                rect.bottom() - normalized.y as f32 * rect.height(),
+            )
+        };
+        painter.line_segment(
+            [
+                to_screen(DVec2::new(minimum.x, 0.0)),
+                to_screen(DVec2::new(maximum.x, 0.0)),
+            ],
+            Stroke::new(1.0, ui.visuals().weak_text_color()),
+        );
+
+        // Draw a dot for each point of each tracked body.
+        for (tracked, color) in self.tracked_bodies.iter().zip(TRACK_COLORS.iter().cycle()) {
+            for point in tracked.points.iter() {
+                painter.circle_filled(to_screen(DVec2::from(*point)), 1.0, *color);
+            }
+        }
+
+        // Label the axes with their ranges.
+        let units = &universe.universe_settings.units;
+        ui.label(tr_format(
+            "Radius: {} to {}",
+            &[
+                &units.format(Quantity::Length, minimum.x),
+                &units.format(Quantity::Length, maximum.x),
+            ],
+        ));
+        ui.label(tr_format(
+            "Radial Velocity: {} to {}",
+            &[
+                &units.format(Quantity::Velocity, minimum.y),
+                &units.format(Quantity::Velocity, maximum.y),
+            ],
+        ));
+        ui.end_row();
+    }
+}
@@ -0,0 +1,232 @@
+use crate::body::*;
+use crate::profiler::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use std::{f64::consts::TAU, time::Instant};
+
+// The time step that the scenarios are simulated with.
+const DELTA_TIME: f64 = 1.0e-3;
+
+// Create a universe with the given bodies and collisions enabled or disabled.
+fn universe(bodies: Vec<Body>, enable_collisions: bool) -> Universe {
+    let mut universe = Universe {
+        universe_settings: UniverseSettings {
+            enable_collisions,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    universe.replace_bodies(bodies);
+    universe
+}
+
+// Create a heavy body and a light body on a circular orbit around their barycenter at the given separation.
+fn circular_pair(gravitational_constant: f64, separation: f64) -> Vec<Body> {
+    let (heavy_mass, light_mass) = (1000.0, 1.0);
+    let total_mass = heavy_mass + light_mass;
+    let relative_speed = (gravitational_constant * total_mass / separation).sqrt();
+    vec![
+        Body {
+            position: DVec2::new(-separation * light_mass / total_mass, 0.0),
+            velocity: DVec2::new(0.0, -relative_speed * light_mass / total_mass),
+            mass: heavy_mass,
+            ..Default::default()
+        },
+        Body {
+            position: DVec2::new(separation * heavy_mass / total_mass, 0.0),
+            velocity: DVec2::new(0.0, relative_speed * heavy_mass / total_mass),
+            mass: light_mass,
+            ..Default::default()
+        },
+    ]
+}
+
+// Return the total momentum of the bodies of a universe.
+fn momentum(universe: &Universe) -> DVec2 {
+    universe
+        .bodies
+        .iter()
+        .map(|body| body.velocity * body.mass)
+        .sum()
+}
+
+// Create the given amount of bodies with different masses scattered on a spiral, so no two are in the same place.
+fn scattered_bodies(amount: usize) -> Vec<Body> {
+    (0..amount)
+        .map(|i| Body {
+            position: DVec2::from_angle(i as f64 * 2.399) * (i as f64 + 1.0).sqrt() * 10.0,
+            mass: 1.0 + (i % 7) as f64,
+            ..Default::default()
+        })
+        .collect()
+}
+
+// Return the acceleration of gravity of each body of a universe summed over every other body one pair at a time through the bodies, the way the direct solver did before it was split into phases over arrays, as a reference for it.
+fn pairwise_accelerations(universe: &Universe) -> Vec<DVec2> {
+    let gravitational_constant = universe.universe_settings.gravitational_constant;
+    let bodies = &universe.bodies;
+    let mut accelerations = vec![DVec2::ZERO; bodies.len()];
+    for i in 0..bodies.len() {
+        for j in (i + 1)..bodies.len() {
+            let distance_squared = bodies[i].position.distance_squared(bodies[j].position);
+            if distance_squared > 0.0 {
+                let force = (bodies[j].position - bodies[i].position).normalize()
+                    * gravitational_constant
+                    / distance_squared;
+                accelerations[i] += force * bodies[j].mass;
+                accelerations[j] -= force * bodies[i].mass;
+            }
+        }
+    }
+    accelerations
+}
+
+#[test]
+fn circular_orbit_has_keplerian_period() {
+    let gravitational_constant = UniverseSettings::default().gravitational_constant;
+    let separation = 100.0;
+    let mut universe = universe(circular_pair(gravitational_constant, separation), false);
+    let mut profiler = Profiler::default();
+
+    // Step until the light body has swept a full turn around the heavy body.
+    let expected_period = TAU * (separation.powi(3) / (gravitational_constant * 1001.0)).sqrt();
+    let mut swept_angle = 0.0;
+    let mut previous_offset = universe.bodies[1].position - universe.bodies[0].position;
+    while swept_angle < TAU {
+        universe.update(DELTA_TIME, &mut profiler);
+        let offset = universe.bodies[1].position - universe.bodies[0].position;
+        swept_angle += previous_offset.angle_between(offset);
+        previous_offset = offset;
+        assert!(universe.simulation_time < expected_period * 2.0);
+    }
+
+    // The measured period should match Kepler's third law, and the orbit should stay circular.
+    let period_error = (universe.simulation_time - expected_period).abs() / expected_period;
+    assert!(period_error < 0.01, "period error {}", period_error);
+    let separation_error = (previous_offset.length() - separation).abs() / separation;
+    assert!(
+        separation_error < 0.01,
+        "separation error {}",
+        separation_error
+    );
+}
+
+#[test]
+fn energy_is_conserved_over_ten_thousand_steps() {
+    // Put a few light bodies on circular orbits at different radii around a heavy body.
+    let gravitational_constant = UniverseSettings::default().gravitational_constant;
+    let mut bodies = vec![Body {
+        mass: 1000.0,
+        ..Default::default()
+    }];
+    for (i, radius) in [80.0, 140.0, 220.0].into_iter().enumerate() {
+        let angle = i as f64 * 2.0;
+        let speed = (gravitational_constant * 1000.0 / radius).sqrt();
+        bodies.push(Body {
+            position: DVec2::from_angle(angle) * radius,
+            velocity: DVec2::from_angle(angle).perp() * speed,
+            mass: 0.1,
+            ..Default::default()
+        });
+    }
+    let mut universe = universe(bodies, false);
+    let mut profiler = Profiler::default();
+
+    // The total energy should drift by less than a tenth of a percent.
+    let initial_energy = universe.total_energy();
+    for _ in 0..10_000 {
+        universe.update(DELTA_TIME, &mut profiler);
+    }
+    let drift = ((universe.total_energy() - initial_energy) / initial_energy).abs();
+    assert!(drift < 1.0e-3, "energy drift {}", drift);
+}
+
+#[test]
+fn momentum_is_conserved_through_mergers() {
+    // Send pairs of bodies with different masses and velocities towards each other.
+    let bodies = (0..4)
+        .flat_map(|i| {
+            let y = i as f64 * 50.0;
+            [
+                Body {
+                    position: DVec2::new(-20.0, y),
+                    velocity: DVec2::new(15.0 + i as f64, 2.0),
+                    mass: 2.0 + i as f64,
+                    ..Default::default()
+                },
+                Body {
+                    position: DVec2::new(20.0, y + 1.0),
+                    velocity: DVec2::new(-10.0, 2.0),
+                    mass: 5.0,
+                    ..Default::default()
+                },
+            ]
+        })
+        .collect();
+    let mut universe = universe(bodies, true);
+    let mut profiler = Profiler::default();
+
+    // Step until every pair has merged, checking that the mass and momentum never change.
+    let (initial_mass, initial_momentum) = (universe.total_mass(), momentum(&universe));
+    for _ in 0..10_000 {
+        universe.update(DELTA_TIME, &mut profiler);
+        assert!((universe.total_mass() - initial_mass).abs() < 1.0e-9);
+        assert!((momentum(&universe) - initial_momentum).length() < 1.0e-9);
+        if universe.run_statistics.mergers == 4 {
+            break;
+        }
+    }
+    assert_eq!(universe.run_statistics.mergers, 4);
+    assert_eq!(universe.bodies.len(), 4);
+}
+
+#[test]
+fn direct_forces_match_pairwise_sum() {
+    // Step once with velocity Verlet, which leaves the accelerations found where the bodies ended up.
+    let mut universe = universe(scattered_bodies(300), false);
+    universe.universe_settings.integrator = Integrator::VelocityVerlet;
+    let mut profiler = Profiler::default();
+    universe.update(DELTA_TIME, &mut profiler);
+
+    // The accelerations of the direct solver should match the pairwise sum up to rounding.
+    for (acceleration, expected) in universe
+        .accelerations
+        .iter()
+        .zip(pairwise_accelerations(&universe))
+    {
+        let error = (*acceleration - expected).length() / expected.length();
+        assert!(error < 1.0e-9, "acceleration error {}", error);
+    }
+}
+
+// Run with `cargo test --release -- --ignored --nocapture` to compare the direct solver with the pairwise sum.
+#[test]
+#[ignore]
+fn direct_forces_benchmark() {
+    let amount = 4000;
+    let pairs = (amount * (amount - 1) / 2) as f64;
+    let mut universe = universe(scattered_bodies(amount), false);
+    let mut profiler = Profiler::default();
+
+    // Time the fastest of a few steps of each, which are dominated by the forces.
+    let mut direct_time = f64::MAX;
+    let mut pairwise_time = f64::MAX;
+    for _ in 0..5 {
+        let start = Instant::now();
+        universe.update(DELTA_TIME, &mut profiler);
+        direct_time = direct_time.min(start.elapsed().as_secs_f64());
+        let start = Instant::now();
+        std::hint::black_box(pairwise_accelerations(&universe));
+        pairwise_time = pairwise_time.min(start.elapsed().as_secs_f64());
+    }
+    println!(
+        "direct solver: {:.1} ms, {:.0} million pairs per second",
+        direct_time * 1.0e3,
+        pairs / direct_time / 1.0e6
+    );
+    println!(
+        "pairwise sum: {:.1} ms, {:.0} million pairs per second",
+        pairwise_time * 1.0e3,
+        pairs / pairwise_time / 1.0e6
+    );
+}
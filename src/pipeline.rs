@@ -0,0 +1,207 @@
+use crate::{governor::*, profiler::*, universe::*};
+use std::{
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+};
+
+// An edit of the universe, which can be applied again to the copy of it on the worker thread.
+pub type UniverseEdit = Box<dyn Fn(&mut Universe) + Send>;
+
+// The edits made to the universe since the step running on the worker thread was submitted, and the token of that submission, which is reset when the universe is replaced or copied so a replaced universe is noticed.
+#[derive(Default)]
+pub struct EditQueue {
+    token: u64,
+    edits: Vec<UniverseEdit>,
+}
+
+// Implementations for EditQueue.
+impl EditQueue {
+    // Record an edit if a step is running on the worker thread, so it can be replayed on that step once it's swapped in.
+    pub fn record(&mut self, edit: UniverseEdit) {
+        if self.token != 0 {
+            self.edits.push(edit);
+        }
+    }
+}
+
+// A copy of a universe doesn't take the edits or the token of the original, since it's not the one being advanced.
+impl Clone for EditQueue {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// Edit queues don't take part in comparing universes.
+impl PartialEq for EditQueue {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+// A step for the worker thread: the pacing to advance by, the edits to apply first, and the universe to start from if it was replaced.
+struct Job {
+    pacing: Pacing,
+    edits: Vec<UniverseEdit>,
+    universe: Option<Universe>,
+}
+
+// A worker thread that keeps its own copy of the universe, applying the edits it's sent and advancing it, and sends back a copy of each step.
+struct Worker {
+    jobs: Sender<Job>,
+    results: Receiver<(Universe, Profiler)>,
+}
+
+// Implementations for Worker.
+impl Worker {
+    // Spawn a worker thread that advances its universe for each job it receives and sends a copy of it back with the time each phase took.
+    fn spawn() -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let (result_sender, results) = mpsc::channel();
+        thread::spawn(move || {
+            // Stop when the pipeline is dropped and no more jobs can be received.
+            let mut universe = Universe::default();
+            for job in job_receiver {
+                if let Some(replacement) = job.universe {
+                    universe = replacement;
+                }
+                for edit in job.edits.iter() {
+                    edit(&mut universe);
+                }
+                let mut profiler = Profiler::default();
+                universe.advance(job.pacing, &mut profiler);
+                if result_sender.send((universe.clone(), profiler)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { jobs, results }
+    }
+}
+
+// A double-buffered pipeline that advances the next step on a worker thread while the main thread draws and edits the current one, swapping the next step in once it's ready without waiting for it. Edits made in the meantime are replayed on the next step after the swap, and the time of frames that pass while a step is running is owed to the step after it.
+#[derive(Default)]
+pub struct PhysicsPipeline {
+    worker: Option<Worker>,
+    running: bool,
+    owed: Option<Pacing>,
+    token: u64,
+    submitted_paused: bool,
+    submitted_pending_steps: usize,
+}
+
+// Implementations for PhysicsPipeline.
+impl PhysicsPipeline {
+    // Advance the universe by the given pacing, on the main thread if the pipeline is disabled, or otherwise by swapping in the step that was running on the worker thread if it's ready and submitting the next one with at most the given substeps.
+    pub fn advance(
+        &mut self,
+        universe: &mut Universe,
+        pacing: Pacing,
+        max_substeps: usize,
+        profiler: &mut Profiler,
+        enabled: bool,
+    ) {
+        // Stop the worker thread and advance the universe directly if the pipeline is disabled.
+        if !enabled {
+            self.stop(universe);
+            universe.advance(pacing, profiler);
+            return;
+        }
+
+        // Add the time owed by the frames that passed while the running step wasn't ready.
+        let pacing = match self.owed.take() {
+            Some(owed) => owed.merge(pacing, max_substeps),
+            None => pacing,
+        };
+
+        // Swap in the running step if it's ready, otherwise keep drawing the current one, owing the time to the next step and clearing the results of the last one so they aren't handled again.
+        let worker = self.worker.take().unwrap_or_else(Worker::spawn);
+        let (edits, replacement) = if self.running {
+            match worker.results.try_recv() {
+                Ok((advanced, step_profiler)) => {
+                    profiler.merge(&step_profiler);
+                    self.swap(universe, advanced)
+                }
+                Err(TryRecvError::Empty) => {
+                    self.worker = Some(worker);
+                    self.owed = Some(pacing);
+                    universe.collision_events.clear();
+                    universe.frame_force_evaluations = 0;
+                    return;
+                }
+                Err(TryRecvError::Disconnected) => {
+                    self.stop(universe);
+                    universe.advance(pacing, profiler);
+                    return;
+                }
+            }
+        } else {
+            (Vec::new(), Some(universe.clone()))
+        };
+
+        // Submit the next step with the edits replayed on this one, so the universe of the worker matches it, or a copy of the universe if it was replaced.
+        self.token += 1;
+        universe.edits = EditQueue {
+            token: self.token,
+            edits: Vec::new(),
+        };
+        self.submitted_paused = universe.paused;
+        self.submitted_pending_steps = universe.pending_steps;
+        let job = Job {
+            pacing,
+            edits,
+            universe: replacement,
+        };
+        match worker.jobs.send(job) {
+            Ok(()) => {
+                self.worker = Some(worker);
+                self.running = true;
+            }
+            // Fall back to advancing on the main thread if the worker stopped.
+            Err(error) => {
+                self.stop(universe);
+                universe.advance(error.0.pacing, profiler);
+            }
+        }
+    }
+
+    // Stop the worker thread and forget the running step, so the universe is advanced on the main thread.
+    fn stop(&mut self, universe: &mut Universe) {
+        *self = Self::default();
+        universe.edits = EditQueue::default();
+    }
+
+    // Swap the advanced step in as the universe, replaying the edits made to the universe while it was running and carrying over the settings and requested pauses and steps, and return the edits so the worker can apply them too. If the universe was replaced in the meantime, the advanced step is dropped and a copy of the replacement is returned for the worker to start from.
+    fn swap(
+        &mut self,
+        universe: &mut Universe,
+        mut advanced: Universe,
+    ) -> (Vec<UniverseEdit>, Option<Universe>) {
+        if universe.edits.token != self.token {
+            return (Vec::new(), Some(universe.clone()));
+        }
+
+        // Replay the edits in the order they were made.
+        let mut edits = std::mem::take(&mut universe.edits.edits);
+        for edit in edits.iter() {
+            edit(&mut advanced);
+        }
+
+        // Keep the current settings, pause if either the universe or the step paused, such as because numbers blew up, and add the steps requested since the submission.
+        let universe_settings = universe.universe_settings.clone();
+        let paused = universe.paused || (advanced.paused && !self.submitted_paused);
+        let pending_steps = advanced.pending_steps
+            + universe
+                .pending_steps
+                .saturating_sub(self.submitted_pending_steps);
+        let carry_over: UniverseEdit = Box::new(move |universe| {
+            universe.universe_settings = universe_settings.clone();
+            universe.paused = paused;
+            universe.pending_steps = pending_steps;
+        });
+        carry_over(&mut advanced);
+        edits.push(carry_over);
+
+        std::mem::swap(universe, &mut advanced);
+        (edits, None)
+    }
+}
@@ -0,0 +1,427 @@
+use crate::body::*;
+use crate::camera::*;
+use crate::locale::*;
+use crate::profiler::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{draw::*, math::DVec2, prelude::*};
+use notan_egui::*;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+// The amount of directions tried in each pass of the search.
+const ANGLE_SAMPLES: usize = 36;
+// The amount of magnitudes tried in each pass of the search.
+const MAGNITUDE_SAMPLES: usize = 10;
+// The maximum amount of points kept in a planned trajectory.
+const TRAJECTORY_POINTS: usize = 500;
+
+// A change in velocity for the spacecraft and the trajectory it results in.
+pub struct Plan {
+    pub delta_v: DVec2,
+    pub closest_distance: f64,
+    pub closest_time: f64,
+    pub closest_position: DVec2,
+    pub trajectory: Vec<DVec2>,
+    pub target_trajectory: Vec<DVec2>,
+}
+
+// A search for the plan with the closest approach running on a background thread.
+pub struct PlannerTask {
+    progress: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    candidates: usize,
+    handle: Option<JoinHandle<Option<Plan>>>,
+}
+
+// Implementations for PlannerTask.
+impl PlannerTask {
+    // Start searching for a plan on a background thread with a copy of the universe.
+    fn start(
+        universe: &Universe,
+        spacecraft: BodyId,
+        target: BodyId,
+        horizon: f64,
+        time_step: f64,
+        max_delta_v: f64,
+    ) -> Self {
+        let progress = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        // Copy the universe without anything that removes or reorders bodies, so the spacecraft and target keep their indices.
+        let mut universe = universe.clone();
+        universe.universe_settings.reorder_interval = 0;
+        universe.universe_settings.enable_collisions = false;
+        universe.universe_settings.enable_mass_transfer = false;
+        universe.universe_settings.ejection_mode = EjectionMode::Disabled;
+        for body in universe.bodies.iter_mut() {
+            body.black_hole = false;
+        }
+
+        // Move the copy and counters into the thread.
+        let handle = {
+            let progress = progress.clone();
+            let cancelled = cancelled.clone();
+            thread::spawn(move || {
+                search(
+                    &universe,
+                    spacecraft,
+                    target,
+                    horizon,
+                    time_step,
+                    max_delta_v,
+                    &progress,
+                    &cancelled,
+                )
+            })
+        };
+
+        Self {
+            progress,
+            cancelled,
+            candidates: 2 * ANGLE_SAMPLES * MAGNITUDE_SAMPLES,
+            handle: Some(handle),
+        }
+    }
+
+    // Return the fraction of candidates that have been tried.
+    pub fn progress(&self) -> f32 {
+        self.progress.load(Ordering::Relaxed) as f32 / self.candidates as f32
+    }
+
+    // Cancel the search.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // Return the best plan if the search has finished, which is None if it was cancelled.
+    pub fn poll(&mut self) -> Option<Option<Plan>> {
+        // Only join the thread once it has finished so the app doesn't block.
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        Some(self.handle.take()?.join().ok().flatten())
+    }
+}
+
+// Search a grid of directions and magnitudes for the change in velocity that brings the spacecraft closest to the target, then search a finer grid around the best one.
+#[allow(clippy::too_many_arguments)]
+fn search(
+    universe: &Universe,
+    spacecraft: BodyId,
+    target: BodyId,
+    horizon: f64,
+    time_step: f64,
+    max_delta_v: f64,
+    progress: &AtomicUsize,
+    cancelled: &AtomicBool,
+) -> Option<Plan> {
+    let mut best: Option<Plan> = None;
+    let (mut center_angle, mut angle_range) = (0.0, std::f64::consts::TAU);
+    let (mut center_magnitude, mut magnitude_range) = (max_delta_v * 0.5, max_delta_v);
+
+    for _ in 0..2 {
+        for i in 0..ANGLE_SAMPLES {
+            for j in 0..MAGNITUDE_SAMPLES {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+
+                // Spread the candidates evenly over the ranges around the centers.
+                let angle = center_angle + angle_range * (i as f64 / ANGLE_SAMPLES as f64 - 0.5);
+                let magnitude = (center_magnitude
+                    + magnitude_range * (j as f64 / (MAGNITUDE_SAMPLES - 1) as f64 - 0.5))
+                    .clamp(0.0, max_delta_v);
+                let delta_v = DVec2::from_angle(angle) * magnitude;
+
+                // Keep the candidate if it comes closer than the best so far.
+                let plan = simulate(universe, spacecraft, target, horizon, time_step, delta_v);
+                if best
+                    .as_ref()
+                    .is_none_or(|best| plan.closest_distance < best.closest_distance)
+                {
+                    best = Some(plan);
+                }
+                progress.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // Narrow the ranges to the neighbours of the best candidate for the next pass.
+        let best_delta_v = best.as_ref()?.delta_v;
+        center_angle = best_delta_v.y.atan2(best_delta_v.x);
+        angle_range = 2.0 * angle_range / ANGLE_SAMPLES as f64;
+        center_magnitude = best_delta_v.length();
+        magnitude_range = 2.0 * magnitude_range / (MAGNITUDE_SAMPLES - 1) as f64;
+    }
+
+    best
+}
+
+// Simulate a copy of the universe after changing the velocity of the spacecraft, recording its closest approach to the target and the trajectories of both, and stopping early if either of them is gone.
+fn simulate(
+    universe: &Universe,
+    spacecraft_id: BodyId,
+    target_id: BodyId,
+    horizon: f64,
+    time_step: f64,
+    delta_v: DVec2,
+) -> Plan {
+    let mut universe = universe.clone();
+    let mut profiler = Profiler::default();
+
+    // Find the spacecraft and target by their ids, which stay the same if the bodies are reordered.
+    let (mut spacecraft, mut target) = (
+        universe.index_of(spacecraft_id).unwrap_or(0),
+        universe.index_of(target_id).unwrap_or(0),
+    );
+    if let Some(body) = universe.bodies.get_mut(spacecraft) {
+        body.velocity += delta_v;
+    }
+
+    // Record a point of the trajectories every few steps so they stay short.
+    let steps = (horizon / time_step).ceil().max(1.0) as usize;
+    let record_interval = steps.div_ceil(TRAJECTORY_POINTS).max(1);
+
+    let mut plan = Plan {
+        delta_v,
+        closest_distance: f64::MAX,
+        closest_time: 0.0,
+        closest_position: DVec2::ZERO,
+        trajectory: Vec::new(),
+        target_trajectory: Vec::new(),
+    };
+    for step in 0..=steps {
+        // Look the bodies up again if they moved to other indices.
+        if universe
+            .bodies
+            .get(spacecraft)
+            .is_none_or(|body| body.id != spacecraft_id)
+        {
+            let Some(index) = universe.index_of(spacecraft_id) else {
+                break;
+            };
+            spacecraft = index;
+        }
+        if universe
+            .bodies
+            .get(target)
+            .is_none_or(|body| body.id != target_id)
+        {
+            let Some(index) = universe.index_of(target_id) else {
+                break;
+            };
+            target = index;
+        }
+        let (position, target_position) = (
+            universe.bodies[spacecraft].position,
+            universe.bodies[target].position,
+        );
+
+        // Record the closest approach.
+        let distance = position.distance(target_position);
+        if distance < plan.closest_distance {
+            plan.closest_distance = distance;
+            plan.closest_time = step as f64 * time_step;
+            plan.closest_position = position;
+        }
+        if step % record_interval == 0 || step == steps {
+            plan.trajectory.push(position);
+            plan.target_trajectory.push(target_position);
+        }
+        if step < steps {
+            universe.update(time_step, &mut profiler);
+        }
+    }
+    plan
+}
+
+// A planner that searches for a change in velocity of the selected body that brings it close to a target body.
+pub struct Planner {
+    pub target: BodyId,
+    pub horizon: f64,
+    pub time_step: f64,
+    pub max_delta_v: f64,
+    pub spacecraft: Option<BodyId>,
+    pub planned_time: f64,
+    pub task: Option<PlannerTask>,
+    pub plan: Option<Plan>,
+    pub status: String,
+}
+
+// Default value for Planner.
+impl Default for Planner {
+    fn default() -> Self {
+        Self {
+            target: BodyId::default(),
+            horizon: 10.0,
+            time_step: 0.01,
+            max_delta_v: 10.0,
+            spacecraft: None,
+            planned_time: 0.0,
+            task: None,
+            plan: None,
+            status: String::new(),
+        }
+    }
+}
+
+// Implementations for Planner.
+impl Planner {
+    // Update the planner, storing the plan once the search has finished.
+    pub fn update(&mut self) {
+        if let Some(result) = self.task.as_mut().and_then(|task| task.poll()) {
+            self.plan = result;
+            self.status = match &self.plan {
+                Some(_) => tr("Search finished").to_string(),
+                None => tr("Search cancelled").to_string(),
+            };
+            self.task = None;
+        }
+    }
+
+    // Draw the planned trajectory of the spacecraft in green, the trajectory of the target in gray, and a circle at the closest approach.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera) {
+        let Some(plan) = &self.plan else {
+            return;
+        };
+        for (trajectory, color) in [
+            (
+                &plan.target_trajectory,
+                Color::from_rgba(1.0, 1.0, 1.0, 0.4),
+            ),
+            (&plan.trajectory, Color::from_rgb(0.3, 1.0, 0.5)),
+        ] {
+            for (position1, position2) in trajectory.iter().zip(trajectory.iter().skip(1)) {
+                draw.line(
+                    (position1.x as f32, position1.y as f32),
+                    (position2.x as f32, position2.y as f32),
+                )
+                .width(1.0 / camera.scale)
+                .color(color);
+            }
+        }
+        draw.circle(6.0 / camera.scale)
+            .position(
+                plan.closest_position.x as f32,
+                plan.closest_position.y as f32,
+            )
+            .tolerance(camera.circle_tolerance())
+            .stroke(1.0 / camera.scale)
+            .color(Color::from_rgb(0.3, 1.0, 0.5));
+    }
+
+    // Show the planner in the given UI, planning for the selected body.
+    pub fn show(&mut self, ui: &mut Ui, universe: &mut Universe, selected_body: Option<usize>) {
+        // Show a message if no body is selected.
+        let Some(spacecraft) = selected_body else {
+            ui.label(tr(
+                "Select the body to plan for, which acts as the spacecraft.",
+            ));
+            return;
+        };
+
+        // Create widgets to choose the target by its index, falling back to the first body if it no longer exists, and modify the search.
+        let mut target = universe.index_of(self.target).unwrap_or(0);
+        let label_id = ui.label(tr("Target Body")).id;
+        ui.add(DragValue::new(&mut target).clamp_range(0..=universe.bodies.len() - 1))
+            .labelled_by(label_id);
+        self.target = universe.bodies[target].id;
+        ui.end_row();
+        let label_id = ui.label(tr("Horizon")).id;
+        ui.add(
+            DragValue::new(&mut self.horizon)
+                .speed(0.1)
+                .clamp_range(0.01..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Time Step")).id;
+        ui.add(
+            DragValue::new(&mut self.time_step)
+                .speed(0.001)
+                .clamp_range(0.0001..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Max Delta-V")).id;
+        ui.add(
+            DragValue::new(&mut self.max_delta_v)
+                .speed(0.1)
+                .clamp_range(0.0..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a button to start or cancel the search, showing its progress while it runs.
+        if let Some(task) = &self.task {
+            ui.add(ProgressBar::new(task.progress()).show_percentage());
+            if ui.button(tr("Cancel")).clicked() {
+                task.cancel();
+            }
+        } else if ui.button(tr("Search")).clicked() {
+            if target == spacecraft {
+                self.status = tr("The target must be a different body").to_string();
+            } else {
+                self.spacecraft = Some(universe.bodies[spacecraft].id);
+                self.planned_time = universe.simulation_time;
+                self.plan = None;
+                self.status = tr("Searching").to_string();
+                self.task = Some(PlannerTask::start(
+                    universe,
+                    universe.bodies[spacecraft].id,
+                    universe.bodies[target].id,
+                    self.horizon,
+                    self.time_step,
+                    self.max_delta_v,
+                ));
+            }
+        }
+        ui.label(&self.status);
+        ui.end_row();
+
+        // Show the best plan with buttons to apply it to the spacecraft it was planned for or clear it.
+        let Some(plan) = &self.plan else {
+            return;
+        };
+        let units = &universe.universe_settings.units;
+        ui.label(tr_format(
+            "Delta-V: {} at {}°",
+            &[
+                &units.format(Quantity::Velocity, plan.delta_v.length()),
+                &format!("{:.1}", plan.delta_v.y.atan2(plan.delta_v.x).to_degrees()),
+            ],
+        ));
+        ui.label(tr_format(
+            "Closest Approach: {} after {}",
+            &[
+                &units.format(Quantity::Length, plan.closest_distance),
+                &units.format(Quantity::Time, plan.closest_time),
+            ],
+        ));
+        if universe.simulation_time != self.planned_time {
+            ui.colored_label(
+                Color32::YELLOW,
+                tr("The universe has moved on since the search, so the plan is out of date"),
+            );
+        }
+        ui.end_row();
+        let delta_v = plan.delta_v;
+        ui.horizontal(|ui| {
+            if ui.button(tr("Apply")).clicked() {
+                if let Some(spacecraft) = self.spacecraft {
+                    universe.edit_body(spacecraft, move |body| body.velocity += delta_v);
+                }
+                self.plan = None;
+            }
+            if ui.button(tr("Clear")).clicked() {
+                self.plan = None;
+            }
+        });
+        ui.end_row();
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+// How much each new measurement affects the smoothed timing of a phase.
+const SMOOTHING: f64 = 0.1;
+
+// A phase of a frame that can be timed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Collisions,
+    Forces,
+    Tracers,
+    Integration,
+    Drawing,
+    UI,
+}
+
+// Implementations for Phase.
+impl Phase {
+    // All phases, in the order they happen in a frame.
+    pub const ALL: [Phase; 6] = [
+        Phase::Collisions,
+        Phase::Forces,
+        Phase::Tracers,
+        Phase::Integration,
+        Phase::Drawing,
+        Phase::UI,
+    ];
+
+    // Return the name of a phase.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Collisions => "Collisions",
+            Phase::Forces => "Forces",
+            Phase::Tracers => "Tracers",
+            Phase::Integration => "Integration",
+            Phase::Drawing => "Drawing",
+            Phase::UI => "UI",
+        }
+    }
+}
+
+// A profiler that measures how long each phase of a frame takes.
+#[derive(Default)]
+pub struct Profiler {
+    timings: [f64; Phase::ALL.len()],
+    pending: [f64; Phase::ALL.len()],
+}
+
+// Implementations for Profiler.
+impl Profiler {
+    // Add a duration to the time spent in a phase this frame.
+    pub fn record(&mut self, phase: Phase, duration: Duration) {
+        self.pending[phase as usize] += duration.as_secs_f64();
+    }
+
+    // Run a function and add how long it takes to the time spent in a phase this frame.
+    pub fn time<T>(&mut self, phase: Phase, function: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = function();
+        self.record(phase, start.elapsed());
+        result
+    }
+
+    // Add the time spent in each phase by another profiler, such as one on a worker thread, to this frame.
+    pub fn merge(&mut self, other: &Profiler) {
+        for (pending, other_pending) in self.pending.iter_mut().zip(other.pending.iter()) {
+            *pending += other_pending;
+        }
+    }
+
+    // Finish a frame, smoothing the time spent in each phase into its timing.
+    pub fn finish_frame(&mut self) {
+        for (timing, pending) in self.timings.iter_mut().zip(self.pending.iter_mut()) {
+            *timing += (*pending - *timing) * SMOOTHING;
+            *pending = 0.0;
+        }
+    }
+
+    // Return the smoothed time spent in a phase per frame in seconds.
+    pub fn timing(&self, phase: Phase) -> f64 {
+        self.timings[phase as usize]
+    }
+
+    // Return the smoothed time spent in all phases per frame in seconds.
+    pub fn total(&self) -> f64 {
+        self.timings.iter().sum()
+    }
+}
@@ -0,0 +1,930 @@
+use crate::blobs::*;
+use crate::body::*;
+use crate::camera::*;
+use crate::gpu_render::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{
+    draw::*,
+    math::{DVec2, Mat3, Vec2},
+    prelude::*,
+};
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// The directory that screenshots are written to.
+const SCREENSHOT_DIRECTORY: &str = "screenshots";
+
+// The distance in pixels between a body and its label.
+const LABEL_MARGIN: f32 = 4.0;
+// The size in pixels of the center of mass and origin markers.
+const MARKER_SIZE: f32 = 10.0;
+
+// The minimum masses of each spectral class relative to the average body with their colors, from hottest to coolest.
+const SPECTRAL_CLASSES: [(f64, [u8; 3]); 7] = [
+    (16.0, [155, 176, 255]),
+    (2.1, [170, 191, 255]),
+    (1.4, [202, 215, 255]),
+    (1.04, [248, 247, 255]),
+    (0.8, [255, 244, 234]),
+    (0.45, [255, 210, 161]),
+    (0.0, [255, 180, 107]),
+];
+// The stops of the viridis palette, from dark purple through teal to yellow.
+const VIRIDIS_STOPS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+// The stops of the cividis palette, from dark blue through gray to yellow, which reads the same with red-green color blindness.
+const CIVIDIS_STOPS: [[u8; 3]; 9] = [
+    [0, 34, 78],
+    [18, 53, 112],
+    [59, 73, 108],
+    [87, 92, 109],
+    [112, 113, 115],
+    [138, 134, 120],
+    [165, 156, 116],
+    [195, 179, 105],
+    [254, 232, 56],
+];
+// The exponent relating the mass of a star to its drawn radius.
+const STAR_SIZE_EXPONENT: f64 = 0.5;
+// How much twinkling dims stars at most.
+const TWINKLE_DEPTH: f32 = 0.4;
+// How many times per second stars twinkle.
+const TWINKLE_FREQUENCY: f32 = 1.5;
+
+// What the labels of massive bodies show.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LabelContent {
+    Mass,
+    #[serde(alias = "Index")]
+    Id,
+    Rank,
+}
+
+// Implementations for LabelContent.
+impl LabelContent {
+    // All label contents, in the order they are listed in the UI.
+    pub const ALL: [LabelContent; 3] = [LabelContent::Mass, LabelContent::Id, LabelContent::Rank];
+
+    // Return the name of a label content.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LabelContent::Mass => "Mass",
+            LabelContent::Id => "Id",
+            LabelContent::Rank => "Mass Rank",
+        }
+    }
+}
+
+// How bodies are colored.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorMode {
+    Default,
+    OrbitalEnergy,
+    AngularMomentum,
+    Acceleration,
+    Speed,
+    Mass,
+    Age,
+}
+
+// Implementations for ColorMode.
+impl ColorMode {
+    // All color modes, in the order they are listed in the UI.
+    pub const ALL: [ColorMode; 7] = [
+        ColorMode::Default,
+        ColorMode::OrbitalEnergy,
+        ColorMode::AngularMomentum,
+        ColorMode::Acceleration,
+        ColorMode::Speed,
+        ColorMode::Mass,
+        ColorMode::Age,
+    ];
+
+    // Return the name of a color mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorMode::Default => "Default",
+            ColorMode::OrbitalEnergy => "Orbital Energy",
+            ColorMode::AngularMomentum => "Angular Momentum",
+            ColorMode::Acceleration => "Acceleration",
+            ColorMode::Speed => "Speed",
+            ColorMode::Mass => "Mass",
+            ColorMode::Age => "Age",
+        }
+    }
+
+    // Return whether a color mode maps a value onto the palette.
+    pub fn uses_palette(&self) -> bool {
+        matches!(
+            self,
+            ColorMode::Acceleration | ColorMode::Speed | ColorMode::Mass | ColorMode::Age
+        )
+    }
+}
+
+// A palette that the acceleration, speed, mass, and age color modes map values onto.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    Default,
+    Viridis,
+    Cividis,
+}
+
+// Implementations for Palette.
+impl Palette {
+    // All palettes, in the order they are listed in the UI.
+    pub const ALL: [Palette; 3] = [Palette::Default, Palette::Viridis, Palette::Cividis];
+
+    // Return the name of a palette.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Default => "Default",
+            Palette::Viridis => "Viridis (Colorblind-Safe)",
+            Palette::Cividis => "Cividis (Colorblind-Safe)",
+        }
+    }
+
+    // Return the color of a palette at the given fraction between its ends, interpolating between its stops.
+    fn color(&self, fraction: f64) -> Color {
+        let stops = match self {
+            Palette::Default => return acceleration_color(fraction),
+            Palette::Viridis => &VIRIDIS_STOPS,
+            Palette::Cividis => &CIVIDIS_STOPS,
+        };
+        let position = fraction.clamp(0.0, 1.0) as f32 * (stops.len() - 1) as f32;
+        let index = (position as usize).min(stops.len() - 2);
+        let t = position - index as f32;
+        let [red, green, blue] = [0, 1, 2].map(|channel| {
+            stops[index][channel] as f32 * (1.0 - t) + stops[index + 1][channel] as f32 * t
+        });
+        Color::from_rgb(red / 255.0, green / 255.0, blue / 255.0)
+    }
+}
+
+// How bodies are drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Skin {
+    Plain,
+    StarCatalog,
+}
+
+// Implementations for Skin.
+impl Skin {
+    // All skins, in the order they are listed in the UI.
+    pub const ALL: [Skin; 2] = [Skin::Plain, Skin::StarCatalog];
+
+    // Return the name of a skin.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skin::Plain => "Plain",
+            Skin::StarCatalog => "Star Catalog",
+        }
+    }
+}
+
+// Return the spectral color of a star with the given mass relative to the average body.
+fn spectral_color(relative_mass: f64) -> Color {
+    let [red, green, blue] = SPECTRAL_CLASSES
+        .iter()
+        .find(|(minimum_mass, _)| relative_mass >= *minimum_mass)
+        .map_or(
+            SPECTRAL_CLASSES[SPECTRAL_CLASSES.len() - 1].1,
+            |(_, color)| *color,
+        );
+    Color::from_bytes(red, green, blue, 255)
+}
+
+// Return the color of a body with the given specific angular momentum as a fraction of the largest, from purple against the rotation of the system to white to orange with it.
+fn angular_momentum_color(fraction: f64) -> Color {
+    let fraction = fraction.clamp(-1.0, 1.0) as f32;
+    let (red, green, blue) = if fraction >= 0.0 {
+        (1.0, 1.0 - 0.45 * fraction, 1.0 - 0.8 * fraction)
+    } else {
+        (1.0 + 0.4 * fraction, 1.0 + 0.7 * fraction, 1.0)
+    };
+    Color::from_rgb(red, green, blue)
+}
+
+// Return the color of the default palette at the given fraction, from dark blue to yellow.
+fn acceleration_color(fraction: f64) -> Color {
+    let fraction = fraction.clamp(0.0, 1.0) as f32;
+    Color::from_rgb(
+        0.15 + 0.85 * fraction,
+        0.2 + 0.7 * fraction,
+        0.6 - 0.4 * fraction,
+    )
+}
+
+// Return the color of each body by the color mode, before it's dimmed by twinkling or fading in, given the specific orbital energies and angular momenta of the bodies if they're shown.
+fn body_colors(
+    render_settings: &RenderSettings,
+    specific_energies: &[f64],
+    angular_momenta: &[f64],
+    universe: &Universe,
+) -> Vec<Color> {
+    // Find the average mass of the massive bodies, which the star catalog skin treats as a sun-like star.
+    let (total_mass, count) = universe
+        .bodies
+        .iter()
+        .filter(|body| body.is_massive())
+        .fold((0.0, 0), |(mass, count), body| {
+            (mass + body.mass, count + 1)
+        });
+    let average_mass = if count > 0 {
+        total_mass / count as f64
+    } else {
+        1.0
+    };
+
+    // Find the largest specific angular momentum, in the sense the system rotates in, that the angular momentum color map is scaled to.
+    let rotation_sense = angular_momenta.iter().sum::<f64>().signum();
+    let maximum_angular_momentum = angular_momenta
+        .iter()
+        .fold(f64::EPSILON, |maximum, momentum| {
+            maximum.max(momentum.abs())
+        });
+
+    // Find the range of the logarithms of the accelerations cached in the last step, that the acceleration color map is scaled to.
+    let accelerations = if render_settings.color_mode == ColorMode::Acceleration
+        && universe.accelerations.len() == universe.bodies.len()
+    {
+        universe.accelerations.as_slice()
+    } else {
+        &[]
+    };
+    let (minimum_acceleration, maximum_acceleration) = accelerations
+        .iter()
+        .map(|acceleration| acceleration.length())
+        .filter(|acceleration| *acceleration > 0.0)
+        .fold((f64::MAX, f64::MIN), |(minimum, maximum), acceleration| {
+            (
+                minimum.min(acceleration.ln()),
+                maximum.max(acceleration.ln()),
+            )
+        });
+    let acceleration_range = (maximum_acceleration - minimum_acceleration).max(f64::EPSILON);
+    // Find the range of the speeds, and of the logarithms of the masses, of the bodies that aren't frozen, that the speed and mass color maps are scaled to.
+    let (minimum_speed, maximum_speed, minimum_mass, maximum_mass) = universe
+        .bodies
+        .iter()
+        .filter(|body| !body.frozen && body.mass > 0.0)
+        .fold(
+            (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+            |(minimum_speed, maximum_speed, minimum_mass, maximum_mass), body| {
+                let speed = body.velocity.length();
+                (
+                    minimum_speed.min(speed),
+                    maximum_speed.max(speed),
+                    minimum_mass.min(body.mass.ln()),
+                    maximum_mass.max(body.mass.ln()),
+                )
+            },
+        );
+    let speed_range = (maximum_speed - minimum_speed).max(f64::EPSILON);
+    let mass_range = (maximum_mass - minimum_mass).max(f64::EPSILON);
+    let palette = render_settings.palette;
+
+    // Color bodies blue if they're bound to the system and red if they'll escape, from orange to white to purple by how strongly they orbit with or against the system, along the palette by how strongly they are pulled, how fast they move, how massive they are, or how long ago they were created, or by spectral class if they're drawn as stars, leaving frozen bodies gray.
+    universe
+        .bodies
+        .iter()
+        .enumerate()
+        .map(|(index, body)| {
+            match (
+                render_settings.color_mode,
+                specific_energies.get(index),
+                angular_momenta.get(index),
+            ) {
+                (ColorMode::OrbitalEnergy, Some(energy), _) if !body.frozen => {
+                    if *energy < 0.0 {
+                        Color::from_rgb(0.3, 0.5, 1.0)
+                    } else {
+                        Color::from_rgb(1.0, 0.3, 0.3)
+                    }
+                }
+                (ColorMode::AngularMomentum, _, Some(momentum)) if !body.frozen => {
+                    angular_momentum_color(momentum * rotation_sense / maximum_angular_momentum)
+                }
+                (ColorMode::Acceleration, _, _) if !body.frozen && index < accelerations.len() => {
+                    palette.color(
+                        (accelerations[index].length().max(f64::MIN_POSITIVE).ln()
+                            - minimum_acceleration)
+                            / acceleration_range,
+                    )
+                }
+                (ColorMode::Speed, _, _) if !body.frozen => {
+                    palette.color((body.velocity.length() - minimum_speed) / speed_range)
+                }
+                (ColorMode::Mass, _, _) if !body.frozen && body.mass > 0.0 => {
+                    palette.color((body.mass.ln() - minimum_mass) / mass_range)
+                }
+                (ColorMode::Age, _, _) if !body.frozen => palette.color(
+                    (universe.simulation_time - body.created_at)
+                        / universe.simulation_time.max(f64::EPSILON),
+                ),
+                _ if render_settings.skin == Skin::StarCatalog && body.is_massive() => {
+                    spectral_color(body.mass / average_mass)
+                }
+                _ => body.color(),
+            }
+        })
+        .collect()
+}
+
+// The order bodies are drawn in, where bodies drawn later are on top.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DrawOrder {
+    Newest,
+    Smallest,
+    Largest,
+}
+
+// Implementations for DrawOrder.
+impl DrawOrder {
+    // All draw orders, in the order they are listed in the UI.
+    pub const ALL: [DrawOrder; 3] = [DrawOrder::Newest, DrawOrder::Smallest, DrawOrder::Largest];
+
+    // Return the name of a draw order.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DrawOrder::Newest => "Newest on Top",
+            DrawOrder::Smallest => "Small on Top",
+            DrawOrder::Largest => "Large on Top",
+        }
+    }
+}
+
+// A sprite that bodies with a tag are drawn with instead of circles.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagSprite {
+    pub tag: String,
+    pub path: String,
+}
+
+// Settings to render the universe with.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSettings {
+    pub animate_merges: bool,
+    pub merge_duration: f32,
+    pub show_labels: bool,
+    pub label_count: usize,
+    pub label_content: LabelContent,
+    pub hide_labels_with_ui: bool,
+    pub power_saving: bool,
+    pub pipelined_physics: bool,
+    pub color_mode: ColorMode,
+    pub palette: Palette,
+    pub fade_in: bool,
+    pub fade_in_duration: f64,
+    pub energy_interval: f32,
+    pub skin: Skin,
+    pub twinkle: bool,
+    pub draw_order: DrawOrder,
+    pub show_center_of_mass: bool,
+    pub show_origin: bool,
+    pub show_principal_axes: bool,
+    pub gpu_points: bool,
+    pub show_trails: bool,
+    pub trail_length: usize,
+    pub sprite_path: String,
+    pub tag_sprites: Vec<TagSprite>,
+    pub show_spin: bool,
+    pub blobs: bool,
+    pub blob_resolution: f32,
+    pub blob_reach: f64,
+}
+
+// Default value for RenderSettings.
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            animate_merges: true,
+            merge_duration: 0.25,
+            show_labels: false,
+            label_count: 10,
+            label_content: LabelContent::Mass,
+            hide_labels_with_ui: true,
+            power_saving: true,
+            pipelined_physics: false,
+            color_mode: ColorMode::Default,
+            palette: Palette::Default,
+            fade_in: false,
+            fade_in_duration: 1.0,
+            energy_interval: 0.5,
+            skin: Skin::Plain,
+            twinkle: false,
+            draw_order: DrawOrder::Newest,
+            show_center_of_mass: false,
+            show_origin: false,
+            show_principal_axes: false,
+            gpu_points: true,
+            show_trails: false,
+            trail_length: 100,
+            sprite_path: String::new(),
+            tag_sprites: Vec::new(),
+            show_spin: false,
+            blobs: false,
+            blob_resolution: 4.0,
+            blob_reach: 2.5,
+        }
+    }
+}
+
+// An animation of two bodies shrinking into the body they merged into.
+struct MergeAnimation {
+    collision_event: CollisionEvent,
+    progress: f32,
+}
+
+// A scene renderer that draws effects on top of the universe.
+#[derive(Default)]
+pub struct SceneRenderer {
+    pub render_settings: RenderSettings,
+    merge_animations: Vec<MergeAnimation>,
+    specific_energies: Vec<f64>,
+    angular_momenta: Vec<f64>,
+    energy_timer: f32,
+    time: f32,
+    draw_order: Vec<usize>,
+    pub gpu_renderer: GpuRenderer,
+    pub sprites: HashMap<String, Result<Texture, String>>,
+}
+
+// Implementations for SceneRenderer.
+impl SceneRenderer {
+    // Update the scene renderer using the time since the last frame.
+    pub fn update(&mut self, universe: &Universe, delta_time: f32) {
+        // Advance the time that twinkling is animated with.
+        self.time += delta_time;
+
+        // Advance each merge animation and remove the finished ones.
+        let merge_duration = self.render_settings.merge_duration.max(f32::EPSILON);
+        for merge_animation in self.merge_animations.iter_mut() {
+            merge_animation.progress += delta_time / merge_duration;
+        }
+        self.merge_animations
+            .retain(|merge_animation| merge_animation.progress < 1.0);
+
+        // Start a merge animation for each collision that happened this step if it's enabled.
+        if self.render_settings.animate_merges {
+            self.merge_animations
+                .extend(
+                    universe
+                        .collision_events
+                        .iter()
+                        .map(|collision_event| MergeAnimation {
+                            collision_event: *collision_event,
+                            progress: 0.0,
+                        }),
+                );
+        } else {
+            self.merge_animations.clear();
+        }
+
+        // Recompute the specific orbital energies periodically while they're shown, or immediately if bodies were added or removed.
+        if self.render_settings.color_mode == ColorMode::OrbitalEnergy {
+            self.energy_timer -= delta_time;
+            if self.energy_timer <= 0.0 || self.specific_energies.len() != universe.bodies.len() {
+                self.specific_energies = universe.specific_energies();
+                self.energy_timer = self.render_settings.energy_interval;
+            }
+        } else {
+            self.specific_energies.clear();
+        }
+
+        // Recompute the specific angular momenta every frame while they're shown, since they're cheap.
+        if self.render_settings.color_mode == ColorMode::AngularMomentum {
+            self.angular_momenta = universe.specific_angular_momenta();
+        } else {
+            self.angular_momenta.clear();
+        }
+
+        // Record a sample of the trails of all bodies while they're shown, colored like the bodies by the color mode.
+        if self.render_settings.show_trails {
+            self.gpu_renderer
+                .record_trails(universe, self.render_settings.trail_length, || {
+                    body_colors(
+                        &self.render_settings,
+                        &self.specific_energies,
+                        &self.angular_momenta,
+                        universe,
+                    )
+                });
+        } else {
+            self.gpu_renderer.clear_trails();
+        }
+    }
+
+    // Create the pipelines of the GPU renderer if they're needed and don't exist yet.
+    pub fn prepare_gpu(&mut self, graphics: &mut Graphics) {
+        if self.render_settings.gpu_points || self.render_settings.show_trails {
+            self.gpu_renderer.prepare(graphics);
+        }
+    }
+
+    // Load the image of each sprite path in the settings that hasn't been loaded yet, keeping the error if it couldn't be.
+    pub fn prepare_sprites(&mut self, graphics: &mut Graphics) {
+        let render_settings = &self.render_settings;
+        let paths = std::iter::once(&render_settings.sprite_path).chain(
+            render_settings
+                .tag_sprites
+                .iter()
+                .map(|tag_sprite| &tag_sprite.path),
+        );
+        for path in paths {
+            if path.is_empty() || self.sprites.contains_key(path) {
+                continue;
+            }
+            let texture = fs::read(path)
+                .map_err(|error| error.to_string())
+                .and_then(|bytes| graphics.create_texture().from_image(&bytes).build());
+            self.sprites.insert(path.clone(), texture);
+        }
+    }
+
+    // Return the loaded sprite a body is drawn with, which is the sprite of its first tag that has one, or the sprite of all bodies.
+    fn sprite(&self, body: &Body, universe: &Universe) -> Option<&Texture> {
+        let render_settings = &self.render_settings;
+        let tags = universe.components.tags.get(body.id);
+        let path = render_settings
+            .tag_sprites
+            .iter()
+            .find(|tag_sprite| {
+                !tag_sprite.path.is_empty()
+                    && tags.is_some_and(|tags| tags.contains(&tag_sprite.tag))
+            })
+            .map(|tag_sprite| &tag_sprite.path)
+            .unwrap_or(&render_settings.sprite_path);
+        self.sprites.get(path)?.as_ref().ok()
+    }
+
+    // Return whether the trails and points are drawn by the GPU renderer, which clears the screen under the draw surface.
+    pub fn uses_gpu(&self) -> bool {
+        self.gpu_renderer.is_ready()
+            && (self.render_settings.gpu_points || self.render_settings.show_trails)
+    }
+
+    // Create a renderer for the trails and points of the GPU renderer with the given camera matrix on a surface of the given size, if it's used.
+    pub fn create_gpu_renderer(
+        &mut self,
+        graphics: &mut Graphics,
+        camera: &Camera,
+        camera_matrix: Mat3,
+        size: (f32, f32),
+    ) -> Option<Renderer> {
+        if !self.uses_gpu() {
+            return None;
+        }
+        self.gpu_renderer
+            .create_renderer(graphics, camera_matrix, camera.scale, size)
+    }
+
+    // Draw the bodies of the universe with the skin, colored by the color mode.
+    pub fn draw_bodies(&mut self, draw: &mut Draw, camera: &Camera, universe: &Universe) {
+        // Sort the indices of the bodies into the draw order, reusing the order of the last frame since it's nearly sorted and the stable sort runs in close to linear time on it.
+        if self.draw_order.len() != universe.bodies.len() {
+            self.draw_order = (0..universe.bodies.len()).collect();
+        }
+        let bodies = &universe.bodies;
+        match self.render_settings.draw_order {
+            // Sort by when the bodies were created rather than by index, since reordering the bodies shuffles their indices, breaking ties between bodies created at once by id.
+            DrawOrder::Newest => self.draw_order.sort_by(|&index1, &index2| {
+                bodies[index1]
+                    .created_at
+                    .total_cmp(&bodies[index2].created_at)
+                    .then(bodies[index1].id.cmp(&bodies[index2].id))
+            }),
+            DrawOrder::Smallest => self
+                .draw_order
+                .sort_by(|&index1, &index2| bodies[index2].mass.total_cmp(&bodies[index1].mass)),
+            DrawOrder::Largest => self
+                .draw_order
+                .sort_by(|&index1, &index2| bodies[index1].mass.total_cmp(&bodies[index2].mass)),
+        }
+
+        // Find the average mass of the massive bodies, which the star catalog skin treats as a sun-like star.
+        let (total_mass, count) = universe
+            .bodies
+            .iter()
+            .filter(|body| body.is_massive())
+            .fold((0.0, 0), |(mass, count), body| {
+                (mass + body.mass, count + 1)
+            });
+        let average_mass = if count > 0 {
+            total_mass / count as f64
+        } else {
+            1.0
+        };
+        let average_radius = average_mass.cbrt();
+
+        // Draw the bodies other than black holes as points with the GPU renderer if it's enabled and works, or as circles on the draw surface otherwise.
+        let gpu_points = self.render_settings.gpu_points && self.gpu_renderer.is_ready();
+        self.gpu_renderer.clear_points();
+        // Find the color of each body by the color mode, which the trails are colored with too.
+        let body_colors = body_colors(
+            &self.render_settings,
+            &self.specific_energies,
+            &self.angular_momenta,
+            universe,
+        );
+
+        // Find the bounds of the world on the screen, which blobs are only computed within.
+        let inverse_matrix = camera.create_matrix(draw).inverse();
+        let (width, height) = draw.size();
+        let visible_bounds = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+            .iter()
+            .map(|&(x, y)| inverse_matrix.transform_point2(Vec2::new(x, y)).as_dvec2())
+            .fold(
+                (DVec2::splat(f64::MAX), DVec2::splat(f64::MIN)),
+                |(minimum, maximum), corner| (minimum.min(corner), maximum.max(corner)),
+            );
+        let mut blob_bodies = Vec::new();
+
+        for &index in self.draw_order.iter() {
+            let body = &universe.bodies[index];
+
+            // Draw black holes as black discs with a glowing ring, surrounded by a faint outline of their accretion radius.
+            if body.black_hole {
+                let position = (body.position.x as f32, body.position.y as f32);
+                let radius = body.radius() as f32;
+                draw.circle(radius * universe.universe_settings.accretion_radius as f32)
+                    .position(position.0, position.1)
+                    .tolerance(camera.circle_tolerance())
+                    .stroke(1.0 / camera.scale)
+                    .color(Color::from_rgba(1.0, 0.6, 0.2, 0.3));
+                body.draw(draw, Color::BLACK, radius as f64, camera.circle_tolerance());
+                draw.circle(radius)
+                    .position(position.0, position.1)
+                    .tolerance(camera.circle_tolerance())
+                    .stroke((radius * 0.2).max(2.0 / camera.scale))
+                    .color(Color::from_rgb(1.0, 0.6, 0.2));
+                continue;
+            }
+
+            // Start from the color of the body by the color mode.
+            let mut color = body_colors[index];
+
+            // Size stars nonlinearly by mass and dim them over time with a different phase for each one, found from its id so reordering the bodies doesn't shuffle them, if twinkling is enabled.
+            let mut radius = body.radius();
+            if self.render_settings.skin == Skin::StarCatalog && body.is_massive() {
+                radius = average_radius * (body.mass / average_mass).powf(STAR_SIZE_EXPONENT);
+                if self.render_settings.twinkle {
+                    let phase = (body.id.0 as f64 * 2.399).rem_euclid(std::f64::consts::TAU) as f32;
+                    let wave =
+                        (self.time * TWINKLE_FREQUENCY * std::f32::consts::TAU + phase).sin();
+                    color.a = 1.0 - TWINKLE_DEPTH * (0.5 + 0.5 * wave);
+                }
+            }
+            // Fade in bodies that were spawned or merged during the run over the fade-in duration if it's enabled, leaving primordial bodies opaque.
+            if self.render_settings.fade_in && body.created_at > 0.0 {
+                color.a *= ((universe.simulation_time - body.created_at)
+                    / self.render_settings.fade_in_duration.max(f64::EPSILON))
+                .clamp(0.0, 1.0) as f32;
+            }
+            // Draw the body as its sprite fit to the unit circle and turned to its angle if it has one, keeping the transparency of twinkling.
+            let position = (body.position.x as f32, body.position.y as f32);
+            let sprite = self.sprite(body, universe);
+            if self.render_settings.blobs
+                && sprite.is_none()
+                && body.is_massive()
+                && radius * self.render_settings.blob_reach * camera.scale as f64
+                    >= 2.0 * self.render_settings.blob_resolution as f64
+            {
+                // Leave bodies that cover enough cells on the screen to blend into blobs, which are drawn once all bodies are found.
+                blob_bodies.push(BlobBody {
+                    position: body.position,
+                    radius,
+                    color,
+                });
+            } else if let Some(sprite) = sprite {
+                draw.image(sprite)
+                    .position(position.0 - radius as f32, position.1 - radius as f32)
+                    .size(radius as f32 * 2.0, radius as f32 * 2.0)
+                    .alpha(color.a)
+                    .rotate_from(position, body.angle as f32);
+            } else if gpu_points {
+                self.gpu_renderer.push_point(body.position, radius, color);
+            } else {
+                body.draw(draw, color, radius, camera.circle_tolerance());
+            }
+
+            // Draw a line from the center of a spinning body to its edge at its angle if it's enabled.
+            if self.render_settings.show_spin && body.spin != 0.0 {
+                let edge = body.position + DVec2::from_angle(body.angle) * radius;
+                draw.line(position, (edge.x as f32, edge.y as f32))
+                    .width(1.0 / camera.scale)
+                    .color(Color::from_rgba(1.0, 0.3, 0.3, color.a));
+            }
+        }
+
+        // Draw the bodies left for blobs, with cells of the blob resolution in pixels.
+        draw_blobs(
+            draw,
+            &blob_bodies,
+            visible_bounds,
+            self.render_settings.blob_resolution as f64 / camera.scale as f64,
+            self.render_settings.blob_reach,
+        );
+    }
+
+    // Draw the effects of the scene renderer.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera) {
+        // Draw each merge animation.
+        for merge_animation in self.merge_animations.iter() {
+            // Ease the progress so the bodies slow down as they reach the merged body.
+            let progress = merge_animation.progress as f64;
+            let eased_progress = 1.0 - (1.0 - progress).powi(2);
+            let collision_event = &merge_animation.collision_event;
+
+            // Draw each colliding body moving into the merged body while shrinking and fading out.
+            for (position, mass) in collision_event
+                .positions
+                .iter()
+                .zip(collision_event.masses.iter())
+            {
+                let position: DVec2 = position.lerp(collision_event.position, eased_progress);
+                draw.circle((mass.cbrt() * (1.0 - eased_progress)) as f32)
+                    .position(position.x as f32, position.y as f32)
+                    .tolerance(camera.circle_tolerance())
+                    .color(Color::WHITE)
+                    .alpha(1.0 - merge_animation.progress);
+            }
+        }
+    }
+
+    // Draw the markers of the origin, the center of mass, and the principal axes of the mass distribution that are enabled, sized to stay the same on the screen.
+    pub fn draw_markers(&self, draw: &mut Draw, camera: &Camera, universe: &Universe) {
+        let size = MARKER_SIZE / camera.scale;
+        let width = 1.5 / camera.scale;
+
+        // Draw a gray cross at the origin.
+        if self.render_settings.show_origin {
+            let color = Color::from_rgba(0.6, 0.6, 0.6, 0.8);
+            draw.line((-size, 0.0), (size, 0.0))
+                .width(width)
+                .color(color);
+            draw.line((0.0, -size), (0.0, size))
+                .width(width)
+                .color(color);
+        }
+
+        // Find the center of mass the other markers are drawn at, skipping them if there's no mass.
+        let (center_of_mass, _, total_mass) = universe.center_of_mass();
+        if total_mass <= 0.0 {
+            return;
+        }
+        let center = (center_of_mass.x as f32, center_of_mass.y as f32);
+
+        // Draw the principal axes through the center of mass, each as long as twice the spread of the mass along it, with the major axis in green and the minor axis in blue.
+        if self.render_settings.show_principal_axes {
+            if let Some(axes) = universe.principal_axes() {
+                for ((direction, spread), color) in axes.into_iter().zip([
+                    Color::from_rgba(0.4, 1.0, 0.4, 0.8),
+                    Color::from_rgba(0.4, 0.7, 1.0, 0.8),
+                ]) {
+                    let offset = direction * spread * 2.0;
+                    draw.line(
+                        (center.0 - offset.x as f32, center.1 - offset.y as f32),
+                        (center.0 + offset.x as f32, center.1 + offset.y as f32),
+                    )
+                    .width(width)
+                    .color(color);
+                }
+            }
+        }
+
+        // Draw a yellow circle with a cross at the center of mass.
+        if self.render_settings.show_center_of_mass {
+            let color = Color::from_rgb(1.0, 0.85, 0.2);
+            draw.circle(size * 0.6)
+                .position(center.0, center.1)
+                .tolerance(camera.circle_tolerance())
+                .stroke(width)
+                .color(color);
+            draw.line((center.0 - size, center.1), (center.0 + size, center.1))
+                .width(width)
+                .color(color);
+            draw.line((center.0, center.1 - size), (center.0, center.1 + size))
+                .width(width)
+                .color(color);
+        }
+    }
+
+    // Draw labels next to the most massive bodies on the background of the UI, skipping labels that would overlap.
+    pub fn draw_labels(
+        &self,
+        context: &Context,
+        app: &mut App,
+        camera: &Camera,
+        universe: &Universe,
+        ui_hidden: bool,
+    ) {
+        // Only draw labels if it's enabled and they aren't hidden along with the UI.
+        if !self.render_settings.show_labels
+            || (ui_hidden && self.render_settings.hide_labels_with_ui)
+            || universe.bodies.is_empty()
+        {
+            return;
+        }
+
+        // Find the indices of the most massive bodies, sorted from most to least massive.
+        let mut indices: Vec<usize> = (0..universe.bodies.len()).collect();
+        let label_count = self.render_settings.label_count.min(indices.len());
+        let by_mass = |index1: &usize, index2: &usize| {
+            universe.bodies[*index2]
+                .mass
+                .total_cmp(&universe.bodies[*index1].mass)
+        };
+        if label_count < indices.len() {
+            indices.select_nth_unstable_by(label_count, by_mass);
+        }
+        indices.truncate(label_count);
+        indices.sort_unstable_by(by_mass);
+
+        // Draw the labels behind the UI.
+        let painter = context.layer_painter(LayerId::background());
+        let units = &universe.universe_settings.units;
+        let mut placed_rects: Vec<Rect> = vec![];
+        for (rank, index) in indices.into_iter().enumerate() {
+            // Lay out the text of the label, where the rank follows mergers since the bodies are sorted every frame.
+            let body = &universe.bodies[index];
+            let text = match self.render_settings.label_content {
+                LabelContent::Mass => units.format(Quantity::Mass, body.mass),
+                LabelContent::Id => format!("#{}", body.id),
+                LabelContent::Rank => format!("{}", rank + 1),
+            };
+            let galley = painter.layout_no_wrap(text, FontId::proportional(12.0), Color32::WHITE);
+
+            // Find the position and radius of the body on the screen.
+            let position = camera.world_to_screen(app, body.position);
+            let center = pos2(position.x, position.y);
+            let offset = body.radius() as f32 * camera.scale + LABEL_MARGIN;
+            let size = galley.size();
+
+            // Try placing the label to the right, left, above, and below the body, using the first place that doesn't overlap another label.
+            let candidates = [
+                pos2(center.x + offset, center.y - size.y * 0.5),
+                pos2(center.x - offset - size.x, center.y - size.y * 0.5),
+                pos2(center.x - size.x * 0.5, center.y - offset - size.y),
+                pos2(center.x - size.x * 0.5, center.y + offset),
+            ];
+            let placement = candidates
+                .into_iter()
+                .map(|position| Rect::from_min_size(position, size))
+                .find(|rect| !placed_rects.iter().any(|placed| placed.intersects(*rect)));
+
+            // Draw the label if a place was found.
+            if let Some(rect) = placement {
+                painter.galley(rect.min, galley, Color32::WHITE);
+                placed_rects.push(rect);
+            }
+        }
+    }
+}
+
+// Render a draw surface to a PNG file in the screenshot directory, over the GPU renderer if there is one, and return the path of the file.
+pub fn save_screenshot(
+    graphics: &mut Graphics,
+    gpu_renderer: Option<&Renderer>,
+    draw: &Draw,
+) -> Result<PathBuf, String> {
+    // Create a render texture the size of the draw surface and render to it.
+    let (width, height) = draw.size();
+    let render_texture = graphics
+        .create_render_texture(width as u32, height as u32)
+        .build()?;
+    if let Some(gpu_renderer) = gpu_renderer {
+        graphics.render_to(&render_texture, gpu_renderer);
+    }
+    graphics.render_to(&render_texture, draw);
+
+    // Name the screenshot after the time since unix epoch and write it to the screenshot directory.
+    fs::create_dir_all(SCREENSHOT_DIRECTORY).map_err(|error| error.to_string())?;
+    let path = Path::new(SCREENSHOT_DIRECTORY).join(format!(
+        "screenshot_{}.png",
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    ));
+    render_texture.to_file(graphics, &path)?;
+    Ok(path)
+}
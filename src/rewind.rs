@@ -0,0 +1,158 @@
+use crate::body::*;
+use crate::locale::*;
+use crate::pipeline::*;
+use crate::universe::*;
+use notan_egui::*;
+use std::collections::VecDeque;
+
+// A snapshot of the bodies and simulation time in the rewind buffer, with the seconds the universe had been running for when it was taken, which leaves out the components, caches, and statistics of the universe so snapshots stay light.
+struct RewindSnapshot {
+    time: f64,
+    simulation_time: f64,
+    bodies: Vec<Body>,
+}
+
+// A rewind buffer that keeps snapshots of the last seconds the universe was running, taken every interval, so any of them can be restored and resumed from after something unexpected happens.
+pub struct RewindBuffer {
+    pub enabled: bool,
+    pub duration: f64,
+    pub interval: f64,
+    snapshots: VecDeque<RewindSnapshot>,
+    elapsed: f64,
+    simulation_time: Option<f64>,
+    position: Option<usize>,
+}
+
+// Default value for RewindBuffer.
+impl Default for RewindBuffer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration: 10.0,
+            interval: 0.25,
+            snapshots: VecDeque::new(),
+            elapsed: 0.0,
+            simulation_time: None,
+            position: None,
+        }
+    }
+}
+
+// Implementations for RewindBuffer.
+impl RewindBuffer {
+    // Take a snapshot of the universe if it advanced and an interval has passed since the last one, given the seconds since the last frame, and forget the snapshots older than the duration.
+    pub fn update(&mut self, universe: &Universe, delta_time: f32) {
+        if !self.enabled {
+            self.snapshots.clear();
+            self.position = None;
+            return;
+        }
+
+        // Only count the time the universe is advancing.
+        if universe.paused || self.simulation_time == Some(universe.simulation_time) {
+            return;
+        }
+        self.simulation_time = Some(universe.simulation_time);
+        self.elapsed += delta_time as f64;
+
+        // Forget the snapshots after the restored one, since the universe moved on from it.
+        if let Some(position) = self.position.take() {
+            self.snapshots.truncate(position + 1);
+        }
+
+        // Take a snapshot once an interval has passed, and forget the ones that are too old.
+        if !self
+            .snapshots
+            .back()
+            .is_some_and(|snapshot| self.elapsed - snapshot.time < self.interval)
+        {
+            self.snapshots.push_back(RewindSnapshot {
+                time: self.elapsed,
+                simulation_time: universe.simulation_time,
+                bodies: universe.bodies.clone(),
+            });
+        }
+        while self
+            .snapshots
+            .front()
+            .is_some_and(|snapshot| self.elapsed - snapshot.time > self.duration)
+        {
+            self.snapshots.pop_front();
+        }
+    }
+
+    // Restore the bodies and simulation time of the snapshot at the given position in the buffer and pause the universe there, keeping the rest of the universe so rewinding doesn't undo changes to the settings, and dropping the step running on the worker thread since the bodies were replaced.
+    fn restore(&mut self, position: usize, universe: &mut Universe) {
+        let snapshot = &self.snapshots[position];
+        universe.bodies = snapshot.bodies.clone();
+        universe.simulation_time = snapshot.simulation_time;
+        universe.edits = EditQueue::default();
+        universe.paused = true;
+        self.elapsed = snapshot.time;
+        self.simulation_time = Some(universe.simulation_time);
+        self.position = Some(position);
+    }
+
+    // Show the settings of the rewind buffer in the given UI, with a slider to restore any snapshot in it and a button to resume from the restored one.
+    pub fn show(&mut self, ui: &mut Ui, universe: &mut Universe) {
+        // Create a checkbox to toggle the buffer and drag values to modify how far back it reaches and how often snapshots are taken.
+        let label_id = ui.label(tr("Keep Rewind Buffer")).id;
+        ui.add(Checkbox::new(&mut self.enabled, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Buffer Length")).id;
+        ui.add(
+            DragValue::new(&mut self.duration)
+                .speed(0.1)
+                .suffix(" s")
+                .clamp_range(0.1..=600.0),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Snapshot Interval")).id;
+        ui.add(
+            DragValue::new(&mut self.interval)
+                .speed(0.01)
+                .suffix(" s")
+                .clamp_range(0.01..=10.0),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a slider over the snapshots that restores the chosen one, showing how long before the latest one it was taken.
+        if self.snapshots.is_empty() {
+            ui.label(tr("The buffer fills while the universe is running"));
+            ui.end_row();
+            return;
+        }
+        let latest = self.snapshots.len() - 1;
+        let mut position = self.position.unwrap_or(latest);
+        let label_id = ui.label(tr("Rewind")).id;
+        if ui
+            .add(Slider::new(&mut position, 0..=latest).show_value(false))
+            .labelled_by(label_id)
+            .changed()
+        {
+            self.restore(position, universe);
+        }
+        ui.end_row();
+        let seconds_ago = self.snapshots[latest].time - self.snapshots[position].time;
+        ui.label(tr_format(
+            "{} s ago, {} snapshots",
+            &[&format!("{:.2}", seconds_ago), &self.snapshots.len()],
+        ));
+        ui.end_row();
+
+        // Create a button to resume from the restored snapshot.
+        if ui
+            .add_enabled(
+                self.position.is_some() && universe.paused,
+                Button::new(tr("Resume From Here")),
+            )
+            .clicked()
+        {
+            universe.paused = false;
+        }
+        ui.end_row();
+    }
+}
@@ -0,0 +1,195 @@
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan_egui::*;
+
+// The fraction of bodies that the outermost bin reaches out to, so a few escaping bodies don't squash the curve.
+const RADIUS_FRACTION: f64 = 0.95;
+
+// A bin of bodies at similar distances from the center of mass.
+#[derive(Clone, Copy, Default)]
+pub struct RadialBin {
+    pub radius: f64,
+    pub bodies: usize,
+    pub tangential_speed: f64,
+    pub circular_speed: f64,
+}
+
+// A rotation curve that bins bodies by their distance from the center of mass and plots their mean tangential speed against the speed of a circular orbit around the mass enclosed, recomputed periodically.
+pub struct RotationCurve {
+    pub bin_count: usize,
+    pub interval: f32,
+    pub timer: f32,
+    pub bins: Vec<RadialBin>,
+}
+
+// Default value for RotationCurve.
+impl Default for RotationCurve {
+    fn default() -> Self {
+        Self {
+            bin_count: 20,
+            interval: 1.0,
+            timer: 0.0,
+            bins: Vec::new(),
+        }
+    }
+}
+
+// Implementations for RotationCurve.
+impl RotationCurve {
+    // Update the rotation curve using the time since the last frame, recomputing it once per interval.
+    pub fn update(&mut self, universe: &Universe, delta_time: f32) {
+        self.timer -= delta_time;
+        if self.timer > 0.0 {
+            return;
+        }
+        self.timer = self.interval;
+        self.compute(universe);
+    }
+
+    // Recompute the bins of the rotation curve from the bodies of the universe.
+    fn compute(&mut self, universe: &Universe) {
+        // Find the distance and the tangential velocity of each moving body relative to the center of mass, positive in the sense the system rotates in.
+        let (center_of_mass, center_velocity, _) = universe.center_of_mass();
+        let rotation_sense = universe
+            .specific_angular_momenta()
+            .iter()
+            .sum::<f64>()
+            .signum();
+        let mut samples: Vec<(f64, f64, f64)> = universe
+            .bodies
+            .iter()
+            .filter(|body| !body.frozen)
+            .filter_map(|body| {
+                let offset = body.position - center_of_mass;
+                let radius = offset.length();
+                (radius > 0.0).then(|| {
+                    let momentum = offset.perp_dot(body.velocity - center_velocity);
+                    let mass = if body.is_massive() { body.mass } else { 0.0 };
+                    (radius, momentum * rotation_sense / radius, mass)
+                })
+            })
+            .collect();
+        if samples.is_empty() || self.bin_count == 0 {
+            self.bins.clear();
+            return;
+        }
+
+        // Sort the samples by radius, and reach out to the radius containing most of them.
+        samples.sort_unstable_by(|sample1, sample2| sample1.0.total_cmp(&sample2.0));
+        let outer_index = ((samples.len() - 1) as f64 * RADIUS_FRACTION) as usize;
+        let maximum_radius = samples[outer_index].0.max(f64::EPSILON);
+        let width = maximum_radius / self.bin_count as f64;
+
+        // Sum the tangential speeds and masses in each bin, counting the mass of samples beyond the outermost bin as enclosed by none of them.
+        let mut bins = vec![RadialBin::default(); self.bin_count];
+        let mut masses = vec![0.0; self.bin_count];
+        for &(radius, tangential_speed, mass) in samples.iter() {
+            if radius > maximum_radius {
+                break;
+            }
+            let index = ((radius / width) as usize).min(self.bin_count - 1);
+            bins[index].bodies += 1;
+            bins[index].tangential_speed += tangential_speed;
+            masses[index] += mass;
+        }
+
+        // Average the tangential speeds and find the circular speed around the mass enclosed by the outer edge of each bin.
+        let gravitational_constant = universe.universe_settings.gravitational_constant;
+        let mut enclosed_mass = 0.0;
+        for (index, bin) in bins.iter_mut().enumerate() {
+            bin.radius = (index as f64 + 0.5) * width;
+            if bin.bodies > 0 {
+                bin.tangential_speed /= bin.bodies as f64;
+            }
+            enclosed_mass += masses[index];
+            let edge = (index as f64 + 1.0) * width;
+            bin.circular_speed = (gravitational_constant * enclosed_mass / edge)
+                .max(0.0)
+                .sqrt();
+        }
+        self.bins = bins;
+    }
+
+    // Show the rotation curve in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe) {
+        // Create drag values to modify the amount of bins and the seconds between updates, and a button to update now.
+        let label_id = ui.label(tr("Bins")).id;
+        ui.add(DragValue::new(&mut self.bin_count).clamp_range(1..=200))
+            .labelled_by(label_id);
+        ui.end_row();
+        let label_id = ui.label(tr("Update Interval")).id;
+        ui.add(Slider::new(&mut self.interval, 0.1..=10.0).suffix(" s"))
+            .labelled_by(label_id);
+        ui.end_row();
+        if ui.button(tr("Update Now")).clicked() {
+            self.timer = 0.0;
+        }
+        ui.end_row();
+
+        // Allocate space for the plot.
+        let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 160.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        let Some(last) = self.bins.last() else {
+            ui.label(tr("No bodies to bin"));
+            ui.end_row();
+            return;
+        };
+
+        // Scale the plot to fit both curves, starting both axes at zero.
+        let maximum_radius = (last.radius + self.bins[0].radius).max(f64::EPSILON);
+        let maximum_speed = self
+            .bins
+            .iter()
+            .map(|bin| bin.tangential_speed.abs().max(bin.circular_speed))
+            .fold(f64::EPSILON, f64::max);
+        let to_screen = |radius: f64, speed: f64| {
+            pos2(
+                rect.left() + (radius / maximum_radius) as f32 * rect.width(),
+                rect.bottom() - (speed / maximum_speed) as f32 * rect.height(),
+            )
+        };
+
+        // Draw the circular speed as a faint line and the mean tangential speed of the bins that have bodies as a bright line with dots.
+        let circular: Vec<Pos2> = self
+            .bins
+            .iter()
+            .map(|bin| to_screen(bin.radius, bin.circular_speed))
+            .collect();
+        painter.add(Shape::line(
+            circular,
+            Stroke::new(1.0, ui.visuals().weak_text_color()),
+        ));
+        let tangential: Vec<Pos2> = self
+            .bins
+            .iter()
+            .filter(|bin| bin.bodies > 0)
+            .map(|bin| to_screen(bin.radius, bin.tangential_speed))
+            .collect();
+        for point in tangential.iter() {
+            painter.circle_filled(*point, 2.0, Color32::from_rgb(255, 153, 51));
+        }
+        painter.add(Shape::line(
+            tangential,
+            Stroke::new(1.5, Color32::from_rgb(255, 153, 51)),
+        ));
+
+        // Explain the curves and label the axes with their ranges.
+        let units = &universe.universe_settings.units;
+        ui.colored_label(Color32::from_rgb(255, 153, 51), tr("Mean tangential speed"));
+        ui.colored_label(
+            ui.visuals().weak_text_color(),
+            tr("Circular speed around the enclosed mass"),
+        );
+        ui.label(tr_format(
+            "Radius: 0 to {}",
+            &[&units.format(Quantity::Length, maximum_radius)],
+        ));
+        ui.label(tr_format(
+            "Speed: 0 to {}",
+            &[&units.format(Quantity::Velocity, maximum_speed)],
+        ));
+        ui.end_row();
+    }
+}
@@ -0,0 +1,387 @@
+use crate::{body::*, dialogs::*, generation::*, locale::*, units::*, universe::*};
+use notan::math::DVec2;
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicUsize},
+};
+
+// A body placed by a scenario. Values are given in display units.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScenarioBody {
+    pub name: String,
+    pub position: DVec2,
+    pub velocity: DVec2,
+    pub mass: f64,
+    pub frozen: bool,
+    pub test_particle: bool,
+    pub black_hole: bool,
+}
+
+// Default value for ScenarioBody.
+impl Default for ScenarioBody {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            position: DVec2::ZERO,
+            velocity: DVec2::ZERO,
+            mass: 1.0,
+            frozen: false,
+            test_particle: false,
+            black_hole: false,
+        }
+    }
+}
+
+// Implementations for ScenarioBody.
+impl ScenarioBody {
+    // Create the body that a scenario body describes, using the given units to convert from display units to simulation units.
+    fn to_body(&self, units: &Units) -> Body {
+        Body {
+            name: self.name.clone(),
+            position: self.position * units.simulation_value(Quantity::Length, 1.0),
+            velocity: self.velocity * units.simulation_value(Quantity::Velocity, 1.0),
+            mass: units.simulation_value(Quantity::Mass, self.mass),
+            frozen: self.frozen,
+            test_particle: self.test_particle,
+            black_hole: self.black_hole,
+            ..Default::default()
+        }
+    }
+}
+
+// Return the seed that clusters are spawned with if a scenario doesn't give one, which isn't 0 so they're the same every time.
+fn default_seed() -> u64 {
+    1
+}
+
+// An action that a scenario event performs on the universe. Values are given in display units.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScenarioAction {
+    SpawnCluster {
+        #[serde(default = "default_seed")]
+        seed: u64,
+        #[serde(default)]
+        cluster: ClusterSettings,
+    },
+    AddBody {
+        body: ScenarioBody,
+    },
+    RemoveBody {
+        body: String,
+    },
+    ApplyImpulse {
+        body: String,
+        delta_v: DVec2,
+    },
+    SetGravitationalConstant {
+        value: f64,
+    },
+    SetCollisions {
+        enabled: bool,
+    },
+    Pause,
+}
+
+// Implementations for ScenarioAction.
+impl ScenarioAction {
+    // Return a short description of an action to list it in the UI.
+    pub fn describe(&self) -> String {
+        match self {
+            ScenarioAction::SpawnCluster { cluster, .. } => {
+                tr_format("Spawn a cluster of {} bodies", &[&cluster.body_amount])
+            }
+            ScenarioAction::AddBody { body } => tr_format("Add body \"{}\"", &[&body.name]),
+            ScenarioAction::RemoveBody { body } => tr_format("Remove body \"{}\"", &[&body]),
+            ScenarioAction::ApplyImpulse { body, .. } => {
+                tr_format("Apply an impulse to body \"{}\"", &[&body])
+            }
+            ScenarioAction::SetGravitationalConstant { value } => {
+                tr_format("Set the gravitational constant to {}", &[&value])
+            }
+            ScenarioAction::SetCollisions { enabled } => {
+                if *enabled {
+                    tr("Enable collisions").to_string()
+                } else {
+                    tr("Disable collisions").to_string()
+                }
+            }
+            ScenarioAction::Pause => tr("Pause the simulation").to_string(),
+        }
+    }
+
+    // Perform an action on the universe, returning a message describing what happened.
+    fn perform(&self, universe: &mut Universe) -> Result<String, String> {
+        let units = universe.universe_settings.units;
+        match self {
+            // Generate the cluster with a portable random number generator so it's the same on every platform.
+            ScenarioAction::SpawnCluster { seed, cluster } => {
+                let generation_settings = GenerationSettings {
+                    seed: *seed,
+                    rng_kind: RngKind::ChaCha8,
+                    clusters: vec![cluster.clone()],
+                    ..Default::default()
+                };
+                let bodies = generation_settings
+                    .generate(
+                        &units,
+                        universe.universe_settings.gravitational_constant,
+                        &AtomicUsize::new(0),
+                        &AtomicBool::new(false),
+                    )
+                    .unwrap_or_default();
+                let amount = bodies.len();
+                universe.bodies.extend(bodies);
+                Ok(tr_format("Spawned {} bodies", &[&amount]))
+            }
+            ScenarioAction::AddBody { body } => {
+                universe.bodies.push(body.to_body(&units));
+                Ok(tr_format("Added body \"{}\"", &[&body.name]))
+            }
+            ScenarioAction::RemoveBody { body } => {
+                let index = find_body(universe, body)?;
+                universe.bodies.remove(index);
+                Ok(tr_format("Removed body \"{}\"", &[&body]))
+            }
+            // Change the velocity of the body directly, since an impulse is an instant change in momentum.
+            ScenarioAction::ApplyImpulse { body, delta_v } => {
+                let index = find_body(universe, body)?;
+                universe.bodies[index].velocity +=
+                    *delta_v * units.simulation_value(Quantity::Velocity, 1.0);
+                Ok(tr_format("Applied an impulse to body \"{}\"", &[&body]))
+            }
+            ScenarioAction::SetGravitationalConstant { value } => {
+                universe.universe_settings.gravitational_constant = *value;
+                Ok(tr_format("Set the gravitational constant to {}", &[&value]))
+            }
+            ScenarioAction::SetCollisions { enabled } => {
+                universe.universe_settings.enable_collisions = *enabled;
+                Ok(self.describe())
+            }
+            ScenarioAction::Pause => {
+                universe.paused = true;
+                Ok(tr("Paused the simulation").to_string())
+            }
+        }
+    }
+}
+
+// Return the index of the body with the given name.
+fn find_body(universe: &Universe, name: &str) -> Result<usize, String> {
+    universe
+        .bodies
+        .iter()
+        .position(|body| body.name == name)
+        .ok_or_else(|| tr_format("No body is named \"{}\"", &[&name]))
+}
+
+// An event of a scenario that performs an action once the simulation reaches its time, given in display units.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    pub time: f64,
+    #[serde(flatten)]
+    pub action: ScenarioAction,
+}
+
+// A scenario that sets up the universe and changes it at fixed simulation times, so a demonstration plays out the same way every time.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub name: String,
+    pub universe_settings: Option<UniverseSettings>,
+    pub bodies: Vec<ScenarioBody>,
+    pub events: Vec<ScenarioEvent>,
+}
+
+// Implementations for Scenario.
+impl Scenario {
+    // Load a scenario from a JSON file at the given path, sorting its events by time.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        // Read the file and deserialize the scenario.
+        let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let mut scenario: Scenario =
+            serde_json::from_str(&json).map_err(|error| error.to_string())?;
+
+        // Sort the events stably so events at the same time keep the order they were written in.
+        scenario
+            .events
+            .sort_by(|event1, event2| event1.time.total_cmp(&event2.time));
+        Ok(scenario)
+    }
+}
+
+// A timeline that plays the events of a loaded scenario as the simulation reaches their times.
+pub struct Timeline {
+    pub path: String,
+    pub scenario: Option<Scenario>,
+    pub universe_settings: UniverseSettings,
+    pub next_event: usize,
+    pub simulation_time: f64,
+    pub status: String,
+}
+
+// Default value for Timeline.
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            path: Default::default(),
+            scenario: None,
+            universe_settings: Default::default(),
+            next_event: 0,
+            simulation_time: 0.0,
+            status: tr("No scenario loaded").to_string(),
+        }
+    }
+}
+
+// Implementations for Timeline.
+impl Timeline {
+    // Load the scenario at the path and start it, returning whether it succeeded. Scenarios without settings keep the current ones, which restarting goes back to.
+    pub fn load(&mut self, universe: &mut Universe) -> bool {
+        match Scenario::load(Path::new(&self.path)) {
+            Ok(scenario) => {
+                self.universe_settings = scenario
+                    .universe_settings
+                    .clone()
+                    .unwrap_or_else(|| universe.universe_settings.clone());
+                self.scenario = Some(scenario);
+                self.restart(universe);
+                true
+            }
+            Err(error) => {
+                self.status = tr_format("Loading failed: {}", &[&error]);
+                false
+            }
+        }
+    }
+
+    // Start the loaded scenario over, replacing the settings and bodies of the universe with its own.
+    pub fn restart(&mut self, universe: &mut Universe) {
+        let Some(scenario) = &self.scenario else {
+            return;
+        };
+
+        // Apply the settings first so the bodies are converted with the units of the scenario, undoing any changes made by events.
+        universe.universe_settings = self.universe_settings.clone();
+        let units = universe.universe_settings.units;
+        universe.replace_bodies(
+            scenario
+                .bodies
+                .iter()
+                .map(|body| body.to_body(&units))
+                .collect(),
+        );
+
+        self.next_event = 0;
+        self.simulation_time = 0.0;
+        self.status = tr_format(
+            "Started {} with {} events",
+            &[&scenario.name, &scenario.events.len()],
+        );
+    }
+
+    // Update the timeline, performing every event whose time the simulation has reached.
+    pub fn update(&mut self, universe: &mut Universe) {
+        let Some(scenario) = &self.scenario else {
+            return;
+        };
+
+        // Go back to the first event that hasn't happened yet if the simulation time went backwards, such as when a snapshot was loaded.
+        if universe.simulation_time < self.simulation_time {
+            let time = universe
+                .universe_settings
+                .units
+                .display_value(Quantity::Time, universe.simulation_time);
+            self.next_event = scenario.events.partition_point(|event| event.time < time);
+        }
+        self.simulation_time = universe.simulation_time;
+
+        // Perform the events in order, since later ones may act on bodies that earlier ones added.
+        let time = universe
+            .universe_settings
+            .units
+            .display_value(Quantity::Time, universe.simulation_time);
+        while let Some(event) = scenario.events.get(self.next_event) {
+            if event.time > time {
+                break;
+            }
+            let units = universe.universe_settings.units;
+            let event_time = units.format(
+                Quantity::Time,
+                units.simulation_value(Quantity::Time, event.time),
+            );
+            let action = event.action.clone();
+            self.status = match universe.edit(move |universe| action.perform(universe)) {
+                Ok(message) => tr_format("t = {}: {}", &[&event_time, &message]),
+                Err(error) => tr_format("t = {}: Event failed: {}", &[&event_time, &error]),
+            };
+            self.next_event += 1;
+        }
+    }
+
+    // Show the timeline in the given UI and return whether a scenario replaced the bodies.
+    pub fn show(&mut self, ui: &mut Ui, universe: &mut Universe) -> bool {
+        // Create a text field to enter the path, and a button to pick it with a file dialog if they're available.
+        let label_id = ui.label(tr("Path")).id;
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.path).hint_text("scenarios/demo.json"))
+                .labelled_by(label_id);
+            if DIALOGS_AVAILABLE && ui.button(tr("Browse...")).clicked() {
+                if let Some(path) = open_file(tr("Open Scenario"), &[JSON_FILTER]) {
+                    self.path = path.display().to_string();
+                }
+            }
+        });
+        ui.end_row();
+
+        // Create buttons to load the scenario and to start the loaded one over.
+        let mut replaced = false;
+        ui.horizontal(|ui| {
+            if ui.button(tr("Load Scenario")).clicked() {
+                replaced = self.load(universe);
+            }
+            if ui
+                .add_enabled(self.scenario.is_some(), Button::new(tr("Restart")))
+                .clicked()
+            {
+                self.restart(universe);
+                replaced = true;
+            }
+        });
+        ui.label(&self.status);
+        ui.end_row();
+
+        // List the events of the scenario, dimming the ones that already happened.
+        let Some(scenario) = &self.scenario else {
+            return replaced;
+        };
+        let units = universe.universe_settings.units;
+        ScrollArea::vertical()
+            .id_source("Scenario Events")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for (index, event) in scenario.events.iter().enumerate() {
+                    let text = format!(
+                        "{}  {}",
+                        units.format(
+                            Quantity::Time,
+                            units.simulation_value(Quantity::Time, event.time)
+                        ),
+                        event.action.describe()
+                    );
+                    if index < self.next_event {
+                        ui.weak(text);
+                    } else {
+                        ui.label(text);
+                    }
+                }
+            });
+        ui.end_row();
+
+        replaced
+    }
+}
@@ -0,0 +1,133 @@
+use notan::math::DVec2;
+use std::collections::HashMap;
+
+// The fraction of indices that can change cells in an update before rebuilding the grid is cheaper.
+const REBUILD_FRACTION: f64 = 0.25;
+// How much wider than needed the cells of a cached grid are made, so small changes in the needed size don't force a rebuild.
+const CELL_SLACK: f64 = 1.25;
+
+// A uniform grid of square cells that sorts indices by position, so nearby indices can be found without checking all of them.
+#[derive(Clone)]
+pub struct SpatialGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    locations: HashMap<usize, (i64, i64)>,
+}
+
+// Implementations for SpatialGrid.
+impl SpatialGrid {
+    // Sort the given indices by their positions into cells of the given size.
+    pub fn new(cell_size: f64, items: impl Iterator<Item = (usize, DVec2)>) -> Self {
+        let mut spatial_grid = Self {
+            cell_size: cell_size.max(f64::MIN_POSITIVE),
+            cells: HashMap::new(),
+            locations: HashMap::new(),
+        };
+        for (index, position) in items {
+            let cell = spatial_grid.cell(position);
+            spatial_grid.cells.entry(cell).or_default().push(index);
+            spatial_grid.locations.insert(index, cell);
+        }
+        spatial_grid
+    }
+
+    // Move the given indices that were already in the grid to the cells of their new positions, returning how many changed cells.
+    pub fn update(&mut self, items: impl Iterator<Item = (usize, DVec2)>) -> usize {
+        let mut moved = 0;
+        for (index, position) in items {
+            let cell = self.cell(position);
+            let Some(location) = self.locations.get_mut(&index) else {
+                continue;
+            };
+            if *location == cell {
+                continue;
+            }
+
+            // Remove the index from its old cell, dropping the cell if it's empty, and add it to the new one.
+            if let Some(indices) = self.cells.get_mut(location) {
+                if let Some(position) = indices.iter().position(|&other| other == index) {
+                    indices.swap_remove(position);
+                }
+                if indices.is_empty() {
+                    self.cells.remove(location);
+                }
+            }
+            *location = cell;
+            self.cells.entry(cell).or_default().push(index);
+            moved += 1;
+        }
+        moved
+    }
+
+    // Return the cell that contains a position.
+    fn cell(&self, position: DVec2) -> (i64, i64) {
+        let cell = (position / self.cell_size).floor();
+        (cell.x as i64, cell.y as i64)
+    }
+
+    // Return the indices in every cell that overlaps the square around a position with the given half width.
+    pub fn query(&self, position: DVec2, radius: f64) -> impl Iterator<Item = usize> + '_ {
+        let (minimum, maximum) = (
+            self.cell(position - DVec2::splat(radius)),
+            self.cell(position + DVec2::splat(radius)),
+        );
+        (minimum.0..=maximum.0)
+            .flat_map(move |x| (minimum.1..=maximum.1).map(move |y| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    // Return the indices in the cell that contains a position and the cells next to it.
+    pub fn neighbours(&self, position: DVec2) -> impl Iterator<Item = usize> + '_ {
+        self.query(position, self.cell_size)
+    }
+}
+
+// A spatial grid kept between steps that is updated incrementally while the indices and needed cell size stay the same, and rebuilt otherwise.
+#[derive(Clone, Default)]
+pub struct SpatialCache {
+    spatial_grid: Option<SpatialGrid>,
+    indices: Vec<usize>,
+}
+
+// Implementations for SpatialCache.
+impl SpatialCache {
+    // Return the grid for the given indices and positions with cells at least the given size, updating the cached grid if it can be reused, and whether it was rebuilt and how many indices changed cells.
+    pub fn grid(
+        &mut self,
+        cell_size: f64,
+        indices: &[usize],
+        position: impl Fn(usize) -> DVec2,
+    ) -> (&SpatialGrid, bool, usize) {
+        // Try updating the cached grid if it holds the same indices and its cells are wide enough but not too wide.
+        let mut moved = 0;
+        let mut rebuild = true;
+        if let Some(spatial_grid) = &mut self.spatial_grid {
+            if self.indices == indices
+                && spatial_grid.cell_size >= cell_size
+                && spatial_grid.cell_size <= cell_size * CELL_SLACK * CELL_SLACK
+            {
+                moved = spatial_grid.update(indices.iter().map(|&index| (index, position(index))));
+                rebuild = moved as f64 > indices.len() as f64 * REBUILD_FRACTION;
+            }
+        }
+
+        // Otherwise rebuild the grid from scratch.
+        if rebuild {
+            self.spatial_grid = Some(SpatialGrid::new(
+                cell_size * CELL_SLACK,
+                indices.iter().map(|&index| (index, position(index))),
+            ));
+            self.indices = indices.to_vec();
+        }
+        (self.spatial_grid.as_ref().unwrap(), rebuild, moved)
+    }
+}
+
+// A cache doesn't change what a universe is, so all caches are equal.
+impl PartialEq for SpatialCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
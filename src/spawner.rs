@@ -0,0 +1,247 @@
+use crate::body::*;
+use crate::camera::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{
+    draw::*,
+    math::{DVec2, Vec2},
+    prelude::*,
+};
+use notan_egui::*;
+
+// The amount of line segments the previewed orbit is drawn with.
+const ORBIT_SEGMENTS: usize = 96;
+
+// A body about to be spawned, with the body it orbits if it's snapped to an orbit.
+#[derive(Clone, Copy)]
+pub struct SpawnPreview {
+    pub position: DVec2,
+    pub velocity: DVec2,
+    pub primary: Option<usize>,
+}
+
+// A tool that spawns bodies where the left mouse button is pressed, either with the velocity dragged out before it's released or snapped to an orbit around the body with the strongest pull there.
+pub struct Spawner {
+    pub enabled: bool,
+    pub mass: f64,
+    pub snap_to_orbit: bool,
+    pub eccentricity: f64,
+    pub clockwise: bool,
+    pub drag_sensitivity: f64,
+    pub start: Option<DVec2>,
+    pub preview: Option<SpawnPreview>,
+}
+
+// Default value for Spawner.
+impl Default for Spawner {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mass: 1.0,
+            snap_to_orbit: true,
+            eccentricity: 0.0,
+            clockwise: false,
+            drag_sensitivity: 1.0,
+            start: None,
+            preview: None,
+        }
+    }
+}
+
+// Implementations for Spawner.
+impl Spawner {
+    // Return the velocity of a body of the given mass at a position for an orbit around the body with the strongest pull there, starting at periapsis so the eccentricity only changes its speed, along with the index of that body.
+    fn orbit_velocity(
+        &self,
+        universe: &Universe,
+        position: DVec2,
+        mass: f64,
+    ) -> Option<(DVec2, usize)> {
+        let primary = universe.dominant_body_at(position, None)?;
+        let body = &universe.bodies[primary];
+        let offset = position - body.position;
+
+        // Use the vis-viva equation at periapsis, where the velocity is perpendicular to the offset.
+        let gravitational_parameter =
+            universe.universe_settings.gravitational_constant * (body.mass + mass);
+        let speed = (gravitational_parameter * (1.0 + self.eccentricity) / offset.length()).sqrt();
+        let direction = if self.clockwise {
+            -offset.normalize().perp()
+        } else {
+            offset.normalize().perp()
+        };
+        Some((body.velocity + direction * speed, primary))
+    }
+
+    // Return the body that would be spawned at a position, with the velocity dragged out to the given position if it isn't snapped to an orbit.
+    fn preview(&self, universe: &Universe, position: DVec2, dragged_to: DVec2) -> SpawnPreview {
+        let mass = universe
+            .universe_settings
+            .units
+            .simulation_value(Quantity::Mass, self.mass);
+        match self
+            .snap_to_orbit
+            .then(|| self.orbit_velocity(universe, position, mass))
+            .flatten()
+        {
+            Some((velocity, primary)) => SpawnPreview {
+                position,
+                velocity,
+                primary: Some(primary),
+            },
+            None => SpawnPreview {
+                position,
+                velocity: (dragged_to - position) * self.drag_sensitivity,
+                primary: None,
+            },
+        }
+    }
+
+    // Update the spawner, spawning a body when the left mouse button is released after being pressed outside of the UI.
+    pub fn update(
+        &mut self,
+        app: &mut App,
+        camera: &Camera,
+        universe: &mut Universe,
+        pointer_over_ui: bool,
+    ) {
+        self.preview = None;
+        if !self.enabled {
+            self.start = None;
+            return;
+        }
+
+        // Start placing a body where the left mouse button is pressed.
+        let position = camera.screen_to_world(app, Vec2::from(app.mouse.position()));
+        if app.mouse.left_was_pressed() && !pointer_over_ui {
+            self.start = Some(position);
+        }
+
+        // Preview the body being placed, or the one that would be placed under the mouse.
+        let preview = match self.start {
+            Some(start) => self.preview(universe, start, position),
+            None if !pointer_over_ui => self.preview(universe, position, position),
+            None => return,
+        };
+        self.preview = Some(preview);
+
+        // Spawn the body once the left mouse button is released.
+        if self.start.is_some() && !app.mouse.left_is_down() {
+            self.start = None;
+            let body = Body {
+                position: preview.position,
+                velocity: preview.velocity,
+                mass: universe
+                    .universe_settings
+                    .units
+                    .simulation_value(Quantity::Mass, self.mass),
+                ..Default::default()
+            };
+            universe.edit(move |universe| universe.bodies.push(body.clone()));
+        }
+    }
+
+    // Draw the body about to be spawned with its velocity, and the orbit it would follow if it's snapped to one.
+    pub fn draw(&self, draw: &mut Draw, camera: &Camera, universe: &Universe) {
+        let Some(preview) = self.preview else {
+            return;
+        };
+        let color = Color::from_rgba(0.3, 1.0, 0.5, 0.8);
+        let mass = universe
+            .universe_settings
+            .units
+            .simulation_value(Quantity::Mass, self.mass);
+
+        // Draw an outline of the body and a line along its velocity, as far as it moves in one time unit.
+        draw.circle(mass.cbrt().max((4.0 / camera.scale) as f64) as f32)
+            .position(preview.position.x as f32, preview.position.y as f32)
+            .tolerance(camera.circle_tolerance())
+            .stroke(1.0 / camera.scale)
+            .color(color);
+        let end = preview.position + preview.velocity;
+        draw.line(
+            (preview.position.x as f32, preview.position.y as f32),
+            (end.x as f32, end.y as f32),
+        )
+        .width(1.0 / camera.scale)
+        .color(color);
+
+        // Draw the ellipse of the orbit around the primary, with the spawn position at periapsis.
+        let Some(primary) = preview.primary.and_then(|index| universe.bodies.get(index)) else {
+            return;
+        };
+        let offset = preview.position - primary.position;
+        let semi_major_axis = offset.length() / (1.0 - self.eccentricity);
+        let semi_minor_axis = semi_major_axis * (1.0 - self.eccentricity.powi(2)).sqrt();
+        let (periapsis, normal) = (offset.normalize(), offset.normalize().perp());
+        let points: Vec<DVec2> = (0..=ORBIT_SEGMENTS)
+            .map(|segment| {
+                let angle = std::f64::consts::TAU * segment as f64 / ORBIT_SEGMENTS as f64;
+                primary.position
+                    + periapsis * semi_major_axis * (angle.cos() - self.eccentricity)
+                    + normal * semi_minor_axis * angle.sin()
+            })
+            .collect();
+        for (position1, position2) in points.iter().zip(points.iter().skip(1)) {
+            draw.line(
+                (position1.x as f32, position1.y as f32),
+                (position2.x as f32, position2.y as f32),
+            )
+            .width(1.0 / camera.scale)
+            .color(Color::from_rgba(0.3, 1.0, 0.5, 0.4));
+        }
+    }
+
+    // Show the settings of the spawner in the given UI.
+    pub fn show(&mut self, ui: &mut Ui, units: &Units) {
+        // Create a checkbox to toggle the spawner, which replaces selecting bodies with the left mouse button.
+        let label_id = ui.label(tr("Enabled")).id;
+        ui.add(Checkbox::new(&mut self.enabled, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+
+        // Create a drag value to modify the mass of the spawned bodies.
+        let label_id = ui.label(tr("Mass")).id;
+        ui.add(
+            DragValue::new(&mut self.mass)
+                .speed(0.1)
+                .suffix(format!(" {}", units.symbol(Quantity::Mass)))
+                .clamp_range(f64::EPSILON..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+
+        // Create widgets to snap the velocity to an orbit with an eccentricity and direction, or to modify how fast a drag makes the body.
+        let label_id = ui.label(tr("Snap to Orbit")).id;
+        ui.add(Checkbox::new(&mut self.snap_to_orbit, ""))
+            .labelled_by(label_id);
+        ui.end_row();
+        if self.snap_to_orbit {
+            let label_id = ui.label(tr("Eccentricity")).id;
+            ui.add(Slider::new(&mut self.eccentricity, 0.0..=0.95))
+                .labelled_by(label_id);
+            ui.end_row();
+            let label_id = ui.label(tr("Clockwise")).id;
+            ui.add(Checkbox::new(&mut self.clockwise, ""))
+                .labelled_by(label_id);
+            ui.end_row();
+            ui.label(tr(
+                "Click to spawn a body in orbit around the body with the strongest pull there, starting at periapsis.",
+            ));
+        } else {
+            let label_id = ui.label(tr("Drag Sensitivity")).id;
+            ui.add(
+                DragValue::new(&mut self.drag_sensitivity)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::MAX),
+            )
+            .labelled_by(label_id);
+            ui.end_row();
+            ui.label(tr(
+                "Press to place a body and drag to give it a velocity before releasing.",
+            ));
+        }
+        ui.end_row();
+    }
+}
@@ -0,0 +1,63 @@
+use crate::camera::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::{math::Vec2, prelude::*};
+use notan_egui::*;
+
+// The distance in points between the status bar and the bottom of the screen.
+const STATUS_BAR_MARGIN: f32 = 10.0;
+
+// A status bar at the bottom of the screen that shows the position of the cursor in the world and its distance from the center of mass, so bodies can be placed precisely.
+pub struct StatusBar {
+    pub enabled: bool,
+}
+
+// Default value for StatusBar.
+impl Default for StatusBar {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+// Implementations for StatusBar.
+impl StatusBar {
+    // Show the status bar in the given context.
+    pub fn show(&self, context: &Context, app: &mut App, camera: &Camera, universe: &Universe) {
+        if !self.enabled {
+            return;
+        }
+
+        // Find the position of the cursor in the world with the inverse transform of the camera, and its distance from the center of mass.
+        let position = camera.screen_to_world(app, Vec2::from(app.mouse.position()));
+        let units = &universe.universe_settings.units;
+        let text = if universe.bodies.is_empty() {
+            tr_format(
+                "Cursor: ({}, {})",
+                &[
+                    &units.format(Quantity::Length, position.x),
+                    &units.format(Quantity::Length, position.y),
+                ],
+            )
+        } else {
+            let (center_of_mass, _, _) = universe.center_of_mass();
+            tr_format(
+                "Cursor: ({}, {})  Distance From Center of Mass: {}",
+                &[
+                    &units.format(Quantity::Length, position.x),
+                    &units.format(Quantity::Length, position.y),
+                    &units.format(Quantity::Length, position.distance(center_of_mass)),
+                ],
+            )
+        };
+
+        Area::new("Status Bar")
+            .anchor(Align2::CENTER_BOTTOM, vec2(0.0, -STATUS_BAR_MARGIN))
+            .interactable(false)
+            .show(context, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(text);
+                });
+            });
+    }
+}
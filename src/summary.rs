@@ -0,0 +1,104 @@
+use crate::{locale::*, units::*, universe::*};
+use notan_egui::*;
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// The directory that run summaries are exported to.
+const SUMMARY_DIRECTORY: &str = "exports";
+
+// A summary of a run of the universe since its bodies were last replaced.
+#[derive(Clone, Serialize)]
+pub struct RunSummary {
+    pub body_amount: usize,
+    pub steps: usize,
+    pub simulation_time: f64,
+    pub mergers: usize,
+    pub ejections: usize,
+    pub energy_drift_percentage: Option<f64>,
+    pub average_steps_per_second: f64,
+}
+
+// Implementations for RunSummary.
+impl RunSummary {
+    // Summarize the run of a universe.
+    pub fn new(universe: &Universe) -> Self {
+        let run_statistics = &universe.run_statistics;
+
+        // Compare the current energy with the energy before the first step, if it's not zero.
+        let energy_drift_percentage = run_statistics
+            .initial_energy
+            .filter(|initial_energy| *initial_energy != 0.0)
+            .map(|initial_energy| {
+                (universe.total_energy() - initial_energy) / initial_energy.abs() * 100.0
+            });
+
+        Self {
+            body_amount: universe.bodies.len(),
+            steps: run_statistics.steps,
+            simulation_time: universe.simulation_time,
+            mergers: run_statistics.mergers,
+            ejections: universe.ejected_bodies,
+            energy_drift_percentage,
+            average_steps_per_second: if run_statistics.wall_time > 0.0 {
+                run_statistics.steps as f64 / run_statistics.wall_time
+            } else {
+                0.0
+            },
+        }
+    }
+
+    // Return the name of a new summary file, named after the time since unix epoch.
+    pub fn file_name() -> String {
+        format!(
+            "summary_{}.json",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+        )
+    }
+
+    // Export a run summary to a new JSON file in the summary directory and return its path.
+    pub fn export(&self) -> Result<PathBuf, String> {
+        fs::create_dir_all(SUMMARY_DIRECTORY).map_err(|error| error.to_string())?;
+        let path = Path::new(SUMMARY_DIRECTORY).join(Self::file_name());
+        self.save(&path)?;
+        Ok(path)
+    }
+
+    // Save a run summary to a JSON file at the given path.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        fs::write(path, json).map_err(|error| error.to_string())
+    }
+
+    // Show a run summary in the given UI, with values in the given units.
+    pub fn show(&self, ui: &mut Ui, units: &Units) {
+        ui.label(format!("{} {}", self.body_amount, tr("bodies")));
+        ui.label(format!("{} {}", self.steps, tr("steps")));
+        ui.label(format!(
+            "{} {}",
+            units.format(Quantity::Time, self.simulation_time),
+            tr("simulated")
+        ));
+        ui.label(format!("{} {}", self.mergers, tr("mergers")));
+        ui.label(format!("{} {}", self.ejections, tr("ejections")));
+        ui.label(match self.energy_drift_percentage {
+            Some(energy_drift_percentage) => tr_format(
+                "{}% energy drift",
+                &[&format!("{:+.4}", energy_drift_percentage)],
+            ),
+            None => tr("Energy drift unavailable").to_string(),
+        });
+        ui.label(format!(
+            "{:.1} {}",
+            self.average_steps_per_second,
+            tr("steps per second on average")
+        ));
+        ui.end_row();
+    }
+}
@@ -0,0 +1,390 @@
+use crate::generation::*;
+use crate::locale::*;
+use crate::profiler::*;
+use crate::summary::*;
+use crate::universe::*;
+use notan_egui::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+
+// The file that the sweep settings are stored in, which a headless sweep reads by default.
+pub const SWEEP_PATH: &str = "config/sweep.json";
+// The directory that sweep results are written to.
+const SWEEP_DIRECTORY: &str = "exports";
+
+// A range of values that a parameter is swept over, spaced evenly or logarithmically.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SweepRange {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+    pub logarithmic: bool,
+}
+
+// Default value for SweepRange.
+impl Default for SweepRange {
+    fn default() -> Self {
+        Self {
+            start: 1.0,
+            end: 1.0,
+            count: 1,
+            logarithmic: false,
+        }
+    }
+}
+
+// Implementations for SweepRange.
+impl SweepRange {
+    // Return the values of the range, from the start to the end inclusive, or only the start if there's a single value.
+    pub fn values(&self) -> Vec<f64> {
+        let count = self.count.max(1);
+        if count == 1 {
+            return vec![self.start];
+        }
+        (0..count)
+            .map(|index| {
+                let fraction = index as f64 / (count - 1) as f64;
+                if self.logarithmic && self.start > 0.0 && self.end > 0.0 {
+                    self.start * (self.end / self.start).powf(fraction)
+                } else {
+                    self.start + (self.end - self.start) * fraction
+                }
+            })
+            .collect()
+    }
+
+    // Show widgets to modify the range in the given UI under the given label.
+    fn show(&mut self, ui: &mut Ui, label: &str, speed: f64) {
+        let label_id = ui.label(tr(label)).id;
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut self.start).speed(speed))
+                .labelled_by(label_id);
+            ui.label("–");
+            ui.add(DragValue::new(&mut self.end).speed(speed))
+                .labelled_by(label_id);
+            ui.add(
+                DragValue::new(&mut self.count)
+                    .clamp_range(1..=1000)
+                    .suffix(format!(" {}", tr("values"))),
+            )
+            .labelled_by(label_id);
+            ui.checkbox(&mut self.logarithmic, tr("Logarithmic"));
+        });
+        ui.end_row();
+    }
+}
+
+// Settings for a sweep that runs the same generated universe with each combination of gravitational constants and step sizes for a number of steps.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SweepSettings {
+    pub generation_settings: GenerationSettings,
+    pub universe_settings: UniverseSettings,
+    pub gravitational_constant: SweepRange,
+    pub delta_time: SweepRange,
+    pub steps: usize,
+}
+
+// Default value for SweepSettings.
+impl Default for SweepSettings {
+    fn default() -> Self {
+        let universe_settings = UniverseSettings::default();
+        Self {
+            generation_settings: Default::default(),
+            gravitational_constant: SweepRange {
+                start: universe_settings.gravitational_constant * 0.5,
+                end: universe_settings.gravitational_constant * 2.0,
+                count: 3,
+                logarithmic: true,
+            },
+            universe_settings,
+            delta_time: SweepRange {
+                start: 1.0 / 240.0,
+                end: 1.0 / 30.0,
+                count: 4,
+                logarithmic: true,
+            },
+            steps: 1000,
+        }
+    }
+}
+
+// Implementations for SweepSettings.
+impl SweepSettings {
+    // Return the amount of runs in the sweep.
+    pub fn run_amount(&self) -> usize {
+        self.gravitational_constant.values().len() * self.delta_time.values().len()
+    }
+
+    // Run the sweep, counting each step in the given progress and stopping if cancelled, and return the summary of each run as CSV, or None if cancelled.
+    pub fn run(&self, progress: &AtomicUsize, cancelled: &AtomicBool) -> Option<String> {
+        // Generate the bodies once, since the seed is the same for every run.
+        let units = self.universe_settings.units;
+        let bodies = self.generation_settings.generate(
+            &units,
+            self.universe_settings.gravitational_constant,
+            &AtomicUsize::new(0),
+            cancelled,
+        )?;
+
+        // Run each combination of parameters from the same bodies and write a row of its summary.
+        let mut csv = String::from(
+            "gravitational_constant,delta_time,body_amount,steps,simulation_time,mergers,ejections,energy_drift_percentage,average_steps_per_second\n",
+        );
+        let mut profiler = Profiler::default();
+        for gravitational_constant in self.gravitational_constant.values() {
+            for delta_time in self.delta_time.values() {
+                let mut universe = Universe {
+                    universe_settings: UniverseSettings {
+                        gravitational_constant,
+                        ..self.universe_settings.clone()
+                    },
+                    ..Default::default()
+                };
+                universe.replace_bodies(bodies.clone());
+                let start = Instant::now();
+                for _ in 0..self.steps {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    universe.update(delta_time, &mut profiler);
+                    progress.fetch_add(1, Ordering::Relaxed);
+                }
+                universe.run_statistics.wall_time = start.elapsed().as_secs_f64();
+
+                let summary = RunSummary::new(&universe);
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{},{},{}\n",
+                    gravitational_constant,
+                    delta_time,
+                    summary.body_amount,
+                    summary.steps,
+                    summary.simulation_time,
+                    summary.mergers,
+                    summary.ejections,
+                    summary
+                        .energy_drift_percentage
+                        .map_or(String::new(), |drift| drift.to_string()),
+                    summary.average_steps_per_second,
+                ));
+            }
+        }
+        Some(csv)
+    }
+
+    // Load sweep settings from a JSON file at the given path.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+
+    // Save the sweep settings to a JSON file at the given path.
+    fn save(&self, path: &Path) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the settings and write them to the file.
+        let json = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        fs::write(path, json).map_err(|error| error.to_string())
+    }
+}
+
+// Write the results of a sweep to a new CSV file in the sweep directory, named after the time since unix epoch, and return its path.
+fn write_results(csv: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(SWEEP_DIRECTORY).map_err(|error| error.to_string())?;
+    let path = Path::new(SWEEP_DIRECTORY).join(format!(
+        "sweep_{}.csv",
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    ));
+    fs::write(&path, csv).map_err(|error| error.to_string())?;
+    Ok(path)
+}
+
+// Run the sweep in the settings file at the given path without a window, printing the progress after each run, and return the path of the results.
+pub fn run_headless_sweep(path: &Path) -> Result<PathBuf, String> {
+    let sweep_settings = SweepSettings::load(path)?;
+    let total_steps = sweep_settings.run_amount() * sweep_settings.steps;
+    println!(
+        "{}",
+        tr_format(
+            "Running {} runs of {} steps",
+            &[&sweep_settings.run_amount(), &sweep_settings.steps]
+        )
+    );
+
+    // Run the sweep on a thread so the progress can be printed while it runs.
+    let progress = Arc::new(AtomicUsize::new(0));
+    let handle = {
+        let progress = progress.clone();
+        thread::spawn(move || sweep_settings.run(&progress, &AtomicBool::new(false)))
+    };
+    while !handle.is_finished() {
+        thread::sleep(Duration::from_secs(1));
+        println!(
+            "{}",
+            tr_format(
+                "{} of {} steps",
+                &[&progress.load(Ordering::Relaxed), &total_steps]
+            )
+        );
+    }
+    let csv = handle
+        .join()
+        .ok()
+        .flatten()
+        .ok_or_else(|| tr("The sweep failed").to_string())?;
+    write_results(&csv)
+}
+
+// A sweep running on a background thread.
+pub struct SweepTask {
+    progress: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    total_steps: usize,
+    handle: Option<JoinHandle<Option<String>>>,
+}
+
+// Implementations for SweepTask.
+impl SweepTask {
+    // Start running a sweep with the given settings on a background thread.
+    pub fn start(sweep_settings: &SweepSettings) -> Self {
+        let progress = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        // Move copies of the settings and counters into the thread.
+        let handle = {
+            let sweep_settings = sweep_settings.clone();
+            let progress = progress.clone();
+            let cancelled = cancelled.clone();
+            thread::spawn(move || sweep_settings.run(&progress, &cancelled))
+        };
+
+        Self {
+            progress,
+            cancelled,
+            total_steps: sweep_settings.run_amount() * sweep_settings.steps,
+            handle: Some(handle),
+        }
+    }
+
+    // Return the fraction of steps that have been run.
+    pub fn progress(&self) -> f32 {
+        if self.total_steps == 0 {
+            1.0
+        } else {
+            self.progress.load(Ordering::Relaxed) as f32 / self.total_steps as f32
+        }
+    }
+
+    // Cancel the sweep.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // Return the results as CSV if the sweep has finished, which are None if it was cancelled.
+    pub fn poll(&mut self) -> Option<Option<String>> {
+        // Only join the thread once it has finished so the app doesn't block.
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        Some(self.handle.take()?.join().ok().flatten())
+    }
+}
+
+// A sweep runner that runs sweeps of the current universe in the background, or saves them for a headless run.
+pub struct Sweep {
+    pub sweep_settings: SweepSettings,
+    pub task: Option<SweepTask>,
+    pub status: String,
+}
+
+// Default value for Sweep.
+impl Default for Sweep {
+    fn default() -> Self {
+        Self {
+            sweep_settings: SweepSettings::load(Path::new(SWEEP_PATH)).unwrap_or_default(),
+            task: None,
+            status: String::new(),
+        }
+    }
+}
+
+// Implementations for Sweep.
+impl Sweep {
+    // Write the results of the sweep once it has finished.
+    pub fn update(&mut self) {
+        let Some(result) = self.task.as_mut().and_then(|task| task.poll()) else {
+            return;
+        };
+        self.task = None;
+        self.status = match result.map(|csv| write_results(&csv)) {
+            Some(Ok(path)) => tr_format("Wrote {}", &[&path.display()]),
+            Some(Err(error)) => tr_format("Writing the results failed: {}", &[&error]),
+            None => tr("Sweep cancelled").to_string(),
+        };
+    }
+
+    // Show the sweep runner in the given UI, sweeping the current universe and generation settings.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        universe: &Universe,
+        generation_settings: &GenerationSettings,
+    ) {
+        // Create widgets to modify the ranges and the amount of steps of each run.
+        self.sweep_settings
+            .gravitational_constant
+            .show(ui, "Gravitational Constant", 0.1);
+        self.sweep_settings.delta_time.show(ui, "Step Size", 0.0001);
+        let label_id = ui.label(tr("Steps per Run")).id;
+        ui.add(DragValue::new(&mut self.sweep_settings.steps).clamp_range(1..=usize::MAX))
+            .labelled_by(label_id);
+        ui.end_row();
+        ui.label(tr_format("{} runs", &[&self.sweep_settings.run_amount()]));
+        ui.end_row();
+
+        // Show the progress of a running sweep with a button to cancel it, or buttons to start one and to save it for a headless run.
+        self.sweep_settings.generation_settings = generation_settings.clone();
+        self.sweep_settings.universe_settings = universe.universe_settings.clone();
+        if let Some(task) = &self.task {
+            ui.add(ProgressBar::new(task.progress()).show_percentage());
+            if ui.button(tr("Cancel")).clicked() {
+                task.cancel();
+            }
+        } else {
+            ui.horizontal(|ui| {
+                if ui.button(tr("Run Sweep")).clicked() {
+                    self.task = Some(SweepTask::start(&self.sweep_settings));
+                    self.status = tr("Sweep running").to_string();
+                }
+                if ui.button(tr("Save for Headless Run")).clicked() {
+                    self.status = match self.sweep_settings.save(Path::new(SWEEP_PATH)) {
+                        Ok(()) => tr_format(
+                            "Saved {}, run it with --sweep {}",
+                            &[&SWEEP_PATH, &SWEEP_PATH],
+                        ),
+                        Err(error) => tr_format("Saving the sweep failed: {}", &[&error]),
+                    };
+                }
+            });
+        }
+        ui.end_row();
+        ui.label(&self.status);
+        ui.end_row();
+    }
+}
@@ -0,0 +1,65 @@
+use notan_egui::*;
+
+// A theme that the UI is drawn with.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum UiTheme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+// Implementations for UiTheme.
+impl UiTheme {
+    // All themes, in the order they are listed in the UI.
+    pub const ALL: [UiTheme; 3] = [UiTheme::Dark, UiTheme::Light, UiTheme::HighContrast];
+
+    // Return the name of a theme.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UiTheme::Dark => "Dark",
+            UiTheme::Light => "Light",
+            UiTheme::HighContrast => "High Contrast",
+        }
+    }
+
+    // Return the visuals of a theme, where the high contrast theme draws white text and outlines on black with yellow highlights.
+    pub fn visuals(&self) -> Visuals {
+        match self {
+            UiTheme::Dark => Visuals::dark(),
+            UiTheme::Light => Visuals::light(),
+            UiTheme::HighContrast => {
+                let mut visuals = Visuals::dark();
+                let highlight = Color32::from_rgb(255, 214, 0);
+                visuals.override_text_color = Some(Color32::WHITE);
+                visuals.panel_fill = Color32::BLACK;
+                visuals.window_fill = Color32::BLACK;
+                visuals.extreme_bg_color = Color32::BLACK;
+                visuals.faint_bg_color = Color32::from_gray(24);
+                visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+                visuals.hyperlink_color = highlight;
+                visuals.selection.bg_fill = highlight;
+                visuals.selection.stroke = Stroke::new(2.0, Color32::BLACK);
+
+                // Outline every widget in white, and in yellow while it's hovered or pressed.
+                for widget in [
+                    &mut visuals.widgets.noninteractive,
+                    &mut visuals.widgets.inactive,
+                    &mut visuals.widgets.open,
+                ] {
+                    widget.bg_fill = Color32::BLACK;
+                    widget.weak_bg_fill = Color32::BLACK;
+                    widget.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+                    widget.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+                }
+                for widget in [&mut visuals.widgets.hovered, &mut visuals.widgets.active] {
+                    widget.bg_fill = Color32::from_gray(32);
+                    widget.weak_bg_fill = Color32::from_gray(32);
+                    widget.bg_stroke = Stroke::new(2.0, highlight);
+                    widget.fg_stroke = Stroke::new(2.0, highlight);
+                }
+                visuals
+            }
+        }
+    }
+}
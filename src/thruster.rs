@@ -0,0 +1,91 @@
+use notan::math::DVec2;
+use serde::{Deserialize, Serialize};
+
+// A direction that a thruster pushes its body in.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThrustDirection {
+    Fixed,
+    Prograde,
+    Retrograde,
+    RadialOut,
+    RadialIn,
+}
+
+// Implementations for ThrustDirection.
+impl ThrustDirection {
+    // All thrust directions, in the order they are listed in the UI.
+    pub const ALL: [ThrustDirection; 5] = [
+        ThrustDirection::Fixed,
+        ThrustDirection::Prograde,
+        ThrustDirection::Retrograde,
+        ThrustDirection::RadialOut,
+        ThrustDirection::RadialIn,
+    ];
+
+    // Return the name of a thrust direction.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThrustDirection::Fixed => "Fixed Angle",
+            ThrustDirection::Prograde => "Prograde",
+            ThrustDirection::Retrograde => "Retrograde",
+            ThrustDirection::RadialOut => "Radial Out",
+            ThrustDirection::RadialIn => "Radial In",
+        }
+    }
+}
+
+// A burn that fires a thruster for a duration from a start time in simulation time.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Burn {
+    pub start: f64,
+    pub duration: f64,
+}
+
+// A thruster that pushes a body with a constant force while it has fuel, burning fuel from the mass of the body.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thruster {
+    pub direction: ThrustDirection,
+    pub angle: f64,
+    pub force: f64,
+    pub exhaust_velocity: f64,
+    pub fuel: f64,
+    pub burns: Vec<Burn>,
+}
+
+// Default value for Thruster.
+impl Default for Thruster {
+    fn default() -> Self {
+        Self {
+            direction: ThrustDirection::Prograde,
+            angle: 0.0,
+            force: 10.0,
+            exhaust_velocity: 100.0,
+            fuel: 0.0,
+            burns: Vec::new(),
+        }
+    }
+}
+
+// Implementations for Thruster.
+impl Thruster {
+    // Return whether a thruster fires at the given simulation time, which is always if it has no burns scheduled.
+    pub fn is_firing(&self, simulation_time: f64) -> bool {
+        self.fuel > 0.0
+            && (self.burns.is_empty()
+                || self.burns.iter().any(|burn| {
+                    (burn.start..burn.start + burn.duration).contains(&simulation_time)
+                }))
+    }
+
+    // Return the unit direction a thruster pushes in, given the position and velocity of its body relative to the body it orbits.
+    pub fn thrust_direction(&self, offset: DVec2, relative_velocity: DVec2) -> DVec2 {
+        match self.direction {
+            ThrustDirection::Fixed => DVec2::from_angle(self.angle.to_radians()),
+            ThrustDirection::Prograde => relative_velocity.normalize_or_zero(),
+            ThrustDirection::Retrograde => -relative_velocity.normalize_or_zero(),
+            ThrustDirection::RadialOut => offset.normalize_or_zero(),
+            ThrustDirection::RadialIn => -offset.normalize_or_zero(),
+        }
+    }
+}
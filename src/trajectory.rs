@@ -0,0 +1,161 @@
+use crate::body::*;
+use crate::locale::*;
+use crate::units::*;
+use crate::universe::*;
+use notan::math::DVec2;
+use notan_egui::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// The directory that trajectories are exported to.
+const TRAJECTORY_DIRECTORY: &str = "exports";
+// The most samples kept before recording stops on its own, so a forgotten recording can't use up the memory.
+const SAMPLE_LIMIT: usize = 5_000_000;
+
+// The state of a tracked body at a moment of the simulation.
+#[derive(Clone, Copy)]
+pub struct TrajectorySample {
+    pub id: BodyId,
+    pub time: f64,
+    pub position: DVec2,
+    pub velocity: DVec2,
+}
+
+// A recorder that keeps the time series of the position and velocity of chosen bodies while it's recording, and exports them to a CSV file when stopped, so orbits can be analyzed without saving the whole universe every step.
+#[derive(Default)]
+pub struct TrajectoryRecorder {
+    pub tracked: Vec<BodyId>,
+    pub recording: bool,
+    pub samples: Vec<TrajectorySample>,
+    pub simulation_time: Option<f64>,
+    pub status: String,
+}
+
+// Implementations for TrajectoryRecorder.
+impl TrajectoryRecorder {
+    // Record a sample of each tracked body that still exists whenever the simulation time has advanced since the last sample.
+    pub fn update(&mut self, universe: &Universe) {
+        if !self.recording || self.simulation_time == Some(universe.simulation_time) {
+            return;
+        }
+        self.simulation_time = Some(universe.simulation_time);
+        for &id in self.tracked.iter() {
+            if let Some(index) = universe.index_of(id) {
+                let body = &universe.bodies[index];
+                self.samples.push(TrajectorySample {
+                    id,
+                    time: universe.simulation_time,
+                    position: body.position,
+                    velocity: body.velocity,
+                });
+            }
+        }
+
+        // Stop and export once there are too many samples.
+        if self.samples.len() >= SAMPLE_LIMIT {
+            self.stop(&universe.universe_settings.units);
+        }
+    }
+
+    // Start recording, discarding the samples of the last recording.
+    fn start(&mut self) {
+        self.recording = true;
+        self.samples.clear();
+        self.simulation_time = None;
+        self.status = tr("Recording").to_string();
+    }
+
+    // Stop recording and export the samples, storing the result as the status.
+    fn stop(&mut self, units: &Units) {
+        self.recording = false;
+        self.status = match self.export(units) {
+            Ok(path) => tr_format(
+                "Exported {} samples to {}",
+                &[&self.samples.len(), &path.display()],
+            ),
+            Err(error) => tr_format("Export failed: {}", &[&error]),
+        };
+    }
+
+    // Write the samples to a new CSV file in the trajectory directory, named after the time since unix epoch, with values in the given display units, and return its path.
+    fn export(&self, units: &Units) -> Result<PathBuf, String> {
+        // Write the header followed by one row for each sample.
+        let mut csv = String::from("id,t,x,y,vx,vy\n");
+        for sample in self.samples.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                sample.id,
+                units.display_value(Quantity::Time, sample.time),
+                units.display_value(Quantity::Length, sample.position.x),
+                units.display_value(Quantity::Length, sample.position.y),
+                units.display_value(Quantity::Velocity, sample.velocity.x),
+                units.display_value(Quantity::Velocity, sample.velocity.y),
+            ));
+        }
+
+        // Write the rows to the file.
+        fs::create_dir_all(TRAJECTORY_DIRECTORY).map_err(|error| error.to_string())?;
+        let path = Path::new(TRAJECTORY_DIRECTORY).join(format!(
+            "trajectory_{}.csv",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+        ));
+        fs::write(&path, csv).map_err(|error| error.to_string())?;
+        Ok(path)
+    }
+
+    // Show the recorder in the given UI, with a button to track the selected body and one to start or stop recording.
+    pub fn show(&mut self, ui: &mut Ui, universe: &Universe, selected: Option<BodyId>) {
+        // Create a button to track the selected body if it isn't already.
+        let trackable = selected.filter(|id| !self.tracked.contains(id));
+        if ui
+            .add_enabled(trackable.is_some(), Button::new(tr("Track Selected Body")))
+            .clicked()
+        {
+            self.tracked.extend(trackable);
+        }
+        ui.end_row();
+
+        // List the tracked bodies with a button to stop tracking each.
+        let mut removed = None;
+        for (position, id) in self.tracked.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if universe.index_of(*id).is_some() {
+                    ui.label(tr_format("Body {}", &[id]));
+                } else {
+                    ui.label(tr_format("Body {} (gone)", &[id]));
+                }
+                if ui.button(tr("Remove")).clicked() {
+                    removed = Some(position);
+                }
+            });
+            ui.end_row();
+        }
+        if let Some(position) = removed {
+            self.tracked.remove(position);
+        }
+
+        // Create a button to start recording, or to stop and export the recording.
+        if self.recording {
+            if ui.button(tr("Stop and Export")).clicked() {
+                self.stop(&universe.universe_settings.units);
+            }
+            ui.label(tr_format("{} samples", &[&self.samples.len()]));
+        } else if ui
+            .add_enabled(!self.tracked.is_empty(), Button::new(tr("Start Recording")))
+            .clicked()
+        {
+            self.start();
+        }
+        ui.end_row();
+        ui.label(tr("A sample is taken each frame the simulation advances"));
+        ui.end_row();
+        ui.label(&self.status);
+        ui.end_row();
+    }
+}
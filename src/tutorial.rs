@@ -0,0 +1,252 @@
+use crate::camera::*;
+use crate::locale::*;
+use crate::universe::*;
+use notan::math::Vec2;
+use notan_egui::*;
+use std::{fs, path::Path};
+
+// The file that marks the tutorial as seen, so it only starts by itself on the first launch.
+const TUTORIAL_PATH: &str = "config/tutorial_seen.txt";
+// The distance in pixels the camera has to be panned to finish the camera step.
+const PAN_DISTANCE: f32 = 50.0;
+// The factor the camera has to be zoomed by, in or out, to finish the camera step.
+const ZOOM_FACTOR: f32 = 1.25;
+
+// A step of the tutorial.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TutorialStep {
+    Welcome,
+    Generate,
+    Camera,
+    Gravity,
+    Collisions,
+    Finished,
+}
+
+// Implementations for TutorialStep.
+impl TutorialStep {
+    // All steps, in the order they are shown.
+    pub const ALL: [TutorialStep; 6] = [
+        TutorialStep::Welcome,
+        TutorialStep::Generate,
+        TutorialStep::Camera,
+        TutorialStep::Gravity,
+        TutorialStep::Collisions,
+        TutorialStep::Finished,
+    ];
+
+    // Return the title of a step.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TutorialStep::Welcome => "Welcome",
+            TutorialStep::Generate => "Generating Bodies",
+            TutorialStep::Camera => "Panning and Zooming",
+            TutorialStep::Gravity => "Adjusting Gravity",
+            TutorialStep::Collisions => "Enabling Collisions",
+            TutorialStep::Finished => "All Done",
+        }
+    }
+
+    // Return the instructions of a step.
+    pub fn instructions(&self) -> &'static str {
+        match self {
+            TutorialStep::Welcome => "This tour walks through the basics of the simulator. Each step finishes by itself once you've tried it, or you can skip ahead with Next.",
+            TutorialStep::Generate => "Press Generate Bodies in the generation settings to fill the universe with a new set of bodies.",
+            TutorialStep::Camera => "Drag with the right mouse button to pan and scroll to zoom. The arrow keys and the = and - keys do the same.",
+            TutorialStep::Gravity => "Drag the gravitational constant left or right to weaken or strengthen gravity, and watch how the orbits change.",
+            TutorialStep::Collisions => "Tick Enable Collisions so bodies that touch merge into one.",
+            TutorialStep::Finished => "That's the basics. The other sections hold many more tools, and the tour can be restarted at any time.",
+        }
+    }
+}
+
+// A guided tour that walks through the basics, highlighting the widget each step is about and finishing steps once they've been tried.
+pub struct Tutorial {
+    pub active: bool,
+    pub step: usize,
+    pub step_started: bool,
+    pub start_translation: Vec2,
+    pub start_scale: f32,
+    pub start_gravitational_constant: f64,
+    pub start_collisions: bool,
+    pub generated: bool,
+    pub anchor: Option<Rect>,
+    pub scrolled: bool,
+}
+
+// Default value for Tutorial.
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self {
+            active: !Path::new(TUTORIAL_PATH).exists(),
+            step: 0,
+            step_started: false,
+            start_translation: Vec2::ZERO,
+            start_scale: 1.0,
+            start_gravitational_constant: 0.0,
+            start_collisions: false,
+            generated: false,
+            anchor: None,
+            scrolled: false,
+        }
+    }
+}
+
+// Implementations for Tutorial.
+impl Tutorial {
+    // Return the current step, if the tutorial is active.
+    pub fn current(&self) -> Option<TutorialStep> {
+        self.active.then(|| TutorialStep::ALL[self.step])
+    }
+
+    // Start the tutorial from the first step.
+    pub fn start(&mut self) {
+        self.active = true;
+        self.go_to(0);
+    }
+
+    // Stop the tutorial and remember that it was seen, ignoring failures since it's only a convenience.
+    pub fn stop(&mut self) {
+        self.active = false;
+        let _ = fs::create_dir_all("config");
+        let _ = fs::write(TUTORIAL_PATH, "seen");
+    }
+
+    // Move to a step, recording the state it's measured against on the next update.
+    fn go_to(&mut self, step: usize) {
+        self.step = step.min(TutorialStep::ALL.len() - 1);
+        self.step_started = false;
+        self.generated = false;
+        self.scrolled = false;
+    }
+
+    // Record that new bodies were generated, which finishes the generation step.
+    pub fn notify_generated(&mut self) {
+        self.generated = true;
+    }
+
+    // Return whether a collapsing header has to be forced open so the widget of the current step can be seen.
+    pub fn open_section(&self, step: TutorialStep) -> Option<bool> {
+        (self.current() == Some(step)).then_some(true)
+    }
+
+    // Anchor the callout of the current step to the widget of a response if it belongs to the given step, scrolling it into view when the step starts.
+    pub fn anchor(&mut self, step: TutorialStep, response: &Response) {
+        if self.current() != Some(step) {
+            return;
+        }
+        if !self.scrolled {
+            response.scroll_to_me(Some(Align::Center));
+            self.scrolled = true;
+        }
+        self.anchor = Some(response.rect);
+    }
+
+    // Update the tutorial, moving to the next step once the current one has been tried.
+    pub fn update(&mut self, camera: &Camera, universe: &Universe) {
+        let Some(step) = self.current() else {
+            return;
+        };
+
+        // Record the state the step is measured against when it starts.
+        let universe_settings = &universe.universe_settings;
+        if !self.step_started {
+            self.step_started = true;
+            self.start_translation = camera.target_translation;
+            self.start_scale = camera.target_scale;
+            self.start_gravitational_constant = universe_settings.gravitational_constant;
+            self.start_collisions = universe_settings.enable_collisions;
+            return;
+        }
+
+        // Check whether the step has been tried.
+        let finished = match step {
+            TutorialStep::Welcome | TutorialStep::Finished => false,
+            TutorialStep::Generate => self.generated,
+            TutorialStep::Camera => {
+                let zoom = camera.target_scale / self.start_scale;
+                camera.target_translation.distance(self.start_translation) > PAN_DISTANCE
+                    && !(1.0 / ZOOM_FACTOR..ZOOM_FACTOR).contains(&zoom)
+            }
+            TutorialStep::Gravity => {
+                universe_settings.gravitational_constant != self.start_gravitational_constant
+            }
+            TutorialStep::Collisions => {
+                universe_settings.enable_collisions && !self.start_collisions
+            }
+        };
+        if finished {
+            self.go_to(self.step + 1);
+        }
+    }
+
+    // Show the callout of the current step next to the widget it's anchored to, or in the middle of the screen if it has none, with a highlight around the widget.
+    pub fn show(&mut self, context: &Context) {
+        let Some(step) = self.current() else {
+            return;
+        };
+        let anchor = self.anchor.take();
+
+        // Draw a highlight around the widget, pulsing so it catches the eye.
+        if let Some(rect) = anchor {
+            let time = context.input(|input| input.time);
+            let alpha = (0.6 + 0.4 * (time * 4.0).sin()) as f32;
+            context
+                .layer_painter(LayerId::new(
+                    Order::Foreground,
+                    Id::new("Tutorial Highlight"),
+                ))
+                .rect_stroke(
+                    rect.expand(4.0),
+                    4.0,
+                    Stroke::new(3.0, Color32::from_rgb(255, 200, 0).gamma_multiply(alpha)),
+                );
+            context.request_repaint();
+        }
+
+        // Place the callout to the right of the widget, or in the middle of the screen.
+        let area = Area::new(Id::new("Tutorial")).order(Order::Foreground);
+        let area = match anchor {
+            Some(rect) => area.fixed_pos(rect.right_top() + vec2(16.0, 0.0)),
+            None => area
+                .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
+                .movable(false),
+        };
+        area.show(context, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(320.0);
+
+                // Show the title, progress, and instructions of the step.
+                ui.heading(tr(step.name()));
+                ui.label(tr_format(
+                    "Step {} of {}",
+                    &[&(self.step + 1), &TutorialStep::ALL.len()],
+                ));
+                ui.label(tr(step.instructions()));
+                ui.separator();
+
+                // Create buttons to go back, skip the tour, and move on.
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.step > 0, Button::new(tr("Back")))
+                        .clicked()
+                    {
+                        self.go_to(self.step - 1);
+                    }
+                    if step == TutorialStep::Finished {
+                        if ui.button(tr("Finish")).clicked() {
+                            self.stop();
+                        }
+                    } else {
+                        if ui.button(tr("Next")).clicked() {
+                            self.go_to(self.step + 1);
+                        }
+                        if ui.button(tr("Skip Tour")).clicked() {
+                            self.stop();
+                        }
+                    }
+                });
+            });
+        });
+    }
+}
@@ -1,24 +1,331 @@
+use crate::autosave::*;
+use crate::autotune::*;
+use crate::bookmarks::*;
+use crate::brush::*;
 use crate::camera::*;
+use crate::checkpoint::*;
+use crate::clustering::*;
+use crate::coalescence::*;
+use crate::collision_debug::*;
+use crate::comparison::*;
+use crate::console::*;
+use crate::dialogs::*;
+use crate::diff::*;
+use crate::ensemble::*;
+use crate::events::*;
+use crate::export::*;
+use crate::focus_pause::*;
+use crate::gallery::*;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::*;
+use crate::generation::*;
+use crate::governor::*;
+use crate::gravity_assist::*;
+use crate::group_tools::*;
+use crate::heat_map::*;
+use crate::hotkeys::*;
+use crate::import::*;
+use crate::inspector::*;
+use crate::locale::*;
+use crate::minimap::*;
+use crate::periodic::*;
+use crate::phase_space::*;
+use crate::planner::*;
+use crate::profiler::*;
+use crate::render::*;
+use crate::rewind::*;
+use crate::rotation_curve::*;
+use crate::scenario::*;
+use crate::spawner::*;
+use crate::status_bar::*;
+use crate::summary::*;
+use crate::sweep::*;
+use crate::theme::*;
+use crate::trajectory::*;
+use crate::tutorial::*;
+use crate::units::*;
 use crate::universe::*;
-use notan::prelude::*;
+use crate::workspace::*;
+use notan::{draw::*, prelude::*};
 use notan_egui::*;
+use std::path::Path;
 
 // A UI to create windows and hold values for those windows.
+#[derive(Default)]
 pub struct UI {
     pub generation_settings: GenerationSettings,
+    pub gallery: Gallery,
+    pub generation_task: Option<GenerationTask>,
+    pub inspector: Inspector,
+    pub phase_space_plot: PhaseSpacePlot,
+    pub rotation_curve: RotationCurve,
+    pub clustering_analysis: ClusteringAnalysis,
+    pub collision_debugger: CollisionDebugger,
+    pub collision_heat_map: CollisionHeatMap,
+    pub coalescence_statistics: CoalescenceStatistics,
+    pub event_detector: EventDetector,
+    pub diff_tool: DiffTool,
+    pub brush: Brush,
+    pub spawner: Spawner,
+    pub group_tools: GroupTools,
+    pub newtonian_comparison: NewtonianComparison,
+    pub planner: Planner,
+    pub gravity_assist: GravityAssist,
+    pub auto_tuner: AutoTuner,
+    pub governor: SpeedGovernor,
+    pub rewind_buffer: RewindBuffer,
+    pub minimap: Minimap,
+    pub status_bar: StatusBar,
+    pub theme: UiTheme,
+    pub focus_pause: FocusPause,
+    pub autosave: Autosave,
+    pub long_run: LongRun,
+    pub sweep: Sweep,
+    pub ensemble: Ensemble,
+    #[cfg(feature = "gamepad")]
+    pub gamepad: Gamepad,
+    pub hotkeys: Hotkeys,
+    pub bookmarks: Bookmarks,
+    pub console: Console,
+    pub tutorial: Tutorial,
+    pub timeline: Timeline,
+    pub periodic_orbit_finder: PeriodicOrbitFinder,
+    pub importer: Importer,
+    pub exporter: Exporter,
+    pub trajectory_recorder: TrajectoryRecorder,
+    pub run_summary: Option<RunSummary>,
+    pub run_summary_status: String,
+    pub workspace_path: String,
+    pub workspace_status: String,
+    pub hidden: bool,
+    pub screenshot_requested: bool,
+    pub pointer_over_ui: bool,
+    pub keyboard_over_ui: bool,
+    pub focus_requested: bool,
 }
 
-// The default value for UI.
-impl Default for UI {
-    fn default() -> Self {
-        Self {
-            generation_settings: Default::default(),
+// Implementations for UI.
+impl UI {
+    // Update the UI.
+    pub fn update(&mut self, app: &mut App, camera: &mut Camera, universe: &mut Universe) {
+        // Give bodies added since the last frame an id, so they can be selected and tracked before the next step.
+        universe.assign_ids();
+
+        // Update the group selection box, then the spawner, the brush unless the spawner is enabled, and the inspector without selecting bodies while any of them is using the mouse and only recording the trail when the state changed.
+        self.group_tools
+            .update(app, camera, universe, self.pointer_over_ui);
+        let pointer_taken = self.pointer_over_ui || self.group_tools.is_selecting(app);
+        self.spawner.update(app, camera, universe, pointer_taken);
+        self.brush
+            .update(app, camera, universe, pointer_taken || self.spawner.enabled);
+        self.inspector.update(
+            app,
+            camera,
+            universe,
+            pointer_taken || self.brush.enabled || self.spawner.enabled,
+            self.governor.state_updated(),
+        );
+        // Update the conic of the selected body.
+        self.gravity_assist
+            .update(universe, self.inspector.selected_body(universe));
+        // Update the phase space plot.
+        self.phase_space_plot.update(universe);
+        // Update the rotation curve.
+        self.rotation_curve.update(universe, app.timer.delta_f32());
+        // Update the clustering analysis.
+        self.clustering_analysis
+            .update(universe, app.timer.delta_f32());
+        // Update the collision debugger.
+        self.collision_debugger.update(universe);
+        // Update the collision heat map.
+        self.collision_heat_map.update(universe);
+        // Update the coalescence statistics.
+        self.coalescence_statistics.update(universe);
+        // Update the notable event detector.
+        self.event_detector.update(universe);
+        // Update the trajectory recorder.
+        self.trajectory_recorder.update(universe);
+        // Update the rewind buffer.
+        self.rewind_buffer.update(universe, app.timer.delta_f32());
+        // Update the trajectory planner.
+        self.planner.update();
+        // Update the Newtonian comparison.
+        self.newtonian_comparison.update(camera, universe);
+        // Update the autosaver.
+        self.autosave.update(universe);
+        // Update the long-run mode, deselecting the body if the universe was resumed from a checkpoint.
+        if self.long_run.update(
+            universe,
+            &mut self.generation_settings,
+            app.timer.delta().as_secs_f64(),
+        ) {
+            self.inspector.select(None);
+        }
+        // Update the parameter sweep.
+        self.sweep.update();
+        // Update the periodic orbit finder.
+        self.periodic_orbit_finder.update();
+        // Update the ensemble mode.
+        self.ensemble.update();
+        // Update the scenario timeline.
+        self.timeline.update(universe);
+        // Update the tutorial.
+        self.tutorial.update(camera, universe);
+        // Replace the bodies of the universe once the generation has finished, unless it was cancelled.
+        if let Some(result) = self
+            .generation_task
+            .as_mut()
+            .and_then(|generation_task| generation_task.poll())
+        {
+            if let Some(bodies) = result {
+                universe.replace_bodies(bodies);
+                self.inspector.select(None);
+                self.tutorial.notify_generated();
+            }
+            self.generation_task = None;
+        }
+
+        // Trigger the actions whose hotkeys were pressed.
+        for action in self.hotkeys.update(app, self.keyboard_over_ui) {
+            match action {
+                Action::Pause => universe.paused = !universe.paused,
+                Action::Step => universe.step(),
+                Action::ResetCamera => camera.reset(),
+                Action::FitAll => camera.fit_all(app, universe),
+                Action::Generate => self.generate(universe),
+                Action::Screenshot => self.screenshot_requested = true,
+                Action::ToggleUI => self.hidden = !self.hidden,
+                Action::ToggleConsole => self.console.open = !self.console.open,
+                Action::FocusUI => {
+                    self.hidden = false;
+                    self.focus_requested = true;
+                }
+                Action::SelectNext => self.inspector.select_adjacent(universe, true),
+                Action::SelectPrevious => self.inspector.select_adjacent(universe, false),
+                Action::Deselect => self.inspector.select(None),
+                Action::CenterSelected => {
+                    if let Some(index) = self.inspector.selected_body(universe) {
+                        camera.center_on(universe.bodies[index].position);
+                    }
+                }
+                Action::PanLeft => camera.pan_with_keys((-1.0, 0.0), app.timer.delta_f32()),
+                Action::PanRight => camera.pan_with_keys((1.0, 0.0), app.timer.delta_f32()),
+                Action::PanUp => camera.pan_with_keys((0.0, -1.0), app.timer.delta_f32()),
+                Action::PanDown => camera.pan_with_keys((0.0, 1.0), app.timer.delta_f32()),
+                Action::ZoomIn => camera.zoom_with_keys(1.0, app.timer.delta_f32()),
+                Action::ZoomOut => camera.zoom_with_keys(-1.0, app.timer.delta_f32()),
+            }
+        }
+        // Recall the camera bookmarks whose number keys were pressed.
+        self.bookmarks.update(app, camera, self.keyboard_over_ui);
+        // Update the gamepad controller.
+        #[cfg(feature = "gamepad")]
+        if self.gamepad.update(app, camera, universe) {
+            self.generate(universe);
         }
     }
-}
 
-// Implementations for UI.
-impl UI {
+    // Start generating new bodies for the given universe in the background if no generation is running, autosaving the current universe first so an accidental regenerate can be undone.
+    fn generate(&mut self, universe: &Universe) {
+        if self.generation_task.is_some() {
+            return;
+        }
+        if self.autosave.autosave_settings.enabled && !universe.bodies.is_empty() {
+            self.autosave.save(universe);
+        }
+        self.generation_task = Some(GenerationTask::start(
+            &self.generation_settings,
+            &universe.universe_settings.units,
+            universe.universe_settings.gravitational_constant,
+        ));
+    }
+
+    // Save the universe with the camera, render settings, panel layout, and bookmarks as a workspace at the workspace path, storing the result as the status.
+    fn save_workspace(
+        &mut self,
+        camera: &Camera,
+        universe: &Universe,
+        scene_renderer: &SceneRenderer,
+    ) {
+        let workspace = Workspace {
+            universe: universe.clone(),
+            camera_settings: camera.camera_settings.clone(),
+            translation: camera.target_translation,
+            scale: camera.target_scale,
+            render_settings: scene_renderer.render_settings.clone(),
+            panel_layout: PanelLayout {
+                ui_hidden: self.hidden,
+                console_open: self.console.open,
+                minimap_enabled: self.minimap.enabled,
+                status_bar_enabled: self.status_bar.enabled,
+            },
+            bookmarks: self.bookmarks.bookmarks.clone(),
+        };
+        self.workspace_status = match workspace.save(Path::new(&self.workspace_path)) {
+            Ok(()) => tr_format("Saved {}", &[&self.workspace_path]),
+            Err(error) => tr_format("Saving failed: {}", &[&error]),
+        };
+    }
+
+    // Open the workspace at the workspace path, restoring everything it bundles and storing the result as the status.
+    fn open_workspace(
+        &mut self,
+        camera: &mut Camera,
+        universe: &mut Universe,
+        scene_renderer: &mut SceneRenderer,
+    ) {
+        let workspace = match Workspace::load(Path::new(&self.workspace_path)) {
+            Ok(workspace) => workspace,
+            Err(error) => {
+                self.workspace_status = tr_format("Opening failed: {}", &[&error]);
+                return;
+            }
+        };
+
+        // Restore the universe, deselecting the body since the bodies were replaced.
+        *universe = workspace.universe;
+        self.inspector.select(None);
+
+        // Restore the camera, jumping straight to its pose.
+        camera.reset();
+        camera.camera_settings = workspace.camera_settings;
+        (camera.translation, camera.target_translation) =
+            (workspace.translation, workspace.translation);
+        (camera.scale, camera.target_scale) = (workspace.scale, workspace.scale);
+
+        // Restore the render settings, panel layout, and bookmarks.
+        scene_renderer.render_settings = workspace.render_settings;
+        self.hidden = workspace.panel_layout.ui_hidden;
+        self.console.open = workspace.panel_layout.console_open;
+        self.minimap.enabled = workspace.panel_layout.minimap_enabled;
+        self.status_bar.enabled = workspace.panel_layout.status_bar_enabled;
+        self.bookmarks.bookmarks = workspace.bookmarks;
+
+        self.workspace_status = tr_format("Opened {}", &[&self.workspace_path]);
+    }
+
+    // Draw the parts of the UI that are in the world.
+    pub fn draw_world(&self, draw: &mut Draw, camera: &Camera, universe: &Universe) {
+        // Draw the inspector.
+        self.inspector.draw(draw, camera, universe);
+        // Draw the collision debug overlay.
+        self.collision_debugger.draw(draw, camera, universe);
+        // Draw the collision heat map.
+        self.collision_heat_map.draw(draw);
+        // Draw the planned trajectory.
+        self.planner.draw(draw, camera);
+        // Draw the conic of the selected body.
+        self.gravity_assist.draw(draw, camera);
+        // Draw the Newtonian comparison.
+        self.newtonian_comparison.draw(draw, camera);
+        // Draw the brush.
+        self.brush.draw(draw, camera);
+        // Draw the body about to be spawned.
+        self.spawner.draw(draw, camera, universe);
+        // Draw the group selection.
+        self.group_tools.draw(draw, camera, universe);
+    }
+
     // Draw the UI.
     pub fn draw(
         &mut self,
@@ -26,162 +333,1927 @@ impl UI {
         app: &mut App,
         camera: &mut Camera,
         universe: &mut Universe,
+        profiler: &Profiler,
+        scene_renderer: &mut SceneRenderer,
     ) {
+        // Build the accessibility tree of the UI so its widgets and their labels can be read by assistive technology.
+        #[cfg(feature = "accessibility")]
+        context.enable_accesskit();
+
+        // Draw the UI with the chosen theme.
+        context.set_visuals(self.theme.visuals());
+
+        // Annotate the conic of the selected body, which is part of the world so it's shown even if the UI is hidden.
+        self.gravity_assist
+            .show_annotation(context, app, camera, universe);
+
+        // Report the bodies that blew up, which is shown even if the UI is hidden since the universe was paused because of it.
+        show_blowup_report(context, universe);
+
+        // Don't draw anything if the UI is hidden, leaving the whole screen to the world.
+        if self.hidden {
+            self.pointer_over_ui = false;
+            self.keyboard_over_ui = false;
+            return;
+        }
+
         // Create a window that isn't movable, resizable, and has no title bar.
         Window::new("N-Body Simulation")
             .movable(false)
             .resizable(false)
             .title_bar(false)
             .show(context, |ui| {
+                // Create buttons to pause or resume the universe and to take a single step while it's paused, next to the language. The pause button takes the keyboard focus when it's requested, so the rest of the UI can be reached with tab and the arrow keys.
+                ui.horizontal(|ui| {
+                    let pause_button =
+                        ui.button(tr(if universe.paused { "Resume" } else { "Pause" }));
+                    if std::mem::take(&mut self.focus_requested) {
+                        pause_button.request_focus();
+                    }
+                    if pause_button.clicked() {
+                        universe.paused = !universe.paused;
+                    }
+                    if ui
+                        .add_enabled(universe.paused, Button::new(tr("Step")))
+                        .clicked()
+                    {
+                        universe.step();
+                    }
+
+                    // Create a combo box to choose the language of the UI.
+                    show_language(ui);
+
+                    // Create a button to start the guided tour.
+                    if ui.button(tr("Tour")).clicked() {
+                        self.tutorial.start();
+                    }
+                });
+
                 // Create a collapsing header to contain statistics.
-                CollapsingHeader::new("Statistics")
+                CollapsingHeader::new(tr("Statistics"))
                     .default_open(true)
                     .show(ui, |ui| {
-                        ui.label(format!("{} fps", app.timer.fps().round()));
-                        ui.label(format!("{} bodies", universe.bodies.len()));
+                        ui.label(format!("{} {}", app.timer.fps().round(), tr("fps")));
+                        ui.label(format!("{} {}", universe.bodies.len(), tr("bodies")));
+                        ui.label(format!(
+                            "{} {}",
+                            tr(universe.universe_settings.solver.name()),
+                            tr("solver")
+                        ));
+                        if universe.universe_settings.solver == Solver::Direct {
+                            ui.label(format!(
+                                "{} {}",
+                                tr(universe.universe_settings.precision.name()),
+                                tr("precision")
+                            ));
+                        }
+                        if universe.universe_settings.solver == Solver::Direct
+                            && universe.universe_settings.enable_interaction_cutoff
+                        {
+                            ui.label(tr_format(
+                                "{} pairs beyond the interaction cutoff skipped per step",
+                                &[&universe.step_statistics.skipped_pairs],
+                            ));
+                        }
+                        if universe.universe_settings.uses_spatial_grid() {
+                            ui.label(if universe.step_statistics.grid_rebuilt {
+                                tr("Spatial grid rebuilt").to_string()
+                            } else {
+                                tr_format(
+                                    "Spatial grid updated, {} bodies changed cells",
+                                    &[&universe.step_statistics.grid_moves],
+                                )
+                            });
+                        }
+                        ui.label(format!(
+                            "{} {}",
+                            universe.step_statistics.force_evaluations,
+                            tr("force evaluations per step")
+                        ));
+                        ui.label(format!(
+                            "{:.3e} {}",
+                            self.governor.force_evaluation_rate,
+                            tr("force evaluations per second")
+                        ));
+                        ui.label(format!(
+                            "{} {}",
+                            universe.step_statistics.tracer_force_evaluations,
+                            tr("tracer force evaluations per step")
+                        ));
+                        ui.label(format!(
+                            "{} {}",
+                            universe.step_statistics.collision_checks,
+                            tr("collision checks per step")
+                        ));
                         ui.label(format!(
-                            "{} interactions per frame",
-                            universe.bodies.len().pow(2) - universe.bodies.len()
+                            "{} {}",
+                            universe.ejected_bodies,
+                            tr("ejected bodies")
                         ));
+                        let units = universe.universe_settings.units;
+                        ui.label(format!(
+                            "{} {}",
+                            units.format(Quantity::Time, universe.simulation_time),
+                            tr("simulation time")
+                        ));
+                        // Show the time since the epoch with a button to move the epoch to now.
+                        ui.horizontal(|ui| {
+                            ui.label(format!(
+                                "{} {}",
+                                units.format(
+                                    Quantity::Time,
+                                    universe.simulation_time - universe.epoch
+                                ),
+                                tr("since epoch")
+                            ));
+                            if ui.button(tr("Reset Epoch")).clicked() {
+                                universe.edit(|universe| universe.reset_epoch());
+                            }
+                        });
+                        ui.label(format!(
+                            "{} {}",
+                            universe
+                                .universe_settings
+                                .units
+                                .format(Quantity::Mass, universe.total_mass()),
+                            tr("total mass")
+                        ));
+                        ui.end_row();
+
+                        // Create a collapsing header to contain the frame time breakdown.
+                        CollapsingHeader::new(tr("Frame Time"))
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                show_profiler(ui, profiler);
+                            });
+
+                        // Create a collapsing header to contain the auto tuner.
+                        CollapsingHeader::new(tr("Auto Tuning"))
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                self.auto_tuner.show(ui, profiler);
+                            });
+                    });
+
+                // Create a collapsing header to contain the speed governor.
+                CollapsingHeader::new(tr("Simulation Speed"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.governor.show(ui);
+
+                        // Create a checkbox to toggle pausing the universe while the window is unfocused.
+                        let label_id = ui.label(tr("Pause When Unfocused")).id;
+                        ui.add(Checkbox::new(&mut self.focus_pause.enabled, ""))
+                            .labelled_by(label_id);
                         ui.end_row();
                     });
 
+                // Create a collapsing header to contain the rewind buffer.
+                CollapsingHeader::new(tr("Rewind"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.rewind_buffer.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the inspector.
+                CollapsingHeader::new(tr("Inspector"))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        self.inspector.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the phase space plot.
+                CollapsingHeader::new(tr("Phase Space"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let selected_body = self.inspector.selected_body(universe);
+                        self.phase_space_plot.show(ui, universe, selected_body);
+                    });
+
+                // Create a collapsing header to contain the rotation curve.
+                CollapsingHeader::new(tr("Rotation Curve"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.rotation_curve.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the clustering analysis.
+                CollapsingHeader::new(tr("Clustering"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.clustering_analysis.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the trajectory planner.
+                CollapsingHeader::new(tr("Trajectory Planner"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let selected_body = self.inspector.selected_body(universe);
+                        self.planner.show(ui, universe, selected_body);
+                    });
+
+                // Create a collapsing header to contain the gravity assist visualizer.
+                CollapsingHeader::new(tr("Gravity Assist"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.gravity_assist.show(ui);
+                    });
+
+                // Create a collapsing header to contain the coalescence statistics.
+                CollapsingHeader::new(tr("Coalescence"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.coalescence_statistics.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the notable events.
+                CollapsingHeader::new(tr("Notable Events"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.event_detector.show(ui, camera, universe);
+                    });
+
+                // Create a collapsing header to contain the collision debugger.
+                CollapsingHeader::new(tr("Collision Debug"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.collision_debugger.show(ui, camera, universe);
+                    });
+
+                // Create a collapsing header to contain the collision heat map.
+                CollapsingHeader::new(tr("Collision Heat Map"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.collision_heat_map.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the Newtonian comparison.
+                CollapsingHeader::new(tr("Newtonian Comparison"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.newtonian_comparison.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the brush.
+                CollapsingHeader::new(tr("Brush"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.brush.show(ui);
+                    });
+
+                // Create a collapsing header to contain the spawner.
+                CollapsingHeader::new(tr("Spawn Tool"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.spawner.show(ui, &universe.universe_settings.units);
+                    });
+
+                // Create a collapsing header to contain the group tools.
+                CollapsingHeader::new(tr("Group Tools"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.group_tools.show(ui, universe, app.timer.delta_f32());
+                    });
+
+                // Create a collapsing header to contain the snapshot diff tool.
+                CollapsingHeader::new(tr("Snapshot Diff"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.diff_tool.show(ui, universe);
+                    });
+
                 // Create a collapsing header to contain the camera settings.
-                CollapsingHeader::new("Camera Settings")
+                CollapsingHeader::new(tr("Camera Settings"))
                     .default_open(true)
                     .show(ui, |ui| {
+                        // Create a checkbox to toggle the minimap.
+                        let label_id = ui.label(tr("Show Minimap")).id;
+                        ui.add(Checkbox::new(&mut self.minimap.enabled, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle the status bar with the cursor coordinates.
+                        let label_id = ui.label(tr("Show Cursor Coordinates")).id;
+                        ui.add(Checkbox::new(&mut self.status_bar.enabled, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
                         // Create a slider to modify the pan sensitivity of the given camera.
-                        ui.label("Pan Sensitivity");
+                        let label_id = ui.label(tr("Pan Sensitivity")).id;
                         ui.add(Slider::new(
                             &mut camera.camera_settings.pan_sensitivity,
                             0.0..=5.0,
-                        ));
+                        ))
+                        .labelled_by(label_id);
                         ui.end_row();
 
                         // Create a slider to modify the zoom sensitivity of the given camera.
-                        ui.label("Zoom Sensitivity");
+                        let label_id = ui.label(tr("Zoom Sensitivity")).id;
                         ui.add(Slider::new(
                             &mut camera.camera_settings.zoom_sensitivity,
                             0.0..=5.0,
-                        ));
+                        ))
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create sliders to modify the sensitivity of two finger panning and pinch zooming.
+                        let label_id = ui.label(tr("Touch Pan Sensitivity")).id;
+                        ui.add(Slider::new(
+                            &mut camera.camera_settings.touch_pan_sensitivity,
+                            0.0..=5.0,
+                        ))
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        let label_id = ui.label(tr("Pinch Sensitivity")).id;
+                        ui.add(Slider::new(
+                            &mut camera.camera_settings.pinch_sensitivity,
+                            0.0..=5.0,
+                        ))
+                        .labelled_by(label_id);
                         ui.end_row();
 
+                        // Create a checkbox to toggle camera smoothing and a slider to modify how quickly the camera reaches its target.
+                        let label_id = ui.label(tr("Smoothing")).id;
+                        ui.add(Checkbox::new(&mut camera.camera_settings.smoothing, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+                        let label_id = ui.label(tr("Damping")).id;
+                        ui.add(Slider::new(&mut camera.camera_settings.damping, 1.0..=50.0))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a slider to modify how long camera animations take, such as centering on a body or recalling a bookmark, and a button to fit all bodies on the screen.
+                        let label_id = ui.label(tr("Animation Duration")).id;
+                        ui.add(
+                            Slider::new(&mut camera.camera_settings.animation_duration, 0.0..=3.0)
+                                .suffix(" s"),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        if ui.button(tr("Fit All Bodies")).clicked() {
+                            camera.fit_all(app, universe);
+                        }
+                        ui.end_row();
+
+                        // Create a checkbox to toggle camera inertia and a slider to modify how quickly the momentum is lost.
+                        let label_id = ui.label(tr("Inertia")).id;
+                        ui.add(Checkbox::new(&mut camera.camera_settings.inertia, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+                        let label_id = ui.label(tr("Friction")).id;
+                        ui.add(Slider::new(
+                            &mut camera.camera_settings.friction,
+                            0.5..=20.0,
+                        ))
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a combo box to select the reference frame the world is viewed in and a drag value to modify the angular rate of the fixed rate frame.
+                        let label_id = ui.label(tr("Reference Frame")).id;
+                        ComboBox::from_id_source("Reference Frame")
+                            .selected_text(tr(camera.camera_settings.reference_frame.name()))
+                            .show_ui(ui, |ui| {
+                                for reference_frame in ReferenceFrame::ALL {
+                                    ui.selectable_value(
+                                        &mut camera.camera_settings.reference_frame,
+                                        reference_frame,
+                                        tr(reference_frame.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+                        let label_id = ui.label(tr("Angular Rate")).id;
+                        ui.add(
+                            DragValue::new(&mut camera.camera_settings.angular_rate)
+                                .speed(0.001)
+                                .suffix(" rad per time unit"),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        if camera.camera_settings.reference_frame == ReferenceFrame::CoRotatingPair
+                            && self.inspector.selected_body(universe).is_none()
+                        {
+                            ui.label(tr(
+                                "Select a body to co-rotate with it and its dominant body.",
+                            ));
+                            ui.end_row();
+                        }
+
                         // Create a button to reset the camera settings.
-                        if ui.button("Reset").clicked() {
+                        if ui.button(tr("Reset")).clicked() {
                             camera.camera_settings = CameraSettings::default();
                         }
                     });
 
+                // Create a collapsing header to contain the render settings.
+                CollapsingHeader::new(tr("Render Settings"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let render_settings = &mut scene_renderer.render_settings;
+
+                        // Create a combo box to select the theme the UI is drawn with.
+                        let label_id = ui.label(tr("UI Theme")).id;
+                        ComboBox::from_id_source("UI Theme")
+                            .selected_text(tr(self.theme.name()))
+                            .show_ui(ui, |ui| {
+                                for theme in UiTheme::ALL {
+                                    ui.selectable_value(&mut self.theme, theme, tr(theme.name()));
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a combo box to select how bodies are drawn.
+                        let label_id = ui.label(tr("Skin")).id;
+                        ComboBox::from_id_source("Skin")
+                            .selected_text(tr(render_settings.skin.name()))
+                            .show_ui(ui, |ui| {
+                                for skin in Skin::ALL {
+                                    ui.selectable_value(
+                                        &mut render_settings.skin,
+                                        skin,
+                                        tr(skin.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle twinkling if stars are drawn.
+                        if render_settings.skin == Skin::StarCatalog {
+                            let label_id = ui.label(tr("Twinkle")).id;
+                            ui.add(Checkbox::new(&mut render_settings.twinkle, ""))
+                                .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a text field for the image all bodies are drawn with, and a row for each tag whose bodies are drawn with another image, showing why an image couldn't be loaded.
+                        let label_id = ui.label(tr("Sprite")).id;
+                        ui.add(
+                            TextEdit::singleline(&mut render_settings.sprite_path)
+                                .hint_text(tr("PNG file, or empty for circles")),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        let mut removed = None;
+                        for (index, tag_sprite) in
+                            render_settings.tag_sprites.iter_mut().enumerate()
+                        {
+                            ui.add(
+                                TextEdit::singleline(&mut tag_sprite.tag)
+                                    .hint_text(tr("Tag"))
+                                    .desired_width(80.0),
+                            );
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    TextEdit::singleline(&mut tag_sprite.path)
+                                        .hint_text(tr("PNG file")),
+                                );
+                                if ui.button(tr("Remove")).clicked() {
+                                    removed = Some(index);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                        if let Some(index) = removed {
+                            render_settings.tag_sprites.remove(index);
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button(tr("Add Tag Sprite")).clicked() {
+                                render_settings.tag_sprites.push(TagSprite::default());
+                            }
+                            if ui.button(tr("Reload Sprites")).clicked() {
+                                scene_renderer.sprites.clear();
+                            }
+                        });
+                        ui.end_row();
+                        let render_settings = &mut scene_renderer.render_settings;
+                        for (path, sprite) in scene_renderer.sprites.iter() {
+                            let used = render_settings.sprite_path == *path
+                                || render_settings
+                                    .tag_sprites
+                                    .iter()
+                                    .any(|tag_sprite| tag_sprite.path == *path);
+                            if let (true, Err(error)) = (used, sprite) {
+                                ui.label(tr_format("Loading {} failed: {}", &[path, error]));
+                                ui.end_row();
+                            }
+                        }
+
+                        // Create a checkbox to toggle marking the angle of spinning bodies.
+                        let label_id = ui.label(tr("Show Spin")).id;
+                        ui.add(Checkbox::new(&mut render_settings.show_spin, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle blending nearby bodies into blobs, with sliders to modify the size of the cells of the blobs and how far bodies reach.
+                        let label_id = ui.label(tr("Blend Bodies into Blobs")).id;
+                        ui.add(Checkbox::new(&mut render_settings.blobs, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+                        if render_settings.blobs {
+                            let label_id = ui.label(tr("Blob Resolution")).id;
+                            ui.add(
+                                Slider::new(&mut render_settings.blob_resolution, 1.0..=16.0)
+                                    .suffix(" px"),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                            let label_id = ui.label(tr("Blob Reach")).id;
+                            ui.add(
+                                Slider::new(&mut render_settings.blob_reach, 1.1..=6.0).suffix("×"),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a combo box to select the order bodies are drawn in.
+                        let label_id = ui.label(tr("Draw Order")).id;
+                        ComboBox::from_id_source("Draw Order")
+                            .selected_text(tr(render_settings.draw_order.name()))
+                            .show_ui(ui, |ui| {
+                                for draw_order in DrawOrder::ALL {
+                                    ui.selectable_value(
+                                        &mut render_settings.draw_order,
+                                        draw_order,
+                                        tr(draw_order.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle drawing bodies as points with the GPU, showing why it's unavailable if the pipelines couldn't be created.
+                        let label_id = ui.label(tr("Draw Bodies on the GPU")).id;
+                        ui.add(Checkbox::new(&mut render_settings.gpu_points, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+                        if let Some(error) = &scene_renderer.gpu_renderer.error {
+                            ui.label(tr_format("GPU rendering unavailable: {}", &[error]));
+                            ui.end_row();
+                        }
+
+                        // Create a checkbox to toggle the trails of all bodies and a slider to modify how many samples they last.
+                        let label_id = ui.label(tr("Show Trails")).id;
+                        ui.add(Checkbox::new(&mut render_settings.show_trails, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+                        if render_settings.show_trails {
+                            let label_id = ui.label(tr("Trail Length")).id;
+                            ui.add(Slider::new(&mut render_settings.trail_length, 2..=1000))
+                                .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a combo box to select how bodies are colored.
+                        let label_id = ui.label(tr("Color Mode")).id;
+                        ComboBox::from_id_source("Color Mode")
+                            .selected_text(tr(render_settings.color_mode.name()))
+                            .show_ui(ui, |ui| {
+                                for color_mode in ColorMode::ALL {
+                                    ui.selectable_value(
+                                        &mut render_settings.color_mode,
+                                        color_mode,
+                                        tr(color_mode.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a combo box to select the palette values are mapped onto if the color mode uses one.
+                        if render_settings.color_mode.uses_palette() {
+                            let label_id = ui.label(tr("Palette")).id;
+                            ComboBox::from_id_source("Palette")
+                                .selected_text(tr(render_settings.palette.name()))
+                                .show_ui(ui, |ui| {
+                                    for palette in Palette::ALL {
+                                        ui.selectable_value(
+                                            &mut render_settings.palette,
+                                            palette,
+                                            tr(palette.name()),
+                                        );
+                                    }
+                                })
+                                .response
+                                .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a slider to modify how many seconds pass between recomputing orbital energies if bodies are colored by them.
+                        if render_settings.color_mode == ColorMode::OrbitalEnergy {
+                            let label_id = ui.label(tr("Energy Interval")).id;
+                            ui.add(
+                                Slider::new(&mut render_settings.energy_interval, 0.0..=5.0)
+                                    .suffix(" s"),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a checkbox to toggle fading in bodies spawned or merged during the run and a drag value to modify how long it takes.
+                        let units = universe.universe_settings.units;
+                        let label_id = ui.label(tr("Fade In New Bodies")).id;
+                        ui.add(Checkbox::new(&mut render_settings.fade_in, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+                        if render_settings.fade_in {
+                            let label_id = ui.label(tr("Fade-In Duration")).id;
+                            let mut fade_in_duration = units
+                                .display_value(Quantity::Time, render_settings.fade_in_duration);
+                            if ui
+                                .add(
+                                    DragValue::new(&mut fade_in_duration)
+                                        .speed(0.01)
+                                        .suffix(format!(" {}", units.symbol(Quantity::Time)))
+                                        .clamp_range(f64::EPSILON..=f64::MAX),
+                                )
+                                .labelled_by(label_id)
+                                .changed()
+                            {
+                                render_settings.fade_in_duration =
+                                    units.simulation_value(Quantity::Time, fade_in_duration);
+                            }
+                            ui.end_row();
+                        }
+
+                        // Create a checkbox to toggle merge animations.
+                        let label_id = ui.label(tr("Animate Merges")).id;
+                        ui.add(Checkbox::new(&mut render_settings.animate_merges, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a slider to modify how many seconds merge animations last.
+                        let label_id = ui.label(tr("Merge Duration")).id;
+                        ui.add(
+                            Slider::new(&mut render_settings.merge_duration, 0.05..=2.0)
+                                .suffix(" s"),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle labels on the most massive bodies.
+                        let label_id = ui.label(tr("Show Labels")).id;
+                        ui.add(Checkbox::new(&mut render_settings.show_labels, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a drag value to modify how many bodies are labeled.
+                        let label_id = ui.label(tr("Label Count")).id;
+                        ui.add(DragValue::new(&mut render_settings.label_count))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a combo box to select what the labels show.
+                        let label_id = ui.label(tr("Label Content")).id;
+                        ComboBox::from_id_source("Label Content")
+                            .selected_text(tr(render_settings.label_content.name()))
+                            .show_ui(ui, |ui| {
+                                for label_content in LabelContent::ALL {
+                                    ui.selectable_value(
+                                        &mut render_settings.label_content,
+                                        label_content,
+                                        tr(label_content.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle hiding the labels along with the UI.
+                        let label_id = ui.label(tr("Hide Labels With UI")).id;
+                        ui.add(Checkbox::new(&mut render_settings.hide_labels_with_ui, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create checkboxes to toggle the markers of the center of mass, the origin, and the principal axes of the mass distribution.
+                        for (label, value) in [
+                            (
+                                "Show Center of Mass",
+                                &mut render_settings.show_center_of_mass,
+                            ),
+                            ("Show Origin", &mut render_settings.show_origin),
+                            (
+                                "Show Principal Axes",
+                                &mut render_settings.show_principal_axes,
+                            ),
+                        ] {
+                            let label_id = ui.label(tr(label)).id;
+                            ui.add(Checkbox::new(value, "")).labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a checkbox to toggle only redrawing on events while paused and unfocused.
+                        let label_id = ui.label(tr("Power Saving When Idle")).id;
+                        ui.add(Checkbox::new(&mut render_settings.power_saving, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle advancing the next step on a worker thread while the current one is rendered.
+                        let label_id = ui.label(tr("Run Physics While Rendering")).id;
+                        ui.add(Checkbox::new(&mut render_settings.pipelined_physics, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a button to hide the UI, showing the key that brings it back and disabled if there's no such key.
+                        let toggle_ui = self.hotkeys.key_bindings.toggle_ui;
+                        if ui
+                            .add_enabled(
+                                toggle_ui != KeyCode::Unknown,
+                                Button::new(tr_format(
+                                    "Hide UI ({} to show)",
+                                    &[&key_name(toggle_ui)],
+                                )),
+                            )
+                            .clicked()
+                        {
+                            self.hidden = true;
+                        }
+                        ui.end_row();
+
+                        // Create a button to reset the render settings.
+                        if ui.button(tr("Reset")).clicked() {
+                            scene_renderer.render_settings = RenderSettings::default();
+                        }
+                        ui.end_row();
+                    });
+
                 // Create a collapsing header to contain the universe settings.
-                CollapsingHeader::new("Universe Settings")
+                CollapsingHeader::new(tr("Universe Settings"))
                     .default_open(true)
+                    .open(
+                        self.tutorial
+                            .open_section(TutorialStep::Gravity)
+                            .or(self.tutorial.open_section(TutorialStep::Collisions)),
+                    )
                     .show(ui, |ui| {
-                        // Create a drag value to modify the gravitational constant of the given universe.
-                        ui.label("Gravitational Constant");
-                        ui.add(DragValue::new(
-                            &mut universe.universe_settings.gravitational_constant,
-                        ));
+                        // Create a drag value to modify the gravitational constant of the given universe, anchoring the tutorial to it.
+                        let label_id = ui.label(tr("Gravitational Constant")).id;
+                        let response = ui
+                            .add(DragValue::new(
+                                &mut universe.universe_settings.gravitational_constant,
+                            ))
+                            .labelled_by(label_id);
+                        self.tutorial.anchor(TutorialStep::Gravity, &response);
+                        ui.end_row();
+
+                        // Create a combo box to select the solver of the given universe.
+                        let label_id = ui.label(tr("Solver")).id;
+                        ComboBox::from_id_source("Solver")
+                            .selected_text(tr(universe.universe_settings.solver.name()))
+                            .show_ui(ui, |ui| {
+                                for solver in Solver::ALL {
+                                    ui.selectable_value(
+                                        &mut universe.universe_settings.solver,
+                                        solver,
+                                        tr(solver.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a combo box to select the integrator of the given universe.
+                        let label_id = ui.label(tr("Integrator")).id;
+                        ComboBox::from_id_source("Integrator")
+                            .selected_text(tr(universe.universe_settings.integrator.name()))
+                            .show_ui(ui, |ui| {
+                                for integrator in Integrator::ALL {
+                                    ui.selectable_value(
+                                        &mut universe.universe_settings.integrator,
+                                        integrator,
+                                        tr(integrator.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a drag value to modify how many steps pass between reordering the bodies along a Z-order curve for cache locality, where zero never reorders them.
+                        let label_id = ui.label(tr("Reorder Interval")).id;
+                        ui.add(
+                            DragValue::new(&mut universe.universe_settings.reorder_interval)
+                                .clamp_range(0..=10000)
+                                .suffix(" steps"),
+                        )
+                        .labelled_by(label_id);
                         ui.end_row();
 
+                        // Create a combo box to select the precision of the direct solver.
+                        if universe.universe_settings.solver == Solver::Direct {
+                            let label_id = ui.label(tr("Precision")).id;
+                            ComboBox::from_id_source("Precision")
+                                .selected_text(tr(universe.universe_settings.precision.name()))
+                                .show_ui(ui, |ui| {
+                                    for precision in Precision::ALL {
+                                        ui.selectable_value(
+                                            &mut universe.universe_settings.precision,
+                                            precision,
+                                            tr(precision.name()),
+                                        );
+                                    }
+                                })
+                                .response
+                                .labelled_by(label_id);
+                            ui.end_row();
+
+                            // Create a checkbox to toggle ignoring gravity beyond the interaction cutoff, and widgets to modify the cutoff and the fraction of it gravity tapers off over.
+                            let universe_settings = &mut universe.universe_settings;
+                            let units = universe_settings.units;
+                            let label_id = ui.label(tr("Interaction Cutoff")).id;
+                            ui.add(Checkbox::new(
+                                &mut universe_settings.enable_interaction_cutoff,
+                                "",
+                            ))
+                            .on_hover_text(tr(
+                                "Ignores gravity between bodies farther apart than the cutoff, trading accuracy for speed",
+                            ))
+                            .labelled_by(label_id);
+                            ui.end_row();
+                            if universe_settings.enable_interaction_cutoff {
+                                let label_id = ui.label(tr("Cutoff Radius")).id;
+                                let mut interaction_cutoff = units.display_value(
+                                    Quantity::Length,
+                                    universe_settings.interaction_cutoff,
+                                );
+                                if ui
+                                    .add(
+                                        DragValue::new(&mut interaction_cutoff)
+                                            .speed(1.0)
+                                            .suffix(format!(
+                                                " {}",
+                                                units.symbol(Quantity::Length)
+                                            ))
+                                            .clamp_range(f64::EPSILON..=f64::MAX),
+                                    )
+                                    .labelled_by(label_id)
+                                    .changed()
+                                {
+                                    universe_settings.interaction_cutoff = units
+                                        .simulation_value(Quantity::Length, interaction_cutoff);
+                                }
+                                ui.end_row();
+                                let label_id = ui.label(tr("Cutoff Taper")).id;
+                                ui.add(Slider::new(&mut universe_settings.cutoff_taper, 0.0..=1.0))
+                                    .labelled_by(label_id);
+                                ui.end_row();
+                            }
+                        }
+
+                        // Create a combo box to select the resolution of the mesh if the particle mesh solver is used.
+                        if universe.universe_settings.solver == Solver::ParticleMesh {
+                            let label_id = ui.label(tr("Mesh Resolution")).id;
+                            ComboBox::from_id_source("Mesh Resolution")
+                                .selected_text(format!(
+                                    "{0}×{0}",
+                                    universe.universe_settings.mesh_resolution
+                                ))
+                                .show_ui(ui, |ui| {
+                                    for resolution in [32, 64, 128, 256] {
+                                        ui.selectable_value(
+                                            &mut universe.universe_settings.mesh_resolution,
+                                            resolution,
+                                            format!("{0}×{0}", resolution),
+                                        );
+                                    }
+                                })
+                                .response
+                                .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
                         // Create a checkbox to toggle collision for the given universe.
-                        ui.label("Enable Collisions");
+                        let label_id = ui.label(tr("Enable Collisions")).id;
+                        let response = ui
+                            .add(Checkbox::new(
+                                &mut universe.universe_settings.enable_collisions,
+                                "",
+                            ))
+                            .labelled_by(label_id);
+                        self.tutorial.anchor(TutorialStep::Collisions, &response);
+                        ui.end_row();
+
+                        // Create a combo box to select what happens to ejected bodies in the given universe.
+                        let label_id = ui.label(tr("Ejected Bodies")).id;
+                        ComboBox::from_id_source("Ejected Bodies")
+                            .selected_text(tr(universe.universe_settings.ejection_mode.name()))
+                            .show_ui(ui, |ui| {
+                                for ejection_mode in EjectionMode::ALL {
+                                    ui.selectable_value(
+                                        &mut universe.universe_settings.ejection_mode,
+                                        ejection_mode,
+                                        tr(ejection_mode.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a drag value to modify the distance from the center of mass that bodies can be ejected beyond.
+                        let label_id = ui.label(tr("Ejection Radius")).id;
+                        ui.add(
+                            DragValue::new(&mut universe.universe_settings.ejection_radius)
+                                .clamp_range(0.0..=f64::MAX),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle mass transfer during close passes for the given universe.
+                        let label_id = ui.label(tr("Enable Mass Transfer")).id;
                         ui.add(Checkbox::new(
-                            &mut universe.universe_settings.enable_collisions,
+                            &mut universe.universe_settings.enable_mass_transfer,
                             "",
-                        ));
+                        ))
+                        .labelled_by(label_id);
                         ui.end_row();
 
+                        // Create drag values to modify the capture radius as a multiple of the sum of the radii of two bodies, and the fraction of mass transferred per second.
+                        let label_id = ui.label(tr("Capture Radius")).id;
+                        ui.add(
+                            DragValue::new(&mut universe.universe_settings.capture_radius)
+                                .speed(0.01)
+                                .clamp_range(1.0..=f64::MAX)
+                                .suffix("× radii"),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        let label_id = ui.label(tr("Transfer Rate")).id;
+                        ui.add(
+                            DragValue::new(&mut universe.universe_settings.mass_transfer_rate)
+                                .speed(0.001)
+                                .clamp_range(0.0..=f64::MAX)
+                                .suffix(" /s"),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a drag value to modify the accretion radius of black holes as a multiple of their radius.
+                        let label_id = ui.label(tr("Accretion Radius")).id;
+                        ui.add(
+                            DragValue::new(&mut universe.universe_settings.accretion_radius)
+                                .speed(0.01)
+                                .clamp_range(1.0..=f64::MAX)
+                                .suffix("× radius"),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle the first post-Newtonian correction and a drag value to modify the speed of light it uses.
+                        let label_id = ui.label(tr("1PN Correction")).id;
+                        ui.add(Checkbox::new(
+                            &mut universe.universe_settings.enable_relativity,
+                            "",
+                        ))
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        if universe.universe_settings.enable_relativity {
+                            let label_id = ui.label(tr("Speed of Light")).id;
+                            ui.add(
+                                DragValue::new(&mut universe.universe_settings.speed_of_light)
+                                    .speed(1.0)
+                                    .clamp_range(1.0..=f64::MAX),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a checkbox to toggle the speed cap and a drag value to modify the speed it limits bodies to.
+                        let units = universe.universe_settings.units;
+                        let label_id = ui.label(tr("Speed Cap")).id;
+                        ui.add(Checkbox::new(
+                            &mut universe.universe_settings.enable_speed_cap,
+                            "",
+                        ))
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        if universe.universe_settings.enable_speed_cap {
+                            let label_id = ui.label(tr("Max Speed")).id;
+                            let mut max_speed = units.display_value(
+                                Quantity::Velocity,
+                                universe.universe_settings.max_speed,
+                            );
+                            if ui
+                                .add(
+                                    DragValue::new(&mut max_speed)
+                                        .speed(1.0)
+                                        .suffix(format!(" {}", units.symbol(Quantity::Velocity)))
+                                        .clamp_range(f64::EPSILON..=f64::MAX),
+                                )
+                                .labelled_by(label_id)
+                                .changed()
+                            {
+                                universe.universe_settings.max_speed =
+                                    units.simulation_value(Quantity::Velocity, max_speed);
+                            }
+                            ui.end_row();
+                        }
+
+                        // Create a checkbox to toggle pausing when the values of bodies stop being finite.
+                        let label_id = ui.label(tr("Pause on Blowup")).id;
+                        ui.add(Checkbox::new(
+                            &mut universe.universe_settings.pause_on_blowup,
+                            "",
+                        ))
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create a checkbox to toggle gas drag for the given universe.
+                        let universe_settings = &mut universe.universe_settings;
+                        let label_id = ui.label(tr("Enable Gas Drag")).id;
+                        ui.add(Checkbox::new(&mut universe_settings.enable_gas_drag, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create widgets to modify the strength of gas drag and the density profile of the gas.
+                        if universe_settings.enable_gas_drag {
+                            let label_id = ui.label(tr("Gas Drag")).id;
+                            ui.add(
+                                DragValue::new(&mut universe_settings.gas_drag)
+                                    .speed(0.001)
+                                    .clamp_range(0.0..=f64::MAX)
+                                    .suffix(" /s"),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                            let label_id = ui.label(tr("Gas Profile")).id;
+                            ComboBox::from_id_source("Gas Profile")
+                                .selected_text(tr(universe_settings.gas_profile.name()))
+                                .show_ui(ui, |ui| {
+                                    for gas_profile in GasProfile::ALL {
+                                        ui.selectable_value(
+                                            &mut universe_settings.gas_profile,
+                                            gas_profile,
+                                            tr(gas_profile.name()),
+                                        );
+                                    }
+                                })
+                                .response
+                                .labelled_by(label_id);
+                            ui.end_row();
+                            if universe_settings.gas_profile != GasProfile::Uniform {
+                                let label_id = ui.label(tr("Gas Scale")).id;
+                                ui.add(
+                                    DragValue::new(&mut universe_settings.gas_scale)
+                                        .clamp_range(f64::EPSILON..=f64::MAX),
+                                )
+                                .labelled_by(label_id);
+                                ui.end_row();
+                            }
+                            if universe_settings.gas_profile == GasProfile::PowerLaw {
+                                let label_id = ui.label(tr("Gas Exponent")).id;
+                                ui.add(
+                                    DragValue::new(&mut universe_settings.gas_exponent).speed(0.01),
+                                )
+                                .labelled_by(label_id);
+                                ui.end_row();
+                            }
+                        }
+
+                        // Create a combo box to select the background potential of the given universe.
+                        let label_id = ui.label(tr("Background Potential")).id;
+                        ComboBox::from_id_source("Background Potential")
+                            .selected_text(tr(universe_settings.background_potential.name()))
+                            .show_ui(ui, |ui| {
+                                for background_potential in BackgroundPotential::ALL {
+                                    ui.selectable_value(
+                                        &mut universe_settings.background_potential,
+                                        background_potential,
+                                        tr(background_potential.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create drag values to modify the parameters that the background potential uses.
+                        let background_potential = universe_settings.background_potential;
+                        if background_potential != BackgroundPotential::None {
+                            let label_id = ui.label(tr("Background Center")).id;
+                            ui.add(DragValue::new(&mut universe_settings.background_center.x))
+                                .labelled_by(label_id);
+                            ui.add(DragValue::new(&mut universe_settings.background_center.y))
+                                .labelled_by(label_id);
+                            ui.end_row();
+                        }
+                        if background_potential.has_mass() {
+                            let label_id = ui.label(tr("Background Mass")).id;
+                            ui.add(
+                                DragValue::new(&mut universe_settings.background_mass)
+                                    .clamp_range(0.0..=f64::MAX),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                        }
+                        if background_potential.has_scale() {
+                            let label_id = ui.label(tr("Background Scale")).id;
+                            ui.add(
+                                DragValue::new(&mut universe_settings.background_scale)
+                                    .clamp_range(f64::EPSILON..=f64::MAX),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                        }
+                        if background_potential == BackgroundPotential::LogarithmicHalo {
+                            let label_id = ui.label(tr("Circular Velocity")).id;
+                            ui.add(
+                                DragValue::new(&mut universe_settings.background_velocity)
+                                    .clamp_range(0.0..=f64::MAX),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a combo box to select the unit system of the given universe.
+                        let units = &mut universe.universe_settings.units;
+                        let label_id = ui.label(tr("Unit System")).id;
+                        ComboBox::from_id_source("Unit System")
+                            .selected_text(tr(units.unit_system.name()))
+                            .show_ui(ui, |ui| {
+                                for unit_system in UnitSystem::ALL {
+                                    ui.selectable_value(
+                                        &mut units.unit_system,
+                                        unit_system,
+                                        tr(unit_system.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create drag values to modify how many display units one simulation unit of length, mass, and time is.
+                        for (label, scale, quantity) in [
+                            ("Length Scale", &mut units.length_scale, Quantity::Length),
+                            ("Mass Scale", &mut units.mass_scale, Quantity::Mass),
+                            ("Time Scale", &mut units.time_scale, Quantity::Time),
+                        ] {
+                            let label_id = ui.label(tr(label)).id;
+                            ui.add(
+                                DragValue::new(scale)
+                                    .speed(0.01)
+                                    .clamp_range(f64::EPSILON..=f64::MAX)
+                                    .suffix(format!(" {}", units.unit_system.symbol(quantity))),
+                            )
+                            .labelled_by(label_id);
+                            ui.end_row();
+                        }
+
+                        // Create a button to set the gravitational constant to its physical value if the unit system is physical.
+                        if let Some(real_gravitational_constant) =
+                            units.real_gravitational_constant()
+                        {
+                            if ui.button(tr("Use Real Gravitational Constant")).clicked() {
+                                universe.universe_settings.gravitational_constant =
+                                    real_gravitational_constant;
+                            }
+                            ui.end_row();
+                        }
+
                         // Create a button to reset the universe settings.
-                        if ui.button("Reset").clicked() {
+                        if ui.button(tr("Reset")).clicked() {
                             universe.universe_settings = UniverseSettings::default();
                         }
                         ui.end_row();
                     });
 
                 // Create a collapsing window to contain the generation settings.
-                CollapsingHeader::new("Generation Settings")
+                CollapsingHeader::new(tr("Generation Settings"))
                     .default_open(true)
+                    .open(self.tutorial.open_section(TutorialStep::Generate))
                     .show(ui, |ui| {
+                        // Store the units that the generation settings are given in.
+                        let units = universe.universe_settings.units;
+
                         // Create a drag value to modify the seed of the generation settings.
-                        ui.label("Seed");
-                        ui.add(DragValue::new(&mut self.generation_settings.seed));
+                        let label_id = ui.label(tr("Seed")).id;
+                        ui.add(DragValue::new(&mut self.generation_settings.seed))
+                            .labelled_by(label_id);
                         ui.end_row();
 
-                        // Create a drag value to modify the body amount of the generation settings.
-                        ui.label("Body Amount");
-                        ui.add(DragValue::new(&mut self.generation_settings.body_amount));
+                        // Create a combo box to select the random number generator of the generation settings.
+                        let label_id = ui.label(tr("Random Number Generator")).id;
+                        ComboBox::from_id_source("Random Number Generator")
+                            .selected_text(tr(self.generation_settings.rng_kind.name()))
+                            .show_ui(ui, |ui| {
+                                for rng_kind in RngKind::ALL {
+                                    ui.selectable_value(
+                                        &mut self.generation_settings.rng_kind,
+                                        rng_kind,
+                                        tr(rng_kind.name()),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label_id);
                         ui.end_row();
 
-                        // Create a drag value to modify the position range of the generation settings that is bounded between 0.0 and the maximum f64 value.
-                        ui.label("Position Range");
-                        ui.add(
-                            DragValue::new(&mut self.generation_settings.position_range.start)
-                                .clamp_range(0.0..=self.generation_settings.position_range.end),
-                        );
-                        ui.add(
-                            DragValue::new(&mut self.generation_settings.position_range.end)
-                                .clamp_range(
-                                    self.generation_settings.position_range.start..=std::f64::MAX,
-                                ),
-                        );
+                        // Create a collapsing header to contain drag values to modify the seeds of the position, velocity, and mass streams, where 0 derives them from the seed.
+                        CollapsingHeader::new(tr("Sub-Seeds"))
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                let sub_seeds = &mut self.generation_settings.sub_seeds;
+                                for (label, sub_seed) in [
+                                    ("Position Seed", &mut sub_seeds.position),
+                                    ("Velocity Seed", &mut sub_seeds.velocity),
+                                    ("Mass Seed", &mut sub_seeds.mass),
+                                ] {
+                                    let label_id = ui.label(tr(label)).id;
+                                    ui.add(DragValue::new(sub_seed)).labelled_by(label_id);
+                                    ui.end_row();
+                                }
+                                ui.label(tr("A sub-seed of 0 is derived from the seed."));
+                                ui.end_row();
+                            });
+
+                        // Create a collapsing header for each cluster of the generation settings.
+                        let mut removed_cluster = None;
+                        for (index, cluster) in
+                            self.generation_settings.clusters.iter_mut().enumerate()
+                        {
+                            CollapsingHeader::new(tr_format("Cluster {}", &[&(index + 1)]))
+                                .default_open(index == 0)
+                                .show(ui, |ui| {
+                                    show_cluster_settings(ui, index, cluster, &units);
+
+                                    // Create a button to remove the cluster.
+                                    if ui.button(tr("Remove Cluster")).clicked() {
+                                        removed_cluster = Some(index);
+                                    }
+                                    ui.end_row();
+                                });
+                        }
+
+                        // Remove the cluster whose remove button was clicked.
+                        if let Some(index) = removed_cluster {
+                            self.generation_settings.clusters.remove(index);
+                        }
+
+                        // Create a button to add a new cluster.
+                        if ui.button(tr("Add Cluster")).clicked() {
+                            self.generation_settings
+                                .clusters
+                                .push(ClusterSettings::default());
+                        }
                         ui.end_row();
 
-                        // Create a drag value to modify the velocity range of the generation settings that is bounded between 0.0 and the maximum f64 value.
-                        ui.label("Velocity Range");
-                        ui.add(
-                            DragValue::new(&mut self.generation_settings.velocity_range.start)
-                                .clamp_range(0.0..=self.generation_settings.velocity_range.end),
-                        );
-                        ui.add(
-                            DragValue::new(&mut self.generation_settings.velocity_range.end)
-                                .clamp_range(
-                                    self.generation_settings.velocity_range.start..=std::f64::MAX,
-                                ),
-                        );
+                        // Create a progress bar and a cancel button while generating, or a button to generate the bodies for the given universe otherwise.
+                        if let Some(generation_task) = &self.generation_task {
+                            ui.add(ProgressBar::new(generation_task.progress()).show_percentage());
+                            if ui.button(tr("Cancel")).clicked() {
+                                generation_task.cancel();
+                            }
+                        } else {
+                            let response = ui.button(tr("Generate Bodies"));
+                            self.tutorial.anchor(TutorialStep::Generate, &response);
+                            if response.clicked() {
+                                self.generate(universe);
+                            }
+                        }
                         ui.end_row();
 
-                        // Create a drag value to modify the mass range of the generation settings that is bounded between the f64 epsilon and maximum value.
-                        ui.label("Mass Range");
+                        // Create a button to reset the generation settings.
+                        if ui.button(tr("Reset")).clicked() {
+                            self.generation_settings = GenerationSettings::default();
+                        }
+                        ui.end_row();
+                    });
+
+                // Create a collapsing header to contain the gallery, regenerating the run of an entry with its settings when it's restored.
+                CollapsingHeader::new(tr("Gallery"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        if let Some((generation_settings, universe_settings)) =
+                            self.gallery.show(ui, universe, &self.generation_settings)
+                        {
+                            self.generation_settings = generation_settings;
+                            universe.universe_settings = universe_settings;
+                            self.generate(universe);
+                        }
+                    });
+
+                // Create a collapsing header to save and open workspaces, which restore exactly what was on screen, unlike universe saves.
+                CollapsingHeader::new(tr("Workspace"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        // Create a text field to enter the path of the workspace.
+                        let label_id = ui.label(tr("Path")).id;
                         ui.add(
-                            DragValue::new(&mut self.generation_settings.mass_range.start)
-                                .clamp_range(
-                                    std::f64::EPSILON..=self.generation_settings.mass_range.end,
-                                ),
-                        );
+                            TextEdit::singleline(&mut self.workspace_path)
+                                .hint_text("workspaces/workspace.json"),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create buttons to save and open the workspace, and ones to pick the path with file dialogs if they're available.
+                        ui.horizontal(|ui| {
+                            if ui.button(tr("Save Workspace")).clicked() {
+                                self.save_workspace(camera, universe, scene_renderer);
+                            }
+                            if ui.button(tr("Open Workspace")).clicked() {
+                                self.open_workspace(camera, universe, scene_renderer);
+                            }
+                        });
+                        if DIALOGS_AVAILABLE {
+                            ui.horizontal(|ui| {
+                                if ui.button(tr("Save Workspace As...")).clicked() {
+                                    if let Some(path) = save_file(
+                                        tr("Save Workspace"),
+                                        &[JSON_FILTER],
+                                        "workspace.json",
+                                    ) {
+                                        self.workspace_path = path.display().to_string();
+                                        self.save_workspace(camera, universe, scene_renderer);
+                                    }
+                                }
+                                if ui.button(tr("Open Workspace...")).clicked() {
+                                    if let Some(path) =
+                                        open_file(tr("Open Workspace"), &[JSON_FILTER])
+                                    {
+                                        self.workspace_path = path.display().to_string();
+                                        self.open_workspace(camera, universe, scene_renderer);
+                                    }
+                                }
+                            });
+                        }
+                        ui.label(&self.workspace_status);
+                        ui.end_row();
+                    });
+
+                // Create a collapsing header to load and play scenarios.
+                CollapsingHeader::new(tr("Scenario Timeline"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        // Deselect the body if the scenario replaced the bodies.
+                        if self.timeline.show(ui, universe) {
+                            self.inspector.select(None);
+                        }
+                    });
+
+                // Create a collapsing header to load and refine periodic three-body orbits.
+                CollapsingHeader::new(tr("Periodic Orbits"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        // Deselect the body if the orbit replaced the bodies.
+                        if self.periodic_orbit_finder.show(ui, universe) {
+                            self.inspector.select(None);
+                        }
+                    });
+
+                // Create a collapsing header to contain the importer.
+                CollapsingHeader::new(tr("Import"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        // Deselect the body if the import replaced the bodies.
+                        if self.importer.show(ui, universe) {
+                            self.inspector.select(None);
+                        }
+                    });
+
+                // Create a collapsing header to contain the exporter.
+                CollapsingHeader::new(tr("Export"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.exporter.show(ui, universe);
+                    });
+
+                // Create a collapsing header to contain the trajectory recorder.
+                CollapsingHeader::new(tr("Trajectory Export"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.trajectory_recorder
+                            .show(ui, universe, self.inspector.selected);
+                    });
+
+                // Create a collapsing header to contain the run summary.
+                CollapsingHeader::new(tr("Run Summary"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        // Create a button to summarize the run, which is done on demand since measuring the energy drift visits every pair of bodies.
+                        if ui.button(tr("Summarize Run")).clicked() {
+                            self.run_summary = Some(RunSummary::new(universe));
+                            self.run_summary_status.clear();
+                        }
+                        ui.end_row();
+
+                        // Show the last summary with a button to export it.
+                        if let Some(run_summary) = &self.run_summary {
+                            run_summary.show(ui, &universe.universe_settings.units);
+                            ui.horizontal(|ui| {
+                                if ui.button(tr("Export JSON")).clicked() {
+                                    self.run_summary_status = match run_summary.export() {
+                                        Ok(path) => tr_format("Exported to {}", &[&path.display()]),
+                                        Err(error) => tr_format("Export failed: {}", &[&error]),
+                                    };
+                                }
+                                if DIALOGS_AVAILABLE && ui.button(tr("Export JSON As...")).clicked()
+                                {
+                                    if let Some(path) = save_file(
+                                        tr("Export Run Summary"),
+                                        &[JSON_FILTER],
+                                        &RunSummary::file_name(),
+                                    ) {
+                                        self.run_summary_status = match run_summary.save(&path) {
+                                            Ok(()) => {
+                                                tr_format("Exported to {}", &[&path.display()])
+                                            }
+                                            Err(error) => tr_format("Export failed: {}", &[&error]),
+                                        };
+                                    }
+                                }
+                            });
+                            ui.label(&self.run_summary_status);
+                            ui.end_row();
+                        }
+                    });
+
+                // Create a collapsing header to contain the gamepad settings.
+                #[cfg(feature = "gamepad")]
+                CollapsingHeader::new(tr("Gamepad"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.gamepad.show(ui);
+                    });
+
+                // Create a collapsing header to contain the camera bookmarks.
+                CollapsingHeader::new(tr("Bookmarks"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.bookmarks.show(ui, camera);
+                    });
+
+                // Create a collapsing header to contain the hotkeys.
+                CollapsingHeader::new(tr("Hotkeys"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.hotkeys.show(ui);
+                    });
+
+                // Create a collapsing header to contain the autosave settings.
+                CollapsingHeader::new(tr("Autosave"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let autosave_settings = &mut self.autosave.autosave_settings;
+
+                        // Create a checkbox to toggle autosaving.
+                        let label_id = ui.label(tr("Enable Autosave")).id;
+                        ui.add(Checkbox::new(&mut autosave_settings.enabled, ""))
+                            .labelled_by(label_id);
+                        ui.end_row();
+
+                        // Create drag values to modify how many minutes and steps pass between autosaves, where 0 disables the interval.
+                        let label_id = ui.label(tr("Interval (Minutes)")).id;
                         ui.add(
-                            DragValue::new(&mut self.generation_settings.mass_range.end)
-                                .clamp_range(
-                                    self.generation_settings.mass_range.start..=std::f64::MAX,
-                                ),
-                        );
+                            DragValue::new(&mut autosave_settings.interval_minutes)
+                                .speed(0.1)
+                                .clamp_range(0.0..=f64::MAX),
+                        )
+                        .labelled_by(label_id);
+                        ui.end_row();
+                        let label_id = ui.label(tr("Interval (Steps)")).id;
+                        ui.add(DragValue::new(&mut autosave_settings.interval_steps))
+                            .labelled_by(label_id);
                         ui.end_row();
 
-                        // Create a checkbox to toggle tangential velocity.
-                        ui.label("Tangential Velocity");
-                        ui.add(Checkbox::new(
-                            &mut self.generation_settings.tangential_velocity,
-                            "",
-                        ));
+                        // Create a drag value to modify how many autosave files are rotated between.
+                        let label_id = ui.label(tr("Slots")).id;
+                        ui.add(
+                            DragValue::new(&mut autosave_settings.slot_amount)
+                                .clamp_range(1..=usize::MAX),
+                        )
+                        .labelled_by(label_id);
                         ui.end_row();
 
-                        // Create a button to generate the bodies for the given universe.
-                        if ui.button("Generate Bodies").clicked() {
-                            universe.generate_bodies(&self.generation_settings);
+                        // Create a button to restore the universe from the last autosave, deselecting the body if the bodies were replaced.
+                        if ui.button(tr("Restore Last Autosave")).clicked()
+                            && self.autosave.restore(universe)
+                        {
+                            self.inspector.select(None);
                         }
+                        ui.label(&self.autosave.status);
                         ui.end_row();
 
-                        // Create a button to reset the generation settings.
-                        if ui.button("Reset").clicked() {
-                            self.generation_settings = GenerationSettings::default();
+                        // Create a button to reset the autosave settings.
+                        if ui.button(tr("Reset")).clicked() {
+                            self.autosave.autosave_settings = AutosaveSettings::default();
                         }
                         ui.end_row();
                     });
 
+                // Create a collapsing header to contain the parameter sweep.
+                CollapsingHeader::new(tr("Parameter Sweep"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.sweep.show(ui, universe, &self.generation_settings);
+                    });
+
+                // Create a collapsing header to contain the ensemble mode.
+                CollapsingHeader::new(tr("Ensemble"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        self.ensemble.show(ui, universe, &self.generation_settings);
+                    });
+
+                // Create a collapsing header to contain the long-run mode.
+                CollapsingHeader::new(tr("Long Run"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        if self
+                            .long_run
+                            .show(ui, universe, &mut self.generation_settings)
+                        {
+                            self.inspector.select(None);
+                        }
+                    });
+
                 // Create an exit button that exits the app if clicked.
-                if ui.button("Exit App").clicked() {
+                if ui.button(tr("Exit App")).clicked() {
                     app.exit();
                 }
                 ui.end_row();
             });
+
+        // Show the aggregate of the bodies under the mouse.
+        self.inspector.show_aggregate(context, universe);
+
+        // Show the minimap.
+        self.minimap.show(context, app, camera, universe);
+
+        // Show the status bar with the cursor coordinates.
+        self.status_bar.show(context, app, camera, universe);
+
+        // Show the indicator of the soft pause if the universe is paused while the window is unfocused.
+        self.focus_pause.show(context);
+
+        // Show the console if it's open.
+        self.console.show(context, universe);
+
+        // Show the callout of the current tutorial step.
+        self.tutorial.show(context);
+
+        // Store whether the pointer is over the UI so clicks aren't passed through to the world.
+        self.pointer_over_ui = context.is_pointer_over_area();
+        // Store whether the UI is taking keyboard input or has a focused widget, so typing and navigating the UI don't trigger hotkeys.
+        self.keyboard_over_ui =
+            context.wants_keyboard_input() || context.memory(|memory| memory.focus().is_some());
+    }
+}
+
+// Show the settings of a generation cluster in the given UI, with values in the given units.
+fn show_cluster_settings(ui: &mut Ui, index: usize, cluster: &mut ClusterSettings, units: &Units) {
+    // Create a drag value to modify the body amount of the cluster.
+    let label_id = ui.label(tr("Body Amount")).id;
+    ui.add(DragValue::new(&mut cluster.body_amount))
+        .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a checkbox to toggle whether the cluster is made of test particles that feel gravity but exert none and never collide.
+    let label_id = ui.label(tr("Test Particles")).id;
+    ui.add(Checkbox::new(&mut cluster.test_particles, ""))
+        .labelled_by(label_id);
+    ui.end_row();
+
+    // Create drag values to modify the center of the cluster.
+    let label_id = ui.label(tr("Center")).id;
+    ui.add(
+        DragValue::new(&mut cluster.center.x)
+            .suffix(format!(" {}", units.symbol(Quantity::Length))),
+    )
+    .labelled_by(label_id);
+    ui.add(
+        DragValue::new(&mut cluster.center.y)
+            .suffix(format!(" {}", units.symbol(Quantity::Length))),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create drag values to modify the bulk velocity of the cluster.
+    let label_id = ui.label(tr("Bulk Velocity")).id;
+    ui.add(
+        DragValue::new(&mut cluster.bulk_velocity.x)
+            .suffix(format!(" {}", units.symbol(Quantity::Velocity))),
+    )
+    .labelled_by(label_id);
+    ui.add(
+        DragValue::new(&mut cluster.bulk_velocity.y)
+            .suffix(format!(" {}", units.symbol(Quantity::Velocity))),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a drag value to modify the position range of the cluster that is bounded between 0.0 and the maximum f64 value.
+    let label_id = ui.label(tr("Position Range")).id;
+    ui.add(
+        DragValue::new(&mut cluster.position_range.start)
+            .suffix(format!(" {}", units.symbol(Quantity::Length)))
+            .clamp_range(0.0..=cluster.position_range.end),
+    )
+    .labelled_by(label_id);
+    ui.add(
+        DragValue::new(&mut cluster.position_range.end)
+            .suffix(format!(" {}", units.symbol(Quantity::Length)))
+            .clamp_range(cluster.position_range.start..=f64::MAX),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a combo box to select the radial profile of the cluster.
+    let label_id = ui.label(tr("Radial Profile")).id;
+    ComboBox::from_id_source(("Radial Profile", index))
+        .selected_text(tr(cluster.radial_profile.name()))
+        .show_ui(ui, |ui| {
+            for radial_profile in RadialProfile::ALL {
+                ui.selectable_value(
+                    &mut cluster.radial_profile,
+                    radial_profile,
+                    tr(radial_profile.name()),
+                );
+            }
+        })
+        .response
+        .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a drag value to modify the scale length of the radial profile if it uses one.
+    if cluster.radial_profile.has_scale() {
+        let label_id = ui.label(tr("Radial Scale")).id;
+        ui.add(
+            DragValue::new(&mut cluster.radial_scale)
+                .suffix(format!(" {}", units.symbol(Quantity::Length)))
+                .clamp_range(f64::EPSILON..=f64::MAX),
+        )
+        .labelled_by(label_id);
+        ui.end_row();
+    }
+
+    // Create a drag value to modify the ratio between the minor and major axes of the cluster.
+    let label_id = ui.label(tr("Axis Ratio")).id;
+    ui.add(
+        DragValue::new(&mut cluster.axis_ratio)
+            .speed(0.01)
+            .clamp_range(0.0..=1.0),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a drag value to modify the rotation angle of the cluster.
+    let label_id = ui.label(tr("Rotation Angle")).id;
+    ui.add(DragValue::new(&mut cluster.rotation_angle).suffix("°"))
+        .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a collapsing header to contain a preview histogram of the radial profile.
+    CollapsingHeader::new(tr("Radial Preview"))
+        .id_source(("Radial Preview", index))
+        .show(ui, |ui| {
+            show_histogram(ui, &cluster.radial_histogram(2000, 32));
+        });
+
+    // Create a drag value to modify the velocity range of the cluster that is bounded between 0.0 and the maximum f64 value.
+    let label_id = ui.label(tr("Velocity Range")).id;
+    ui.add(
+        DragValue::new(&mut cluster.velocity_range.start)
+            .suffix(format!(" {}", units.symbol(Quantity::Velocity)))
+            .clamp_range(0.0..=cluster.velocity_range.end),
+    )
+    .labelled_by(label_id);
+    ui.add(
+        DragValue::new(&mut cluster.velocity_range.end)
+            .suffix(format!(" {}", units.symbol(Quantity::Velocity)))
+            .clamp_range(cluster.velocity_range.start..=f64::MAX),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a drag value to modify the mass range of the cluster that is bounded between the f64 epsilon and maximum value.
+    let label_id = ui.label(tr("Mass Range")).id;
+    ui.add(
+        DragValue::new(&mut cluster.mass_range.start)
+            .suffix(format!(" {}", units.symbol(Quantity::Mass)))
+            .clamp_range(f64::EPSILON..=cluster.mass_range.end),
+    )
+    .labelled_by(label_id);
+    ui.add(
+        DragValue::new(&mut cluster.mass_range.end)
+            .suffix(format!(" {}", units.symbol(Quantity::Mass)))
+            .clamp_range(cluster.mass_range.start..=f64::MAX),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a combo box to select the mass distribution of the cluster.
+    let label_id = ui.label(tr("Mass Distribution")).id;
+    ComboBox::from_id_source(("Mass Distribution", index))
+        .selected_text(tr(cluster.mass_distribution.name()))
+        .show_ui(ui, |ui| {
+            for mass_distribution in MassDistribution::ALL {
+                ui.selectable_value(
+                    &mut cluster.mass_distribution,
+                    mass_distribution,
+                    tr(mass_distribution.name()),
+                );
+            }
+        })
+        .response
+        .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a drag value to modify the slope of the power law mass distribution.
+    if cluster.mass_distribution == MassDistribution::PowerLaw {
+        let label_id = ui.label(tr("Mass Slope")).id;
+        ui.add(DragValue::new(&mut cluster.mass_slope).speed(0.01))
+            .labelled_by(label_id);
+        ui.end_row();
+    }
+
+    // Create a drag value to modify the amplitude of the rotation curve of the cluster.
+    let label_id = ui.label(tr("Rotation Velocity")).id;
+    ui.add(
+        DragValue::new(&mut cluster.rotation_velocity)
+            .suffix(format!(" {}", units.symbol(Quantity::Velocity))),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create a drag value to modify the isotropic velocity dispersion of the cluster.
+    let label_id = ui.label(tr("Velocity Dispersion")).id;
+    ui.add(
+        DragValue::new(&mut cluster.velocity_dispersion)
+            .suffix(format!(" {}", units.symbol(Quantity::Velocity)))
+            .clamp_range(0.0..=f64::MAX),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+
+    // Create widgets to modify the fraction of bodies that get moons, how many each gets, and how many levels of moons orbit moons.
+    let label_id = ui.label(tr("Moon Fraction")).id;
+    ui.add(Slider::new(&mut cluster.moon_fraction, 0.0..=1.0))
+        .labelled_by(label_id);
+    ui.end_row();
+    let label_id = ui.label(tr("Max Moons per Body")).id;
+    ui.add(DragValue::new(&mut cluster.moon_amount).clamp_range(1..=16))
+        .labelled_by(label_id);
+    ui.end_row();
+    let label_id = ui.label(tr("Moon Levels")).id;
+    ui.add(DragValue::new(&mut cluster.moon_levels).clamp_range(1..=3))
+        .labelled_by(label_id);
+    ui.end_row();
+
+    // Create widgets to modify the mass of moons as a fraction of their parent and their distance in radii of their parent.
+    let label_id = ui.label(tr("Moon Mass Ratio")).id;
+    ui.add(
+        DragValue::new(&mut cluster.moon_mass_ratio)
+            .speed(0.001)
+            .clamp_range(0.0..=1.0),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+    let label_id = ui.label(tr("Moon Distance")).id;
+    ui.add(
+        DragValue::new(&mut cluster.moon_distance_range.start)
+            .speed(0.1)
+            .suffix(format!(" {}", tr("radii")))
+            .clamp_range(1.0..=cluster.moon_distance_range.end),
+    )
+    .labelled_by(label_id);
+    ui.add(
+        DragValue::new(&mut cluster.moon_distance_range.end)
+            .speed(0.1)
+            .suffix(format!(" {}", tr("radii")))
+            .clamp_range(cluster.moon_distance_range.start..=f64::MAX),
+    )
+    .labelled_by(label_id);
+    ui.end_row();
+}
+
+// The most offending bodies listed in the blowup report.
+const BLOWUP_REPORT_LENGTH: usize = 20;
+
+// Show a window in the given context that reports the bodies of a universe whose values stopped being finite, with buttons to remove them or dismiss the report.
+fn show_blowup_report(context: &Context, universe: &mut Universe) {
+    if universe.blown_up_bodies.is_empty() {
+        return;
+    }
+
+    // Create a window for the report, with an id that stays the same in every language.
+    Window::new(tr("Numerical Blowup"))
+        .id(Id::new("Numerical Blowup"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
+        .show(context, |ui| {
+            // List the first of the offending bodies.
+            ui.label(tr_format(
+                "The values of {} bodies stopped being finite, so the universe was paused.",
+                &[&universe.blown_up_bodies.len()],
+            ));
+            let ids: Vec<String> = universe
+                .blown_up_bodies
+                .iter()
+                .take(BLOWUP_REPORT_LENGTH)
+                .map(|id| id.to_string())
+                .collect();
+            let mut text = ids.join(", ");
+            if universe.blown_up_bodies.len() > BLOWUP_REPORT_LENGTH {
+                text.push_str(", …");
+            }
+            ui.label(tr_format("Bodies: {}", &[&text]));
+            ui.label(tr(
+                "Lowering the gravitational constant or the time step, or enabling the speed cap, can prevent this.",
+            ));
+
+            // Create buttons to remove the offending bodies or dismiss the report.
+            ui.horizontal(|ui| {
+                if ui.button(tr("Remove Offending Bodies")).clicked() {
+                    universe.edit(|universe| universe.remove_blown_up_bodies());
+                }
+                if ui.button(tr("Dismiss")).clicked() {
+                    universe.edit(|universe| universe.blown_up_bodies.clear());
+                }
+            });
+        });
+}
+
+// Show a bar chart of a histogram in the given UI.
+fn show_histogram(ui: &mut Ui, histogram: &[usize]) {
+    // Allocate space for the histogram.
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 64.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    // Draw a bar for each bin scaled to the largest bin.
+    let maximum = histogram.iter().copied().max().unwrap_or_default().max(1) as f32;
+    let bar_width = rect.width() / histogram.len().max(1) as f32;
+    for (bin, count) in histogram.iter().enumerate() {
+        let height = rect.height() * *count as f32 / maximum;
+        painter.rect_filled(
+            Rect::from_min_max(
+                pos2(rect.left() + bin as f32 * bar_width, rect.bottom() - height),
+                pos2(
+                    rect.left() + (bin + 1) as f32 * bar_width - 1.0,
+                    rect.bottom(),
+                ),
+            ),
+            0.0,
+            ui.visuals().selection.bg_fill,
+        );
+    }
+}
+
+// The colors of each phase in the frame time bar chart.
+const PHASE_COLORS: [Color32; Phase::ALL.len()] = [
+    Color32::from_rgb(230, 90, 90),
+    Color32::from_rgb(90, 160, 230),
+    Color32::from_rgb(90, 210, 210),
+    Color32::from_rgb(120, 200, 120),
+    Color32::from_rgb(230, 190, 80),
+    Color32::from_rgb(180, 120, 220),
+];
+
+// Show the time spent in each phase of a frame and a stacked bar chart of them in the given UI.
+fn show_profiler(ui: &mut Ui, profiler: &Profiler) {
+    // Allocate space for the bar chart.
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 16.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    // Draw a section of the bar for each phase proportional to the time spent in it.
+    let total = profiler.total().max(f64::EPSILON);
+    let mut left = rect.left();
+    for (phase, color) in Phase::ALL.iter().zip(PHASE_COLORS) {
+        let width = rect.width() * (profiler.timing(*phase) / total) as f32;
+        painter.rect_filled(
+            Rect::from_min_max(pos2(left, rect.top()), pos2(left + width, rect.bottom())),
+            0.0,
+            color,
+        );
+        left += width;
+    }
+
+    // Show the time spent in each phase in milliseconds.
+    for (phase, color) in Phase::ALL.iter().zip(PHASE_COLORS) {
+        ui.colored_label(
+            color,
+            format!(
+                "{}: {:.2} ms",
+                tr(phase.name()),
+                profiler.timing(*phase) * 1000.0
+            ),
+        );
     }
+    ui.label(format!(
+        "{}: {:.2} ms",
+        tr("Total"),
+        profiler.total() * 1000.0
+    ));
 }
@@ -0,0 +1,154 @@
+// The gravitational constant in SI units (m³ kg⁻¹ s⁻²).
+const SI_GRAVITATIONAL_CONSTANT: f64 = 6.674_30e-11;
+
+// The length of an astronomical unit in meters.
+const ASTRONOMICAL_UNIT: f64 = 1.495_978_707e+11;
+// The mass of the sun in kilograms.
+const SOLAR_MASS: f64 = 1.988_47e+30;
+// The length of a julian year in seconds.
+const YEAR: f64 = 3.155_76e+7;
+
+use serde::{Deserialize, Serialize};
+
+// A kind of physical quantity that can be converted between simulation and display units.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Length,
+    Mass,
+    Time,
+    Velocity,
+}
+
+// A system of units that quantities are displayed and entered in.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitSystem {
+    Simulation,
+    Astronomical,
+    SI,
+}
+
+// Implementations for UnitSystem.
+impl UnitSystem {
+    // All unit systems, in the order they are listed in the UI.
+    pub const ALL: [UnitSystem; 3] = [
+        UnitSystem::Simulation,
+        UnitSystem::Astronomical,
+        UnitSystem::SI,
+    ];
+
+    // Return the name of a unit system.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnitSystem::Simulation => "Simulation",
+            UnitSystem::Astronomical => "Astronomical (AU, Msun, yr)",
+            UnitSystem::SI => "SI (m, kg, s)",
+        }
+    }
+
+    // Return the length, mass, and time of one display unit in meters, kilograms, and seconds, or None if the unit system isn't physical.
+    fn si_factors(&self) -> Option<(f64, f64, f64)> {
+        match self {
+            UnitSystem::Simulation => None,
+            UnitSystem::Astronomical => Some((ASTRONOMICAL_UNIT, SOLAR_MASS, YEAR)),
+            UnitSystem::SI => Some((1.0, 1.0, 1.0)),
+        }
+    }
+
+    // Return the symbol of a quantity in a unit system.
+    pub fn symbol(&self, quantity: Quantity) -> &'static str {
+        match (self, quantity) {
+            (UnitSystem::Simulation, _) => "",
+            (UnitSystem::Astronomical, Quantity::Length) => "AU",
+            (UnitSystem::Astronomical, Quantity::Mass) => "Msun",
+            (UnitSystem::Astronomical, Quantity::Time) => "yr",
+            (UnitSystem::Astronomical, Quantity::Velocity) => "AU/yr",
+            (UnitSystem::SI, Quantity::Length) => "m",
+            (UnitSystem::SI, Quantity::Mass) => "kg",
+            (UnitSystem::SI, Quantity::Time) => "s",
+            (UnitSystem::SI, Quantity::Velocity) => "m/s",
+        }
+    }
+}
+
+// A unit configuration that converts between simulation units and display units.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Units {
+    pub unit_system: UnitSystem,
+    pub length_scale: f64,
+    pub mass_scale: f64,
+    pub time_scale: f64,
+}
+
+// The default value for Units.
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            unit_system: UnitSystem::Simulation,
+            length_scale: 1.0,
+            mass_scale: 1.0,
+            time_scale: 1.0,
+        }
+    }
+}
+
+// Implementations for Units.
+impl Units {
+    // Return how many display units one simulation unit of a quantity is.
+    pub fn scale(&self, quantity: Quantity) -> f64 {
+        match quantity {
+            Quantity::Length => self.length_scale,
+            Quantity::Mass => self.mass_scale,
+            Quantity::Time => self.time_scale,
+            Quantity::Velocity => self.length_scale / self.time_scale,
+        }
+    }
+
+    // Convert a quantity from simulation units to display units.
+    pub fn display_value(&self, quantity: Quantity, value: f64) -> f64 {
+        value * self.scale(quantity)
+    }
+
+    // Convert a quantity from display units to simulation units.
+    pub fn simulation_value(&self, quantity: Quantity, value: f64) -> f64 {
+        value / self.scale(quantity)
+    }
+
+    // Return the symbol of a quantity in the current unit system.
+    pub fn symbol(&self, quantity: Quantity) -> &'static str {
+        self.unit_system.symbol(quantity)
+    }
+
+    // Format a quantity given in simulation units as a string in display units.
+    pub fn format(&self, quantity: Quantity, value: f64) -> String {
+        let value = self.display_value(quantity, value);
+        let symbol = self.symbol(quantity);
+
+        // Use scientific notation for very large or very small values.
+        let number = if value != 0.0 && !(1.0e-3..1.0e+6).contains(&value.abs()) {
+            format!("{:.3e}", value)
+        } else {
+            format!("{:.3}", value)
+        };
+
+        // Only append the symbol if there is one.
+        if symbol.is_empty() {
+            number
+        } else {
+            format!("{} {}", number, symbol)
+        }
+    }
+
+    // Return the physical gravitational constant expressed in simulation units, or None if the unit system isn't physical.
+    pub fn real_gravitational_constant(&self) -> Option<f64> {
+        // Find the size of each display unit in SI units.
+        let (length, mass, time) = self.unit_system.si_factors()?;
+
+        // Convert the SI gravitational constant to display units, then to simulation units.
+        let display_constant = SI_GRAVITATIONAL_CONSTANT * mass * time.powi(2) / length.powi(3);
+        Some(
+            display_constant * self.mass_scale * self.time_scale.powi(2)
+                / self.length_scale.powi(3),
+        )
+    }
+}
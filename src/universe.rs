@@ -1,39 +1,314 @@
-use crate::body::*;
-use notan::{
-    draw::*,
-    math::DVec2,
-    random::{rand::Rng, utils::Random},
+use crate::{
+    body::*, components::*, governor::*, mesh::*, pairs::*, pipeline::*, profiler::*, spatial::*,
+    units::*,
 };
-use std::{ops::Range, time::SystemTime};
+use notan::math::DVec2;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, thread};
 
-// Settings to generate the universe with.
-pub struct GenerationSettings {
-    pub seed: u64,
-    pub body_amount: usize,
-    pub position_range: Range<f64>,
-    pub velocity_range: Range<f64>,
-    pub mass_range: Range<f64>,
-    pub tangential_velocity: bool,
+// The time in seconds that a single requested step advances a paused universe by.
+pub const STEP_DELTA_TIME: f64 = 1.0 / 60.0;
+
+// What happens to bodies that are ejected from the universe.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EjectionMode {
+    Disabled,
+    Remove,
+    Freeze,
 }
 
-// Default value for GenerationSettings.
-impl Default for GenerationSettings {
-    fn default() -> Self {
-        Self {
-            seed: Default::default(),
-            body_amount: 2500,
-            position_range: 0.0..250.0,
-            velocity_range: 0.0..125.0,
-            mass_range: 1.0..10.0,
-            tangential_velocity: false,
+// Implementations for EjectionMode.
+impl EjectionMode {
+    // All ejection modes, in the order they are listed in the UI.
+    pub const ALL: [EjectionMode; 3] = [
+        EjectionMode::Disabled,
+        EjectionMode::Remove,
+        EjectionMode::Freeze,
+    ];
+
+    // Return the name of an ejection mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EjectionMode::Disabled => "Disabled",
+            EjectionMode::Remove => "Remove",
+            EjectionMode::Freeze => "Freeze",
+        }
+    }
+}
+
+// An analytic background potential that acts on all bodies in addition to the gravity between them.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackgroundPotential {
+    None,
+    PointMass,
+    Plummer,
+    LogarithmicHalo,
+    UniformDisc,
+}
+
+// Implementations for BackgroundPotential.
+impl BackgroundPotential {
+    // All background potentials, in the order they are listed in the UI.
+    pub const ALL: [BackgroundPotential; 5] = [
+        BackgroundPotential::None,
+        BackgroundPotential::PointMass,
+        BackgroundPotential::Plummer,
+        BackgroundPotential::LogarithmicHalo,
+        BackgroundPotential::UniformDisc,
+    ];
+
+    // Return the name of a background potential.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundPotential::None => "None",
+            BackgroundPotential::PointMass => "Point Mass",
+            BackgroundPotential::Plummer => "Plummer Sphere",
+            BackgroundPotential::LogarithmicHalo => "Logarithmic Halo",
+            BackgroundPotential::UniformDisc => "Uniform Disc",
+        }
+    }
+
+    // Return whether a background potential is set by its mass, as opposed to its circular velocity.
+    pub fn has_mass(&self) -> bool {
+        !matches!(
+            self,
+            BackgroundPotential::None | BackgroundPotential::LogarithmicHalo
+        )
+    }
+
+    // Return whether a background potential has a scale length.
+    pub fn has_scale(&self) -> bool {
+        !matches!(
+            self,
+            BackgroundPotential::None | BackgroundPotential::PointMass
+        )
+    }
+
+    // Return the acceleration caused by a background potential at the given position.
+    fn acceleration(&self, universe_settings: &UniverseSettings, position: DVec2) -> DVec2 {
+        // Find the offset from the center of the potential and its parameters.
+        let offset = position - universe_settings.background_center;
+        let radius = offset.length();
+        let gravitational_mass =
+            universe_settings.gravitational_constant * universe_settings.background_mass;
+        let scale = universe_settings.background_scale;
+
+        match self {
+            BackgroundPotential::None => DVec2::ZERO,
+            BackgroundPotential::PointMass if radius > 0.0 => {
+                -offset * gravitational_mass / radius.powi(3)
+            }
+            BackgroundPotential::PointMass => DVec2::ZERO,
+            BackgroundPotential::Plummer => {
+                -offset * gravitational_mass / (radius.powi(2) + scale.powi(2)).powf(1.5)
+            }
+            BackgroundPotential::LogarithmicHalo => {
+                -offset * universe_settings.background_velocity.powi(2)
+                    / (radius.powi(2) + scale.powi(2))
+            }
+            // Use the mass enclosed within the radius, which pulls with constant strength inside the disc.
+            BackgroundPotential::UniformDisc if radius > 0.0 => {
+                -offset / radius * gravitational_mass / radius.max(scale).powi(2)
+            }
+            BackgroundPotential::UniformDisc => DVec2::ZERO,
+        }
+    }
+
+    // Return the potential energy per unit mass of a background potential at the given position.
+    fn potential(&self, universe_settings: &UniverseSettings, position: DVec2) -> f64 {
+        // Find the distance from the center of the potential and its parameters.
+        let radius = position.distance(universe_settings.background_center);
+        let gravitational_mass =
+            universe_settings.gravitational_constant * universe_settings.background_mass;
+        let scale = universe_settings.background_scale;
+
+        match self {
+            BackgroundPotential::None => 0.0,
+            BackgroundPotential::PointMass if radius > 0.0 => -gravitational_mass / radius,
+            BackgroundPotential::PointMass => 0.0,
+            BackgroundPotential::Plummer => {
+                -gravitational_mass / (radius.powi(2) + scale.powi(2)).sqrt()
+            }
+            BackgroundPotential::LogarithmicHalo => {
+                0.5 * universe_settings.background_velocity.powi(2)
+                    * (radius.powi(2) + scale.powi(2)).ln()
+            }
+            BackgroundPotential::UniformDisc if radius < scale => {
+                gravitational_mass * ((radius - scale) / scale.powi(2) - 1.0 / scale)
+            }
+            BackgroundPotential::UniformDisc => -gravitational_mass / radius,
+        }
+    }
+}
+
+// A solver that computes the gravitational forces between bodies.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Solver {
+    Direct,
+    ParticleMesh,
+}
+
+// Implementations for Solver.
+impl Solver {
+    // All solvers, in the order they are listed in the UI.
+    pub const ALL: [Solver; 2] = [Solver::Direct, Solver::ParticleMesh];
+
+    // Return the name of a solver.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::Direct => "Direct Summation",
+            Solver::ParticleMesh => "Particle-Particle Particle-Mesh",
+        }
+    }
+}
+
+// A floating point precision that the direct solver computes forces in.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Precision {
+    Double,
+    Single,
+}
+
+// Implementations for Precision.
+impl Precision {
+    // All precisions, in the order they are listed in the UI.
+    pub const ALL: [Precision; 2] = [Precision::Double, Precision::Single];
+
+    // Return the name of a precision.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Precision::Double => "Double (f64)",
+            Precision::Single => "Single (f32, fast)",
+        }
+    }
+}
+
+// An integrator that advances the positions and velocities of bodies with their accelerations.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Integrator {
+    SymplecticEuler,
+    VelocityVerlet,
+}
+
+// Implementations for Integrator.
+impl Integrator {
+    // All integrators, in the order they are listed in the UI.
+    pub const ALL: [Integrator; 2] = [Integrator::SymplecticEuler, Integrator::VelocityVerlet];
+
+    // Return the name of an integrator.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::SymplecticEuler => "Symplectic Euler",
+            Integrator::VelocityVerlet => "Velocity Verlet",
+        }
+    }
+}
+
+// Counts of the work done during the last step of a universe.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct StepStatistics {
+    pub collision_checks: usize,
+    pub force_evaluations: usize,
+    pub tracer_force_evaluations: usize,
+    pub grid_rebuilt: bool,
+    pub grid_moves: usize,
+    pub skipped_pairs: usize,
+}
+
+// Counts accumulated over the whole run of a universe since its bodies were last replaced.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunStatistics {
+    pub steps: usize,
+    pub mergers: usize,
+    pub wall_time: f64,
+    pub initial_energy: Option<f64>,
+}
+
+// An event recorded when two bodies collide and merge into one.
+#[derive(Clone, Copy, PartialEq)]
+pub struct CollisionEvent {
+    pub positions: [DVec2; 2],
+    pub masses: [f64; 2],
+    pub position: DVec2,
+}
+
+// The radial density profile of the gas disc that drags bodies.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GasProfile {
+    Uniform,
+    PowerLaw,
+    Exponential,
+}
+
+// Implementations for GasProfile.
+impl GasProfile {
+    // All gas profiles, in the order they are listed in the UI.
+    pub const ALL: [GasProfile; 3] = [
+        GasProfile::Uniform,
+        GasProfile::PowerLaw,
+        GasProfile::Exponential,
+    ];
+
+    // Return the name of a gas profile.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GasProfile::Uniform => "Uniform",
+            GasProfile::PowerLaw => "Power Law",
+            GasProfile::Exponential => "Exponential",
+        }
+    }
+
+    // Return the density of the gas at the given radius relative to its density at the scale length.
+    fn relative_density(&self, universe_settings: &UniverseSettings, radius: f64) -> f64 {
+        let scale = universe_settings.gas_scale;
+        match self {
+            GasProfile::Uniform => 1.0,
+            // Clamp the radius near the center so the density stays finite.
+            GasProfile::PowerLaw => (radius / scale)
+                .max(0.01)
+                .powf(-universe_settings.gas_exponent),
+            GasProfile::Exponential => (1.0 - radius / scale).exp(),
         }
     }
 }
 
 // Settings to simulate the universe with.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UniverseSettings {
     pub gravitational_constant: f64,
+    pub solver: Solver,
+    pub precision: Precision,
+    pub integrator: Integrator,
+    pub mesh_resolution: usize,
     pub enable_collisions: bool,
+    pub units: Units,
+    pub ejection_mode: EjectionMode,
+    pub ejection_radius: f64,
+    pub enable_mass_transfer: bool,
+    pub capture_radius: f64,
+    pub mass_transfer_rate: f64,
+    pub accretion_radius: f64,
+    pub enable_relativity: bool,
+    pub speed_of_light: f64,
+    pub background_potential: BackgroundPotential,
+    pub background_center: DVec2,
+    pub background_mass: f64,
+    pub background_scale: f64,
+    pub background_velocity: f64,
+    pub enable_gas_drag: bool,
+    pub gas_drag: f64,
+    pub gas_profile: GasProfile,
+    pub gas_scale: f64,
+    pub gas_exponent: f64,
+    pub reorder_interval: usize,
+    pub enable_speed_cap: bool,
+    pub max_speed: f64,
+    pub pause_on_blowup: bool,
+    pub enable_interaction_cutoff: bool,
+    pub interaction_cutoff: f64,
+    pub cutoff_taper: f64,
 }
 
 // Default value for UniverseSettings.
@@ -41,154 +316,1337 @@ impl Default for UniverseSettings {
     fn default() -> Self {
         Self {
             gravitational_constant: 1.0e+2,
+            solver: Solver::Direct,
+            precision: Precision::Double,
+            integrator: Integrator::SymplecticEuler,
+            mesh_resolution: 64,
             enable_collisions: true,
+            units: Default::default(),
+            ejection_mode: EjectionMode::Disabled,
+            ejection_radius: 5000.0,
+            enable_mass_transfer: false,
+            capture_radius: 3.0,
+            mass_transfer_rate: 0.1,
+            accretion_radius: 2.0,
+            enable_relativity: false,
+            speed_of_light: 1.0e+4,
+            background_potential: BackgroundPotential::None,
+            background_center: DVec2::ZERO,
+            background_mass: 1.0e+4,
+            background_scale: 500.0,
+            background_velocity: 100.0,
+            enable_gas_drag: false,
+            gas_drag: 0.01,
+            gas_profile: GasProfile::PowerLaw,
+            gas_scale: 200.0,
+            gas_exponent: 1.0,
+            reorder_interval: 0,
+            enable_speed_cap: false,
+            max_speed: 1.0e+4,
+            pause_on_blowup: true,
+            enable_interaction_cutoff: false,
+            interaction_cutoff: 1000.0,
+            cutoff_taper: 0.2,
+        }
+    }
+}
+
+// Implementations for UniverseSettings.
+impl UniverseSettings {
+    // Return the factor gravity is scaled by at the given square distance, which tapers smoothly from one to zero over the outer fraction of the interaction cutoff if it's enabled, only taking the square root within the taper.
+    pub fn cutoff_factor(&self, distance_squared: f64) -> f64 {
+        if !self.enable_interaction_cutoff {
+            return 1.0;
+        }
+        let taper_start = self.interaction_cutoff * (1.0 - self.cutoff_taper);
+        if distance_squared <= taper_start * taper_start {
+            1.0
+        } else if distance_squared >= self.interaction_cutoff * self.interaction_cutoff {
+            0.0
+        } else {
+            let fraction = (self.interaction_cutoff - distance_squared.sqrt())
+                / (self.interaction_cutoff - taper_start);
+            fraction * fraction * (3.0 - 2.0 * fraction)
+        }
+    }
+
+    // Return whether the solver sorts the massive bodies into a spatial grid, which the particle mesh solver does for its short range forces and the direct solver does to find the pairs within the interaction cutoff.
+    pub fn uses_spatial_grid(&self) -> bool {
+        match self.solver {
+            Solver::Direct => self.enable_interaction_cutoff,
+            Solver::ParticleMesh => true,
         }
     }
 }
 
 // A universe that represents a group of bodies all interacting with each other.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Universe {
     pub universe_settings: UniverseSettings,
     pub bodies: Vec<Body>,
+    pub components: Components,
+    pub ejected_bodies: usize,
+    pub simulation_time: f64,
+    pub epoch: f64,
+    pub run_statistics: RunStatistics,
+    pub next_id: u64,
+    #[serde(skip)]
+    pub step_statistics: StepStatistics,
+    #[serde(skip)]
+    pub frame_force_evaluations: usize,
+    #[serde(skip)]
+    pub collision_events: Vec<CollisionEvent>,
+    #[serde(skip)]
+    pub accelerations: Vec<DVec2>,
+    #[serde(skip)]
+    pub acceleration_state: Vec<(BodyId, DVec2, f64)>,
+    #[serde(skip)]
+    pub spatial_cache: SpatialCache,
+    #[serde(skip)]
+    pub paused: bool,
+    #[serde(skip)]
+    pub pending_steps: usize,
+    #[serde(skip)]
+    pub blown_up_bodies: Vec<BodyId>,
+    #[serde(skip)]
+    pub edits: EditQueue,
 }
 
-// Default value for Universe.
-impl Default for Universe {
-    fn default() -> Self {
-        Self {
-            universe_settings: Default::default(),
-            bodies: Default::default(),
+// Implementations for Universe.
+impl Universe {
+    // Replace the bodies of a universe, dropping the components of the old ones, and reset the ejected body counter and simulation time, making every body primordial, and forget the edits so the physics pipeline starts over from the new bodies.
+    pub fn replace_bodies(&mut self, bodies: Vec<Body>) {
+        self.bodies = bodies;
+        for body in self.bodies.iter_mut() {
+            body.created_at = 0.0;
         }
+        self.components = Components::default();
+        self.ejected_bodies = 0;
+        self.simulation_time = 0.0;
+        self.epoch = 0.0;
+        self.run_statistics = RunStatistics::default();
+        self.edits = EditQueue::default();
+        self.assign_ids();
     }
-}
 
-// Implementations for Universe.
-impl Universe {
-    // Generate new bodies for a universe.
-    pub fn generate_bodies(&mut self, generation_settings: &GenerationSettings) {
-        // Create a new random number generator using the given seed, or time since unix epoch if the given seed is 0.
-        let mut rng = Random::new(if generation_settings.seed == 0 {
-            // Use time since unix epoch.
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs()
-        } else {
-            // Use the given seed.
-            generation_settings.seed
+    // Apply an edit to the universe, giving any bodies it added an id, and record it so it's replayed on the step running on the worker thread if there is one, returning what the edit returned.
+    pub fn edit<T>(&mut self, edit: impl Fn(&mut Universe) -> T + Send + 'static) -> T {
+        let result = edit(self);
+        self.assign_ids();
+        self.edits.record(Box::new(move |universe| {
+            edit(universe);
+            universe.assign_ids();
+        }));
+        result
+    }
+
+    // Apply an edit to the body with the given id if it still exists, recording it like any other edit.
+    pub fn edit_body(&mut self, id: BodyId, edit: impl Fn(&mut Body) + Send + 'static) {
+        self.edit(move |universe| {
+            if let Some(index) = universe.index_of(id) {
+                edit(&mut universe.bodies[index]);
+            }
         });
+    }
 
-        // Set bodies to a new empty vector.
-        self.bodies = vec![];
-
-        // Generate the amount of bodies given.
-        for _ in 0..generation_settings.body_amount {
-            // Create a random angle for the position to be generated from.
-            let position_theta = rng.gen_range(0.0..(std::f64::consts::PI * 2.0));
-            // Create an angle for the velocity to be generated from using a random angle or an angle perpendicular to the position angle.
-            let velocity_theta = if generation_settings.tangential_velocity {
-                // Use the angle perpendicular to the position angle.
-                position_theta - std::f64::consts::PI / 2.0
-            } else {
-                // Use a random angle.
-                rng.gen_range(0.0..(std::f64::consts::PI * 2.0))
-            };
+    // Give every body that doesn't have an id yet a new one, never reusing the id of a body that was removed, and record the time it was created at.
+    pub fn assign_ids(&mut self) {
+        if self.bodies.iter().all(|body| body.id.is_assigned()) {
+            return;
+        }
 
-            // Push a new random body to the bodies vector.
-            self.bodies.push(Body {
-                // Generate a random position using the position angle and position range.
-                position: DVec2::new(position_theta.cos(), position_theta.sin())
-                    * if generation_settings.position_range.is_empty() {
-                        generation_settings.position_range.start
-                    } else {
-                        rng.gen_range(generation_settings.position_range.clone())
-                    },
-                // Generate a random velocity using the velocity angle and velocity range.
-                velocity: DVec2::new(velocity_theta.cos(), velocity_theta.sin())
-                    * if generation_settings.velocity_range.is_empty() {
-                        generation_settings.velocity_range.start
-                    } else {
-                        rng.gen_range(generation_settings.velocity_range.clone())
-                    },
-                // Generate a random mass using the mass range.
-                mass: if generation_settings.mass_range.is_empty() {
-                    generation_settings.mass_range.start
-                } else {
-                    rng.gen_range(generation_settings.mass_range.clone())
-                },
-            });
+        // Start after the largest id in use, in case the bodies came from somewhere that didn't count them.
+        let largest_id = self.bodies.iter().map(|body| body.id.0).max().unwrap_or(0);
+        self.next_id = self.next_id.max(largest_id + 1);
+        for body in self.bodies.iter_mut() {
+            if !body.id.is_assigned() {
+                body.id = BodyId(self.next_id);
+                body.created_at = self.simulation_time;
+                self.next_id += 1;
+            }
+        }
+    }
+
+    // Return the index of the body with the given id if it still exists.
+    pub fn index_of(&self, id: BodyId) -> Option<usize> {
+        if !id.is_assigned() {
+            return None;
         }
+        self.bodies.iter().position(|body| body.id == id)
+    }
+
+    // Mark the current simulation time as the epoch that durations are measured from.
+    pub fn reset_epoch(&mut self) {
+        self.epoch = self.simulation_time;
     }
 
-    // Update a universe.
-    pub fn update(&mut self, delta_time: f64) {
+    // Save a universe to a JSON file at the given path, writing to a temporary file first so a crash can't leave a partial save.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the universe and write it to a temporary file.
+        let json = serde_json::to_string(self).map_err(|error| error.to_string())?;
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, json).map_err(|error| error.to_string())?;
+
+        // Replace the file with the temporary file.
+        fs::rename(&temporary_path, path).map_err(|error| error.to_string())
+    }
+
+    // Load a universe from a JSON file at the given path.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        // Read the file and deserialize the universe.
+        let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+
+    // Request a single step of a paused universe.
+    pub fn step(&mut self) {
+        self.pending_steps += 1;
+    }
+
+    // Advance a universe by the substeps of the given pacing unless it's paused, in which case only requested single steps are taken.
+    pub fn advance(&mut self, pacing: Pacing, profiler: &mut Profiler) {
+        // Count the force evaluations of every step taken this frame, since the statistics only hold the last one.
+        self.frame_force_evaluations = 0;
+        if !self.paused && pacing.substeps > 0 {
+            // Count the time the universe ran for, and discard steps requested before it was unpaused.
+            self.pending_steps = 0;
+            self.run_statistics.wall_time += pacing.wall_time;
+
+            // Take each substep, keeping the collision events of all of them so they're handled once per frame.
+            let mut collision_events = Vec::new();
+            for _ in 0..pacing.substeps {
+                self.update(pacing.delta_time, profiler);
+                self.frame_force_evaluations += self.step_statistics.force_evaluations;
+                // Stop taking substeps if the step paused the universe because numbers blew up.
+                if self.paused {
+                    collision_events.append(&mut self.collision_events);
+                    break;
+                }
+                collision_events.append(&mut self.collision_events);
+            }
+            self.collision_events = collision_events;
+        } else if !self.paused {
+            // Wait for enough time to take a substep, counting the time and clearing the results of the last step.
+            self.pending_steps = 0;
+            self.run_statistics.wall_time += pacing.wall_time;
+            self.step_statistics = StepStatistics::default();
+            self.collision_events.clear();
+        } else if self.pending_steps > 0 {
+            // Take a single step.
+            self.pending_steps -= 1;
+            self.update(STEP_DELTA_TIME, profiler);
+            self.frame_force_evaluations = self.step_statistics.force_evaluations;
+        } else {
+            // Clear the results of the last step so they aren't handled again.
+            self.step_statistics = StepStatistics::default();
+            self.collision_events.clear();
+        }
+    }
+
+    // Update a universe, recording how long each phase takes in the given profiler.
+    pub fn update(&mut self, delta_time: f64, profiler: &mut Profiler) {
+        // Reset the counts of work done and the collision events of this step.
+        self.step_statistics = StepStatistics::default();
+        self.collision_events.clear();
+
+        // Give bodies added since the last step an id.
+        self.assign_ids();
+
+        // Reorder the bodies along a Z-order curve every interval of steps if it's enabled.
+        let reorder_interval = self.universe_settings.reorder_interval;
+        if reorder_interval > 0 && self.run_statistics.steps.is_multiple_of(reorder_interval) {
+            profiler.time(Phase::Integration, || self.reorder_bodies());
+        }
+
+        // Record the energy before the first step so the energy drift can be measured.
+        if self.run_statistics.initial_energy.is_none() {
+            self.run_statistics.initial_energy = Some(self.total_energy());
+        }
+
+        // Let black holes consume the bodies within their accretion radius, whether collisions are enabled or not.
+        if self.bodies.iter().any(|body| body.black_hole) {
+            profiler.time(Phase::Collisions, || self.update_accretion());
+        }
+
         // Check and update for collisions if it's enabled.
         if self.universe_settings.enable_collisions {
-            // Iterate over each combination of bodies.
-            for i in 0..self.bodies.len() {
-                for j in (i + 1)..self.bodies.len() {
-                    // Calculate the distance between the bodies.
-                    let distance = self.bodies[i].position.distance(self.bodies[j].position);
-                    // If the distance between the bodies is less than or equal to the sum of their radii, they are colliding.
-                    if distance <= self.bodies[i].mass.cbrt() + self.bodies[j].mass.cbrt() {
-                        // Calculate the total mass of the bodies and the percent mass each body makes up.
-                        let total_mass = self.bodies[i].mass + self.bodies[j].mass;
-                        let mass_ratio1 = self.bodies[i].mass / total_mass;
-                        let mass_ratio2 = 1.0 - mass_ratio1;
-
-                        // Push a new body to the bodies vector by averaging the two colliding bodies together.
-                        self.bodies.push(Body {
-                            position: self.bodies[i].position * mass_ratio1
-                                + self.bodies[j].position * mass_ratio2,
-                            velocity: self.bodies[i].velocity * mass_ratio1
-                                + self.bodies[j].velocity * mass_ratio2,
-                            mass: total_mass,
-                        });
-
-                        // Remove the colliding bodies.
-                        self.bodies.remove(j);
-                        self.bodies.remove(i);
-
-                        break;
-                    }
+            profiler.time(Phase::Collisions, || self.update_collisions());
+        }
+
+        // Transfer mass between bodies passing close to each other if it's enabled.
+        if self.universe_settings.enable_mass_transfer {
+            profiler.time(Phase::Collisions, || self.update_mass_transfer(delta_time));
+        }
+
+        // Find the accelerations of the bodies where they are, reusing the ones cached at the end of the last step if velocity Verlet is used and the bodies haven't changed since.
+        let integrator = self.universe_settings.integrator;
+        if integrator == Integrator::SymplecticEuler || !self.accelerations_current() {
+            self.update_accelerations(profiler);
+        }
+
+        match integrator {
+            // Kick the velocities by the whole step, then drift the bodies with them and remove or freeze ejected bodies.
+            Integrator::SymplecticEuler => {
+                self.kick(delta_time);
+                self.update_impulses(delta_time, profiler);
+                self.clamp_speeds();
+                profiler.time(Phase::Integration, || self.update_bodies(delta_time));
+            }
+            // Kick the velocities by half a step, drift the bodies with them and remove or freeze ejected bodies, then find the accelerations where the bodies ended up, which are cached for the next step, and kick by the other half.
+            Integrator::VelocityVerlet => {
+                self.kick(delta_time * 0.5);
+                self.update_impulses(delta_time, profiler);
+                self.clamp_speeds();
+                profiler.time(Phase::Integration, || self.update_bodies(delta_time));
+                self.update_accelerations(profiler);
+                self.kick(delta_time * 0.5);
+                self.clamp_speeds();
+            }
+        }
+        // Drop the components of the bodies that merged, were consumed, or were removed.
+        self.components.prune(&self.bodies);
+
+        // Advance the simulation time and count the step.
+        self.simulation_time += delta_time;
+        self.run_statistics.steps += 1;
+
+        // Pause and report the bodies whose values stopped being finite if the guard is enabled, before the blowup spreads to every other body through their forces.
+        if self.universe_settings.pause_on_blowup {
+            let blown_up_bodies: Vec<BodyId> = self
+                .bodies
+                .iter()
+                .filter(|body| !body.is_finite())
+                .map(|body| body.id)
+                .collect();
+            if !blown_up_bodies.is_empty() {
+                self.blown_up_bodies = blown_up_bodies;
+                self.paused = true;
+            }
+        }
+    }
+
+    // Limit the speed of every body to the speed cap if it's enabled.
+    fn clamp_speeds(&mut self) {
+        if !self.universe_settings.enable_speed_cap {
+            return;
+        }
+        let max_speed = self.universe_settings.max_speed;
+        for body in self.bodies.iter_mut() {
+            body.velocity = body.velocity.clamp_length_max(max_speed);
+        }
+    }
+
+    // Remove the bodies that were reported as blown up, dropping their components.
+    pub fn remove_blown_up_bodies(&mut self) {
+        let blown_up_bodies = std::mem::take(&mut self.blown_up_bodies);
+        self.bodies
+            .retain(|body| !blown_up_bodies.contains(&body.id));
+        self.components.prune(&self.bodies);
+    }
+
+    // Merge bodies that are colliding.
+    fn update_collisions(&mut self) {
+        // Iterate over each combination of bodies.
+        for i in 0..self.bodies.len() {
+            for j in (i + 1)..self.bodies.len() {
+                // Frozen bodies and test particles don't collide, so skip the rest of the combinations of the first body if it's one.
+                if !self.bodies[i].is_massive() {
+                    break;
+                }
+                if !self.bodies[j].is_massive() {
+                    continue;
+                }
+                self.step_statistics.collision_checks += 1;
+
+                // Calculate the distance between the bodies.
+                let distance = self.bodies[i].position.distance(self.bodies[j].position);
+                // If the distance between the bodies is less than or equal to the sum of their radii, they are colliding.
+                if distance <= self.bodies[i].radius() + self.bodies[j].radius() {
+                    // Calculate the total mass of the bodies and the percent mass each body makes up.
+                    let total_mass = self.bodies[i].mass + self.bodies[j].mass;
+                    let mass_ratio1 = self.bodies[i].mass / total_mass;
+                    let mass_ratio2 = 1.0 - mass_ratio1;
+
+                    // Create a new body by averaging the two colliding bodies together, keeping the id and name of the heavier one so references and components follow the merged body.
+                    let heavier = if mass_ratio1 >= mass_ratio2 { i } else { j };
+                    let mut body = Body {
+                        id: self.bodies[heavier].id,
+                        name: self.bodies[heavier].name.clone(),
+                        position: self.bodies[i].position * mass_ratio1
+                            + self.bodies[j].position * mass_ratio2,
+                        velocity: self.bodies[i].velocity * mass_ratio1
+                            + self.bodies[j].velocity * mass_ratio2,
+                        mass: total_mass,
+                        black_hole: self.bodies[i].black_hole || self.bodies[j].black_hole,
+                        accreted_mass: self.bodies[i].accreted_mass + self.bodies[j].accreted_mass,
+                        accreted_bodies: self.bodies[i].accreted_bodies
+                            + self.bodies[j].accreted_bodies,
+                        angle: self.bodies[heavier].angle,
+                        created_at: self.simulation_time,
+                        ..Default::default()
+                    };
+                    // Keep the spin and orbital angular momentum of the colliding bodies as the spin of the new body.
+                    body.spin = Body::merged_spin(
+                        &self.bodies[i],
+                        &self.bodies[j],
+                        body.position,
+                        body.velocity,
+                    );
+
+                    // Record the collision.
+                    self.collision_events.push(CollisionEvent {
+                        positions: [self.bodies[i].position, self.bodies[j].position],
+                        masses: [self.bodies[i].mass, self.bodies[j].mass],
+                        position: body.position,
+                    });
+
+                    // Push the new body to the bodies vector and count the merger.
+                    self.bodies.push(body);
+                    self.run_statistics.mergers += 1;
+
+                    // Remove the colliding bodies.
+                    self.bodies.remove(j);
+                    self.bodies.remove(i);
+
+                    break;
+                }
+            }
+        }
+    }
+
+    // Reorder the bodies along a Z-order curve of their positions, so bodies that are close in space are close in memory.
+    fn reorder_bodies(&mut self) {
+        if self.bodies.len() < 2 {
+            return;
+        }
+
+        // Find the bounds of the positions of the bodies.
+        let (minimum, maximum) = self.bodies.iter().fold(
+            (DVec2::splat(f64::MAX), DVec2::splat(f64::MIN)),
+            |(minimum, maximum), body| (minimum.min(body.position), maximum.max(body.position)),
+        );
+        let size = (maximum - minimum).max(DVec2::splat(f64::EPSILON));
+
+        // Sort the indices of the bodies by the Morton code of their positions quantized to 16 bits in each axis.
+        let mut order: Vec<usize> = (0..self.bodies.len()).collect();
+        order.sort_by_cached_key(|&index| {
+            let cell = (self.bodies[index].position - minimum) / size * u16::MAX as f64;
+            morton_code(cell.x as u16, cell.y as u16)
+        });
+
+        // Move the bodies into their new order.
+        let mut bodies: Vec<Option<Body>> = self.bodies.drain(..).map(Some).collect();
+        for &old_index in order.iter() {
+            if let Some(body) = bodies[old_index].take() {
+                self.bodies.push(body);
+            }
+        }
+    }
+
+    // Let each black hole consume the bodies within its accretion radius, given as a multiple of its radius, keeping the mass and momentum of massive bodies. Black holes only consume black holes that are at most as heavy.
+    fn update_accretion(&mut self) {
+        let mut i = 0;
+        while i < self.bodies.len() {
+            // Skip bodies that aren't black holes.
+            if !self.bodies[i].black_hole {
+                i += 1;
+                continue;
+            }
+
+            let mut j = 0;
+            while j < self.bodies.len() {
+                // Skip the black hole itself, bodies outside of the accretion radius, and heavier black holes.
+                let black_hole = &self.bodies[i];
+                let other = &self.bodies[j];
+                let accretion_radius =
+                    black_hole.radius() * self.universe_settings.accretion_radius;
+                if j == i
+                    || black_hole.position.distance(other.position) > accretion_radius
+                    || (other.black_hole && other.mass > black_hole.mass)
+                {
+                    j += 1;
+                    continue;
+                }
+
+                // Remove the consumed body, keeping track of where the black hole moved to.
+                let other = self.bodies.remove(j);
+                if j < i {
+                    i -= 1;
+                }
+
+                // Record the accretion.
+                let black_hole = &self.bodies[i];
+                self.collision_events.push(CollisionEvent {
+                    positions: [black_hole.position, other.position],
+                    masses: [black_hole.mass, other.mass],
+                    position: black_hole.position,
+                });
+
+                // Add the mass and momentum of the consumed body if it's massive, and count what it had accreted itself.
+                let black_hole = &mut self.bodies[i];
+                if other.is_massive() {
+                    let mass = black_hole.mass + other.mass;
+                    let velocity = (black_hole.velocity * black_hole.mass
+                        + other.velocity * other.mass)
+                        / mass;
+                    black_hole.spin =
+                        Body::merged_spin(black_hole, &other, black_hole.position, velocity);
+                    black_hole.velocity = velocity;
+                    black_hole.mass = mass;
+                    black_hole.accreted_mass += other.mass;
                 }
+                black_hole.accreted_mass += other.accreted_mass;
+                black_hole.accreted_bodies += 1 + other.accreted_bodies;
             }
+            i += 1;
         }
+    }
+
+    // Transfer mass from the lighter to the heavier body of each pair passing within the capture radius of each other.
+    fn update_mass_transfer(&mut self, delta_time: f64) {
+        // Find the fraction of the lighter body's mass that is transferred this step.
+        let fraction = (self.universe_settings.mass_transfer_rate * delta_time).clamp(0.0, 1.0);
 
         // Iterate over each combination of bodies.
         for i in 0..self.bodies.len() {
             for j in (i + 1)..self.bodies.len() {
-                // Calculate the square distance between the bodies.
-                let distance_squared = self.bodies[i]
-                    .position
-                    .distance_squared(self.bodies[j].position);
-                // If the bodies aren't in the same position, they will apply gravitational force to each other.
-                if distance_squared > 0.0 {
-                    // Find the force between the bodies.
-                    let force = (self.bodies[j].position - self.bodies[i].position).normalize()
-                        * self.universe_settings.gravitational_constant
-                        / distance_squared;
-                    // Store the mass of the body that body that is applying force on the other object. This needs to be in a variable due to Rust's borrow checker.
-                    let mut mass = self.bodies[j].mass;
-                    // Integrate the acceleration of gravity over time.
-                    self.bodies[i].velocity += force * mass * delta_time;
-                    mass = self.bodies[i].mass;
-                    self.bodies[j].velocity -= force * mass * delta_time;
+                // Frozen bodies and test particles don't transfer mass, so skip the rest of the combinations of the first body if it's one.
+                if !self.bodies[i].is_massive() {
+                    break;
+                }
+                if !self.bodies[j].is_massive() {
+                    continue;
+                }
+
+                // Only transfer mass if the bodies are within the capture radius, given as a multiple of the sum of their radii.
+                let distance = self.bodies[i].position.distance(self.bodies[j].position);
+                if distance
+                    > (self.bodies[i].radius() + self.bodies[j].radius())
+                        * self.universe_settings.capture_radius
+                {
+                    continue;
+                }
+
+                // Find which body is heavier.
+                let (heavier, lighter) = if self.bodies[i].mass >= self.bodies[j].mass {
+                    (i, j)
+                } else {
+                    (j, i)
+                };
+
+                // Move the transferred mass, which carries the velocity of the lighter body, so momentum is conserved. The lighter body keeps its velocity.
+                let transferred_mass = self.bodies[lighter].mass * fraction;
+                let lighter_velocity = self.bodies[lighter].velocity;
+                let heavier_body = &mut self.bodies[heavier];
+                let mass = heavier_body.mass + transferred_mass;
+                heavier_body.velocity = (heavier_body.velocity * heavier_body.mass
+                    + lighter_velocity * transferred_mass)
+                    / mass;
+                heavier_body.mass = mass;
+                self.bodies[lighter].mass -= transferred_mass;
+            }
+        }
+    }
+
+    // Find the acceleration of each body from gravity, the background potential, and the post-Newtonian correction, and remember the bodies they were found for so they can be reused until the bodies change.
+    fn update_accelerations(&mut self, profiler: &mut Profiler) {
+        self.accelerations.clear();
+        self.accelerations.resize(self.bodies.len(), DVec2::ZERO);
+
+        // Add the gravitational forces between the bodies.
+        profiler.time(Phase::Forces, || self.update_forces());
+        // Add the background potential to every body if there is one.
+        if self.universe_settings.background_potential != BackgroundPotential::None {
+            profiler.time(Phase::Forces, || self.update_background());
+        }
+        // Add the first post-Newtonian correction between the massive bodies if it's enabled.
+        if self.universe_settings.enable_relativity {
+            profiler.time(Phase::Forces, || self.update_relativity());
+        }
+        // Add gravitational forces from the massive bodies to the test particles in a separate parallel pass.
+        profiler.time(Phase::Tracers, || self.update_tracers());
+
+        self.acceleration_state = self
+            .bodies
+            .iter()
+            .map(|body| (body.id, body.position, body.mass))
+            .collect();
+    }
+
+    // Return whether the cached accelerations were found for the bodies as they are now, which isn't the case after bodies merged, moved, or were edited between steps.
+    fn accelerations_current(&self) -> bool {
+        self.acceleration_state.len() == self.bodies.len()
+            && self.bodies.iter().zip(self.acceleration_state.iter()).all(
+                |(body, &(id, position, mass))| {
+                    body.id == id && body.position == position && body.mass == mass
+                },
+            )
+    }
+
+    // Change the velocity of each body by its cached acceleration over the given time.
+    fn kick(&mut self, delta_time: f64) {
+        for (body, acceleration) in self.bodies.iter_mut().zip(self.accelerations.iter()) {
+            body.velocity += *acceleration * delta_time;
+        }
+    }
+
+    // Apply the changes of velocity that aren't cached as accelerations, since they burn fuel or damp the velocity directly.
+    fn update_impulses(&mut self, delta_time: f64, profiler: &mut Profiler) {
+        // Push the bodies that have thrusters and burn their fuel.
+        if !self.components.thrusters.is_empty() {
+            profiler.time(Phase::Forces, || self.update_thrust(delta_time));
+        }
+        // Drag bodies toward the circular velocity of the gas disc around the most massive body if it's enabled.
+        if self.universe_settings.enable_gas_drag {
+            profiler.time(Phase::Forces, || self.update_gas_drag(delta_time));
+        }
+    }
+
+    // Add the gravitational forces between massive bodies with the solver.
+    fn update_forces(&mut self) {
+        match self.universe_settings.solver {
+            Solver::Direct if self.universe_settings.enable_interaction_cutoff => {
+                match self.universe_settings.precision {
+                    Precision::Double => self.update_forces_cutoff(),
+                    Precision::Single => self.update_forces_cutoff_single(),
                 }
             }
+            Solver::Direct => match self.universe_settings.precision {
+                Precision::Double => self.update_forces_direct(),
+                Precision::Single => self.update_forces_direct_single(),
+            },
+            Solver::ParticleMesh => self.update_forces_particle_mesh(),
+        }
+    }
+
+    // Add the gravitational forces between each pair of massive bodies directly.
+    fn update_forces_direct(&mut self) {
+        // Find the massive bodies, leaving test particles to their own pass, and copy their positions and masses into arrays in order, so the later bodies of each one are contiguous slices.
+        let massive_bodies: Vec<usize> = (0..self.bodies.len())
+            .filter(|&index| self.bodies[index].is_massive())
+            .collect();
+        let body_arrays = BodyArrays::gather(self, &massive_bodies);
+        let mut pair_buffer = PairBuffer::default();
+
+        // Sum the accelerations of each body paired with every later one into arrays in the same order, finding the distances and strengths of all its pairs before applying any of them.
+        let count = massive_bodies.len();
+        let mut acceleration_x = vec![0.0; count];
+        let mut acceleration_y = vec![0.0; count];
+        for k in 0..count {
+            pair_buffer.measure_following(&body_arrays, k);
+            pair_buffer.weigh(self.universe_settings.gravitational_constant);
+            pair_buffer.apply_following(&body_arrays, k, &mut acceleration_x, &mut acceleration_y);
         }
+        self.step_statistics.force_evaluations += count * count.saturating_sub(1) / 2;
+
+        // Add the accelerations to the bodies.
+        for (k, &index) in massive_bodies.iter().enumerate() {
+            self.accelerations[index] += DVec2::new(acceleration_x[k], acceleration_y[k]);
+        }
+    }
+
+    // Add the gravitational forces between each pair of massive bodies within the interaction cutoff of each other, tapering them toward it, and find the pairs with a spatial grid so the ones beyond it are never visited.
+    fn update_forces_cutoff(&mut self) {
+        // Find the massive bodies, leaving test particles to their own pass.
+        let massive_bodies: Vec<usize> = (0..self.bodies.len())
+            .filter(|&index| self.bodies[index].is_massive())
+            .collect();
+        let body_arrays = BodyArrays::new(self);
+        let mut pair_buffer = PairBuffer::default();
+
+        // Sort the massive bodies into cells at least as wide as the cutoff, so only neighbouring cells need to be checked, reusing the grid of the last step where possible.
+        let cutoff = self.universe_settings.interaction_cutoff;
+        let mut spatial_cache = std::mem::take(&mut self.spatial_cache);
+        let (spatial_grid, rebuilt, moves) =
+            spatial_cache.grid(cutoff, &massive_bodies, |index| body_arrays.position(index));
+        self.step_statistics.grid_rebuilt = rebuilt;
+        self.step_statistics.grid_moves = moves;
+
+        let mut interacting_pairs = 0;
+        for &i in massive_bodies.iter() {
+            // Pair the body with the later bodies in its neighbouring cells, which prunes most of the pairs beyond the cutoff.
+            pair_buffer.partners.clear();
+            pair_buffer.partners.extend(
+                spatial_grid
+                    .neighbours(body_arrays.position(i))
+                    .filter(|&j| j > i),
+            );
+            self.step_statistics.force_evaluations += pair_buffer.partners.len();
+
+            // Find the distances and tapered strengths of all the pairs before applying any of them, counting the ones within the cutoff.
+            pair_buffer.measure(&body_arrays, i);
+            pair_buffer.weigh(self.universe_settings.gravitational_constant);
+            interacting_pairs += pair_buffer.taper(&self.universe_settings);
+            pair_buffer.apply(&body_arrays, i, &mut self.accelerations);
+        }
+
+        // Count the pairs that were skipped for being beyond the cutoff.
+        let count = massive_bodies.len();
+        self.step_statistics.skipped_pairs =
+            (count * count.saturating_sub(1) / 2).saturating_sub(interacting_pairs);
+
+        // Keep the grid for the next step.
+        self.spatial_cache = spatial_cache;
+    }
+
+    // Add the gravitational forces between each pair of massive bodies within the interaction cutoff of each other in single precision, tapering them toward it, with the positions and masses in separate arrays and the pairs found with a spatial grid.
+    fn update_forces_cutoff_single(&mut self) {
+        // Find the massive bodies, leaving test particles to their own pass.
+        let massive_bodies: Vec<usize> = (0..self.bodies.len())
+            .filter(|&index| self.bodies[index].is_massive())
+            .collect();
+        // Copy the positions and masses of every body, so the bodies in the grid can be looked up by index.
+        let x: Vec<f32> = self
+            .bodies
+            .iter()
+            .map(|body| body.position.x as f32)
+            .collect();
+        let y: Vec<f32> = self
+            .bodies
+            .iter()
+            .map(|body| body.position.y as f32)
+            .collect();
+        let mass: Vec<f32> = self.bodies.iter().map(|body| body.mass as f32).collect();
+        let gravitational_constant = self.universe_settings.gravitational_constant as f32;
 
+        // Sort the massive bodies into cells at least as wide as the cutoff, so only neighbouring cells need to be checked, reusing the grid of the last step where possible.
+        let cutoff = self.universe_settings.interaction_cutoff;
+        let mut spatial_cache = std::mem::take(&mut self.spatial_cache);
+        let bodies = &self.bodies;
+        let (spatial_grid, rebuilt, moves) =
+            spatial_cache.grid(cutoff, &massive_bodies, |index| bodies[index].position);
+        self.step_statistics.grid_rebuilt = rebuilt;
+        self.step_statistics.grid_moves = moves;
+
+        // Sum the tapered accelerations of each pair in neighbouring cells, counting the ones within the cutoff.
+        let mut acceleration_x = vec![0.0f32; self.bodies.len()];
+        let mut acceleration_y = vec![0.0f32; self.bodies.len()];
+        let mut interacting_pairs = 0;
+        for &i in massive_bodies.iter() {
+            let (mut sum_x, mut sum_y) = (0.0, 0.0);
+            for j in spatial_grid
+                .neighbours(self.bodies[i].position)
+                .filter(|&j| j > i)
+            {
+                self.step_statistics.force_evaluations += 1;
+
+                // Find the tapered force between the bodies, leaving out bodies in the same position.
+                let (offset_x, offset_y) = (x[j] - x[i], y[j] - y[i]);
+                let distance_squared = offset_x * offset_x + offset_y * offset_y;
+                let strength = if distance_squared > 0.0 {
+                    gravitational_constant / (distance_squared * distance_squared.sqrt())
+                        * self
+                            .universe_settings
+                            .cutoff_factor(distance_squared as f64) as f32
+                } else {
+                    0.0
+                };
+                if strength > 0.0 {
+                    interacting_pairs += 1;
+                }
+                sum_x += offset_x * strength * mass[j];
+                sum_y += offset_y * strength * mass[j];
+                acceleration_x[j] -= offset_x * strength * mass[i];
+                acceleration_y[j] -= offset_y * strength * mass[i];
+            }
+            acceleration_x[i] += sum_x;
+            acceleration_y[i] += sum_y;
+        }
+
+        // Count the pairs that were skipped for being beyond the cutoff.
+        let count = massive_bodies.len();
+        self.step_statistics.skipped_pairs =
+            (count * count.saturating_sub(1) / 2).saturating_sub(interacting_pairs);
+
+        // Add the accelerations to the massive bodies.
+        for &index in massive_bodies.iter() {
+            self.accelerations[index] +=
+                DVec2::new(acceleration_x[index] as f64, acceleration_y[index] as f64);
+        }
+
+        // Keep the grid for the next step.
+        self.spatial_cache = spatial_cache;
+    }
+
+    // Push each body that has a firing thruster in its direction, relative to its dominant body, and remove the fuel it burns from its mass.
+    fn update_thrust(&mut self, delta_time: f64) {
+        for index in 0..self.bodies.len() {
+            let body = &self.bodies[index];
+            let Some(thruster) = self.components.thrusters.get(body.id) else {
+                continue;
+            };
+            if body.frozen || !thruster.is_firing(self.simulation_time) {
+                continue;
+            }
+
+            // Find the direction of the thrust from the position and velocity relative to the dominant body.
+            let (offset, relative_velocity) = match self.dominant_body(index) {
+                Some(dominant_body) => (
+                    body.position - self.bodies[dominant_body].position,
+                    body.velocity - self.bodies[dominant_body].velocity,
+                ),
+                None => (body.position, body.velocity),
+            };
+            let direction = thruster.thrust_direction(offset, relative_velocity);
+            let force = thruster.force;
+
+            // Burn the fuel needed for the thrust this step, scaling the thrust down if the fuel runs out, and never burning the whole body.
+            let wanted_fuel = if thruster.exhaust_velocity > 0.0 {
+                thruster.force / thruster.exhaust_velocity * delta_time
+            } else {
+                0.0
+            };
+            let burned_fuel = wanted_fuel.min(thruster.fuel).min(body.mass * 0.5);
+            let fraction = if wanted_fuel > 0.0 {
+                burned_fuel / wanted_fuel
+            } else {
+                1.0
+            };
+
+            // Integrate the thrust over time and remove the burned fuel.
+            let body = &mut self.bodies[index];
+            body.velocity += direction * force * fraction / body.mass * delta_time;
+            body.mass -= burned_fuel;
+            if let Some(thruster) = self.components.thrusters.get_mut(body.id) {
+                thruster.fuel -= burned_fuel;
+            }
+        }
+    }
+
+    // Apply the first post-Newtonian correction to gravity between each combination of massive bodies, treating each one as a test body around the other, which makes orbits precess.
+    fn update_relativity(&mut self) {
+        let gravitational_constant = self.universe_settings.gravitational_constant;
+        let speed_of_light_squared = self.universe_settings.speed_of_light.powi(2);
+
+        // Find the massive bodies.
+        let massive_bodies: Vec<usize> = (0..self.bodies.len())
+            .filter(|&index| self.bodies[index].is_massive())
+            .collect();
+
+        // Sum the corrections to the acceleration of each body.
+        for &i in massive_bodies.iter() {
+            for &j in massive_bodies.iter() {
+                if i == j {
+                    continue;
+                }
+
+                // Find the position and velocity relative to the other body.
+                let offset = self.bodies[i].position - self.bodies[j].position;
+                let distance = offset.length();
+                if distance <= 0.0 {
+                    continue;
+                }
+                let relative_velocity = self.bodies[i].velocity - self.bodies[j].velocity;
+
+                // Add the correction GM / (c² r³) * ((4GM / r - v²) r + 4 (r · v) v).
+                let gravitational_mass = gravitational_constant * self.bodies[j].mass;
+                self.accelerations[i] += (offset
+                    * (4.0 * gravitational_mass / distance - relative_velocity.length_squared())
+                    + relative_velocity * 4.0 * offset.dot(relative_velocity))
+                    * gravitational_mass
+                    / (speed_of_light_squared * distance.powi(3));
+            }
+        }
+    }
+
+    // Add the gravitational forces exactly between each combination of massive bodies in single precision, with the positions and masses in separate arrays so the inner loop can be vectorized.
+    fn update_forces_direct_single(&mut self) {
+        // Find the massive bodies, leaving test particles to their own pass.
+        let massive_bodies: Vec<usize> = (0..self.bodies.len())
+            .filter(|&index| self.bodies[index].is_massive())
+            .collect();
+        let x: Vec<f32> = massive_bodies
+            .iter()
+            .map(|&index| self.bodies[index].position.x as f32)
+            .collect();
+        let y: Vec<f32> = massive_bodies
+            .iter()
+            .map(|&index| self.bodies[index].position.y as f32)
+            .collect();
+        let mass: Vec<f32> = massive_bodies
+            .iter()
+            .map(|&index| self.bodies[index].mass as f32)
+            .collect();
+        let gravitational_constant = self.universe_settings.gravitational_constant as f32;
+
+        // Sum the accelerations of each combination of massive bodies.
+        let count = massive_bodies.len();
+        let mut acceleration_x = vec![0.0f32; count];
+        let mut acceleration_y = vec![0.0f32; count];
+        for i in 0..count {
+            let (mut sum_x, mut sum_y) = (0.0, 0.0);
+            for j in (i + 1)..count {
+                // Find the force between the bodies without branching, leaving out bodies in the same position.
+                let (offset_x, offset_y) = (x[j] - x[i], y[j] - y[i]);
+                let distance_squared = offset_x * offset_x + offset_y * offset_y;
+                let strength = if distance_squared > 0.0 {
+                    gravitational_constant / (distance_squared * distance_squared.sqrt())
+                } else {
+                    0.0
+                };
+                sum_x += offset_x * strength * mass[j];
+                sum_y += offset_y * strength * mass[j];
+                acceleration_x[j] -= offset_x * strength * mass[i];
+                acceleration_y[j] -= offset_y * strength * mass[i];
+            }
+            acceleration_x[i] += sum_x;
+            acceleration_y[i] += sum_y;
+        }
+        self.step_statistics.force_evaluations += count * count.saturating_sub(1) / 2;
+
+        // Add the accelerations to the bodies.
+        for (k, &index) in massive_bodies.iter().enumerate() {
+            self.accelerations[index] +=
+                DVec2::new(acceleration_x[k] as f64, acceleration_y[k] as f64);
+        }
+    }
+
+    // Add the long range gravitational forces between massive bodies with a mesh and the short range forces between nearby pairs directly.
+    fn update_forces_particle_mesh(&mut self) {
+        // Find the massive bodies, leaving test particles to their own pass.
+        let massive_bodies: Vec<usize> = (0..self.bodies.len())
+            .filter(|&index| self.bodies[index].is_massive())
+            .collect();
+        if massive_bodies.len() < 2 {
+            return;
+        }
+
+        // Build the mesh from the positions and masses of the massive bodies.
+        let sources: Vec<(DVec2, f64)> = massive_bodies
+            .iter()
+            .map(|&index| (self.bodies[index].position, self.bodies[index].mass))
+            .collect();
+        let gravitational_constant = self.universe_settings.gravitational_constant;
+        let particle_mesh = ParticleMesh::new(
+            &sources,
+            self.universe_settings.mesh_resolution,
+            gravitational_constant,
+        );
+
+        // Add the long range acceleration from the mesh to each massive body.
+        for &index in massive_bodies.iter() {
+            self.accelerations[index] += particle_mesh.acceleration(self.bodies[index].position);
+        }
+
+        // Sort the massive bodies into cells at least as wide as the short range cutoff, so only neighbouring cells need to be checked, reusing the grid of the last step where possible.
+        let cutoff = particle_mesh.cutoff();
+        let mut spatial_cache = std::mem::take(&mut self.spatial_cache);
+        let bodies = &self.bodies;
+        let (spatial_grid, rebuilt, moves) =
+            spatial_cache.grid(cutoff, &massive_bodies, |index| bodies[index].position);
+        self.step_statistics.grid_rebuilt = rebuilt;
+        self.step_statistics.grid_moves = moves;
+
+        // Apply the short range force between each pair of bodies within the cutoff of each other.
+        for &i in massive_bodies.iter() {
+            for j in spatial_grid
+                .neighbours(self.bodies[i].position)
+                .filter(|&j| j > i)
+            {
+                self.step_statistics.force_evaluations += 1;
+
+                // Find the inverse square force between the bodies, scaled to the part the mesh doesn't handle.
+                let distance = self.bodies[i].position.distance(self.bodies[j].position);
+                if distance <= 0.0 || distance > cutoff {
+                    continue;
+                }
+                let force = (self.bodies[j].position - self.bodies[i].position) / distance
+                    * gravitational_constant
+                    / distance.powi(2)
+                    * particle_mesh.short_range_factor(distance);
+
+                // Add the acceleration of gravity to each body.
+                self.accelerations[i] += force * self.bodies[j].mass;
+                self.accelerations[j] -= force * self.bodies[i].mass;
+            }
+        }
+
+        // Keep the grid for the next step.
+        self.spatial_cache = spatial_cache;
+    }
+
+    // Add the acceleration of the background potential to each body that isn't frozen.
+    fn update_background(&mut self) {
+        let universe_settings = &self.universe_settings;
+        let background_potential = universe_settings.background_potential;
+        for (body, acceleration) in self
+            .bodies
+            .iter()
+            .zip(self.accelerations.iter_mut())
+            .filter(|(body, _)| !body.frozen)
+        {
+            *acceleration += background_potential.acceleration(universe_settings, body.position);
+        }
+    }
+
+    // Drag each body toward the velocity of gas on a circular orbit around the most massive body, scaled by the density of the gas where it is.
+    fn update_gas_drag(&mut self, delta_time: f64) {
+        // Find the most massive body, which the gas disc orbits.
+        let Some(central) = self
+            .bodies
+            .iter()
+            .enumerate()
+            .filter(|(_, body)| body.is_massive())
+            .max_by(|(_, body1), (_, body2)| body1.mass.total_cmp(&body2.mass))
+            .map(|(index, _)| index)
+        else {
+            return;
+        };
+        let (center, center_velocity) =
+            (self.bodies[central].position, self.bodies[central].velocity);
+        let gravitational_mass =
+            self.universe_settings.gravitational_constant * self.bodies[central].mass;
+
+        let universe_settings = &self.universe_settings;
+        for (index, body) in self.bodies.iter_mut().enumerate() {
+            if index == central || body.frozen {
+                continue;
+            }
+
+            // Find the position and velocity relative to the central body.
+            let offset = body.position - center;
+            let radius = offset.length();
+            if radius <= 0.0 {
+                continue;
+            }
+            let relative_velocity = body.velocity - center_velocity;
+
+            // Find the velocity of the gas, orbiting in the same direction as the body.
+            let direction = if offset.perp_dot(relative_velocity) < 0.0 {
+                -offset.perp()
+            } else {
+                offset.perp()
+            };
+            let gas_velocity = direction / radius * (gravitational_mass / radius).sqrt();
+
+            // Damp the difference between the velocity of the body and the gas without overshooting it.
+            let density = universe_settings
+                .gas_profile
+                .relative_density(universe_settings, radius);
+            let damping = (universe_settings.gas_drag * density * delta_time).min(1.0);
+            body.velocity -= (relative_velocity - gas_velocity) * damping;
+        }
+    }
+
+    // Add the gravity of each massive body to each test particle without any reaction, splitting the test particles between threads.
+    fn update_tracers(&mut self) {
+        // Store the position and mass of each massive body so the threads don't need to borrow the bodies.
+        let sources: Vec<(DVec2, f64)> = self
+            .bodies
+            .iter()
+            .filter(|body| body.is_massive())
+            .map(|body| (body.position, body.mass))
+            .collect();
+        let tracer_amount = self
+            .bodies
+            .iter()
+            .filter(|body| body.test_particle && !body.frozen)
+            .count();
+        if sources.is_empty() || tracer_amount == 0 {
+            return;
+        }
+        self.step_statistics.tracer_force_evaluations += tracer_amount * sources.len();
+
+        // Split the bodies into a chunk for each thread.
+        let thread_amount = thread::available_parallelism()
+            .map(|amount| amount.get())
+            .unwrap_or(1);
+        let chunk_size = self.bodies.len().div_ceil(thread_amount).max(1);
+        let gravitational_constant = self.universe_settings.gravitational_constant;
+        let universe_settings = &self.universe_settings;
+
+        thread::scope(|scope| {
+            for (chunk, acceleration_chunk) in self
+                .bodies
+                .chunks(chunk_size)
+                .zip(self.accelerations.chunks_mut(chunk_size))
+            {
+                let sources = &sources;
+                scope.spawn(move || {
+                    // Accelerate each test particle in the chunk towards each massive body.
+                    for (body, acceleration) in chunk
+                        .iter()
+                        .zip(acceleration_chunk.iter_mut())
+                        .filter(|(body, _)| body.test_particle && !body.frozen)
+                    {
+                        for &(position, mass) in sources.iter() {
+                            let distance_squared = body.position.distance_squared(position);
+                            if distance_squared > 0.0 {
+                                *acceleration += (position - body.position).normalize()
+                                    * gravitational_constant
+                                    * mass
+                                    / distance_squared
+                                    * universe_settings.cutoff_factor(distance_squared);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    // Update each body and remove or freeze ejected bodies.
+    fn update_bodies(&mut self, delta_time: f64) {
         // Update each body.
         for body in self.bodies.iter_mut() {
             body.update(delta_time);
         }
+
+        // Remove or freeze ejected bodies if it's enabled.
+        if self.universe_settings.ejection_mode != EjectionMode::Disabled {
+            self.eject_bodies();
+        }
+    }
+
+    // Remove or freeze bodies that are beyond the ejection radius from the center of mass and moving faster than the escape velocity.
+    fn eject_bodies(&mut self) {
+        // Find the center of mass, its velocity, and the total mass of the massive bodies.
+        let (center_of_mass, center_of_mass_velocity, total_mass) = self.center_of_mass();
+        let gravitational_constant = self.universe_settings.gravitational_constant;
+        let ejection_radius = self.universe_settings.ejection_radius;
+
+        // Check whether a body has been ejected.
+        let is_ejected = |body: &Body| {
+            let distance = body.position.distance(center_of_mass);
+            !body.frozen
+                && distance > ejection_radius
+                && body.velocity.distance(center_of_mass_velocity)
+                    > (2.0 * gravitational_constant * total_mass / distance).sqrt()
+        };
+
+        match self.universe_settings.ejection_mode {
+            EjectionMode::Disabled => {}
+            // Remove ejected bodies and count how many were removed.
+            EjectionMode::Remove => {
+                let body_amount = self.bodies.len();
+                self.bodies.retain(|body| !is_ejected(body));
+                self.ejected_bodies += body_amount - self.bodies.len();
+            }
+            // Freeze ejected bodies and count how many were frozen.
+            EjectionMode::Freeze => {
+                for body in self.bodies.iter_mut() {
+                    if is_ejected(body) {
+                        body.frozen = true;
+                        self.ejected_bodies += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    // Return the center of mass, the velocity of the center of mass, and the total mass of the massive bodies.
+    pub fn center_of_mass(&self) -> (DVec2, DVec2, f64) {
+        // Sum the mass weighted positions and velocities of the bodies.
+        let (weighted_position, weighted_velocity, total_mass) =
+            self.bodies.iter().filter(|body| body.is_massive()).fold(
+                (DVec2::ZERO, DVec2::ZERO, 0.0),
+                |(position, velocity, mass), body| {
+                    (
+                        position + body.position * body.mass,
+                        velocity + body.velocity * body.mass,
+                        mass + body.mass,
+                    )
+                },
+            );
+
+        // Divide by the total mass if there is any.
+        if total_mass > 0.0 {
+            (
+                weighted_position / total_mass,
+                weighted_velocity / total_mass,
+                total_mass,
+            )
+        } else {
+            (DVec2::ZERO, DVec2::ZERO, 0.0)
+        }
     }
 
-    // Draw a universe.
-    pub fn draw(&self, draw: &mut Draw) {
-        // Draw each body.
-        for body in self.bodies.iter() {
-            body.draw(draw);
+    // Return the principal axes of the mass distribution of the massive bodies as unit directions with the root mean square distance of the mass along each, major axis first, or None if there's no mass. They're the eigenvectors of the second moment of the mass about the center of mass, which share their directions with the inertia tensor.
+    pub fn principal_axes(&self) -> Option<[(DVec2, f64); 2]> {
+        // Sum the mass weighted second moments about the center of mass.
+        let (center_of_mass, _, total_mass) = self.center_of_mass();
+        if total_mass <= 0.0 {
+            return None;
         }
+        let (xx, yy, xy) = self.bodies.iter().filter(|body| body.is_massive()).fold(
+            (0.0, 0.0, 0.0),
+            |(xx, yy, xy), body| {
+                let offset = body.position - center_of_mass;
+                (
+                    xx + body.mass * offset.x * offset.x,
+                    yy + body.mass * offset.y * offset.y,
+                    xy + body.mass * offset.x * offset.y,
+                )
+            },
+        );
+        let (xx, yy, xy) = (xx / total_mass, yy / total_mass, xy / total_mass);
+
+        // Find the eigenvalues and the angle of the major axis of the symmetric 2x2 matrix.
+        let mean = (xx + yy) * 0.5;
+        let radius = (((xx - yy) * 0.5).powi(2) + xy * xy).sqrt();
+        let angle = 0.5 * (2.0 * xy).atan2(xx - yy);
+        let major = DVec2::from_angle(angle);
+        Some([
+            (major, (mean + radius).max(0.0).sqrt()),
+            (major.perp(), (mean - radius).max(0.0).sqrt()),
+        ])
+    }
+
+    // Return the index of the body that exerts the strongest gravitational pull on the body at the given index.
+    pub fn dominant_body(&self, index: usize) -> Option<usize> {
+        self.dominant_body_at(self.bodies[index].position, Some(index))
+    }
+
+    // Return the index of the body that exerts the strongest gravitational pull at the given position, excluding the body at the given index if there is one.
+    pub fn dominant_body_at(&self, position: DVec2, excluded: Option<usize>) -> Option<usize> {
+        // Find the body with the greatest mass over square distance.
+        self.bodies
+            .iter()
+            .enumerate()
+            .filter(|(other_index, other)| {
+                Some(*other_index) != excluded && other.position.distance_squared(position) > 0.0
+            })
+            .map(|(other_index, other)| {
+                (
+                    other_index,
+                    other.mass / other.position.distance_squared(position),
+                )
+            })
+            .max_by(|(_, pull1), (_, pull2)| pull1.total_cmp(pull2))
+            .map(|(other_index, _)| other_index)
     }
+
+    // Return the velocity needed for the body at the given index to escape the body at the other index.
+    pub fn escape_velocity(&self, index: usize, other_index: usize) -> f64 {
+        // Calculate the escape velocity at the current distance between the bodies.
+        let distance = self.bodies[index]
+            .position
+            .distance(self.bodies[other_index].position);
+        (2.0 * self.universe_settings.gravitational_constant
+            * (self.bodies[index].mass + self.bodies[other_index].mass)
+            / distance)
+            .sqrt()
+    }
+
+    // Return the total mass of a universe.
+    pub fn total_mass(&self) -> f64 {
+        self.bodies.iter().map(|body| body.mass).sum()
+    }
+
+    // Return the total kinetic and gravitational potential energy of the massive bodies, including the background potential.
+    pub fn total_energy(&self) -> f64 {
+        let gravitational_constant = self.universe_settings.gravitational_constant;
+        let mut energy = 0.0;
+
+        // Iterate over each massive body.
+        for (i, body) in self.bodies.iter().enumerate() {
+            if !body.is_massive() {
+                continue;
+            }
+
+            // Add the kinetic energy of the body and its potential energy in the background potential.
+            energy += 0.5 * body.mass * body.velocity.length_squared();
+            energy += body.mass
+                * self
+                    .universe_settings
+                    .background_potential
+                    .potential(&self.universe_settings, body.position);
+
+            // Add the potential energy between the body and each following body.
+            for other in self.bodies[(i + 1)..].iter() {
+                let distance = body.position.distance(other.position);
+                if other.is_massive() && distance > 0.0 {
+                    energy -= gravitational_constant * body.mass * other.mass / distance;
+                }
+            }
+        }
+
+        energy
+    }
+
+    // Split the body at the given index into smaller bodies arranged in a ring around its position, each moving outward with the given speed so the momentum is unchanged. The pieces are new bodies, so they get new ids.
+    pub fn split_body(&mut self, index: usize, count: usize, dispersal_speed: f64) {
+        if count < 2 || index >= self.bodies.len() {
+            return;
+        }
+        let body = self.bodies.remove(index);
+        let piece_mass = body.mass / count as f64;
+
+        // Space the pieces so neighbours in the ring don't touch, since they would merge straight away.
+        let piece_radius = piece_mass.cbrt();
+        let ring_radius = piece_radius / (std::f64::consts::PI / count as f64).sin() * 1.1;
+
+        // Add each piece in its place around the ring.
+        for i in 0..count {
+            let direction = DVec2::from_angle(std::f64::consts::TAU * i as f64 / count as f64);
+            self.bodies.push(Body {
+                id: BodyId::default(),
+                position: body.position + direction * ring_radius,
+                velocity: body.velocity + direction * dispersal_speed,
+                mass: piece_mass,
+                black_hole: false,
+                accreted_mass: 0.0,
+                accreted_bodies: 0,
+                ..body.clone()
+            });
+        }
+        self.assign_ids();
+    }
+
+    // Return the specific angular momentum of each body about the center of mass, positive for counterclockwise orbits.
+    pub fn specific_angular_momenta(&self) -> Vec<f64> {
+        let (center_of_mass, center_velocity, _) = self.center_of_mass();
+        self.bodies
+            .iter()
+            .map(|body| (body.position - center_of_mass).perp_dot(body.velocity - center_velocity))
+            .collect()
+    }
+
+    // Return the specific orbital energy of each body relative to the center of mass, from its kinetic energy and the potential of the massive bodies and the background.
+    pub fn specific_energies(&self) -> Vec<f64> {
+        let gravitational_constant = self.universe_settings.gravitational_constant;
+        let (_, center_velocity, _) = self.center_of_mass();
+
+        self.bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                // Find the kinetic energy relative to the center of mass and the potential energy in the background potential per unit mass.
+                let mut energy = 0.5 * (body.velocity - center_velocity).length_squared()
+                    + self
+                        .universe_settings
+                        .background_potential
+                        .potential(&self.universe_settings, body.position);
+
+                // Subtract the potential energy per unit mass from each other massive body.
+                for (j, other) in self.bodies.iter().enumerate() {
+                    let distance = body.position.distance(other.position);
+                    if j != i && other.is_massive() && distance > 0.0 {
+                        energy -= gravitational_constant * other.mass / distance;
+                    }
+                }
+
+                energy
+            })
+            .collect()
+    }
+}
+
+// Return the Morton code of a cell, interleaving the bits of its coordinates.
+fn morton_code(x: u16, y: u16) -> u32 {
+    // Spread the bits of a coordinate out so there's a zero between each of them.
+    let spread = |value: u16| {
+        let mut value = value as u32;
+        value = (value | (value << 8)) & 0x00ff_00ff;
+        value = (value | (value << 4)) & 0x0f0f_0f0f;
+        value = (value | (value << 2)) & 0x3333_3333;
+        (value | (value << 1)) & 0x5555_5555
+    };
+    spread(x) | (spread(y) << 1)
 }
@@ -0,0 +1,52 @@
+use crate::bookmarks::*;
+use crate::camera::*;
+use crate::render::*;
+use crate::universe::*;
+use notan::math::Vec2;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+// Which panels of the UI are visible.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub ui_hidden: bool,
+    pub console_open: bool,
+    pub minimap_enabled: bool,
+    pub status_bar_enabled: bool,
+}
+
+// A workspace that bundles the universe with the camera, render settings, and panel layout it was viewed with.
+#[derive(Serialize, Deserialize)]
+pub struct Workspace {
+    pub universe: Universe,
+    pub camera_settings: CameraSettings,
+    pub translation: Vec2,
+    pub scale: f32,
+    pub render_settings: RenderSettings,
+    pub panel_layout: PanelLayout,
+    #[serde(default)]
+    pub bookmarks: Vec<CameraBookmark>,
+}
+
+// Implementations for Workspace.
+impl Workspace {
+    // Save a workspace to a JSON file at the given path.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        // Create the directory of the file if it doesn't exist.
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+        }
+
+        // Serialize the workspace and write it to the file.
+        let json = serde_json::to_string(self).map_err(|error| error.to_string())?;
+        fs::write(path, json).map_err(|error| error.to_string())
+    }
+
+    // Load a workspace from a JSON file at the given path.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        // Read the file and deserialize the workspace.
+        let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+}